@@ -0,0 +1,238 @@
+//! Reading and writing document values by the paths diff results carry.
+
+use pyo3::prelude::*;
+use pyo3::types::{PyDict, PyList, PyString};
+
+use crate::paths::split_path;
+use crate::segments::{self, Segment};
+
+/// Return the value at a diff path
+///
+/// String paths are read exactly as diff results write them and traced
+/// through the document, so a key named `"a.b"` is found even though the
+/// path does not escape it. A list of segments (as from
+/// `path_style="segments"` or `parse_path()`) is followed as given.
+///
+/// Args:
+///     doc: The document (dict, list, or primitive)
+///     path: A result path such as `spec.ports[0]`, or a list of segments
+///
+/// Returns:
+///     The value itself, not a copy
+///
+/// Raises:
+///     KeyError: If nothing exists at the path
+#[pyfunction]
+pub fn get_at_path<'py>(
+    doc: &Bound<'py, PyAny>,
+    path: &Bound<'py, PyAny>,
+) -> PyResult<Bound<'py, PyAny>> {
+    let segments = resolve(doc, path, false)?;
+    let mut value = doc.clone();
+    for segment in &segments {
+        value = child(&value, segment)?.ok_or_else(|| missing(path))?;
+    }
+    Ok(value)
+}
+
+/// Set the value at a diff path, modifying the document in place
+///
+/// Paths are resolved like `get_at_path()`. The last segment may name a new
+/// dict key, or the position just past the end of a list to append.
+///
+/// Args:
+///     doc: The document to modify (dict or list)
+///     path: A result path, or a list of segments
+///     value: The new value
+///
+/// Raises:
+///     KeyError: If the parent of the path does not exist
+///     ValueError: If the path is the document root
+#[pyfunction]
+pub fn set_at_path(
+    doc: &Bound<'_, PyAny>,
+    path: &Bound<'_, PyAny>,
+    value: &Bound<'_, PyAny>,
+) -> PyResult<()> {
+    let segments = resolve(doc, path, true)?;
+    let Some((last, parents)) = segments.split_last() else {
+        return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(
+            "Cannot set the document root; replace the document instead",
+        ));
+    };
+    let mut parent = doc.clone();
+    for segment in parents {
+        parent = child(&parent, segment)?.ok_or_else(|| missing(path))?;
+    }
+    match (
+        last,
+        parent.downcast::<PyDict>(),
+        parent.downcast::<PyList>(),
+    ) {
+        (Segment::Key(key), Ok(dict), _) => dict.set_item(key, value),
+        (Segment::Index(index), _, Ok(list)) if *index == list.len() => list.append(value),
+        (Segment::Index(index), _, Ok(list)) if *index < list.len() => list.set_item(*index, value),
+        (Segment::Id(fields), _, Ok(list)) => match find_element(list, fields)? {
+            Some(index) => list.set_item(index, value),
+            None => Err(missing(path)),
+        },
+        _ => Err(missing(path)),
+    }
+}
+
+/// Segments of `path`: a list is taken as given, a string is traced through
+/// `doc`. With `allow_new`, the last segment may be absent from the document.
+fn resolve(
+    doc: &Bound<'_, PyAny>,
+    path: &Bound<'_, PyAny>,
+    allow_new: bool,
+) -> PyResult<Vec<Segment>> {
+    if let Ok(segments) = path.downcast::<PyList>() {
+        return segments
+            .iter()
+            .map(|segment| Segment::from_python(&segment))
+            .collect();
+    }
+    let text = path.downcast::<PyString>()?.to_cow()?;
+    let found = match walk(&text, doc, false)? {
+        Some(segments) => Some(segments),
+        None if allow_new => walk(&text, doc, true)?,
+        None => None,
+    };
+    found.ok_or_else(|| missing(path))
+}
+
+/// Trace `rest` through `value`, preferring longer keys and backtracking.
+/// With `allow_new`, a final key or one-past-the-end index need not exist.
+pub fn walk(
+    rest: &str,
+    value: &Bound<'_, PyAny>,
+    allow_new: bool,
+) -> PyResult<Option<Vec<Segment>>> {
+    if rest.is_empty() {
+        return Ok(Some(Vec::new()));
+    }
+    if let Ok(dict) = value.downcast::<PyDict>() {
+        let mut keys: Vec<String> = dict
+            .keys()
+            .iter()
+            .filter_map(|key| key.extract::<String>().ok())
+            .filter(|key| {
+                rest.strip_prefix(key.as_str())
+                    .is_some_and(|after| after.is_empty() || after.starts_with(['.', '[']))
+            })
+            .collect();
+        keys.sort_by_key(|key| std::cmp::Reverse(key.len()));
+        for key in keys {
+            let Some(child) = dict.get_item(&key)? else {
+                continue;
+            };
+            if let Some(mut tail) = walk(segments::advance(rest, key.len()), &child, allow_new)? {
+                tail.insert(0, Segment::Key(key));
+                return Ok(Some(tail));
+            }
+        }
+        if allow_new && !rest.contains(['.', '[', ']']) {
+            return Ok(Some(vec![Segment::Key(rest.to_string())]));
+        }
+        return Ok(None);
+    }
+    if let Ok(list) = value.downcast::<PyList>() {
+        let Some(first) = split_path(rest).first().copied() else {
+            return Ok(None);
+        };
+        let segment = Segment::parse(first);
+        let after = segments::advance(rest, first.len());
+        let index = match &segment {
+            Segment::Index(index) => Some(*index).filter(|index| *index < list.len()),
+            Segment::Id(fields) => find_element(list, fields)?,
+            Segment::Key(_) => return Ok(None),
+        };
+        let Some(index) = index else {
+            let appends = matches!(segment, Segment::Index(index) if index == list.len());
+            return Ok((allow_new && appends && after.is_empty()).then(|| vec![segment]));
+        };
+        return Ok(
+            walk(after, &list.get_item(index)?, allow_new)?.map(|mut tail| {
+                tail.insert(0, segment);
+                tail
+            }),
+        );
+    }
+    Ok(None)
+}
+
+pub fn child<'py>(
+    value: &Bound<'py, PyAny>,
+    segment: &Segment,
+) -> PyResult<Option<Bound<'py, PyAny>>> {
+    match (
+        segment,
+        value.downcast::<PyDict>(),
+        value.downcast::<PyList>(),
+    ) {
+        (Segment::Key(key), Ok(dict), _) => dict.get_item(key),
+        (Segment::Index(index), _, Ok(list)) if *index < list.len() => {
+            list.get_item(*index).map(Some)
+        }
+        (Segment::Id(fields), _, Ok(list)) => match find_element(list, fields)? {
+            Some(index) => list.get_item(index).map(Some),
+            None => Ok(None),
+        },
+        _ => Ok(None),
+    }
+}
+
+/// Position of the first element whose identity fields hold the given values.
+fn find_element(
+    list: &Bound<'_, PyList>,
+    fields: &[(String, serde_json::Value)],
+) -> PyResult<Option<usize>> {
+    for (index, item) in list.iter().enumerate() {
+        let mut matches = true;
+        for (field, expected) in fields {
+            let found = match lookup(&item, field)? {
+                Some(found) => crate::python_to_json_value(&found).ok(),
+                None => None,
+            };
+            if found.as_ref() != Some(expected) {
+                matches = false;
+                break;
+            }
+        }
+        if matches {
+            return Ok(Some(index));
+        }
+    }
+    Ok(None)
+}
+
+/// Look up `field` in a dict, falling back to a dotted path (`metadata.uid`)
+/// like `array_id_key` does.
+fn lookup<'py>(item: &Bound<'py, PyAny>, field: &str) -> PyResult<Option<Bound<'py, PyAny>>> {
+    let Ok(dict) = item.downcast::<PyDict>() else {
+        return Ok(None);
+    };
+    if let Some(value) = dict.get_item(field)? {
+        return Ok(Some(value));
+    }
+    let mut value = item.clone();
+    for segment in field.split('.') {
+        match value
+            .downcast::<PyDict>()
+            .ok()
+            .map(|dict| dict.get_item(segment))
+        {
+            Some(Ok(Some(next))) => value = next,
+            _ => return Ok(None),
+        }
+    }
+    Ok(Some(value))
+}
+
+fn missing(path: &Bound<'_, PyAny>) -> PyErr {
+    let path = path
+        .repr()
+        .map_or_else(|_| path.to_string(), |repr| repr.to_string());
+    PyErr::new::<pyo3::exceptions::PyKeyError, _>(format!("No value at path {path}"))
+}
@@ -0,0 +1,116 @@
+//! Minimal sequence alignment (Myers' O(ND) difference algorithm).
+
+/// One step of an edit script turning the old sequence into the new one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Edit {
+    /// `old[i]` and `new[j]` are equal.
+    Keep(usize, usize),
+    /// `old[i]` is not in the new sequence.
+    Delete(usize),
+    /// `new[j]` is not in the old sequence.
+    Insert(usize),
+}
+
+/// Shortest edit script between sequences of length `old_len` and `new_len`.
+///
+/// `equal(i, j)` compares `old[i]` with `new[j]`. Common prefixes and
+/// suffixes are stripped first so typical edits stay cheap.
+pub fn edit_script(
+    old_len: usize,
+    new_len: usize,
+    mut equal: impl FnMut(usize, usize) -> bool,
+) -> Vec<Edit> {
+    let mut prefix = 0;
+    while prefix < old_len && prefix < new_len && equal(prefix, prefix) {
+        prefix += 1;
+    }
+    let mut suffix = 0;
+    while suffix < old_len - prefix
+        && suffix < new_len - prefix
+        && equal(old_len - 1 - suffix, new_len - 1 - suffix)
+    {
+        suffix += 1;
+    }
+
+    let mut edits: Vec<Edit> = (0..prefix).map(|i| Edit::Keep(i, i)).collect();
+    let middle = myers(
+        old_len - prefix - suffix,
+        new_len - prefix - suffix,
+        |i, j| equal(prefix + i, prefix + j),
+    );
+    edits.extend(middle.into_iter().map(|edit| match edit {
+        Edit::Keep(i, j) => Edit::Keep(prefix + i, prefix + j),
+        Edit::Delete(i) => Edit::Delete(prefix + i),
+        Edit::Insert(j) => Edit::Insert(prefix + j),
+    }));
+    edits.extend((0..suffix).map(|k| Edit::Keep(old_len - suffix + k, new_len - suffix + k)));
+    edits
+}
+
+fn myers(n: usize, m: usize, mut equal: impl FnMut(usize, usize) -> bool) -> Vec<Edit> {
+    let max = n + m;
+    if max == 0 {
+        return Vec::new();
+    }
+    let offset = max as isize;
+    let index = |k: isize| (k + offset) as usize;
+
+    // Furthest-reaching x on each diagonal k = x - y. Before each edit
+    // distance d only diagonals -d..=d are saved: the backtrack reads no
+    // others, so the trace takes O(D^2) memory rather than O(D * (N + M)).
+    let mut v = vec![0usize; 2 * max + 2];
+    let mut trace = Vec::new();
+    'search: for d in 0..=max as isize {
+        trace.push(v[index(-d)..=index(d)].to_vec());
+        for k in (-d..=d).step_by(2) {
+            let mut x = if k == -d || (k != d && v[index(k - 1)] < v[index(k + 1)]) {
+                v[index(k + 1)]
+            } else {
+                v[index(k - 1)] + 1
+            };
+            let mut y = (x as isize - k) as usize;
+            while x < n && y < m && equal(x, y) {
+                x += 1;
+                y += 1;
+            }
+            v[index(k)] = x;
+            if x >= n && y >= m {
+                break 'search;
+            }
+        }
+    }
+
+    let mut edits = Vec::new();
+    let (mut x, mut y) = (n as isize, m as isize);
+    for (d, window) in trace.iter().enumerate().rev() {
+        let d = d as isize;
+        // Diagonal k of the window saved before distance d; at d = 0 the
+        // start reads diagonal 1, which is still 0
+        let v = |k: isize| window.get((k + d) as usize).copied().unwrap_or(0);
+        let k = x - y;
+        let previous_k = if k == -d || (k != d && v(k - 1) < v(k + 1)) {
+            k + 1
+        } else {
+            k - 1
+        };
+        let previous_x = v(previous_k) as isize;
+        let previous_y = previous_x - previous_k;
+
+        while x > previous_x && y > previous_y {
+            x -= 1;
+            y -= 1;
+            edits.push(Edit::Keep(x as usize, y as usize));
+        }
+        if d > 0 {
+            if x == previous_x {
+                edits.push(Edit::Insert((y - 1) as usize));
+            } else {
+                edits.push(Edit::Delete((x - 1) as usize));
+            }
+        }
+        x = previous_x;
+        y = previous_y;
+    }
+    edits.reverse();
+    edits
+}
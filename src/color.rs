@@ -0,0 +1,86 @@
+//! ANSI colors for terminal output, honoring `NO_COLOR`.
+
+use pyo3::prelude::*;
+use pyo3::types::{PyBool, PyString};
+
+use crate::result::Difference;
+
+const RESET: &str = "\x1b[0m";
+
+/// Resolve `color=True/False/"auto"`; "auto" colors only when `sys.stdout`
+/// is a terminal and neither `NO_COLOR` is set nor `TERM` is `dumb`.
+pub fn enabled(py: Python<'_>, color: Option<&Bound<'_, PyAny>>) -> PyResult<bool> {
+    let Some(color) = color else {
+        return Ok(false);
+    };
+    if let Ok(color) = color.downcast::<PyBool>() {
+        return Ok(color.is_true());
+    }
+    match color.downcast::<PyString>().map(|mode| mode.to_cow()) {
+        Ok(Ok(mode)) if mode == "auto" => {
+            let no_color = std::env::var_os("NO_COLOR").is_some_and(|value| !value.is_empty());
+            let dumb = std::env::var_os("TERM").is_some_and(|term| term == "dumb");
+            if no_color || dumb {
+                return Ok(false);
+            }
+            let stdout = py.import_bound("sys")?.getattr("stdout")?;
+            if stdout.is_none() {
+                return Ok(false);
+            }
+            stdout.call_method0("isatty")?.is_truthy()
+        }
+        _ => Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+            "color must be True, False or \"auto\", not {}",
+            color.repr()?
+        ))),
+    }
+}
+
+/// Color each line of `output` by the kind of its result: green additions,
+/// red removals, yellow modifications. Lines are matched to results in
+/// order; when a result spans several lines, lines are colored by their
+/// leading marker instead.
+pub fn colorize(output: &str, results: &[Difference]) -> String {
+    let lines: Vec<&str> = output.lines().collect();
+    let mut colored = String::with_capacity(output.len() + lines.len() * 10);
+    for (i, line) in lines.iter().enumerate() {
+        let code = if lines.len() == results.len() {
+            kind_code(results[i].kind())
+        } else {
+            marker_code(line)
+        };
+        match code {
+            Some(code) if !line.is_empty() => {
+                colored.push_str(&format!("\x1b[{code}m{line}{RESET}"));
+            }
+            _ => colored.push_str(line),
+        }
+        colored.push('\n');
+    }
+    if !output.ends_with('\n') {
+        colored.pop();
+    }
+    colored
+}
+
+fn kind_code(kind: &str) -> Option<&'static str> {
+    match kind {
+        "Added" => Some("32"),
+        "Removed" => Some("31"),
+        "Modified" => Some("33"),
+        "TypeChanged" => Some("35"),
+        "Moved" | "Renamed" => Some("36"),
+        "Unchanged" | "Comment" => Some("2"),
+        _ => None,
+    }
+}
+
+fn marker_code(line: &str) -> Option<&'static str> {
+    match line.chars().next()? {
+        '+' => kind_code("Added"),
+        '-' => kind_code("Removed"),
+        '~' => kind_code("Modified"),
+        '!' => kind_code("TypeChanged"),
+        _ => None,
+    }
+}
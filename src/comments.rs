@@ -0,0 +1,390 @@
+//! Comments attached to keys in YAML, TOML and INI sources.
+//!
+//! Parsers drop comments, so these scans read them from the source: the
+//! comment lines directly above a key and the comment at the end of its line
+//! belong to that key.
+
+use std::collections::HashMap;
+
+use pyo3::prelude::*;
+
+use crate::positions::{self, child_path};
+
+/// A source line once its comment is set aside.
+pub enum Line {
+    Blank,
+    /// A line holding only a comment.
+    Comment(String),
+    /// Anything else, with the comment at its end.
+    Code(Option<String>),
+}
+
+/// Comments on the keys of YAML, TOML or INI source
+///
+/// A key's comment is the comment lines directly above it (no blank line in
+/// between) followed by the comment at the end of its line, without the
+/// comment markers. Keys without a comment map to None.
+///
+/// Args:
+///     content: YAML, TOML or INI text that parses successfully
+///     format: "yaml", "toml" or "ini"
+///
+/// Returns:
+///     One dict of path to comment per document
+///
+/// Example:
+///     >>> source_comments("# HTTP only\nport: 80  # default\n", "yaml")
+///     [{'port': 'HTTP only\ndefault'}]
+#[pyfunction]
+pub fn source_comments(
+    content: &str,
+    format: &str,
+) -> PyResult<Vec<HashMap<String, Option<String>>>> {
+    match format.to_lowercase().as_str() {
+        "yaml" | "yml" => {
+            let scan = positions::scan_yaml(content)
+                .map_err(PyErr::new::<pyo3::exceptions::PyValueError, _>)?;
+            let lines = yaml_lines(content);
+            Ok(scan
+                .lines
+                .iter()
+                .map(|keys| attach(&lines, innermost(keys)))
+                .collect())
+        }
+        "toml" => {
+            let (lines, keys) = toml_lines(content);
+            Ok(vec![attach(&lines, keys)])
+        }
+        "ini" | "cfg" => {
+            let (lines, keys) = ini_lines(content);
+            Ok(vec![attach(&lines, keys)])
+        }
+        other => Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+            "Comments are only available for YAML, TOML and INI, not {other:?}"
+        ))),
+    }
+}
+
+/// The comment of each key found at a (1-based) line.
+fn attach(lines: &[Line], keys: Vec<(String, usize)>) -> HashMap<String, Option<String>> {
+    keys.into_iter()
+        .map(|(path, line)| {
+            let index = line - 1;
+            let mut parts: Vec<&str> = lines[..index]
+                .iter()
+                .rev()
+                .map_while(|line| match line {
+                    Line::Comment(text) => Some(text.as_str()),
+                    _ => None,
+                })
+                .collect();
+            parts.reverse();
+            if let Some(Line::Code(Some(text))) = lines.get(index) {
+                parts.push(text);
+            }
+            let comment = (!parts.is_empty()).then(|| parts.join("\n"));
+            (path, comment)
+        })
+        .collect()
+}
+
+/// Keep the deepest path on each line, so `- name: web  # primary` belongs to
+/// `items[0].name` rather than `items[0]`.
+fn innermost(keys: &HashMap<String, usize>) -> Vec<(String, usize)> {
+    let mut by_line: HashMap<usize, &str> = HashMap::new();
+    for (path, &line) in keys {
+        let deeper = by_line
+            .get(&line)
+            .is_none_or(|current| path.len() > current.len());
+        if deeper {
+            by_line.insert(line, path);
+        }
+    }
+    by_line
+        .into_iter()
+        .map(|(line, path)| (path.to_string(), line))
+        .collect()
+}
+
+/// Comment text without its marker and surrounding whitespace.
+fn comment_text(comment: &str) -> String {
+    comment.trim_start_matches(['#', ';']).trim().to_string()
+}
+
+fn yaml_lines(content: &str) -> Vec<Line> {
+    let mut lines = Vec::new();
+    // Indentation of the line that opened a block scalar (`key: |`)
+    let mut block: Option<usize> = None;
+    for line in content.lines() {
+        let indent = line.len() - line.trim_start().len();
+        if let Some(parent) = block {
+            if line.trim().is_empty() || indent > parent {
+                lines.push(Line::Code(None));
+                continue;
+            }
+            block = None;
+        }
+        let (code, comment) = split_yaml_comment(line);
+        let comment = comment.map(comment_text);
+        lines.push(match (code.trim(), comment) {
+            ("", None) => Line::Blank,
+            ("", Some(text)) => Line::Comment(text),
+            (code, comment) => {
+                if opens_block_scalar(code) {
+                    block = Some(indent);
+                }
+                Line::Code(comment)
+            }
+        });
+    }
+    lines
+}
+
+/// Split a YAML line at a `#` that starts a comment: outside quotes and at
+/// the start of the line or after whitespace.
+fn split_yaml_comment(line: &str) -> (&str, Option<&str>) {
+    let mut quote: Option<char> = None;
+    let mut previous = ' ';
+    let mut escaped = false;
+    for (index, c) in line.char_indices() {
+        match quote {
+            Some('"') if escaped => escaped = false,
+            Some('"') if c == '\\' => escaped = true,
+            Some(open) if c == open => quote = None,
+            Some(_) => {}
+            None if c == '#' && previous.is_whitespace() => {
+                return (&line[..index], Some(&line[index..]));
+            }
+            // A quote only opens a scalar where one can start, not in `it's`
+            None if matches!(c, '"' | '\'')
+                && (previous.is_whitespace()
+                    || matches!(previous, ':' | '-' | ',' | '[' | '{')) =>
+            {
+                quote = Some(c)
+            }
+            None => {}
+        }
+        previous = c;
+    }
+    (line, None)
+}
+
+fn opens_block_scalar(code: &str) -> bool {
+    let indicators = code.trim_end_matches(|c: char| c.is_ascii_digit() || c == '+' || c == '-');
+    let before = indicators.len().saturating_sub(1);
+    (indicators.ends_with('|') || indicators.ends_with('>'))
+        && (before == 0 || indicators[..before].ends_with([' ', ':']))
+}
+
+/// Classify TOML lines and find the line of each key and table header.
+pub fn toml_lines(content: &str) -> (Vec<Line>, Vec<(String, usize)>) {
+    let mut lines = Vec::new();
+    let mut keys = Vec::new();
+    let mut table = String::new();
+    // Number of `[[name]]` headers seen so far, by dotted header name
+    let mut array_tables: HashMap<String, usize> = HashMap::new();
+    let mut state = TomlState::default();
+
+    for (number, line) in content.lines().enumerate() {
+        let starts_clean = state.is_clean();
+        let (code, comment) = state.split(line);
+        let comment = comment.map(comment_text);
+        let trimmed = code.trim();
+        lines.push(match (trimmed, &comment) {
+            ("", None) if starts_clean => Line::Blank,
+            ("", Some(text)) if starts_clean => Line::Comment(text.clone()),
+            _ => Line::Code(comment),
+        });
+        if !starts_clean || trimmed.is_empty() {
+            continue;
+        }
+
+        if let Some(header) = trimmed.strip_prefix('[') {
+            let (array, name) = match header.strip_prefix('[') {
+                Some(name) => (true, name.trim_end().trim_end_matches(']')),
+                None => (false, header.trim_end().trim_end_matches(']')),
+            };
+            let parts = split_toml_key(name);
+            let raw = parts.join(".");
+            if array {
+                *array_tables.entry(raw.clone()).or_default() += 1;
+                // Tables nested in the previous element start over
+                let nested = format!("{raw}.");
+                array_tables.retain(|name, _| !name.starts_with(&nested));
+            }
+            table = toml_table_path(&parts, &array_tables);
+            keys.push((table.clone(), number + 1));
+            // A header line carries no value, so it leaves `state` clean
+            continue;
+        }
+
+        if let Some(position) = find_unquoted(trimmed, '=') {
+            let path = split_toml_key(&trimmed[..position])
+                .iter()
+                .fold(table.clone(), |path, part| child_path(&path, part));
+            keys.push((path, number + 1));
+        }
+    }
+    (lines, keys)
+}
+
+/// Path of a table header, with the current index of each array of tables
+/// it runs through (`servers[1].tls`).
+fn toml_table_path(parts: &[String], array_tables: &HashMap<String, usize>) -> String {
+    let mut path = String::new();
+    let mut raw = String::new();
+    for part in parts {
+        raw = child_path(&raw, part);
+        path = child_path(&path, part);
+        if let Some(count) = array_tables.get(&raw) {
+            path = format!("{path}[{}]", count - 1);
+        }
+    }
+    path
+}
+
+/// Split a dotted TOML key, unquoting quoted parts.
+fn split_toml_key(key: &str) -> Vec<String> {
+    let mut parts = Vec::new();
+    let mut rest = key.trim();
+    while !rest.is_empty() {
+        let (part, after) = match rest.chars().next() {
+            Some(quote @ ('"' | '\'')) => {
+                let end = rest[1..].find(quote).map_or(rest.len(), |end| end + 2);
+                let quoted = &rest[..end];
+                let part = match quote {
+                    '"' => serde_json::from_str(quoted)
+                        .unwrap_or_else(|_| quoted.trim_matches('"').to_string()),
+                    _ => quoted.trim_matches('\'').to_string(),
+                };
+                (part, &rest[end..])
+            }
+            _ => {
+                let end = rest.find('.').unwrap_or(rest.len());
+                (rest[..end].trim().to_string(), &rest[end..])
+            }
+        };
+        parts.push(part);
+        rest = after.trim_start().trim_start_matches('.').trim_start();
+    }
+    parts
+}
+
+/// Byte offset of `target` outside quoted strings.
+fn find_unquoted(text: &str, target: char) -> Option<usize> {
+    let mut quote: Option<char> = None;
+    for (index, c) in text.char_indices() {
+        match quote {
+            Some(open) if c == open => quote = None,
+            Some(_) => {}
+            None if c == target => return Some(index),
+            None if matches!(c, '"' | '\'') => quote = Some(c),
+            None => {}
+        }
+    }
+    None
+}
+
+/// Where a TOML scan stands between lines: inside a multi-line string, or
+/// inside an array or inline table that spans lines.
+#[derive(Default)]
+struct TomlState {
+    multiline: Option<&'static str>,
+    depth: usize,
+}
+
+impl TomlState {
+    fn is_clean(&self) -> bool {
+        self.multiline.is_none() && self.depth == 0
+    }
+
+    /// Split `line` at its comment, tracking strings and brackets.
+    fn split<'a>(&mut self, line: &'a str) -> (&'a str, Option<&'a str>) {
+        let is_header = self.is_clean() && line.trim_start().starts_with('[');
+        let mut index = 0;
+        while index < line.len() {
+            let rest = &line[index..];
+            if let Some(delimiter) = self.multiline {
+                match rest.find(delimiter) {
+                    Some(end) => {
+                        index += end + delimiter.len();
+                        self.multiline = None;
+                        continue;
+                    }
+                    None => return (line, None),
+                }
+            }
+            let c = rest.chars().next().unwrap_or_default();
+            if let Some(delimiter) = ["\"\"\"", "'''"].into_iter().find(|d| rest.starts_with(d)) {
+                self.multiline = Some(delimiter);
+                index += delimiter.len();
+                continue;
+            }
+            match c {
+                '#' => return (&line[..index], Some(rest)),
+                '"' | '\'' => {
+                    index += 1 + skip_string(&line[index + 1..], c);
+                    continue;
+                }
+                '[' | '{' if !is_header => self.depth += 1,
+                ']' | '}' if !is_header => self.depth = self.depth.saturating_sub(1),
+                _ => {}
+            }
+            index += c.len_utf8();
+        }
+        (line, None)
+    }
+}
+
+/// Length of a single-line string body up to and including its closing quote.
+fn skip_string(rest: &str, quote: char) -> usize {
+    let mut escaped = false;
+    for (index, c) in rest.char_indices() {
+        match c {
+            '\\' if quote == '"' && !escaped => escaped = true,
+            c if c == quote && !escaped => return index + 1,
+            _ => escaped = false,
+        }
+    }
+    rest.len()
+}
+
+/// Classify INI lines and find the line of each key and section header. INI
+/// comments are whole lines only, as in configparser.
+pub fn ini_lines(content: &str) -> (Vec<Line>, Vec<(String, usize)>) {
+    let mut lines = Vec::new();
+    let mut keys = Vec::new();
+    let mut section = String::new();
+    let mut in_value = false;
+    for (number, line) in content.lines().enumerate() {
+        let trimmed = line.trim();
+        if trimmed.is_empty() {
+            lines.push(Line::Blank);
+            in_value = false;
+            continue;
+        }
+        if trimmed.starts_with(['#', ';']) {
+            lines.push(Line::Comment(comment_text(trimmed)));
+            in_value = false;
+            continue;
+        }
+        lines.push(Line::Code(None));
+        // Indented lines continue the previous value
+        if in_value && line.starts_with(char::is_whitespace) {
+            continue;
+        }
+        if trimmed.starts_with('[') && trimmed.ends_with(']') {
+            section = trimmed[1..trimmed.len() - 1].trim().to_string();
+            keys.push((section.clone(), number + 1));
+            in_value = false;
+            continue;
+        }
+        let key = match trimmed.find(['=', ':']) {
+            Some(position) => trimmed[..position].trim(),
+            None => trimmed,
+        };
+        keys.push((child_path(&section, key), number + 1));
+        in_value = true;
+    }
+    (lines, keys)
+}
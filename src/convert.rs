@@ -0,0 +1,614 @@
+//! Conversion of Python values into the JSON values the diff compares.
+
+use std::cell::RefCell;
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, HashSet};
+
+use pyo3::prelude::*;
+use pyo3::types::{
+    PyByteArray, PyBytes, PyDict, PyFrozenSet, PyList, PyLong, PyMemoryView, PySet, PyTuple, PyType,
+};
+use serde_json::{Map, Value};
+
+use crate::errors::CircularReferenceError;
+use crate::paths::join_path;
+use crate::{datetime, kwargs};
+
+/// How integers outside the 64-bit range are converted.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum BigInt {
+    /// Their exact decimal digits, as a string.
+    #[default]
+    String,
+    /// The nearest float, losing precision.
+    Float,
+    /// Raise `OverflowError`.
+    Error,
+}
+
+impl BigInt {
+    pub fn parse(policy: &str) -> Result<Self, String> {
+        match policy {
+            "string" => Ok(Self::String),
+            "float" => Ok(Self::Float),
+            "error" => Ok(Self::Error),
+            other => Err(format!(
+                "Invalid big_int: {other:?} (expected \"string\", \"float\" or \"error\")"
+            )),
+        }
+    }
+}
+
+/// How `bytes`, `bytearray` and `memoryview` values are converted.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Binary {
+    /// A base64 string, compared exactly.
+    #[default]
+    Base64,
+    /// A list of byte values, so changes name the byte positions.
+    Raw,
+    /// `"sha256:<hex digest>"`, for large blobs.
+    Hash,
+}
+
+impl Binary {
+    pub fn parse(policy: &str) -> Result<Self, String> {
+        match policy {
+            "base64" => Ok(Self::Base64),
+            "raw" => Ok(Self::Raw),
+            "hash" => Ok(Self::Hash),
+            other => Err(format!(
+                "Invalid binary: {other:?} (expected \"base64\", \"raw\" or \"hash\")"
+            )),
+        }
+    }
+}
+
+/// Which part of an `enum.Enum` member is compared.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum EnumMode {
+    #[default]
+    Value,
+    Name,
+}
+
+impl EnumMode {
+    pub fn parse(mode: &str) -> Result<Self, String> {
+        match mode {
+            "value" => Ok(Self::Value),
+            "name" => Ok(Self::Name),
+            other => Err(format!(
+                "Invalid enum: {other:?} (expected \"value\" or \"name\")"
+            )),
+        }
+    }
+}
+
+/// What happens to dict keys that are not strings.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum KeyPolicy {
+    /// Raise `TypeError` naming the key and its path.
+    #[default]
+    Error,
+    /// Use the key's JSON text (`1` becomes `"1"`, a string key stays as is).
+    Stringify,
+    /// Leave the entry out.
+    Skip,
+}
+
+impl KeyPolicy {
+    pub fn parse(policy: &str) -> Result<Self, String> {
+        match policy {
+            "error" => Ok(Self::Error),
+            "stringify" => Ok(Self::Stringify),
+            "skip" => Ok(Self::Skip),
+            other => Err(format!(
+                "Invalid key_policy: {other:?} (expected \"error\", \"stringify\" or \"skip\")"
+            )),
+        }
+    }
+}
+
+/// Deepest nesting of containers converted, like Python's default recursion
+/// limit; deeper input would overflow the stack of the recursive conversion.
+pub const MAX_NESTING: usize = 1000;
+
+/// Where a value sits in the input, rendered only for error messages.
+#[derive(Clone, Copy)]
+enum Location<'a> {
+    Root,
+    Key(&'a Location<'a>, &'a str),
+    Index(&'a Location<'a>, usize),
+    /// Inside the container with this `id()`, which sits at the parent.
+    Inside(&'a Location<'a>, usize),
+}
+
+impl<'a> Location<'a> {
+    /// The location inside `container`, or `CircularReferenceError` when
+    /// `container` is already being converted further up and `RecursionError`
+    /// when it sits inside `MAX_NESTING` containers.
+    fn enter(&'a self, container: &Bound<'_, PyAny>) -> PyResult<Self> {
+        let id = container.as_ptr() as usize;
+        let mut location = self;
+        let mut depth = 0;
+        loop {
+            location = match location {
+                Location::Root => return Ok(Location::Inside(self, id)),
+                Location::Inside(parent, seen) if *seen == id => {
+                    return Err(CircularReferenceError::new_err(format!(
+                        "Circular reference {} (the value {} contains itself)",
+                        self.describe(),
+                        parent.describe()
+                    )));
+                }
+                Location::Inside(..) if depth == MAX_NESTING => {
+                    return Err(PyErr::new::<pyo3::exceptions::PyRecursionError, _>(
+                        format!("Input nested more than {MAX_NESTING} containers deep"),
+                    ));
+                }
+                Location::Inside(parent, _) => {
+                    depth += 1;
+                    parent
+                }
+                Location::Key(parent, _) | Location::Index(parent, _) => parent,
+            };
+        }
+    }
+
+    /// The keys and indexes leading here from the root.
+    fn steps(&self) -> Vec<Step> {
+        let mut steps = Vec::new();
+        let mut location = self;
+        loop {
+            location = match location {
+                Location::Root => break,
+                Location::Key(parent, key) => {
+                    steps.push(Step::Key(key.to_string()));
+                    parent
+                }
+                Location::Index(parent, index) => {
+                    steps.push(Step::Index(*index));
+                    parent
+                }
+                Location::Inside(parent, _) => parent,
+            };
+        }
+        steps.reverse();
+        steps
+    }
+
+    /// `at 'a.b[0]'`, in the path syntax of diff results.
+    fn describe(&self) -> String {
+        let mut segments = Vec::new();
+        let mut location = self;
+        loop {
+            match location {
+                Location::Root => break,
+                Location::Key(parent, key) => {
+                    segments.push(key.to_string());
+                    location = parent;
+                }
+                Location::Index(parent, index) => {
+                    segments.push(format!("[{index}]"));
+                    location = parent;
+                }
+                Location::Inside(parent, _) => location = parent,
+            }
+        }
+        if segments.is_empty() {
+            return "at the root".to_string();
+        }
+        segments.reverse();
+        let segments: Vec<&str> = segments.iter().map(String::as_str).collect();
+        format!("at '{}'", join_path(&segments))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+enum Step {
+    Key(String),
+    Index(usize),
+}
+
+/// The order keys were given in for each object of a converted document,
+/// which the sorted objects of the value model do not keep.
+#[derive(Debug, Default)]
+pub struct KeyOrder {
+    keys: Vec<String>,
+    children: BTreeMap<Step, KeyOrder>,
+}
+
+static SORTED: KeyOrder = KeyOrder {
+    keys: Vec::new(),
+    children: BTreeMap::new(),
+};
+
+impl KeyOrder {
+    /// No recorded order: every object keeps its sorted keys.
+    pub fn sorted() -> &'static Self {
+        &SORTED
+    }
+
+    /// The keys of `map` in recorded order; keys without one follow, sorted.
+    pub fn keys<'a>(&self, map: &'a Map<String, Value>) -> Vec<&'a String> {
+        let recorded: HashSet<&str> = self.keys.iter().map(String::as_str).collect();
+        let mut keys: Vec<&String> = self
+            .keys
+            .iter()
+            .filter_map(|key| map.get_key_value(key).map(|(key, _)| key))
+            .collect();
+        keys.extend(map.keys().filter(|key| !recorded.contains(key.as_str())));
+        keys
+    }
+
+    /// The order inside the value under `key`.
+    pub fn key(&self, key: &str) -> &Self {
+        self.child(Step::Key(key.to_string()))
+    }
+
+    /// The order inside the item at `index`.
+    pub fn item(&self, index: usize) -> &Self {
+        self.child(Step::Index(index))
+    }
+
+    fn child(&self, step: Step) -> &Self {
+        self.children.get(&step).unwrap_or(&SORTED)
+    }
+
+    fn record(&mut self, steps: Vec<Step>, keys: Vec<String>) {
+        let mut order = self;
+        for step in steps {
+            order = order.children.entry(step).or_default();
+        }
+        order.keys = keys;
+    }
+}
+
+/// Options for converting Python inputs.
+#[derive(Debug, Default)]
+pub struct Conversion {
+    pub big_int: BigInt,
+    pub binary: Binary,
+    pub enum_mode: EnumMode,
+    pub key_policy: KeyPolicy,
+    /// Called with values of unsupported types, like `json.dumps(default=)`.
+    pub default: Option<PyObject>,
+    /// Collects the order of dict keys and fields when set.
+    pub key_order: Option<RefCell<KeyOrder>>,
+}
+
+impl Conversion {
+    pub fn from_kwargs(kwargs: Option<&Bound<'_, PyDict>>) -> PyResult<Self> {
+        let big_int = match kwargs::get(kwargs, "big_int")? {
+            Some(policy) => BigInt::parse(&policy.extract::<String>()?)
+                .map_err(PyErr::new::<pyo3::exceptions::PyValueError, _>)?,
+            None => BigInt::default(),
+        };
+        let binary = match kwargs::get(kwargs, "binary")? {
+            Some(policy) => Binary::parse(&policy.extract::<String>()?)
+                .map_err(PyErr::new::<pyo3::exceptions::PyValueError, _>)?,
+            None => Binary::default(),
+        };
+        let enum_mode = match kwargs::get(kwargs, "enum")? {
+            Some(mode) => EnumMode::parse(&mode.extract::<String>()?)
+                .map_err(PyErr::new::<pyo3::exceptions::PyValueError, _>)?,
+            None => EnumMode::default(),
+        };
+        let key_policy = match kwargs::get(kwargs, "key_policy")? {
+            Some(policy) => KeyPolicy::parse(&policy.extract::<String>()?)
+                .map_err(PyErr::new::<pyo3::exceptions::PyValueError, _>)?,
+            None => KeyPolicy::default(),
+        };
+        let default = match kwargs::get(kwargs, "default")? {
+            Some(default) if !default.is_callable() => {
+                return Err(PyErr::new::<pyo3::exceptions::PyTypeError, _>(
+                    "default must be callable",
+                ));
+            }
+            default => default.map(Bound::unbind),
+        };
+        Ok(Self {
+            big_int,
+            binary,
+            enum_mode,
+            key_policy,
+            default,
+            key_order: None,
+        })
+    }
+
+    pub fn to_json(&self, py_obj: &Bound<'_, PyAny>) -> PyResult<Value> {
+        self.convert(py_obj, &Location::Root)
+    }
+
+    fn convert(&self, py_obj: &Bound<'_, PyAny>, at: &Location) -> PyResult<Value> {
+        // IntEnum and StrEnum members would otherwise convert as their value
+        if self.enum_mode == EnumMode::Name && is_enum_member(py_obj)? {
+            return Ok(Value::String(py_obj.getattr("name")?.extract()?));
+        }
+        if py_obj.is_none() {
+            Ok(Value::Null)
+        } else if let Ok(b) = py_obj.extract::<bool>() {
+            Ok(Value::Bool(b))
+        } else if let Ok(i) = py_obj.extract::<i64>() {
+            Ok(Value::Number(i.into()))
+        } else if let Ok(u) = py_obj.extract::<u64>() {
+            Ok(Value::Number(u.into()))
+        } else if let Ok(int) = py_obj.downcast::<PyLong>() {
+            self.big_int_to_json(int)
+        } else if let Ok(f) = py_obj.extract::<f64>() {
+            Ok(Value::Number(
+                serde_json::Number::from_f64(f).unwrap_or(0.into()),
+            ))
+        } else if let Ok(s) = py_obj.extract::<String>() {
+            Ok(Value::String(s))
+        } else if let Some(datetime) = datetime::normalize_python(py_obj)? {
+            Ok(Value::String(datetime))
+        } else if py_obj.is_instance_of::<PyList>() {
+            self.items_to_json(py_obj, at)
+        } else if let Some(fields) = named_tuple_fields(py_obj)? {
+            self.fields_to_json(py_obj, fields, at)
+        } else if py_obj.is_instance_of::<PyTuple>() {
+            self.items_to_json(py_obj, at)
+        } else if let Ok(dict) = py_obj.downcast::<PyDict>() {
+            self.dict_to_json(dict, at)
+        } else if py_obj.is_instance_of::<PySet>() || py_obj.is_instance_of::<PyFrozenSet>() {
+            self.set_to_json(py_obj, at)
+        } else if let Some(value) = self.other_to_json(py_obj, at)? {
+            Ok(value)
+        } else if let Some(default) = &self.default {
+            let converted = default.bind(py_obj.py()).call1((py_obj,))?;
+            if converted.is(py_obj) {
+                return Err(PyErr::new::<pyo3::exceptions::PyTypeError, _>(format!(
+                    "default returned the unsupported {} value {} unchanged",
+                    type_name(py_obj),
+                    at.describe()
+                )));
+            }
+            self.convert(&converted, at)
+        } else {
+            Err(PyErr::new::<pyo3::exceptions::PyTypeError, _>(format!(
+                "Unsupported Python type: {} {} (pass default= to convert it)",
+                type_name(py_obj),
+                at.describe()
+            )))
+        }
+    }
+
+    /// Values of standard library types with a canonical JSON form. Checked
+    /// last, since most documents hold only the basic types above.
+    fn other_to_json(&self, py_obj: &Bound<'_, PyAny>, at: &Location) -> PyResult<Option<Value>> {
+        let py = py_obj.py();
+        if py_obj.is_instance_of::<PyBytes>()
+            || py_obj.is_instance_of::<PyByteArray>()
+            || py_obj.is_instance_of::<PyMemoryView>()
+        {
+            return self.binary_to_json(py_obj).map(Some);
+        }
+        if is_enum_member(py_obj)? {
+            return self.convert(&py_obj.getattr("value")?, at).map(Some);
+        }
+        if py_obj.is_instance(&py.import_bound("uuid")?.getattr("UUID")?)? {
+            return Ok(Some(Value::String(py_obj.str()?.to_string())));
+        }
+        let os = py.import_bound("os")?;
+        if py_obj.is_instance(&os.getattr("PathLike")?)? {
+            return Ok(Some(Value::String(
+                os.call_method1("fsdecode", (py_obj,))?.extract()?,
+            )));
+        }
+        if py_obj.is_instance_of::<PyType>() {
+            return Ok(None);
+        }
+        // Pydantic models: `model_dump()` in v2, `dict()` in v1
+        let model = py_obj.get_type();
+        if model.hasattr("model_fields")? && py_obj.hasattr("model_dump")? {
+            return self
+                .convert(&py_obj.call_method0("model_dump")?, at)
+                .map(Some);
+        }
+        if model.hasattr("__fields__")? && py_obj.hasattr("dict")? {
+            return self.convert(&py_obj.call_method0("dict")?, at).map(Some);
+        }
+        if py_obj.hasattr("__dataclass_fields__")? {
+            let fields = py
+                .import_bound("dataclasses")?
+                .call_method1("fields", (py_obj,))?;
+            return self
+                .fields_to_json(py_obj, field_names(&fields)?, at)
+                .map(Some);
+        }
+        if let Ok(attributes) = py_obj.getattr("__attrs_attrs__") {
+            return self
+                .fields_to_json(py_obj, field_names(&attributes)?, at)
+                .map(Some);
+        }
+        Ok(None)
+    }
+
+    fn items_to_json(&self, container: &Bound<'_, PyAny>, at: &Location) -> PyResult<Value> {
+        let inside = at.enter(container)?;
+        let mut items = Vec::new();
+        for (index, item) in container.iter()?.enumerate() {
+            items.push(self.convert(&item?, &Location::Index(&inside, index))?);
+        }
+        Ok(Value::Array(items))
+    }
+
+    fn dict_to_json(&self, dict: &Bound<'_, PyDict>, at: &Location) -> PyResult<Value> {
+        let inside = at.enter(dict)?;
+        let mut map = serde_json::Map::new();
+        let mut keys = Vec::new();
+        for (key, value) in dict.iter() {
+            let key = match key.extract::<String>() {
+                Ok(key) => key,
+                Err(_) => match self.key_policy {
+                    KeyPolicy::Skip => continue,
+                    KeyPolicy::Error => {
+                        return Err(PyErr::new::<pyo3::exceptions::PyTypeError, _>(format!(
+                            "Dict key {} {} is not a string (key_policy=\"stringify\" \
+                             converts it)",
+                            key.repr()?,
+                            at.describe()
+                        )));
+                    }
+                    KeyPolicy::Stringify => {
+                        let text = match self.convert(&key, at)? {
+                            Value::String(text) => text,
+                            other => other.to_string(),
+                        };
+                        if dict.contains(text.as_str())? || map.contains_key(&text) {
+                            return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+                                "Dict key {} {} collides with another key as {text:?}",
+                                key.repr()?,
+                                at.describe()
+                            )));
+                        }
+                        text
+                    }
+                },
+            };
+            let value = self.convert(&value, &Location::Key(&inside, &key))?;
+            keys.push(key.clone());
+            map.insert(key, value);
+        }
+        self.record_order(at, keys);
+        Ok(Value::Object(map))
+    }
+
+    /// An object of the named fields of a dataclass, attrs class or
+    /// NamedTuple.
+    fn fields_to_json(
+        &self,
+        py_obj: &Bound<'_, PyAny>,
+        names: Vec<String>,
+        at: &Location,
+    ) -> PyResult<Value> {
+        let inside = at.enter(py_obj)?;
+        let mut map = serde_json::Map::new();
+        for name in &names {
+            let value = self.convert(
+                &py_obj.getattr(name.as_str())?,
+                &Location::Key(&inside, name),
+            )?;
+            map.insert(name.clone(), value);
+        }
+        self.record_order(at, names);
+        Ok(Value::Object(map))
+    }
+
+    fn record_order(&self, at: &Location, keys: Vec<String>) {
+        if let Some(order) = &self.key_order {
+            order.borrow_mut().record(at.steps(), keys);
+        }
+    }
+
+    /// Sets have no order, so their items are sorted to make equal sets
+    /// compare equal.
+    fn set_to_json(&self, set: &Bound<'_, PyAny>, at: &Location) -> PyResult<Value> {
+        let Value::Array(mut items) = self.items_to_json(set, at)? else {
+            unreachable!("items convert to an array");
+        };
+        items.sort_by(compare_values);
+        Ok(Value::Array(items))
+    }
+
+    fn binary_to_json(&self, py_obj: &Bound<'_, PyAny>) -> PyResult<Value> {
+        let py = py_obj.py();
+        let data = py.get_type_bound::<PyBytes>().call1((py_obj,))?;
+        match self.binary {
+            Binary::Base64 => {
+                let encoded = py
+                    .import_bound("base64")?
+                    .call_method1("b64encode", (data,))?;
+                Ok(Value::String(encoded.call_method0("decode")?.extract()?))
+            }
+            Binary::Raw => Ok(Value::Array(
+                data.downcast::<PyBytes>()?
+                    .as_bytes()
+                    .iter()
+                    .map(|byte| Value::from(*byte))
+                    .collect(),
+            )),
+            Binary::Hash => {
+                let digest = py
+                    .import_bound("hashlib")?
+                    .call_method1("sha256", (data,))?
+                    .call_method0("hexdigest")?;
+                Ok(Value::String(format!("sha256:{digest}")))
+            }
+        }
+    }
+
+    /// An integer outside both `i64` and `u64`. JSON numbers here are 64-bit,
+    /// so the exact value survives only as a string.
+    fn big_int_to_json(&self, int: &Bound<'_, PyLong>) -> PyResult<Value> {
+        match self.big_int {
+            BigInt::String => Ok(Value::String(int.str()?.to_string())),
+            BigInt::Float => {
+                let f = int.extract::<f64>()?;
+                Ok(Value::Number(
+                    serde_json::Number::from_f64(f).unwrap_or(0.into()),
+                ))
+            }
+            BigInt::Error => Err(PyErr::new::<pyo3::exceptions::PyOverflowError, _>(format!(
+                "Integer {} does not fit in 64 bits (big_int=\"error\")",
+                int.str()?
+            ))),
+        }
+    }
+}
+
+/// A total order on JSON values: null, booleans, numbers, strings, arrays,
+/// objects; values of one type compare by content.
+fn compare_values(a: &Value, b: &Value) -> Ordering {
+    let rank = |value: &Value| match value {
+        Value::Null => 0,
+        Value::Bool(_) => 1,
+        Value::Number(_) => 2,
+        Value::String(_) => 3,
+        Value::Array(_) => 4,
+        Value::Object(_) => 5,
+    };
+    let by_content = match (a, b) {
+        (Value::Bool(a), Value::Bool(b)) => a.cmp(b),
+        (Value::Number(a), Value::Number(b)) => a
+            .as_f64()
+            .partial_cmp(&b.as_f64())
+            .unwrap_or(Ordering::Equal)
+            .then_with(|| a.to_string().cmp(&b.to_string())),
+        (Value::String(a), Value::String(b)) => a.cmp(b),
+        _ => a.to_string().cmp(&b.to_string()),
+    };
+    rank(a).cmp(&rank(b)).then(by_content)
+}
+
+/// The `_fields` of a NamedTuple (or `collections.namedtuple`) instance.
+fn named_tuple_fields(py_obj: &Bound<'_, PyAny>) -> PyResult<Option<Vec<String>>> {
+    if !py_obj.is_instance_of::<PyTuple>() {
+        return Ok(None);
+    }
+    match py_obj.getattr("_fields") {
+        Ok(fields) => Ok(fields.extract().ok()),
+        Err(_) => Ok(None),
+    }
+}
+
+/// The `name` of each dataclass `Field` or attrs `Attribute`.
+fn field_names(fields: &Bound<'_, PyAny>) -> PyResult<Vec<String>> {
+    fields
+        .iter()?
+        .map(|field| field?.getattr("name")?.extract())
+        .collect()
+}
+
+fn is_enum_member(py_obj: &Bound<'_, PyAny>) -> PyResult<bool> {
+    py_obj.is_instance(&py_obj.py().import_bound("enum")?.getattr("Enum")?)
+}
+
+fn type_name(py_obj: &Bound<'_, PyAny>) -> String {
+    py_obj
+        .get_type()
+        .name()
+        .map_or_else(|_| "?".to_string(), |name| name.to_string())
+}
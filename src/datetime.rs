@@ -0,0 +1,244 @@
+//! Datetime normalization and tolerance-based comparison.
+
+use chrono::{DateTime, FixedOffset, NaiveDate, NaiveDateTime, NaiveTime, Utc};
+use pyo3::prelude::*;
+use pyo3::types::{PyDate, PyDateAccess, PyDateTime, PyDelta, PyTime, PyTimeAccess};
+use std::borrow::Cow;
+
+/// Normalize a TOML datetime to ISO 8601.
+///
+/// Offset datetimes are converted to UTC (`2024-01-01T00:00:00Z`) so equal
+/// instants written with different offsets compare equal. Local datetimes,
+/// dates, and times keep their own ISO form.
+pub fn normalize_toml(datetime: &toml::value::Datetime) -> Result<String, String> {
+    let date = datetime
+        .date
+        .map(|date| {
+            NaiveDate::from_ymd_opt(date.year.into(), date.month.into(), date.day.into())
+                .ok_or_else(|| format!("invalid date: {datetime}"))
+        })
+        .transpose()?;
+    let time = datetime
+        .time
+        .map(|time| {
+            NaiveTime::from_hms_nano_opt(
+                time.hour.into(),
+                time.minute.into(),
+                time.second.into(),
+                time.nanosecond,
+            )
+            .ok_or_else(|| format!("invalid time: {datetime}"))
+        })
+        .transpose()?;
+
+    Ok(match (date, time, datetime.offset) {
+        (Some(date), Some(time), Some(offset)) => {
+            let minutes = match offset {
+                toml::value::Offset::Z => 0,
+                toml::value::Offset::Custom { minutes } => i32::from(minutes),
+            };
+            let offset = FixedOffset::east_opt(minutes * 60)
+                .ok_or_else(|| format!("invalid offset: {datetime}"))?;
+            let local = date
+                .and_time(time)
+                .and_local_timezone(offset)
+                .single()
+                .ok_or_else(|| format!("invalid datetime: {datetime}"))?;
+            format_utc(local.with_timezone(&Utc))
+        }
+        (Some(date), Some(time), None) => date
+            .and_time(time)
+            .format("%Y-%m-%dT%H:%M:%S%.f")
+            .to_string(),
+        (Some(date), None, _) => date.format("%Y-%m-%d").to_string(),
+        (None, Some(time), _) => time.format("%H:%M:%S%.f").to_string(),
+        (None, None, _) => return Err(format!("empty datetime: {datetime}")),
+    })
+}
+
+/// Normalize a Python `datetime`, `date` or `time` to ISO 8601 in the same
+/// forms as TOML datetimes: aware datetimes in UTC, the rest as written.
+/// Returns `None` for other objects.
+pub fn normalize_python(value: &Bound<'_, PyAny>) -> PyResult<Option<String>> {
+    let invalid = |value: &Bound<'_, PyAny>| {
+        PyErr::new::<pyo3::exceptions::PyValueError, _>(format!("Invalid datetime: {value}"))
+    };
+    if let Ok(datetime) = value.downcast::<PyDateTime>() {
+        let local = NaiveDate::from_ymd_opt(
+            datetime.get_year(),
+            datetime.get_month().into(),
+            datetime.get_day().into(),
+        )
+        .zip(naive_time(datetime))
+        .map(|(date, time)| date.and_time(time))
+        .ok_or_else(|| invalid(value))?;
+        let offset = datetime.call_method0("utcoffset")?;
+        if offset.is_none() {
+            return Ok(Some(local.format("%Y-%m-%dT%H:%M:%S%.f").to_string()));
+        }
+        let seconds: f64 = offset.call_method0("total_seconds")?.extract()?;
+        let offset = FixedOffset::east_opt(seconds as i32).ok_or_else(|| invalid(value))?;
+        let datetime = local
+            .and_local_timezone(offset)
+            .single()
+            .ok_or_else(|| invalid(value))?;
+        return Ok(Some(format_utc(datetime.with_timezone(&Utc))));
+    }
+    if let Ok(date) = value.downcast::<PyDate>() {
+        let date = NaiveDate::from_ymd_opt(
+            date.get_year(),
+            date.get_month().into(),
+            date.get_day().into(),
+        )
+        .ok_or_else(|| invalid(value))?;
+        return Ok(Some(date.format("%Y-%m-%d").to_string()));
+    }
+    if let Ok(time) = value.downcast::<PyTime>() {
+        let time = naive_time(time).ok_or_else(|| invalid(value))?;
+        return Ok(Some(time.format("%H:%M:%S%.f").to_string()));
+    }
+    Ok(None)
+}
+
+fn naive_time(time: &impl PyTimeAccess) -> Option<NaiveTime> {
+    NaiveTime::from_hms_micro_opt(
+        time.get_hour().into(),
+        time.get_minute().into(),
+        time.get_second().into(),
+        time.get_microsecond(),
+    )
+}
+
+/// How datetime strings compare: `datetime_tolerance` and `datetime_timezone`.
+#[derive(Debug, Clone, Copy)]
+pub struct Tolerance {
+    /// Datetimes at most this many seconds apart are equal.
+    pub seconds: f64,
+    /// Offset naive datetimes are read in, so they compare with aware ones.
+    pub timezone: Option<FixedOffset>,
+}
+
+/// The offset in a `datetime_timezone`: "UTC", "Z" or "+09:00", a
+/// `timedelta`, or a fixed-offset `tzinfo` such as `timezone.utc`.
+pub fn timezone_offset(timezone: &Bound<'_, PyAny>) -> PyResult<FixedOffset> {
+    let invalid = || {
+        PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+            "Invalid datetime_timezone: {timezone} (expected \"UTC\", an offset such as \"+09:00\", a timedelta or a tzinfo)"
+        ))
+    };
+    if let Ok(name) = timezone.extract::<String>() {
+        let offset = match name.as_str() {
+            "UTC" | "Z" => "+00:00",
+            offset => offset,
+        };
+        return offset.parse().map_err(|_| invalid());
+    }
+    let delta = if timezone.is_instance_of::<PyDelta>() {
+        timezone.clone()
+    } else if timezone.hasattr("utcoffset")? {
+        timezone.call_method1("utcoffset", (timezone.py().None(),))?
+    } else {
+        return Err(invalid());
+    };
+    if delta.is_none() {
+        return Err(invalid());
+    }
+    let seconds: f64 = delta.call_method0("total_seconds")?.extract()?;
+    FixedOffset::east_opt(seconds as i32).ok_or_else(invalid)
+}
+
+/// Seconds in a `datetime_tolerance`: a number, or a `timedelta`.
+pub fn tolerance_seconds(tolerance: &Bound<'_, PyAny>) -> PyResult<f64> {
+    if tolerance.is_instance_of::<PyDelta>() {
+        return tolerance.call_method0("total_seconds")?.extract();
+    }
+    tolerance.extract()
+}
+
+fn format_utc(datetime: DateTime<Utc>) -> String {
+    datetime.format("%Y-%m-%dT%H:%M:%S%.fZ").to_string()
+}
+
+/// Parsed datetime string; offset and local datetimes never compare equal.
+enum Instant {
+    Offset(DateTime<FixedOffset>),
+    Local(NaiveDateTime),
+}
+
+/// Parse an ISO 8601 datetime, with or without seconds and a `T`. Local
+/// datetimes are read in `timezone` when one is given.
+fn parse(text: &str, timezone: Option<FixedOffset>) -> Option<Instant> {
+    if let Ok(datetime) = DateTime::parse_from_rfc3339(text) {
+        return Some(Instant::Offset(datetime));
+    }
+    // A trailing `Z` as an offset `%:z` can parse
+    let with_offset = match text.strip_suffix(['Z', 'z']) {
+        Some(local) => Cow::Owned(format!("{local}+00:00")),
+        None => Cow::Borrowed(text),
+    };
+    let offset = [
+        "%Y-%m-%dT%H:%M:%S%.f%:z",
+        "%Y-%m-%d %H:%M:%S%.f%:z",
+        "%Y-%m-%dT%H:%M%:z",
+        "%Y-%m-%d %H:%M%:z",
+    ]
+    .iter()
+    .find_map(|format| DateTime::parse_from_str(&with_offset, format).ok());
+    if let Some(datetime) = offset {
+        return Some(Instant::Offset(datetime));
+    }
+    let local = [
+        "%Y-%m-%dT%H:%M:%S%.f",
+        "%Y-%m-%d %H:%M:%S%.f",
+        "%Y-%m-%dT%H:%M",
+        "%Y-%m-%d %H:%M",
+    ]
+    .iter()
+    .find_map(|format| NaiveDateTime::parse_from_str(text, format).ok())?;
+    match timezone {
+        Some(timezone) => local
+            .and_local_timezone(timezone)
+            .single()
+            .map(Instant::Offset),
+        None => Some(Instant::Local(local)),
+    }
+}
+
+/// Whether two strings are datetimes within the tolerance of each other.
+pub fn within_tolerance(old: &str, new: &str, tolerance: Tolerance) -> bool {
+    let delta = match (
+        parse(old, tolerance.timezone),
+        parse(new, tolerance.timezone),
+    ) {
+        (Some(Instant::Offset(old)), Some(Instant::Offset(new))) => new - old,
+        (Some(Instant::Local(old)), Some(Instant::Local(new))) => new - old,
+        _ => return false,
+    };
+    let seconds = delta.num_milliseconds().unsigned_abs() as f64 / 1000.0;
+    seconds <= tolerance.seconds
+}
+
+/// A datetime string in canonical form: offset datetimes in UTC, every
+/// instant rounded to the nearest multiple of the tolerance. `None` when
+/// `text` is not a datetime.
+pub fn canonical(text: &str, tolerance: Tolerance) -> Option<String> {
+    let round = |millis: i64| -> Option<i64> {
+        let step = tolerance.seconds * 1000.0;
+        if step < 1.0 {
+            return Some(millis);
+        }
+        let rounded = (millis as f64 / step).round() * step;
+        rounded.is_finite().then_some(rounded as i64)
+    };
+    match parse(text, tolerance.timezone)? {
+        Instant::Offset(datetime) => {
+            let millis = round(datetime.timestamp_millis())?;
+            DateTime::from_timestamp_millis(millis).map(format_utc)
+        }
+        Instant::Local(datetime) => {
+            let millis = round(datetime.and_utc().timestamp_millis())?;
+            let datetime = DateTime::from_timestamp_millis(millis)?.naive_utc();
+            Some(datetime.format("%Y-%m-%dT%H:%M:%S%.f").to_string())
+        }
+    }
+}
@@ -0,0 +1,216 @@
+//! CSV/delimited text parsing with configurable dialects, and result export.
+
+use serde_json::{Map, Value};
+
+use crate::result::Difference;
+
+/// CSV dialect, mirroring the names used by Python's `csv` module.
+#[derive(Debug, Clone)]
+pub struct CsvOptions {
+    pub delimiter: u8,
+    pub quotechar: u8,
+    pub escapechar: Option<u8>,
+    /// Lines starting with this byte are skipped.
+    pub comment: Option<u8>,
+    /// Whether the first row holds column names.
+    pub has_header: bool,
+    /// Column names to use instead of the header row.
+    pub columns: Option<Vec<String>>,
+    /// Convert numeric-looking fields to numbers.
+    pub infer_types: bool,
+    /// Abort on a malformed row instead of skipping it.
+    pub strict: bool,
+}
+
+impl Default for CsvOptions {
+    fn default() -> Self {
+        Self {
+            delimiter: b',',
+            quotechar: b'"',
+            escapechar: None,
+            comment: None,
+            has_header: true,
+            columns: None,
+            infer_types: false,
+            strict: true,
+        }
+    }
+}
+
+impl CsvOptions {
+    /// Tab-separated values with otherwise default CSV rules.
+    pub fn tsv() -> Self {
+        Self {
+            delimiter: b'\t',
+            ..Self::default()
+        }
+    }
+}
+
+/// Convert a one-character option value to the single byte the reader expects.
+pub fn dialect_byte(name: &str, value: &str) -> Result<u8, String> {
+    match value.as_bytes() {
+        [byte] if byte.is_ascii() => Ok(*byte),
+        _ => Err(format!(
+            "{name} must be a single ASCII character, got {value:?}"
+        )),
+    }
+}
+
+/// Parse CSV into a list of rows.
+///
+/// Rows are dicts keyed by `columns` or the header row; without either, each
+/// row is a list of fields so differences are reported by column index.
+/// Without `strict`, malformed rows are left out and reported in `skipped`.
+pub fn parse(
+    content: &str,
+    options: &CsvOptions,
+    skipped: &mut Vec<String>,
+) -> Result<Value, String> {
+    let mut builder = csv::ReaderBuilder::new();
+    builder
+        .delimiter(options.delimiter)
+        .quote(options.quotechar)
+        .comment(options.comment)
+        .has_headers(options.has_header);
+    if let Some(escape) = options.escapechar {
+        builder.escape(Some(escape)).double_quote(false);
+    }
+    let mut reader = builder.from_reader(content.as_bytes());
+
+    let columns: Option<Vec<String>> = match &options.columns {
+        Some(columns) => Some(columns.clone()),
+        None if options.has_header => Some(
+            reader
+                .headers()
+                .map_err(|e| e.to_string())?
+                .iter()
+                .map(str::to_string)
+                .collect(),
+        ),
+        None => None,
+    };
+
+    let mut rows = Vec::new();
+    for (index, record) in reader.records().enumerate() {
+        let row = record
+            .map_err(|e| e.to_string())
+            .and_then(|record| row_value(index, &record, columns.as_deref(), options));
+        match row {
+            Ok(row) => rows.push(row),
+            Err(e) if !options.strict => skipped.push(format!("Skipped a malformed row: {e}")),
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(Value::Array(rows))
+}
+
+fn row_value(
+    index: usize,
+    record: &csv::StringRecord,
+    columns: Option<&[String]>,
+    options: &CsvOptions,
+) -> Result<Value, String> {
+    let fields = record.iter().map(|field| {
+        if options.infer_types {
+            infer_value(field)
+        } else {
+            Value::String(field.to_string())
+        }
+    });
+
+    match columns {
+        Some(columns) => {
+            if record.len() != columns.len() {
+                let line = record.position().map_or(0, |position| position.line());
+                return Err(format!(
+                    "row {index} (line {line}) has {} fields but {} columns were given",
+                    record.len(),
+                    columns.len()
+                ));
+            }
+            Ok(Value::Object(
+                columns.iter().cloned().zip(fields).collect::<Map<_, _>>(),
+            ))
+        }
+        None => Ok(Value::Array(fields.collect())),
+    }
+}
+
+/// Convert a field to a number when it is unambiguously numeric.
+///
+/// Fields with leading zeros (`"007"`), signs like `+1`, or non-finite values
+/// stay strings so identifiers are never mangled. So do integers outside the
+/// 64-bit range, keeping their exact digits like `big_int="string"`.
+fn infer_value(field: &str) -> Value {
+    let unsigned = field.strip_prefix('-').unwrap_or(field);
+    let starts_with_digit = unsigned.starts_with(|c: char| c.is_ascii_digit());
+    let has_leading_zero =
+        unsigned.len() > 1 && unsigned.starts_with('0') && unsigned.as_bytes()[1].is_ascii_digit();
+    if !starts_with_digit || has_leading_zero {
+        return Value::String(field.to_string());
+    }
+
+    if let Ok(integer) = field.parse::<i64>() {
+        return Value::Number(integer.into());
+    }
+    if let Ok(integer) = field.parse::<u64>() {
+        return Value::Number(integer.into());
+    }
+    if unsigned.bytes().all(|byte| byte.is_ascii_digit()) {
+        return Value::String(field.to_string());
+    }
+    match field
+        .parse::<f64>()
+        .ok()
+        .and_then(serde_json::Number::from_f64)
+    {
+        Some(number) => Value::Number(number),
+        None => Value::String(field.to_string()),
+    }
+}
+
+/// Write results as delimited text with a `type,path,old_value,new_value`
+/// header. Values are JSON-encoded; the side a change does not have is empty.
+///
+/// Moves record their indices and renames the old and new key names.
+pub fn write_results(results: &[Difference], delimiter: u8) -> String {
+    let mut writer = csv::WriterBuilder::new()
+        .delimiter(delimiter)
+        .from_writer(Vec::new());
+    let mut write = |record: [&str; 4]| {
+        writer
+            .write_record(record)
+            .expect("writing to memory cannot fail");
+    };
+    write(["type", "path", "old_value", "new_value"]);
+    for result in results {
+        let (old_value, new_value) = match result {
+            Difference::Added(_, value) => (String::new(), value.to_string()),
+            Difference::Removed(_, value) => (value.to_string(), String::new()),
+            Difference::Modified(_, old, new) | Difference::TypeChanged(_, old, new) => {
+                (old.to_string(), new.to_string())
+            }
+            Difference::Moved(_, from_index, to_index) => {
+                (from_index.to_string(), to_index.to_string())
+            }
+            Difference::Renamed(path, new_path, _) => (
+                Value::from(path.as_str()).to_string(),
+                Value::from(new_path.as_str()).to_string(),
+            ),
+            Difference::Unchanged(_, value) => (value.to_string(), value.to_string()),
+            Difference::Comment(_, old, new) => (comment_field(old), comment_field(new)),
+        };
+        write([result.kind(), result.path(), &old_value, &new_value]);
+    }
+    let bytes = writer.into_inner().expect("writing to memory cannot fail");
+    String::from_utf8(bytes).expect("fields are UTF-8")
+}
+
+/// A comment as a JSON string, or empty where there is none.
+fn comment_field(comment: &Option<String>) -> String {
+    comment
+        .as_deref()
+        .map(|comment| Value::from(comment).to_string())
+        .unwrap_or_default()
+}
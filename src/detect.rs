@@ -0,0 +1,163 @@
+//! Content sniffing for files whose extension does not name their format.
+
+use pyo3::prelude::*;
+use pyo3::types::PyBytes;
+use serde::de::IgnoredAny;
+use yaml_rust2::parser::{Event, MarkedEventReceiver, Parser};
+use yaml_rust2::scanner::Marker;
+
+/// Format named by a file extension (without the dot, lowercase).
+pub fn from_extension(extension: &str) -> Option<&'static str> {
+    match extension {
+        "json" => Some("json"),
+        "yaml" | "yml" => Some("yaml"),
+        "toml" => Some("toml"),
+        "ini" | "cfg" => Some("ini"),
+        "xml" => Some("xml"),
+        "csv" => Some("csv"),
+        "tsv" => Some("tsv"),
+        _ => None,
+    }
+}
+
+/// Guess the format of `content` from its syntax.
+///
+/// JSON is tried before YAML (every JSON document is also YAML) and TOML
+/// before INI (simple TOML is also INI); YAML counts only when it holds a
+/// mapping or sequence, since almost any text is a YAML scalar.
+pub fn detect(content: &str) -> Option<&'static str> {
+    let content = content.strip_prefix('\u{feff}').unwrap_or(content);
+    let trimmed = content.trim_start();
+    if trimmed.starts_with('<') {
+        return Some("xml");
+    }
+    if trimmed.starts_with(['{', '[']) && serde_json::from_str::<IgnoredAny>(content).is_ok() {
+        return Some("json");
+    }
+    if content
+        .parse::<toml::Table>()
+        .is_ok_and(|table| !table.is_empty())
+    {
+        return Some("toml");
+    }
+    if is_yaml_collection(content) {
+        return Some("yaml");
+    }
+    if is_ini(content) {
+        return Some("ini");
+    }
+    if is_delimited(content, b'\t') {
+        return Some("tsv");
+    }
+    if is_delimited(content, b',') {
+        return Some("csv");
+    }
+    None
+}
+
+/// Detect the format of a document from its content
+///
+/// The same detection `diff_files()` and `run()` use for files whose
+/// extension does not name a format.
+///
+/// Args:
+///     content: Document text (str, or UTF-8 bytes)
+///
+/// Returns:
+///     "json", "yaml", "toml", "ini", "xml", "csv" or "tsv"
+///
+/// Raises:
+///     ValueError: If bytes are not UTF-8 or no format matches
+///
+/// Example:
+///     >>> detect_format(b"name: web\nport: 80\n")
+///     'yaml'
+#[pyfunction]
+pub fn detect_format(content: &Bound<'_, PyAny>) -> PyResult<&'static str> {
+    let text = match content.downcast::<PyBytes>() {
+        Ok(bytes) => std::str::from_utf8(bytes.as_bytes())
+            .map_err(|e| {
+                PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+                    "Content is not valid UTF-8: {e}"
+                ))
+            })?
+            .to_string(),
+        Err(_) => content.extract::<String>()?,
+    };
+    detect(&text).ok_or_else(|| {
+        PyErr::new::<pyo3::exceptions::PyValueError, _>(
+            "Could not detect the format (expected JSON, YAML, TOML, INI, XML, CSV or TSV)",
+        )
+    })
+}
+
+/// Whether `content` is YAML whose first document is a mapping or sequence.
+fn is_yaml_collection(content: &str) -> bool {
+    struct FirstNode(Option<bool>);
+
+    impl MarkedEventReceiver for FirstNode {
+        fn on_event(&mut self, event: Event, _mark: Marker) {
+            if self.0.is_none() {
+                self.0 = match event {
+                    Event::MappingStart(..) | Event::SequenceStart(..) => Some(true),
+                    Event::Scalar(..) | Event::Alias(_) => Some(false),
+                    _ => None,
+                };
+            }
+        }
+    }
+
+    let mut first = FirstNode(None);
+    Parser::new_from_str(content).load(&mut first, true).is_ok() && first.0 == Some(true)
+}
+
+/// Whether every line is a `[section]`, a `key = value` pair, a comment or
+/// the continuation of a value, with at least one pair.
+fn is_ini(content: &str) -> bool {
+    let mut pairs = 0;
+    let mut in_value = false;
+    for line in content.lines() {
+        let trimmed = line.trim();
+        if trimmed.is_empty() || trimmed.starts_with(['#', ';']) {
+            in_value = false;
+            continue;
+        }
+        if in_value && line.starts_with(char::is_whitespace) {
+            continue;
+        }
+        if trimmed.starts_with('[') && trimmed.ends_with(']') {
+            in_value = false;
+            continue;
+        }
+        match trimmed.split_once('=') {
+            Some((key, _)) if !key.trim().is_empty() && !key.contains([':', '"', ',']) => {
+                pairs += 1;
+                in_value = true;
+            }
+            _ => return false,
+        }
+    }
+    pairs > 0
+}
+
+/// Whether `content` has a header with several columns and every record has
+/// as many fields.
+fn is_delimited(content: &str, delimiter: u8) -> bool {
+    let mut reader = csv::ReaderBuilder::new()
+        .delimiter(delimiter)
+        .has_headers(false)
+        .from_reader(content.as_bytes());
+    let mut columns = None;
+    for record in reader.records() {
+        let Ok(record) = record else {
+            return false;
+        };
+        match columns {
+            None if record.len() < 2 => return false,
+            None => columns = Some(record.len()),
+            Some(columns) if record.len() != columns => return false,
+            Some(_) => {}
+        }
+    }
+    columns.is_some()
+}
@@ -15,34 +15,158 @@ Example:
 
 from __future__ import annotations
 
-from typing import Any
+import os
+import warnings
+from typing import Any, Iterable
 
 # Import from native Rust module
 try:
     from diffx_python.diffx_python import (
+        CircularReferenceError,
+        DiffOptions,
+        DiffTimeoutError,
+        MemoryLimitError,
+        ParseError,
+        ParseLimitError,
+        ParseWarning,
+        PatchIssue,
         __version__,
-        diff,
+        canonicalize,
+        convert,
+        detect_format,
+        diff_stats,
+        dump_csv,
+        dump_ini,
+        dump_json,
+        dump_toml,
+        dump_xml,
+        dump_yaml,
+        filter_by_path,
+        filter_by_type,
+        flatten,
         format_output,
+        format_path,
+        get_at_path,
+        get_preset,
+        group_by_prefix,
+        list_presets,
         parse_csv,
         parse_ini,
         parse_json,
+        parse_path,
         parse_toml,
+        parse_tsv,
         parse_xml,
         parse_yaml,
+        parse_yaml_all,
+        register_preset,
+        results_to_json,
+        run,
+        semantic_equal,
+        semantic_hash,
+        set_at_path,
+        similarity,
+        sort_results,
+        source_comments,
+        source_lines,
+        to_merge_patch,
+        to_tree,
+        unflatten,
+        validate_patch,
     )
+    from diffx_python.diffx_python import diff as _diff
+    from diffx_python.diffx_python import results_from_json as _results_from_json
 except ImportError:
     # Fallback for development mode
     from diffx_python import (  # type: ignore[attr-defined]
+        CircularReferenceError,
+        DiffOptions,
+        DiffTimeoutError,
+        MemoryLimitError,
+        ParseError,
+        ParseLimitError,
+        ParseWarning,
+        PatchIssue,
         __version__,
-        diff,
+        canonicalize,
+        convert,
+        detect_format,
+        diff_stats,
+        dump_csv,
+        dump_ini,
+        dump_json,
+        dump_toml,
+        dump_xml,
+        dump_yaml,
+        filter_by_path,
+        filter_by_type,
+        flatten,
         format_output,
+        format_path,
+        get_at_path,
+        get_preset,
+        group_by_prefix,
+        list_presets,
         parse_csv,
         parse_ini,
         parse_json,
+        parse_path,
         parse_toml,
+        parse_tsv,
         parse_xml,
         parse_yaml,
+        parse_yaml_all,
+        register_preset,
+        results_to_json,
+        run,
+        semantic_equal,
+        semantic_hash,
+        set_at_path,
+        similarity,
+        sort_results,
+        source_comments,
+        source_lines,
+        to_merge_patch,
+        to_tree,
+        unflatten,
+        validate_patch,
     )
+    from diffx_python import diff as _diff  # type: ignore[attr-defined]
+    from diffx_python import (  # type: ignore[attr-defined]
+        results_from_json as _results_from_json,
+    )
+
+
+class DiffResults(list):  # type: ignore[type-arg]
+    """List of differences returned by diff().
+
+    ``truncated`` is True when ``max_results`` stopped the diff before all
+    differences were collected; ``Unchanged`` entries do not count.
+    """
+
+    def __init__(self, results: Iterable[Any] = (), truncated: bool = False) -> None:
+        super().__init__(results)
+        self.truncated = truncated
+
+    def to_tree(self) -> dict[str, Any]:
+        """Nest the results by path; see ``to_tree()``."""
+        return to_tree(self)  # type: ignore[no-any-return]
+
+    def group_by_prefix(self, depth: int = 1) -> dict[str, list[Any]]:
+        """Group the results by path prefix; see ``group_by_prefix()``."""
+        return group_by_prefix(self, depth)  # type: ignore[no-any-return]
+
+    def filter_by_type(self, types: str | list[str]) -> DiffResults:
+        """Results of the given kinds; see ``filter_by_type()``."""
+        return DiffResults(filter_by_type(self, types), truncated=self.truncated)
+
+    def filter_by_path(self, pattern: str | list[str]) -> DiffResults:
+        """Results at or below matching paths; see ``filter_by_path()``."""
+        return DiffResults(filter_by_path(self, pattern), truncated=self.truncated)
+
+    def _repr_html_(self) -> str:
+        """Rich display in Jupyter: the HTML report as an embeddable table."""
+        return format_output(self, "html", fragment=True)  # type: ignore[no-any-return]
 
 
 class DiffError(Exception):
@@ -51,16 +175,112 @@ class DiffError(Exception):
     pass
 
 
-def diff_files(file1_path: str, file2_path: str, **kwargs: Any) -> list[dict[str, Any]]:
+class YamlAnchorWarning(UserWarning):
+    """Warning issued when only YAML anchor/alias structure changed."""
+
+    pass
+
+
+def diff(old: Any, new: Any, **kwargs: Any) -> DiffResults:
+    """
+    Compare two Python values semantically.
+
+    Args:
+        old: The old value (dict, list, or primitive)
+        new: The new value (dict, list, or primitive)
+        **kwargs: Diff options such as epsilon, array_id_key, ignore_paths,
+            or max_results (see the README for the full list), plus
+            ``options=DiffOptions(...)`` and ``preset="lenient"``; keyword
+            arguments override options, which override the preset
+
+    Returns:
+        DiffResults: List of differences found; ``truncated`` tells whether
+        ``max_results`` cut it short
+
+    Example:
+        >>> results = diff({"a": 1, "b": 2}, {"a": 3, "b": 4}, max_results=1)
+        >>> len(results), results.truncated
+        (1, True)
+    """
+    max_results = _effective_option(kwargs, "max_results")
+    if max_results is None:
+        return DiffResults(_diff(old, new, **kwargs))
+
+    # Ask for one extra difference to tell whether the limit cut the diff short
+    if isinstance(max_results, int) and max_results >= 0:
+        kwargs["max_results"] = max_results + 1
+    results = _diff(old, new, **kwargs)
+    # Unchanged entries do not count towards the limit
+    changes = [i for i, result in enumerate(results) if result["type"] != "Unchanged"]
+    if len(changes) <= max_results:
+        return DiffResults(results)
+    end = changes[max_results - 1] + 1 if max_results else 0
+    return DiffResults(results[:end], truncated=True)
+
+
+def results_from_json(content: str) -> DiffResults:
+    """
+    Read diff results stored by results_to_json().
+
+    Args:
+        content: The JSON document
+
+    Returns:
+        DiffResults: The stored results, with ``truncated`` restored
+
+    Raises:
+        ValueError: If the document is not stored results, has a newer
+            version, or holds malformed results
+
+    Example:
+        >>> stored = results_to_json(diff({"a": 1}, {"a": 2}))
+        >>> results_from_json(stored)
+        [{'new_value': 2, 'old_value': 1, 'path': 'a', 'type': 'Modified'}]
+    """
+    results, truncated = _results_from_json(content)
+    return DiffResults(results, truncated=truncated)
+
+
+def _effective_option(kwargs: dict[str, Any], name: str) -> Any:
+    """Value of a diff option after applying options= and preset=."""
+    if kwargs.get(name) is not None:
+        return kwargs[name]
+    options = kwargs.get("options")
+    if kwargs.get("preset") is not None:
+        base = options if isinstance(options, DiffOptions) else DiffOptions()
+        options = base.replace(preset=kwargs["preset"])
+    return getattr(options, name, None)
+
+
+def diff_files(
+    file1_path: str | os.PathLike[str],
+    file2_path: str | os.PathLike[str],
+    **kwargs: Any,
+) -> DiffResults:
     """
     Compare two files directly.
 
-    Auto-detects file format from extension and parses accordingly.
+    Auto-detects file format from extension and parses accordingly; files
+    whose extension names no format are sniffed with detect_format().
+    Multi-document YAML streams (e.g. Kubernetes manifests) are compared
+    document by document.
 
     Args:
-        file1_path: Path to first file
-        file2_path: Path to second file
-        **kwargs: Options passed to diff() function
+        file1_path: Path to first file (str or os.PathLike)
+        file2_path: Path to second file (str or os.PathLike)
+        **kwargs: Options passed to diff() function, parse limits
+            (hardened, max_input_bytes, ...) passed to the parsers, plus:
+            document_key: Dotted path (or list of paths) identifying each
+                document of a YAML stream, e.g. "metadata.name" or
+                ["kind", "metadata.name"]. Documents are aligned by this
+                identity instead of by position.
+            line_numbers: Add "old_line" and "new_line" to each result, the
+                line of the changed value in each JSON, YAML, TOML or INI
+                file (None where the value is absent); other formats raise
+                ValueError
+            comments: Also report comments added, removed or edited on keys
+                present in both YAML, TOML or INI files, as "Comment" results
+                with "old_comment" and "new_comment"
 
     Returns:
         List of differences found
@@ -68,35 +288,97 @@ def diff_files(file1_path: str, file2_path: str, **kwargs: Any) -> list[dict[str
     Example:
         >>> result = diff_files("old.json", "new.json")
         >>> result = diff_files("config1.yaml", "config2.yaml", epsilon=0.1)
+        >>> result = diff_files("old.yaml", "new.yaml", document_key="metadata.name")
     """
     from pathlib import Path
 
+    document_key = kwargs.pop("document_key", None)
+    line_numbers = kwargs.pop("line_numbers", False)
+    comments = kwargs.pop("comments", False)
+    parse_options = _pop_parse_options(kwargs)
+
     path1 = Path(file1_path)
     path2 = Path(file2_path)
 
+    memory_limit_mb = parse_options.get("memory_limit_mb")
+    if memory_limit_mb is not None:
+        for path in (path1, path2):
+            _check_file_size(path, memory_limit_mb)
+
     content1 = path1.read_text(encoding="utf-8")
     content2 = path2.read_text(encoding="utf-8")
 
-    ext1 = path1.suffix.lower()
-    ext2 = path2.suffix.lower()
-
-    data1 = _parse_by_extension(content1, ext1)
-    data2 = _parse_by_extension(content2, ext2)
-
-    return diff(data1, data2, **kwargs)
+    format1 = _format_of(content1, _extension(path1))
+    format2 = _format_of(content2, _extension(path2))
+    if line_numbers:
+        _check_line_format(format1)
+        _check_line_format(format2)
+
+    if format1 == "yaml" and format2 == "yaml":
+        docs1 = parse_yaml_all(content1, **parse_options)
+        docs2 = parse_yaml_all(content2, **parse_options)
+        if document_key is not None:
+            data1 = _align_documents(docs1, document_key)
+            data2 = _align_documents(docs2, document_key)
+            prefixes1 = list(data1)
+            prefixes2 = list(data2)
+        elif len(docs1) > 1 or len(docs2) > 1:
+            data1, data2 = docs1, docs2
+            prefixes1 = [f"[{index}]" for index in range(len(docs1))]
+            prefixes2 = [f"[{index}]" for index in range(len(docs2))]
+        else:
+            data1 = docs1[0] if docs1 else None
+            data2 = docs2[0] if docs2 else None
+            prefixes1 = prefixes2 = [""]
+        if comments:
+            kwargs["comments"] = (
+                _source_map(source_comments, content1, "yaml", prefixes1),
+                _source_map(source_comments, content2, "yaml", prefixes2),
+            )
+        results = diff(data1, data2, **kwargs)
+        _warn_if_only_anchors_changed(content1, content2, results, parse_options)
+        if line_numbers:
+            _add_line_numbers(
+                results,
+                _source_map(source_lines, content1, "yaml", prefixes1),
+                _source_map(source_lines, content2, "yaml", prefixes2),
+            )
+        return results
+
+    data1 = _parse_by_format(content1, format1, **parse_options)
+    data2 = _parse_by_format(content2, format2, **parse_options)
+
+    if comments:
+        kwargs["comments"] = (
+            _source_map(source_comments, content1, _comment_format(format1)),
+            _source_map(source_comments, content2, _comment_format(format2)),
+        )
+    results = diff(data1, data2, **kwargs)
+    if line_numbers:
+        _add_line_numbers(
+            results,
+            _source_map(source_lines, content1, format1),
+            _source_map(source_lines, content2, format2),
+        )
+    return results
 
 
 def diff_strings(
     content1: str, content2: str, format: str, **kwargs: Any
-) -> list[dict[str, Any]]:
+) -> DiffResults:
     """
     Compare two string contents directly.
 
     Args:
         content1: First content string
         content2: Second content string
-        format: Content format ("json", "yaml", "toml", "ini", "xml", "csv")
-        **kwargs: Options passed to diff() function
+        format: Content format ("json", "yaml", "toml", "ini", "xml", "csv", "tsv")
+        **kwargs: Options passed to diff() function, parse limits
+            (hardened, max_input_bytes, ...) passed to the parser, plus:
+            line_numbers: Add "old_line" and "new_line" to each result
+                (JSON, YAML, TOML and INI only; see diff_files)
+            comments: Also report comment changes as "Comment" results
+                (YAML, TOML and INI only; see diff_files)
 
     Returns:
         List of differences found
@@ -106,36 +388,235 @@ def diff_strings(
         >>> json2 = '{"name": "Alice", "age": 31}'
         >>> result = diff_strings(json1, json2, "json")
     """
-    data1 = _parse_by_format(content1, format)
-    data2 = _parse_by_format(content2, format)
-    return diff(data1, data2, **kwargs)
+    line_numbers = kwargs.pop("line_numbers", False)
+    comments = kwargs.pop("comments", False)
+    parse_options = _pop_parse_options(kwargs)
+    data1 = _parse_by_format(content1, format, **parse_options)
+    data2 = _parse_by_format(content2, format, **parse_options)
+    format = _FORMAT_ALIASES.get(format.lower(), format.lower())
+    if line_numbers:
+        _check_line_format(format)
+    if comments:
+        kwargs["comments"] = (
+            _source_map(source_comments, content1, _comment_format(format)),
+            _source_map(source_comments, content2, _comment_format(format)),
+        )
+    results = diff(data1, data2, **kwargs)
+    if format == "yaml":
+        _warn_if_only_anchors_changed(content1, content2, results, parse_options)
+    if line_numbers:
+        _add_line_numbers(
+            results,
+            _source_map(source_lines, content1, format),
+            _source_map(source_lines, content2, format),
+        )
+    return results
+
+
+_EXTENSION_FORMATS = {
+    ".json": "json",
+    ".yaml": "yaml",
+    ".yml": "yaml",
+    ".toml": "toml",
+    ".ini": "ini",
+    ".cfg": "ini",
+    ".xml": "xml",
+    ".csv": "csv",
+    ".tsv": "tsv",
+}
+
+# Other names accepted for a format
+_FORMAT_ALIASES = {"yml": "yaml", "cfg": "ini"}
+
+# Formats whose parsed paths can be traced back to source lines
+_LINE_FORMATS = ("json", "yaml", "toml", "ini")
+
+# Formats with comments that can be attached to keys
+_COMMENT_FORMATS = ("yaml", "toml", "ini")
+
+_TOML_LOCKFILES = ("cargo.lock", "poetry.lock")
+
+_PARSE_OPTIONS = (
+    "hardened",
+    "memory_limit_mb",
+    "max_input_bytes",
+    "max_nesting_depth",
+    "max_string_length",
+    "max_nodes",
+    "yaml_anchors",
+    "case_insensitive_keys",
+    "case_insensitive_sections",
+    "default_section",
+    "interpolation",
+    "attribute_prefix",
+    "text_key",
+    "merge_attributes",
+    "delimiter",
+    "quotechar",
+    "escapechar",
+    "comment",
+    "has_header",
+    "columns",
+    "infer_types",
+    "strict",
+    "duplicate_keys",
+)
+
+
+# Options that apply to both parsing and diffing
+_SHARED_OPTIONS = ("memory_limit_mb",)
+
+
+def _pop_parse_options(kwargs: dict[str, Any]) -> dict[str, Any]:
+    """Split parser options out of kwargs destined for diff()."""
+    parse_options = {
+        name: kwargs.pop(name) for name in _PARSE_OPTIONS if name in kwargs
+    }
+    for name in _SHARED_OPTIONS:
+        if name in parse_options:
+            kwargs[name] = parse_options[name]
+    return parse_options
+
+
+def _check_file_size(path: Any, memory_limit_mb: int) -> None:
+    """Reject files that cannot fit in the memory budget before reading them."""
+    size = path.stat().st_size
+    if size > memory_limit_mb * 1024 * 1024:
+        raise MemoryLimitError(
+            f"{path} is {size} bytes, exceeding memory_limit_mb={memory_limit_mb}"
+        )
+
+
+def _warn_if_only_anchors_changed(
+    content1: str, content2: str, results: list[Any], parse_options: dict[str, Any]
+) -> None:
+    """Warn when resolved YAML is equal but its anchor/alias structure differs."""
+    changed = any(result["type"] != "Unchanged" for result in results)
+    if changed or parse_options.get("yaml_anchors") != "resolve":
+        return
+
+    preserved = dict(parse_options, yaml_anchors="preserve")
+    with warnings.catch_warnings():
+        # Skipped documents were already reported by the first parse
+        warnings.simplefilter("ignore", ParseWarning)
+        anchors_differ = parse_yaml_all(content1, **preserved) != parse_yaml_all(
+            content2, **preserved
+        )
+    if anchors_differ:
+        warnings.warn(
+            "YAML anchor/alias structure changed but resolved values are identical",
+            YamlAnchorWarning,
+            stacklevel=3,
+        )
+
+
+def _source_map(
+    scan: Any, content: str, format: str | None, prefixes: list[str] | None = None
+) -> dict[str, Any]:
+    """Results of source_lines() or source_comments() keyed by diff path.
+
+    `prefixes` name the documents of a stream.
+    """
+    if format is None:
+        return {}
+    try:
+        documents = scan(content, format)
+    except ValueError:
+        # A strict=False parse skipped a document the scan cannot read
+        return {}
+    if prefixes is None:
+        return documents[0] if documents else {}
+    by_path: dict[str, Any] = {}
+    for prefix, document in zip(prefixes, documents):
+        for path, found in document.items():
+            separator = "." if prefix and not path.startswith("[") else ""
+            by_path[f"{prefix}{separator}{path}"] = found
+    return by_path
+
+
+def _add_line_numbers(
+    results: list[Any], old_lines: dict[str, int], new_lines: dict[str, int]
+) -> None:
+    """Set "old_line" and "new_line" on each result from the source line maps."""
+    for result in results:
+        path = result["path"]
+        if not isinstance(path, str):
+            path = format_path(path)
+        new_path = result.get("new_path", path)
+        if not isinstance(new_path, str):
+            new_path = format_path(new_path)
+        if result["type"] == "Moved":
+            new_path = f"{path[: path.rfind('[')]}[{result['to_index']}]"
+        kind = result["type"]
+        result["old_line"] = None if kind == "Added" else old_lines.get(path)
+        result["new_line"] = None if kind == "Removed" else new_lines.get(new_path)
+
+
+def _align_documents(
+    documents: list[Any], document_key: str | list[str]
+) -> dict[str, Any]:
+    """Key each document by its identity so streams diff by identity, not order."""
+    key_paths = [document_key] if isinstance(document_key, str) else list(document_key)
+
+    aligned: dict[str, Any] = {}
+    for index, document in enumerate(documents):
+        parts = [_lookup_dotted(document, key_path) for key_path in key_paths]
+        if any(part is None for part in parts):
+            identity = f"[{index}]"
+        else:
+            identity = "/".join(str(part) for part in parts)
+        if identity in aligned:
+            raise DiffError(
+                f"Duplicate document key '{identity}'; "
+                "use a list of paths such as ['kind', 'metadata.name']"
+            )
+        aligned[identity] = document
+    return aligned
+
+
+def _lookup_dotted(document: Any, key_path: str) -> Any:
+    """Resolve a dotted key path in nested dicts, returning None when absent."""
+    current = document
+    for key in key_path.split("."):
+        if not isinstance(current, dict) or key not in current:
+            return None
+        current = current[key]
+    return current
+
+
+def _extension(path: Any) -> str:
+    """File extension used to pick a parser; TOML lockfiles count as .toml."""
+    if path.name.lower() in _TOML_LOCKFILES:
+        return ".toml"
+    return path.suffix.lower()
+
+
+def _format_of(content: str, ext: str) -> str:
+    """Format named by a file extension, or sniffed from the content."""
+    format = _EXTENSION_FORMATS.get(ext)
+    if format is not None:
+        return format
+    try:
+        return detect_format(content)
+    except ValueError as e:
+        raise DiffError(f"Unsupported file format: {ext}") from e
 
 
-def _parse_by_extension(content: str, ext: str) -> Any:
-    """Parse content based on file extension."""
-    parsers = {
-        ".json": parse_json,
-        ".yaml": parse_yaml,
-        ".yml": parse_yaml,
-        ".toml": parse_toml,
-        ".ini": parse_ini,
-        ".cfg": parse_ini,
-        ".xml": parse_xml,
-        ".csv": parse_csv,
-    }
+def _check_line_format(format: str) -> None:
+    """Reject line_numbers for formats without a line map."""
+    if format not in _LINE_FORMATS:
+        raise ValueError(
+            "line_numbers is only available for JSON, YAML, TOML and INI, "
+            f"not {format!r}"
+        )
 
-    parser = parsers.get(ext)
-    if parser:
-        return parser(content)
 
-    # Try JSON as fallback
-    try:
-        return parse_json(content)
-    except Exception as e:
-        raise DiffError(f"Unsupported file format: {ext}") from e
+def _comment_format(format: str) -> str | None:
+    """The format to scan for comments, or None when it has none."""
+    return format if format in _COMMENT_FORMATS else None
 
 
-def _parse_by_format(content: str, format: str) -> Any:
+def _parse_by_format(content: str, format: str, **parse_options: Any) -> Any:
     """Parse content based on format string."""
     parsers = {
         "json": parse_json,
@@ -146,11 +627,12 @@ def _parse_by_format(content: str, format: str) -> Any:
         "cfg": parse_ini,
         "xml": parse_xml,
         "csv": parse_csv,
+        "tsv": parse_tsv,
     }
 
     parser = parsers.get(format.lower())
     if parser:
-        return parser(content)
+        return parser(content, **parse_options)
 
     raise DiffError(f"Unsupported format: {format}")
 
@@ -160,17 +642,65 @@ __all__ = [
     "__version__",
     # Main function
     "diff",
+    "semantic_equal",
+    "diff_stats",
+    "similarity",
+    "canonicalize",
+    "semantic_hash",
+    "DiffOptions",
+    "DiffResults",
+    "PatchIssue",
     # Parser functions
     "parse_json",
     "parse_yaml",
+    "parse_yaml_all",
     "parse_toml",
     "parse_csv",
+    "parse_tsv",
     "parse_ini",
     "parse_xml",
+    "convert",
+    # Serializer functions
+    "dump_json",
+    "dump_yaml",
+    "dump_toml",
+    "dump_xml",
+    "dump_ini",
+    "dump_csv",
     # Utility functions
     "format_output",
+    "to_tree",
+    "group_by_prefix",
+    "sort_results",
+    "filter_by_type",
+    "filter_by_path",
+    "flatten",
+    "unflatten",
+    "parse_path",
+    "format_path",
+    "get_at_path",
+    "set_at_path",
+    "results_to_json",
+    "results_from_json",
+    "to_merge_patch",
+    "validate_patch",
+    "source_lines",
+    "source_comments",
+    "detect_format",
+    "register_preset",
+    "get_preset",
+    "list_presets",
     "diff_files",
     "diff_strings",
-    # Exception
+    "run",
+    # Exceptions
+    "CircularReferenceError",
     "DiffError",
+    "DiffTimeoutError",
+    "MemoryLimitError",
+    "ParseError",
+    "ParseLimitError",
+    # Warnings
+    "ParseWarning",
+    "YamlAnchorWarning",
 ]
@@ -0,0 +1,69 @@
+//! Detection of object keys that appear twice in JSON and YAML sources.
+//!
+//! Parsers keep the last value of a repeated key, which hides mistakes in
+//! hand-edited configs; the source scans in `positions` find the repeats.
+
+use crate::positions;
+
+/// What happens when an object key appears twice.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum DuplicateKeys {
+    /// Keep the last value, like `json.loads` and most YAML loaders.
+    #[default]
+    Ignore,
+    /// Keep the last value and issue a `ParseWarning` for each repeat.
+    Warn,
+    /// Raise `ParseError` at the first repeat.
+    Error,
+}
+
+impl DuplicateKeys {
+    pub fn parse(mode: &str) -> Result<Self, String> {
+        match mode {
+            "ignore" => Ok(Self::Ignore),
+            "warn" => Ok(Self::Warn),
+            "error" => Ok(Self::Error),
+            other => Err(format!(
+                "Invalid duplicate_keys: {other:?} (expected \"ignore\", \"warn\" or \"error\")"
+            )),
+        }
+    }
+}
+
+/// A repeated key; positions are 1-based.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Duplicate {
+    /// Index of the document in a YAML stream (always 0 for JSON).
+    pub document: usize,
+    pub path: String,
+    pub line: usize,
+    pub column: usize,
+    /// Line of the first occurrence.
+    pub first_line: usize,
+}
+
+impl Duplicate {
+    /// `duplicate key 'a.b' at line 4 column 3 (first at line 2)`, in the
+    /// position syntax `ParseError` reads.
+    pub fn describe(&self, stream: bool) -> String {
+        let document = if stream {
+            format!(" in document {}", self.document)
+        } else {
+            String::new()
+        };
+        format!(
+            "duplicate key '{}'{document} at line {} column {} (first at line {})",
+            self.path, self.line, self.column, self.first_line
+        )
+    }
+}
+
+/// Repeated keys in a JSON document that has already parsed successfully.
+pub fn find_json(content: &str) -> Vec<Duplicate> {
+    positions::scan_json(content).duplicates
+}
+
+/// Repeated keys in every document of a YAML stream.
+pub fn find_yaml(content: &str) -> Result<Vec<Duplicate>, String> {
+    positions::scan_yaml(content).map(|scan| scan.duplicates)
+}
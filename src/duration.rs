@@ -0,0 +1,83 @@
+//! Duration strings such as `1h30m`, `300ms` or `PT1H`.
+
+/// Parse a duration string into seconds.
+///
+/// Accepts Go / Prometheus style sequences of a number and a unit (`ns`,
+/// `us` or `µs`, `ms`, `s`, `m`, `h`, `d`, `w`), and ISO 8601 durations of
+/// weeks, days, hours, minutes and seconds (`P1DT2H`, `PT0.5S`). Years and
+/// months have no fixed length and are not accepted. Terms are summed in
+/// whole nanoseconds, so `1.1h` and `66m` are exactly equal.
+pub fn parse_seconds(text: &str) -> Option<f64> {
+    let nanoseconds = match text.strip_prefix(['P', 'p']) {
+        Some(iso) => parse_iso(iso)?,
+        None => parse_units(text)?,
+    };
+    Some(nanoseconds / 1e9)
+}
+
+fn parse_units(text: &str) -> Option<f64> {
+    if text.is_empty() {
+        return None;
+    }
+    let is_number = |c: char| c.is_ascii_digit() || c == '.';
+    let mut rest = text;
+    let mut total = 0.0;
+    while !rest.is_empty() {
+        let (number, tail) = rest.split_at(rest.find(|c| !is_number(c)).unwrap_or(rest.len()));
+        let (unit, tail) = tail.split_at(tail.find(is_number).unwrap_or(tail.len()));
+        total += term(number, unit_nanoseconds(unit)?)?;
+        rest = tail;
+    }
+    Some(total)
+}
+
+fn unit_nanoseconds(unit: &str) -> Option<f64> {
+    Some(match unit {
+        "ns" => 1.0,
+        "us" | "µs" | "μs" => 1e3,
+        "ms" => 1e6,
+        "s" => 1e9,
+        "m" => 60e9,
+        "h" => 3600e9,
+        "d" => 86_400e9,
+        "w" => 604_800e9,
+        _ => return None,
+    })
+}
+
+/// The part of an ISO 8601 duration after `P`.
+fn parse_iso(text: &str) -> Option<f64> {
+    let (date, time) = match text.split_once(['T', 't']) {
+        Some((_, "")) => return None,
+        Some((date, time)) => (date, time),
+        None if text.is_empty() => return None,
+        None => (text, ""),
+    };
+    let date = designated(date, &[('W', 604_800e9), ('D', 86_400e9)])?;
+    let time = designated(time, &[('H', 3600e9), ('M', 60e9), ('S', 1e9)])?;
+    Some(date + time)
+}
+
+/// Sum `<number><designator>` pairs, whose designators must come in the
+/// order of `designators`.
+fn designated(text: &str, designators: &[(char, f64)]) -> Option<f64> {
+    let mut rest = text;
+    let mut allowed = designators;
+    let mut total = 0.0;
+    while !rest.is_empty() {
+        let end = rest.find(|c: char| c.is_ascii_alphabetic())?;
+        let designator = rest[end..].chars().next()?.to_ascii_uppercase();
+        let position = allowed.iter().position(|&(d, _)| d == designator)?;
+        // ISO 8601 allows a decimal comma
+        total += term(&rest[..end].replace(',', "."), allowed[position].1)?;
+        allowed = &allowed[position + 1..];
+        rest = &rest[end + 1..];
+    }
+    Some(total)
+}
+
+/// `number` units of `nanoseconds` each, rounded to a whole nanosecond.
+fn term(number: &str, nanoseconds: f64) -> Option<f64> {
+    let number: f64 = number.parse().ok()?;
+    Some((number * nanoseconds).round())
+}
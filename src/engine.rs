@@ -0,0 +1,1833 @@
+//! Binding-side diff engine for options diffx-core does not support.
+//!
+//! Mirrors diffx-core's traversal and path format (`a.b`, `a[0]`,
+//! `a[id=1]`) so results look the same whichever engine produced them.
+
+use diffx_core::DiffOptions;
+use pyo3::prelude::*;
+use regex::Regex;
+use serde_json::{Map, Number, Value};
+use std::borrow::Cow;
+use std::cell::{Cell, RefCell};
+use std::collections::{HashMap, HashSet};
+use std::time::{Duration, Instant};
+
+use crate::align::{self, Edit};
+use crate::datetime::{self, Tolerance};
+use crate::duration;
+use crate::errors::{DiffTimeoutError, MemoryLimitError};
+use crate::ip;
+use crate::memory::MemoryBudget;
+use crate::paths::PathPattern;
+use crate::quantity;
+use crate::result::Difference;
+use crate::version::Version;
+use crate::{json_value_to_python, python_to_json_value};
+
+/// How array elements are paired when no id key applies.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ArrayOrder {
+    /// Compare element-by-element at the same index.
+    #[default]
+    Ordered,
+    /// Compare as multisets; only unmatched elements are reported.
+    Unordered,
+}
+
+impl ArrayOrder {
+    pub fn parse(mode: &str) -> Result<Self, String> {
+        match mode {
+            "ordered" => Ok(Self::Ordered),
+            "unordered" => Ok(Self::Unordered),
+            other => Err(format!(
+                "Invalid array_order: {other:?} (expected \"ordered\" or \"unordered\")"
+            )),
+        }
+    }
+}
+
+/// How ordered arrays are aligned before comparing elements.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ArrayStrategy {
+    /// Pair elements at the same index.
+    #[default]
+    Index,
+    /// Align with a minimal edit script so insertions and deletions do not
+    /// shift every following element.
+    Lcs,
+}
+
+impl ArrayStrategy {
+    pub fn parse(strategy: &str) -> Result<Self, String> {
+        match strategy {
+            "index" => Ok(Self::Index),
+            "lcs" => Ok(Self::Lcs),
+            other => Err(format!(
+                "Invalid array_strategy: {other:?} (expected \"index\" or \"lcs\")"
+            )),
+        }
+    }
+}
+
+/// Which parts of the values a diff compares.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Compare {
+    /// Keys, array elements and leaf values.
+    #[default]
+    Values,
+    /// Only the shape: which keys exist and the JSON type of every value.
+    /// Array elements are compared by position and extra elements are not
+    /// reported, since an array's length is content rather than shape.
+    Structure,
+    /// Only the JSON type at paths both sides have, like `Structure` without
+    /// reporting added or removed keys.
+    Types,
+    /// Only keys one side has and the other lacks; values, types included,
+    /// are not compared.
+    Keys,
+}
+
+impl Compare {
+    pub fn parse(mode: &str) -> Result<Self, String> {
+        match mode {
+            "values" => Ok(Self::Values),
+            "structure" => Ok(Self::Structure),
+            "types" => Ok(Self::Types),
+            "keys" => Ok(Self::Keys),
+            other => Err(format!(
+                "Invalid compare: {other:?} (expected \"values\", \"structure\", \"types\" or \"keys\")"
+            )),
+        }
+    }
+}
+
+/// Whitespace differences to ignore, each on its own; `ignore_whitespace`
+/// instead drops all whitespace.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Whitespace {
+    /// Strip leading and trailing whitespace.
+    pub trim: bool,
+    /// Replace every run of whitespace with a single space.
+    pub collapse: bool,
+    /// Read `\r\n` and a lone `\r` as `\n`.
+    pub line_endings: bool,
+}
+
+impl Whitespace {
+    fn apply(&self, text: &str) -> String {
+        let mut text = text.to_string();
+        if self.line_endings && text.contains('\r') {
+            text = text.replace("\r\n", "\n").replace('\r', "\n");
+        }
+        if self.collapse {
+            let mut collapsed = String::with_capacity(text.len());
+            let mut in_run = false;
+            for c in text.chars() {
+                if !c.is_whitespace() {
+                    collapsed.push(c);
+                } else if !in_run {
+                    collapsed.push(' ');
+                }
+                in_run = c.is_whitespace();
+            }
+            text = collapsed;
+        }
+        if self.trim {
+            text = text.trim().to_string();
+        }
+        text
+    }
+}
+
+/// Unicode normalization form applied to strings before they are compared.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UnicodeForm {
+    Nfc,
+    Nfd,
+    Nfkc,
+    Nfkd,
+}
+
+impl UnicodeForm {
+    pub fn parse(form: &str) -> Result<Self, String> {
+        match form {
+            "NFC" => Ok(Self::Nfc),
+            "NFD" => Ok(Self::Nfd),
+            "NFKC" => Ok(Self::Nfkc),
+            "NFKD" => Ok(Self::Nfkd),
+            other => Err(format!(
+                "Invalid unicode_normalize: {other:?} (expected \"NFC\", \"NFD\", \"NFKC\" or \"NFKD\")"
+            )),
+        }
+    }
+
+    /// The form's name as `unicodedata.normalize` takes it.
+    fn name(self) -> &'static str {
+        match self {
+            Self::Nfc => "NFC",
+            Self::Nfd => "NFD",
+            Self::Nfkc => "NFKC",
+            Self::Nfkd => "NFKD",
+        }
+    }
+}
+
+/// Spellings `coerce_bools` reads as `true` or `false`: strings, matched
+/// case-insensitively, and numbers.
+#[derive(Debug, Clone)]
+pub struct BoolTable {
+    truthy: Vec<Value>,
+    falsy: Vec<Value>,
+}
+
+impl BoolTable {
+    pub fn new(truthy: Vec<Value>, falsy: Vec<Value>) -> Result<Self, String> {
+        let lowercase = |spellings: Vec<Value>| -> Result<Vec<Value>, String> {
+            spellings
+                .into_iter()
+                .map(|spelling| match spelling {
+                    Value::String(text) => Ok(Value::String(text.to_lowercase())),
+                    Value::Number(_) => Ok(spelling),
+                    other => Err(format!(
+                        "coerce_bools spellings must be strings or numbers, not {other}"
+                    )),
+                })
+                .collect()
+        };
+        let table = Self {
+            truthy: lowercase(truthy)?,
+            falsy: lowercase(falsy)?,
+        };
+        if let Some(both) = table
+            .truthy
+            .iter()
+            .find(|spelling| table.falsy.contains(spelling))
+        {
+            return Err(format!(
+                "coerce_bools spelling {both} is both true and false"
+            ));
+        }
+        Ok(table)
+    }
+
+    /// `value` as a boolean, if it is one or one of its spellings.
+    fn read(&self, value: &Value) -> Option<bool> {
+        let spelled = |spellings: &[Value]| {
+            spellings.iter().any(|spelling| match (spelling, value) {
+                (Value::String(spelling), Value::String(text)) => text.to_lowercase() == *spelling,
+                (Value::Number(spelling), Value::Number(number)) => {
+                    spelling.as_f64() == number.as_f64()
+                }
+                _ => false,
+            })
+        };
+        match value {
+            Value::Bool(value) => Some(*value),
+            _ if spelled(&self.truthy) => Some(true),
+            _ if spelled(&self.falsy) => Some(false),
+            _ => None,
+        }
+    }
+}
+
+impl Default for BoolTable {
+    fn default() -> Self {
+        let spellings = |strings: [&str; 5], number: i64| {
+            strings
+                .into_iter()
+                .map(Value::from)
+                .chain([Value::from(number)])
+                .collect()
+        };
+        Self {
+            truthy: spellings(["true", "yes", "y", "on", "1"], 1),
+            falsy: spellings(["false", "no", "n", "off", "0"], 0),
+        }
+    }
+}
+
+/// Fields that together identify an array element, e.g. `["region", "name"]`
+/// or `["metadata.uid"]`.
+#[derive(Debug, Clone)]
+pub struct IdKey {
+    fields: Vec<String>,
+}
+
+impl IdKey {
+    pub fn new(fields: Vec<String>) -> Self {
+        Self { fields }
+    }
+
+    /// Whether diffx-core can handle this key itself (one top-level field).
+    pub fn is_simple(fields: &[String]) -> bool {
+        matches!(fields, [field] if !field.contains('.'))
+    }
+
+    /// Values of every field, or `None` when any of them is missing.
+    fn identity<'v>(&self, item: &'v Value) -> Option<Vec<&'v Value>> {
+        self.fields
+            .iter()
+            .map(|field| lookup(item, field))
+            .collect()
+    }
+
+    /// Path segment such as `[id=1]` or `[region="us",name="web"]`.
+    fn segment(&self, identity: &[&Value]) -> String {
+        let parts: Vec<String> = self
+            .fields
+            .iter()
+            .zip(identity)
+            .map(|(field, value)| format!("{field}={value}"))
+            .collect();
+        format!("[{}]", parts.join(","))
+    }
+}
+
+/// Look up `field`, falling back to a dotted path (`metadata.uid`) when no
+/// key with that literal name exists.
+pub fn lookup<'v>(item: &'v Value, field: &str) -> Option<&'v Value> {
+    item.get(field).or_else(|| {
+        field
+            .split('.')
+            .try_fold(item, |value, segment| value.get(segment))
+    })
+}
+
+/// Whether some array in `value` holds two elements with the same `field`.
+/// diffx-core keeps only one of them, so such arrays need this engine.
+pub fn has_duplicate_ids(value: &Value, field: &str) -> bool {
+    match value {
+        Value::Object(map) => map.values().any(|value| has_duplicate_ids(value, field)),
+        Value::Array(items) => {
+            let mut ids = HashSet::new();
+            items
+                .iter()
+                .filter_map(|item| item.get(field))
+                .any(|id| !ids.insert(id.to_string()))
+                || items.iter().any(|item| has_duplicate_ids(item, field))
+        }
+        _ => false,
+    }
+}
+
+/// Python callables applied to values before they are compared.
+#[derive(Debug)]
+pub enum Normalize {
+    /// Applied to every scalar value.
+    All(PyObject),
+    /// Applied to values at paths matching a pattern; the first match wins.
+    ByPath(Vec<(PathPattern, PyObject)>),
+}
+
+/// Options handled only by this engine.
+#[derive(Debug, Default)]
+pub struct EngineOptions {
+    /// Composite or nested id key; overrides the core `array_id_key`.
+    pub array_id_key: Option<IdKey>,
+    pub compare: Compare,
+    pub array_order: ArrayOrder,
+    pub array_strategy: ArrayStrategy,
+    /// Report array elements that only changed position as `Moved`.
+    pub detect_moves: bool,
+    /// Report a removed and an added key with equal values as `Renamed`.
+    pub detect_renames: bool,
+    /// Treat a key holding `[]` or `{}` like an absent key.
+    pub empty_as_missing: bool,
+    /// Match object keys case-insensitively; paths use the old document's
+    /// spelling.
+    pub ignore_key_case: bool,
+    /// Normalize strings to this form before comparing them.
+    pub unicode_normalize: Option<UnicodeForm>,
+    /// Also match object keys by their `unicode_normalize` form.
+    pub unicode_normalize_keys: bool,
+    pub whitespace: Whitespace,
+    /// Compare strings holding JSON numbers (`"8080"`) as those numbers.
+    pub coerce_numeric_strings: bool,
+    /// Integers and floats holding the same value are equal (`1` and `1.0`).
+    pub ignore_number_format: bool,
+    /// A boolean equals the strings and numbers spelling it (`"yes"`, `1`).
+    pub coerce_bools: Option<BoolTable>,
+    /// Compare duration strings (`"1h"`, `"3600s"`, `"PT1H"`) by length.
+    pub compare_durations: bool,
+    /// Compare Kubernetes / SI quantities (`"1Gi"`, `"1024Mi"`, `"500m"`) by
+    /// magnitude, with each other and with numbers.
+    pub compare_quantities: bool,
+    /// Compare version strings (`"v1.2"`, `"1.2.0+build"`) by SemVer
+    /// precedence, ahead of the other string coercions.
+    pub compare_versions: bool,
+    /// Compare IP addresses and CIDR blocks in their canonical form
+    /// (`"10.00.000.1"`, `"2001:DB8:0::1"`).
+    pub compare_ips: bool,
+    /// Compare datetime strings as instants (`datetime_tolerance`,
+    /// `datetime_timezone`).
+    pub datetimes: Option<Tolerance>,
+    /// Also report equal values as `Unchanged`.
+    pub report_unchanged: bool,
+    /// Stop collecting once this many results were reported.
+    pub max_results: Option<usize>,
+    /// Abort with `DiffTimeoutError` when the diff takes longer.
+    pub timeout: Option<Duration>,
+    /// Containers nested deeper are compared as opaque values.
+    pub max_depth: Option<usize>,
+    /// Abort with `MemoryLimitError` when the diff allocates more.
+    pub memory_limit_mb: Option<usize>,
+    /// Subtrees excluded from the diff.
+    pub ignore_paths: Vec<PathPattern>,
+    /// When non-empty, only these subtrees are diffed.
+    pub include_paths: Vec<PathPattern>,
+    /// Only results whose path matches are reported.
+    pub path_filter_regex: Option<Regex>,
+    /// `path_filter` given as JSONPath: only results at or below a match are
+    /// reported, and subtrees that cannot contain one are skipped.
+    pub path_filter_jsonpath: Option<PathPattern>,
+    /// Strings that both match are treated as equal (timestamps, UUIDs, ...).
+    pub ignore_values_regex: Option<Regex>,
+    /// Numeric tolerances for matching paths; override the core `epsilon`.
+    pub epsilons: Vec<(PathPattern, f64)>,
+    /// Tolerance as a fraction of the larger magnitude (`0.01` is 1%).
+    pub epsilon_relative: Option<f64>,
+    /// Python callables `fn(old, new) -> bool` deciding equality at matching
+    /// paths; the first matching pattern wins.
+    pub comparators: Vec<(PathPattern, PyObject)>,
+    /// Applied to both sides before comparing; results report the
+    /// normalized values.
+    pub normalize: Option<Normalize>,
+    /// Comments on the keys of each side, by path, as `source_comments`
+    /// returns them; differing comments are reported as `Comment`.
+    pub comments: Option<Comments>,
+}
+
+/// Comments on the keys of the old and the new document, by path.
+#[derive(Debug, Default)]
+pub struct Comments {
+    pub old: HashMap<String, Option<String>>,
+    pub new: HashMap<String, Option<String>>,
+}
+
+struct Context<'a> {
+    options: &'a DiffOptions,
+    engine: &'a EngineOptions,
+    id_key: Option<IdKey>,
+    /// First error raised by a Python callback; the diff fails with it.
+    error: RefCell<Option<PyErr>>,
+    /// Set while `values_equal` checks equality: output-only options such as
+    /// path filters and `report_unchanged` must not affect the answer.
+    probing: Cell<bool>,
+    deadline: Option<Instant>,
+    memory_budget: Option<MemoryBudget>,
+    /// Nesting depth of the containers currently being compared.
+    depth: Cell<usize>,
+    /// Values visited so far; resource limits are checked every few hundred.
+    steps: Cell<u32>,
+    /// Value pairs compared, including equality checks of whole subtrees.
+    nodes_compared: Cell<usize>,
+    /// Deepest nesting level compared; the root is level 0.
+    deepest: Cell<usize>,
+}
+
+/// How many values are visited between resource limit checks.
+const LIMIT_CHECK_INTERVAL: u32 = 256;
+
+/// How much of the inputs a diff examined.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct DiffStats {
+    pub nodes_compared: usize,
+    pub max_depth: usize,
+}
+
+/// Diff two values, honoring both the core options and `engine` options.
+pub fn diff(
+    old: &Value,
+    new: &Value,
+    options: &DiffOptions,
+    engine: &EngineOptions,
+) -> PyResult<Vec<Difference>> {
+    diff_with_stats(old, new, options, engine).map(|(results, _)| results)
+}
+
+pub fn diff_with_stats(
+    old: &Value,
+    new: &Value,
+    options: &DiffOptions,
+    engine: &EngineOptions,
+) -> PyResult<(Vec<Difference>, DiffStats)> {
+    let context = Context::new(options, engine);
+    let mut results = Vec::new();
+    context.diff_value(old, new, "", &mut results);
+    context.report_comments(&mut results);
+    let stats = DiffStats {
+        nodes_compared: context.nodes_compared.get(),
+        max_depth: context.deepest.get(),
+    };
+    match context.error.into_inner() {
+        Some(error) => Err(error),
+        None => Ok((results, stats)),
+    }
+}
+
+/// Cut `results` right after their first `max_results` differences;
+/// `Unchanged` entries do not count. Returns whether a difference was cut off.
+pub fn truncate(results: &mut Vec<Difference>, max_results: usize) -> bool {
+    let changes: Vec<usize> = (0..results.len())
+        .filter(|&index| results[index].is_change())
+        .collect();
+    if changes.len() <= max_results {
+        return false;
+    }
+    let end = max_results
+        .checked_sub(1)
+        .map_or(0, |last| changes[last] + 1);
+    results.truncate(end);
+    true
+}
+
+/// Canonical form of `value` under the options: ignored keys and paths
+/// removed, `normalize` applied, strings folded as `ignore_case` and the
+/// whitespace options compare them, strings matching `ignore_values_regex`
+/// replaced by `IGNORED_VALUE`, numbers rounded to the nearest multiple of
+/// their epsilon, and arrays whose order the diff disregards (`array_order`
+/// "unordered", or every element identified by `array_id_key` without
+/// `detect_moves`) sorted. Keys holding empty collections are dropped under
+/// `empty_as_missing`, numeric strings become numbers under
+/// `coerce_numeric_strings`, integral floats integers under
+/// `ignore_number_format`, versions written as `MAJOR.MINOR.PATCH[-PRE]`
+/// under `compare_versions`, durations written in seconds (`"3600s"`) under
+/// `compare_durations`, quantities written as numbers under
+/// `compare_quantities`, IP addresses in their canonical form under
+/// `compare_ips`, datetimes in UTC rounded to the nearest multiple of the
+/// `datetime_tolerance` (naive ones too under `datetime_timezone`), strings put in the `unicode_normalize` form (keys
+/// too under `unicode_normalize_keys`), and keys lowercased under
+/// `ignore_key_case`. Object keys are always sorted.
+///
+/// Documents that diff as equal mostly share a canonical form; numbers and
+/// datetimes just either side of a rounding boundary, `epsilon_relative`, `coerce_bools`
+/// and `comparators` are the exceptions.
+pub fn canonicalize(
+    value: &Value,
+    options: &DiffOptions,
+    engine: &EngineOptions,
+) -> PyResult<Value> {
+    let context = Context::new(options, engine);
+    let canonical = context.canonical(value, "").unwrap_or(Value::Null);
+    match context.error.into_inner() {
+        Some(error) => Err(error),
+        None => Ok(canonical),
+    }
+}
+
+/// Stands in for strings matching `ignore_values_regex` in canonical forms.
+const IGNORED_VALUE: &str = "<ignored>";
+
+fn join_key(path: &str, key: &str) -> String {
+    if path.is_empty() {
+        key.to_string()
+    } else {
+        format!("{path}.{key}")
+    }
+}
+
+impl<'a> Context<'a> {
+    fn new(options: &'a DiffOptions, engine: &'a EngineOptions) -> Self {
+        let id_key = engine.array_id_key.clone().or_else(|| {
+            options
+                .array_id_key
+                .clone()
+                .map(|field| IdKey::new(vec![field]))
+        });
+        Context {
+            options,
+            engine,
+            id_key,
+            error: RefCell::new(None),
+            probing: Cell::new(false),
+            deadline: engine.timeout.map(|timeout| Instant::now() + timeout),
+            memory_budget: engine.memory_limit_mb.map(MemoryBudget::start),
+            depth: Cell::new(0),
+            steps: Cell::new(0),
+            nodes_compared: Cell::new(0),
+            deepest: Cell::new(0),
+        }
+    }
+
+    fn diff_value(&self, old: &Value, new: &Value, path: &str, results: &mut Vec<Difference>) {
+        if self.is_aborted() || self.is_full(results) {
+            return;
+        }
+        // Skip subtrees that are ignored or cannot contain an included path
+        if self.is_ignored_path(path) || !self.may_include(path) {
+            return;
+        }
+        if !self.probing.get() && !self.may_pass_path_filter(path) {
+            return;
+        }
+        self.nodes_compared.set(self.nodes_compared.get() + 1);
+        self.deepest.set(self.deepest.get().max(self.depth.get()));
+        let old = self.normalize(old, path);
+        let new = self.normalize(new, path);
+        let (old, new) = (old.as_ref(), new.as_ref());
+        if let Some(comparator) = self.comparator_at(path) {
+            if self.call_comparator(comparator, old, new) {
+                self.report_unchanged(old, path, results);
+            } else {
+                self.report_change(old, new, path, results);
+            }
+            return;
+        }
+
+        let depth = self.depth.get();
+        let is_container = |value: &Value| value.is_object() || value.is_array();
+        if is_container(old)
+            && is_container(new)
+            && self
+                .engine
+                .max_depth
+                .is_some_and(|max_depth| depth >= max_depth)
+        {
+            // Too deep to descend: compare the subtrees as opaque values
+            if old == new || self.scalars_equal(old, new, path) {
+                self.report_unchanged(old, path, results);
+            } else {
+                self.report_change(old, new, path, results);
+            }
+            return;
+        }
+
+        if let (Value::Object(old_map), Value::Object(new_map)) = (old, new) {
+            self.check_key_collisions(old_map, path);
+            self.check_key_collisions(new_map, path);
+        }
+        self.depth.set(depth + 1);
+        match (old, new) {
+            (Value::Object(old_map), Value::Object(new_map))
+                if old_map.is_empty() && new_map.is_empty() =>
+            {
+                self.report_unchanged(old, path, results)
+            }
+            (Value::Array(old_items), Value::Array(new_items))
+                if old_items.is_empty() && new_items.is_empty() =>
+            {
+                self.report_unchanged(old, path, results)
+            }
+            (Value::Object(old_map), Value::Object(new_map))
+                if self.engine.compare == Compare::Types =>
+            {
+                // Keys only one side has have no type to compare against
+                for (key, old_value) in old_map {
+                    if let Some(new_value) = self.counterpart(new_map, key) {
+                        if !self.is_ignored_key(key) {
+                            self.diff_value(old_value, new_value, &join_key(path, key), results);
+                        }
+                    }
+                }
+            }
+            (Value::Object(old_map), Value::Object(new_map)) => {
+                let renames = if self.engine.detect_renames {
+                    self.match_renames(old_map, new_map, path)
+                } else {
+                    HashMap::new()
+                };
+                let renamed_to: HashSet<&str> = renames.values().copied().collect();
+
+                for (key, old_value) in old_map {
+                    if self.is_ignored_key(key) {
+                        continue;
+                    }
+                    let child = join_key(path, key);
+                    match (self.counterpart(new_map, key), renames.get(key.as_str())) {
+                        (Some(new_value), _) => {
+                            self.diff_value(old_value, new_value, &child, results)
+                        }
+                        (None, Some(new_key)) => self.report(
+                            results,
+                            Difference::Renamed(child, join_key(path, new_key), old_value.clone()),
+                        ),
+                        (None, None) if self.is_omittable(old_value) => {}
+                        (None, None) => {
+                            self.report(results, Difference::Removed(child, old_value.clone()))
+                        }
+                    }
+                }
+                for (key, new_value) in new_map {
+                    if !self.is_ignored_key(key)
+                        && self.counterpart(old_map, key).is_none()
+                        && !renamed_to.contains(key.as_str())
+                        && !self.is_omittable(new_value)
+                    {
+                        self.report(
+                            results,
+                            Difference::Added(join_key(path, key), new_value.clone()),
+                        );
+                    }
+                }
+            }
+            (Value::Array(old_items), Value::Array(new_items)) => {
+                self.diff_array(old_items, new_items, path, results)
+            }
+            _ => {
+                if self.scalars_equal(old, new, path) {
+                    self.report_unchanged(old, path, results);
+                } else {
+                    self.report_change(old, new, path, results);
+                }
+            }
+        }
+        self.depth.set(depth);
+    }
+
+    /// Report keys both sides comment on differently, after the values, in
+    /// path order. Keys only one side has are reported with their values.
+    fn report_comments(&self, results: &mut Vec<Difference>) {
+        let Some(comments) = &self.engine.comments else {
+            return;
+        };
+        let mut paths: Vec<&String> = comments.new.keys().collect();
+        paths.sort();
+        for path in paths {
+            // The walk skips ignored subtrees; comments below them are skipped here
+            if self
+                .engine
+                .ignore_paths
+                .iter()
+                .any(|pattern| pattern.covers(path))
+            {
+                continue;
+            }
+            let new = &comments.new[path];
+            match comments.old.get(path) {
+                Some(old) if old != new => self.report(
+                    results,
+                    Difference::Comment(path.clone(), old.clone(), new.clone()),
+                ),
+                _ => {}
+            }
+        }
+    }
+
+    /// Pair removed keys with added keys holding an equal value, old key to
+    /// new key. Exact matches are preferred, as in `match_moves`.
+    fn match_renames<'m>(
+        &self,
+        old_map: &'m Map<String, Value>,
+        new_map: &'m Map<String, Value>,
+        path: &str,
+    ) -> HashMap<&'m str, &'m str> {
+        let mut added: Vec<(&str, &Value)> = new_map
+            .iter()
+            .filter(|(key, value)| {
+                !self.is_ignored_key(key)
+                    && self.counterpart(old_map, key).is_none()
+                    && !self.is_omittable(value)
+            })
+            .map(|(key, value)| (key.as_str(), value))
+            .collect();
+
+        let mut renames = HashMap::new();
+        for (key, old) in old_map {
+            if self.is_ignored_key(key)
+                || self.counterpart(new_map, key).is_some()
+                || self.is_omittable(old)
+            {
+                continue;
+            }
+            let child = join_key(path, key);
+            let partner = added.iter().position(|(_, new)| old == *new).or_else(|| {
+                added
+                    .iter()
+                    .position(|(_, new)| self.values_equal(old, new, &child))
+            });
+            if let Some(position) = partner {
+                renames.insert(key.as_str(), added.remove(position).0);
+            }
+        }
+        renames
+    }
+
+    /// The value of `key` in `map`, matching case-insensitively under
+    /// `ignore_key_case` when no key is spelled exactly the same.
+    fn counterpart<'m>(&self, map: &'m Map<String, Value>, key: &str) -> Option<&'m Value> {
+        if let Some(value) = map.get(key) {
+            return Some(value);
+        }
+        if !self.engine.ignore_key_case && !self.engine.unicode_normalize_keys {
+            return None;
+        }
+        let key = self.comparable_key(key);
+        map.iter()
+            .find(|(candidate, _)| self.comparable_key(candidate) == key)
+            .map(|(_, value)| value)
+    }
+
+    /// Fail with `ValueError` when two keys of `map` are the same key under
+    /// `ignore_key_case` or `unicode_normalize_keys`; matching would
+    /// otherwise silently use only one of them.
+    fn check_key_collisions(&self, map: &Map<String, Value>, path: &str) {
+        let option = match (
+            self.engine.ignore_key_case,
+            self.engine.unicode_normalize_keys,
+        ) {
+            (false, false) => return,
+            (true, false) => "ignore_key_case",
+            (false, true) => "unicode_normalize_keys",
+            (true, true) => "ignore_key_case and unicode_normalize_keys",
+        };
+        let mut seen: HashMap<String, &str> = HashMap::new();
+        for key in map.keys().filter(|key| !self.is_ignored_key(key)) {
+            let Some(other) = seen.insert(self.comparable_key(key), key) else {
+                continue;
+            };
+            if self.error.borrow().is_none() {
+                let at = if path.is_empty() {
+                    "at the root".to_string()
+                } else {
+                    format!("at '{path}'")
+                };
+                self.error
+                    .replace(Some(PyErr::new::<pyo3::exceptions::PyValueError, _>(
+                        format!("Keys {other:?} and {key:?} {at} are the same key under {option}"),
+                    )));
+            }
+            return;
+        }
+    }
+
+    /// `key` as key matching sees it under `unicode_normalize_keys` and
+    /// `ignore_key_case`.
+    fn comparable_key(&self, key: &str) -> String {
+        let key = if self.engine.unicode_normalize_keys {
+            self.unicode_normalized(key)
+        } else {
+            Cow::Borrowed(key)
+        };
+        if self.engine.ignore_key_case {
+            key.to_lowercase()
+        } else {
+            key.into_owned()
+        }
+    }
+
+    /// `text` as string comparisons see it: in the `unicode_normalize` form,
+    /// with the `whitespace` differences evened out, then folded by
+    /// `ignore_whitespace` / `ignore_case`.
+    fn comparable_string(&self, text: &str) -> String {
+        let text = self.engine.whitespace.apply(&self.unicode_normalized(text));
+        normalize_string(&text, self.options)
+    }
+
+    /// `text` in the `unicode_normalize` form, by Python's `unicodedata`.
+    /// ASCII text is the same in every form.
+    fn unicode_normalized<'t>(&self, text: &'t str) -> Cow<'t, str> {
+        let Some(form) = self.engine.unicode_normalize else {
+            return Cow::Borrowed(text);
+        };
+        if text.is_ascii() || self.error.borrow().is_some() {
+            return Cow::Borrowed(text);
+        }
+        Python::with_gil(|py| -> PyResult<String> {
+            py.import_bound("unicodedata")?
+                .getattr("normalize")?
+                .call1((form.name(), text))?
+                .extract()
+        })
+        .map(Cow::Owned)
+        .unwrap_or_else(|error| {
+            self.error.replace(Some(error));
+            Cow::Borrowed(text)
+        })
+    }
+
+    /// Whether a key only one side has goes unreported: `empty_as_missing`
+    /// treats empty collections like absent keys.
+    fn is_omittable(&self, value: &Value) -> bool {
+        self.engine.empty_as_missing
+            && match value {
+                Value::Array(items) => items.is_empty(),
+                Value::Object(map) => map.is_empty(),
+                _ => false,
+            }
+    }
+
+    fn report_unchanged(&self, value: &Value, path: &str, results: &mut Vec<Difference>) {
+        if self.engine.report_unchanged && !self.probing.get() {
+            self.report(
+                results,
+                Difference::Unchanged(path.to_string(), value.clone()),
+            );
+        }
+    }
+
+    /// Report `old` replaced by `new` as Modified, or TypeChanged when the
+    /// JSON types differ (unless a coercion option compares them as the
+    /// same type).
+    fn report_change(&self, old: &Value, new: &Value, path: &str, results: &mut Vec<Difference>) {
+        let same_type = self.same_type(old, new);
+        let (old, new) = (old.clone(), new.clone());
+        if same_type {
+            self.report(results, Difference::Modified(path.to_string(), old, new));
+        } else {
+            self.report(results, Difference::TypeChanged(path.to_string(), old, new));
+        }
+    }
+
+    /// Apply the `normalize` callable that applies to `value` at `path`, if any.
+    fn normalize<'v>(&self, value: &'v Value, path: &str) -> Cow<'v, Value> {
+        let normalizer = match &self.engine.normalize {
+            Some(Normalize::All(normalizer)) if !value.is_array() && !value.is_object() => {
+                normalizer
+            }
+            Some(Normalize::ByPath(normalizers)) => {
+                match normalizers
+                    .iter()
+                    .find(|(pattern, _)| pattern.matches(path))
+                {
+                    Some((_, normalizer)) => normalizer,
+                    None => return Cow::Borrowed(value),
+                }
+            }
+            _ => return Cow::Borrowed(value),
+        };
+        if self.error.borrow().is_some() {
+            return Cow::Borrowed(value);
+        }
+        Python::with_gil(|py| -> PyResult<Value> {
+            let value = json_value_to_python(py, value)?;
+            python_to_json_value(&normalizer.bind(py).call1((value,))?)
+        })
+        .map(Cow::Owned)
+        .unwrap_or_else(|error| {
+            self.error.replace(Some(error));
+            Cow::Borrowed(value)
+        })
+    }
+
+    fn comparator_at(&self, path: &str) -> Option<&PyObject> {
+        self.engine
+            .comparators
+            .iter()
+            .find(|(pattern, _)| pattern.matches(path))
+            .map(|(_, comparator)| comparator)
+    }
+
+    /// Ask a Python comparator whether `old` and `new` are equal. Once a
+    /// callback has raised, later calls are skipped and report equality.
+    fn call_comparator(&self, comparator: &PyObject, old: &Value, new: &Value) -> bool {
+        if self.error.borrow().is_some() {
+            return true;
+        }
+        Python::with_gil(|py| -> PyResult<bool> {
+            let old = json_value_to_python(py, old)?;
+            let new = json_value_to_python(py, new)?;
+            comparator.bind(py).call1((old, new))?.is_truthy()
+        })
+        .unwrap_or_else(|error| {
+            self.error.replace(Some(error));
+            true
+        })
+    }
+
+    fn diff_array(
+        &self,
+        old_items: &[Value],
+        new_items: &[Value],
+        path: &str,
+        results: &mut Vec<Difference>,
+    ) {
+        if self.engine.compare != Compare::Values {
+            for (index, (old, new)) in old_items.iter().zip(new_items).enumerate() {
+                self.diff_value(old, new, &format!("{path}[{index}]"), results);
+            }
+            return;
+        }
+
+        if let Some(id_key) = &self.id_key {
+            let has_ids = old_items
+                .iter()
+                .chain(new_items)
+                .any(|item| id_key.identity(item).is_some());
+            if has_ids {
+                return self.diff_array_by_id(old_items, new_items, id_key, path, results);
+            }
+        }
+
+        // Move detection needs an alignment to tell moved elements from shifted ones
+        match (self.engine.array_order, self.engine.array_strategy) {
+            (ArrayOrder::Unordered, _) => {
+                self.diff_array_unordered(old_items, new_items, path, results)
+            }
+            (ArrayOrder::Ordered, ArrayStrategy::Index) if !self.engine.detect_moves => {
+                self.diff_array_by_index(old_items, new_items, path, results)
+            }
+            (ArrayOrder::Ordered, _) => {
+                self.diff_array_aligned(old_items, new_items, path, results)
+            }
+        }
+    }
+
+    fn diff_array_by_index(
+        &self,
+        old_items: &[Value],
+        new_items: &[Value],
+        path: &str,
+        results: &mut Vec<Difference>,
+    ) {
+        for index in 0..old_items.len().max(new_items.len()) {
+            let child = format!("{path}[{index}]");
+            match (old_items.get(index), new_items.get(index)) {
+                (Some(old), Some(new)) => self.diff_value(old, new, &child, results),
+                (Some(old), None) => self.report(results, Difference::Removed(child, old.clone())),
+                (None, Some(new)) => self.report(results, Difference::Added(child, new.clone())),
+                (None, None) => {}
+            }
+        }
+    }
+
+    /// Align with a minimal edit script. Within each run of changes, deleted
+    /// and inserted elements are paired up and compared at the old index;
+    /// leftovers are reported as Removed (old index) or Added (new index).
+    ///
+    /// With `detect_moves`, a deleted element equal to an inserted one is
+    /// reported as `Moved` at its old index instead.
+    fn diff_array_aligned(
+        &self,
+        old_items: &[Value],
+        new_items: &[Value],
+        path: &str,
+        results: &mut Vec<Difference>,
+    ) {
+        let edits = align::edit_script(old_items.len(), new_items.len(), |i, j| {
+            old_items[i] == new_items[j]
+                || self.values_equal(&old_items[i], &new_items[j], &format!("{path}[{i}]"))
+        });
+
+        let moves = if self.engine.detect_moves {
+            self.match_moves(old_items, new_items, &edits, path)
+        } else {
+            HashMap::new()
+        };
+        let moved_to: HashSet<usize> = moves.values().copied().collect();
+
+        let mut deleted = Vec::new();
+        let mut inserted = Vec::new();
+        for edit in edits {
+            match edit {
+                Edit::Delete(i) => match moves.get(&i) {
+                    Some(&j) => {
+                        self.report(results, Difference::Moved(format!("{path}[{i}]"), i, j))
+                    }
+                    None => deleted.push(i),
+                },
+                Edit::Insert(j) if moved_to.contains(&j) => {}
+                Edit::Insert(j) => inserted.push(j),
+                Edit::Keep(i, _) => {
+                    self.report_changed_run(
+                        old_items, new_items, &deleted, &inserted, path, results,
+                    );
+                    deleted.clear();
+                    inserted.clear();
+                    self.report_unchanged(&old_items[i], &format!("{path}[{i}]"), results);
+                }
+            }
+        }
+        self.report_changed_run(old_items, new_items, &deleted, &inserted, path, results);
+    }
+
+    /// Pair deleted elements with equal inserted ones, old index to new index.
+    fn match_moves(
+        &self,
+        old_items: &[Value],
+        new_items: &[Value],
+        edits: &[Edit],
+        path: &str,
+    ) -> HashMap<usize, usize> {
+        let mut inserted: Vec<usize> = edits
+            .iter()
+            .filter_map(|edit| match edit {
+                Edit::Insert(j) => Some(*j),
+                _ => None,
+            })
+            .collect();
+
+        let mut moves = HashMap::new();
+        for edit in edits {
+            let Edit::Delete(i) = *edit else {
+                continue;
+            };
+            let old = &old_items[i];
+            let child = format!("{path}[{i}]");
+            let partner = inserted
+                .iter()
+                .position(|&j| *old == new_items[j])
+                .or_else(|| {
+                    inserted
+                        .iter()
+                        .position(|&j| self.values_equal(old, &new_items[j], &child))
+                });
+            if let Some(position) = partner {
+                moves.insert(i, inserted.remove(position));
+            }
+        }
+        moves
+    }
+
+    fn report_changed_run(
+        &self,
+        old_items: &[Value],
+        new_items: &[Value],
+        deleted: &[usize],
+        inserted: &[usize],
+        path: &str,
+        results: &mut Vec<Difference>,
+    ) {
+        for (&i, &j) in deleted.iter().zip(inserted) {
+            let child = format!("{path}[{i}]");
+            self.diff_value(&old_items[i], &new_items[j], &child, results);
+        }
+        for &i in deleted.iter().skip(inserted.len()) {
+            self.report(
+                results,
+                Difference::Removed(format!("{path}[{i}]"), old_items[i].clone()),
+            );
+        }
+        for &j in inserted.iter().skip(deleted.len()) {
+            self.report(
+                results,
+                Difference::Added(format!("{path}[{j}]"), new_items[j].clone()),
+            );
+        }
+    }
+
+    /// Elements with an id are matched by it; the rest fall back to their index.
+    /// Elements sharing an id are paired one-to-one (see `pair_by_id`), and
+    /// those left over are reported as Removed or Added.
+    ///
+    /// With `detect_moves`, matched elements outside the longest run of
+    /// elements that kept their relative order are reported as `Moved`.
+    fn diff_array_by_id(
+        &self,
+        old_items: &[Value],
+        new_items: &[Value],
+        id_key: &IdKey,
+        path: &str,
+        results: &mut Vec<Difference>,
+    ) {
+        let old_ids: Vec<_> = old_items.iter().map(|item| id_key.identity(item)).collect();
+        let new_ids: Vec<_> = new_items.iter().map(|item| id_key.identity(item)).collect();
+        let partners = self.pair_by_id(old_items, new_items, &old_ids, &new_ids, id_key, path);
+        let mut new_paired = vec![false; new_items.len()];
+        for &j in partners.iter().flatten() {
+            new_paired[j] = true;
+        }
+
+        for (index, old) in old_items.iter().enumerate() {
+            match &old_ids[index] {
+                Some(id) => {
+                    let child = format!("{path}{}", id_key.segment(id));
+                    match partners[index] {
+                        Some(j) => self.diff_value(old, &new_items[j], &child, results),
+                        None => self.report(results, Difference::Removed(child, old.clone())),
+                    }
+                }
+                None => {
+                    let child = format!("{path}[{index}]");
+                    match new_items.get(index) {
+                        Some(new) if new_ids[index].is_none() => {
+                            self.diff_value(old, new, &child, results)
+                        }
+                        _ => self.report(results, Difference::Removed(child, old.clone())),
+                    }
+                }
+            }
+        }
+        for (index, new) in new_items.iter().enumerate() {
+            match &new_ids[index] {
+                Some(id) => {
+                    if !new_paired[index] {
+                        let child = format!("{path}{}", id_key.segment(id));
+                        self.report(results, Difference::Added(child, new.clone()));
+                    }
+                }
+                None => {
+                    let paired = index < old_items.len() && old_ids[index].is_none();
+                    if !paired {
+                        self.report(
+                            results,
+                            Difference::Added(format!("{path}[{index}]"), new.clone()),
+                        );
+                    }
+                }
+            }
+        }
+
+        if self.engine.detect_moves {
+            self.report_moves_by_id(&old_ids, &partners, id_key, path, results);
+        }
+    }
+
+    /// The new element each old element with an id is paired with. Every
+    /// element is paired at most once; among elements sharing an id, equal
+    /// ones are paired first, as in `diff_array_unordered`, then the rest in
+    /// order.
+    fn pair_by_id(
+        &self,
+        old_items: &[Value],
+        new_items: &[Value],
+        old_ids: &[Option<Vec<&Value>>],
+        new_ids: &[Option<Vec<&Value>>],
+        id_key: &IdKey,
+        path: &str,
+    ) -> Vec<Option<usize>> {
+        let mut partners = vec![None; old_items.len()];
+        let mut new_paired = vec![false; new_items.len()];
+        for pass in 0..3 {
+            for (i, old_id) in old_ids.iter().enumerate() {
+                let Some(old_id) = old_id else {
+                    continue;
+                };
+                if partners[i].is_some() {
+                    continue;
+                }
+                let old = &old_items[i];
+                let child = format!("{path}{}", id_key.segment(old_id));
+                let partner = (0..new_items.len()).find(|&j| {
+                    !new_paired[j]
+                        && new_ids[j].as_ref() == Some(old_id)
+                        && match pass {
+                            0 => *old == new_items[j],
+                            1 => self.values_equal(old, &new_items[j], &child),
+                            _ => true,
+                        }
+                });
+                if let Some(j) = partner {
+                    partners[i] = Some(j);
+                    new_paired[j] = true;
+                }
+            }
+        }
+        partners
+    }
+
+    /// Report id-matched elements outside the longest run that kept its order.
+    fn report_moves_by_id(
+        &self,
+        old_ids: &[Option<Vec<&Value>>],
+        partners: &[Option<usize>],
+        id_key: &IdKey,
+        path: &str,
+        results: &mut Vec<Difference>,
+    ) {
+        // (old index, new index) of matched elements, in old and in new order
+        let in_old_order: Vec<(usize, usize)> = partners
+            .iter()
+            .enumerate()
+            .filter_map(|(i, j)| Some((i, (*j)?)))
+            .collect();
+        let mut in_new_order = in_old_order.clone();
+        in_new_order.sort_by_key(|&(_, j)| j);
+
+        let edits = align::edit_script(in_old_order.len(), in_new_order.len(), |a, b| {
+            in_old_order[a] == in_new_order[b]
+        });
+        for edit in edits {
+            if let Edit::Delete(a) = edit {
+                let (i, j) = in_old_order[a];
+                if let Some(id) = &old_ids[i] {
+                    let child = format!("{path}{}", id_key.segment(id));
+                    self.report(results, Difference::Moved(child, i, j));
+                }
+            }
+        }
+    }
+
+    /// Multiset comparison: each old element is paired with an equal new one.
+    ///
+    /// Exact matches are paired first so tolerance-based equality cannot steal
+    /// an element that has an exact partner later in the list.
+    fn diff_array_unordered(
+        &self,
+        old_items: &[Value],
+        new_items: &[Value],
+        path: &str,
+        results: &mut Vec<Difference>,
+    ) {
+        let mut old_matched = vec![false; old_items.len()];
+        let mut new_matched = vec![false; new_items.len()];
+
+        for exact in [true, false] {
+            for (old_index, old) in old_items.iter().enumerate() {
+                if old_matched[old_index] {
+                    continue;
+                }
+                let child = format!("{path}[{old_index}]");
+                let partner = (0..new_items.len()).find(|&new_index| {
+                    let new = &new_items[new_index];
+                    !new_matched[new_index]
+                        && if exact {
+                            old == new
+                        } else {
+                            self.values_equal(old, new, &child)
+                        }
+                });
+                if let Some(new_index) = partner {
+                    old_matched[old_index] = true;
+                    new_matched[new_index] = true;
+                }
+            }
+        }
+
+        for (index, old) in old_items.iter().enumerate() {
+            let child = format!("{path}[{index}]");
+            if old_matched[index] {
+                self.report_unchanged(old, &child, results);
+            } else {
+                self.report(results, Difference::Removed(child, old.clone()));
+            }
+        }
+        for (index, new) in new_items.iter().enumerate() {
+            if !new_matched[index] {
+                self.report(
+                    results,
+                    Difference::Added(format!("{path}[{index}]"), new.clone()),
+                );
+            }
+        }
+    }
+
+    /// Deep equality under the current options, for values found at `path`.
+    fn values_equal(&self, old: &Value, new: &Value, path: &str) -> bool {
+        let probing = self.probing.replace(true);
+        let mut results = Vec::new();
+        self.diff_value(old, new, path, &mut results);
+        self.probing.set(probing);
+        results.is_empty()
+    }
+
+    fn report(&self, results: &mut Vec<Difference>, difference: Difference) {
+        let path = difference.path();
+        if self.is_full(results) || self.is_ignored_path(path) {
+            return;
+        }
+        if !self.is_included_path(path) {
+            // A change above an included path is reported for the included
+            // parts of its values only
+            match &difference {
+                Difference::Added(path, new) => {
+                    self.report_included(None, Some(new), path, results)
+                }
+                Difference::Removed(path, old) => {
+                    self.report_included(Some(old), None, path, results)
+                }
+                Difference::Modified(path, old, new) | Difference::TypeChanged(path, old, new) => {
+                    self.report_included(Some(old), Some(new), path, results)
+                }
+                _ => {}
+            }
+            return;
+        }
+        // Path filters only shape the output, not equality checks
+        if self.probing.get() || self.passes_path_filter(path) {
+            results.push(difference);
+        }
+    }
+
+    /// Report the parts of `old` and `new`, found at `path` above an included
+    /// path, that lie in included subtrees: as Added or Removed where only one
+    /// side has them, diffed where both do.
+    fn report_included(
+        &self,
+        old: Option<&Value>,
+        new: Option<&Value>,
+        path: &str,
+        results: &mut Vec<Difference>,
+    ) {
+        if self.is_ignored_path(path) || !self.may_include(path) {
+            return;
+        }
+        if self.is_included_path(path) {
+            match (old, new) {
+                (Some(old), Some(new)) => self.diff_value(old, new, path, results),
+                (Some(old), None) => {
+                    self.report(results, Difference::Removed(path.to_string(), old.clone()))
+                }
+                (None, Some(new)) => {
+                    self.report(results, Difference::Added(path.to_string(), new.clone()))
+                }
+                (None, None) => {}
+            }
+            return;
+        }
+
+        let old_map = old.and_then(Value::as_object);
+        let new_map = new.and_then(Value::as_object);
+        let mut seen = HashSet::new();
+        for key in old_map.into_iter().chain(new_map).flat_map(Map::keys) {
+            if !self.is_ignored_key(key) && seen.insert(key) {
+                self.report_included(
+                    old_map.and_then(|map| map.get(key)),
+                    new_map.and_then(|map| map.get(key)),
+                    &join_key(path, key),
+                    results,
+                );
+            }
+        }
+        let old_items = old.and_then(Value::as_array).map_or(&[][..], Vec::as_slice);
+        let new_items = new.and_then(Value::as_array).map_or(&[][..], Vec::as_slice);
+        for index in 0..old_items.len().max(new_items.len()) {
+            self.report_included(
+                old_items.get(index),
+                new_items.get(index),
+                &format!("{path}[{index}]"),
+                results,
+            );
+        }
+    }
+
+    /// Whether a callback failed or a resource limit was hit; either ends the diff.
+    fn is_aborted(&self) -> bool {
+        if self.error.borrow().is_some() {
+            return true;
+        }
+        if self.deadline.is_none() && self.memory_budget.is_none() {
+            return false;
+        }
+        let steps = self.steps.get().wrapping_add(1);
+        self.steps.set(steps);
+        if !steps.is_multiple_of(LIMIT_CHECK_INTERVAL) {
+            return false;
+        }
+
+        let error = if self
+            .deadline
+            .is_some_and(|deadline| Instant::now() >= deadline)
+        {
+            let timeout = self.engine.timeout.unwrap_or_default();
+            Some(DiffTimeoutError::new_err(format!(
+                "Diff exceeded timeout_ms={}",
+                timeout.as_millis()
+            )))
+        } else {
+            self.memory_budget
+                .as_ref()
+                .and_then(|budget| budget.check().err())
+                .map(MemoryLimitError::new_err)
+        };
+        let aborted = error.is_some();
+        if aborted {
+            self.error.replace(error);
+        }
+        aborted
+    }
+
+    /// While probing, the first difference settles the answer.
+    fn is_full(&self, results: &[Difference]) -> bool {
+        let max_results = if self.probing.get() {
+            Some(1)
+        } else {
+            self.engine.max_results
+        };
+        max_results.is_some_and(|max_results| {
+            results.iter().filter(|result| result.is_change()).count() >= max_results
+        })
+    }
+
+    fn passes_path_filter(&self, path: &str) -> bool {
+        let filtered_out = self
+            .options
+            .path_filter
+            .as_ref()
+            .is_some_and(|filter| !path.contains(filter.as_str()))
+            || self
+                .engine
+                .path_filter_regex
+                .as_ref()
+                .is_some_and(|regex| !regex.is_match(path))
+            || self
+                .engine
+                .path_filter_jsonpath
+                .as_ref()
+                .is_some_and(|pattern| !pattern.covers(path));
+        !filtered_out
+    }
+
+    /// Canonical form of `value` at `path`, or `None` when it is ignored or
+    /// not included.
+    fn canonical(&self, value: &Value, path: &str) -> Option<Value> {
+        if self.is_ignored_path(path) || !self.may_include(path) {
+            return None;
+        }
+        let value = self.normalize(value, path);
+        match value.as_ref() {
+            Value::Object(map) => {
+                self.check_key_collisions(map, path);
+                Some(Value::Object(
+                    map.iter()
+                        .filter(|(key, child)| {
+                            !self.is_ignored_key(key) && !self.is_omittable(child)
+                        })
+                        .filter_map(|(key, child)| {
+                            let child = self.canonical(child, &join_key(path, key))?;
+                            Some((self.comparable_key(key), child))
+                        })
+                        .collect(),
+                ))
+            }
+            Value::Array(items) => Some(Value::Array(self.canonical_array(items, path))),
+            scalar if self.is_included_path(path) => Some(self.canonical_scalar(scalar, path)),
+            _ => None,
+        }
+    }
+
+    fn canonical_array(&self, items: &[Value], path: &str) -> Vec<Value> {
+        let ids: Vec<Option<String>> = items
+            .iter()
+            .map(|item| {
+                let id_key = self.id_key.as_ref()?;
+                Some(id_key.segment(&id_key.identity(item)?))
+            })
+            .collect();
+        let mut canonical: Vec<(&str, Value)> = items
+            .iter()
+            .zip(&ids)
+            .enumerate()
+            .filter_map(|(index, (item, id))| {
+                let child = match id {
+                    Some(segment) => format!("{path}{segment}"),
+                    None => format!("{path}[{index}]"),
+                };
+                Some((
+                    id.as_deref().unwrap_or_default(),
+                    self.canonical(item, &child)?,
+                ))
+            })
+            .collect();
+
+        if ids.iter().any(Option::is_some) {
+            // Elements pair up by id, so only their ids order them; elements
+            // without one pair up by index and keep their places
+            if ids.iter().all(Option::is_some) && !self.engine.detect_moves {
+                canonical.sort_by_key(|(old_id, _)| *old_id);
+            }
+        } else if self.engine.array_order == ArrayOrder::Unordered {
+            canonical.sort_by_cached_key(|(_, value)| value.to_string());
+        }
+        canonical.into_iter().map(|(_, value)| value).collect()
+    }
+
+    fn canonical_scalar(&self, value: &Value, path: &str) -> Value {
+        match value {
+            Value::String(text) => {
+                let ignored = self
+                    .engine
+                    .ignore_values_regex
+                    .as_ref()
+                    .is_some_and(|regex| regex.is_match(text));
+                if ignored {
+                    Value::String(IGNORED_VALUE.to_string())
+                } else if let Some(instant) = self
+                    .engine
+                    .datetimes
+                    .and_then(|tolerance| datetime::canonical(text, tolerance))
+                {
+                    Value::String(instant)
+                } else if let Some(version) = self
+                    .engine
+                    .compare_versions
+                    .then(|| Version::parse(text))
+                    .flatten()
+                {
+                    Value::String(version.to_string())
+                } else if let Some(number) = self
+                    .engine
+                    .coerce_numeric_strings
+                    .then(|| as_number(value))
+                    .flatten()
+                {
+                    self.canonical_scalar(&Value::Number(number), path)
+                } else if let Some(seconds) = self
+                    .engine
+                    .compare_durations
+                    .then(|| duration::parse_seconds(text))
+                    .flatten()
+                {
+                    Value::String(format!("{seconds}s"))
+                } else if let Some(number) = self
+                    .engine
+                    .compare_quantities
+                    .then(|| quantity::parse_kubernetes(text).and_then(quantity_number))
+                    .flatten()
+                {
+                    self.canonical_scalar(&Value::Number(number), path)
+                } else if let Some(address) = self
+                    .engine
+                    .compare_ips
+                    .then(|| ip::normalize(text))
+                    .flatten()
+                {
+                    Value::String(address)
+                } else {
+                    Value::String(self.comparable_string(text))
+                }
+            }
+            Value::Number(number) => match (self.epsilon_at(path), number.as_f64()) {
+                (Some(epsilon), Some(number)) if epsilon > 0.0 => {
+                    // `+ 0.0` turns a rounded -0.0 into 0.0
+                    serde_json::Number::from_f64((number / epsilon).round() * epsilon + 0.0)
+                        .map_or_else(|| value.clone(), Value::Number)
+                }
+                // Integral floats are written as integers, as `1` and `1.0` compare equal
+                (_, Some(float))
+                    if self.engine.ignore_number_format
+                        && number.is_f64()
+                        && float.fract() == 0.0
+                        && float.abs() < 9e15 =>
+                {
+                    Value::Number((float as i64).into())
+                }
+                _ => value.clone(),
+            },
+            other => other.clone(),
+        }
+    }
+
+    /// Whether `path` passes the JSONPath filter or may lead to a path that does.
+    fn may_pass_path_filter(&self, path: &str) -> bool {
+        self.engine
+            .path_filter_jsonpath
+            .as_ref()
+            .is_none_or(|pattern| pattern.covers(path) || pattern.may_match_below(path))
+    }
+
+    fn is_ignored_path(&self, path: &str) -> bool {
+        self.engine
+            .ignore_paths
+            .iter()
+            .any(|pattern| pattern.matches(path))
+    }
+
+    /// Whether `path` is at or below an included path.
+    fn is_included_path(&self, path: &str) -> bool {
+        let patterns = &self.engine.include_paths;
+        patterns.is_empty() || patterns.iter().any(|pattern| pattern.covers(path))
+    }
+
+    /// Whether `path` is included or may still lead to an included path.
+    fn may_include(&self, path: &str) -> bool {
+        let patterns = &self.engine.include_paths;
+        patterns.is_empty()
+            || patterns
+                .iter()
+                .any(|pattern| pattern.covers(path) || pattern.may_match_below(path))
+    }
+
+    /// Tolerance for numbers at `path`: the first matching `epsilons` entry,
+    /// else the global `epsilon`.
+    fn epsilon_at(&self, path: &str) -> Option<f64> {
+        self.engine
+            .epsilons
+            .iter()
+            .find(|(pattern, _)| pattern.matches(path))
+            .map(|&(_, epsilon)| epsilon)
+            .or(self.options.epsilon)
+    }
+
+    fn is_ignored_key(&self, key: &str) -> bool {
+        self.options
+            .ignore_keys_regex
+            .as_ref()
+            .is_some_and(|regex| regex.is_match(key))
+    }
+
+    fn scalars_equal(&self, old: &Value, new: &Value, path: &str) -> bool {
+        match self.engine.compare {
+            Compare::Values => {}
+            Compare::Structure | Compare::Types => {
+                return self.same_type(old, new);
+            }
+            Compare::Keys => return true,
+        }
+        match (old, new) {
+            (Value::Number(old), Value::Number(new)) => self.numbers_equal(old, new, path),
+            (Value::String(old_text), Value::String(new_text)) => {
+                if let Some(regex) = &self.engine.ignore_values_regex {
+                    if regex.is_match(old_text) && regex.is_match(new_text) {
+                        return true;
+                    }
+                }
+                let same_instant = self.engine.datetimes.is_some_and(|tolerance| {
+                    datetime::within_tolerance(old_text, new_text, tolerance)
+                });
+                if same_instant {
+                    return true;
+                }
+                if let Some((old, new)) = self.versions(old_text, new_text) {
+                    return old == new;
+                }
+                if let Some((old, new)) = self.coerced_numbers(old, new) {
+                    return self.numbers_equal(&old, &new, path);
+                }
+                if let Some((old, new)) = self.durations(old_text, new_text) {
+                    return old == new || self.floats_equal(old, new, path);
+                }
+                if let Some((old, new)) = self.quantities(old, new) {
+                    return old == new || self.floats_equal(old, new, path);
+                }
+                if let Some((old, new)) = self.ip_addresses(old_text, new_text) {
+                    return old == new;
+                }
+                old_text == new_text
+                    || self.comparable_string(old_text) == self.comparable_string(new_text)
+            }
+            _ => {
+                if let Some((old, new)) = self.coerced_bools(old, new) {
+                    return old == new;
+                }
+                if let Some((old, new)) = self.coerced_numbers(old, new) {
+                    return self.numbers_equal(&old, &new, path);
+                }
+                match self.quantities(old, new) {
+                    Some((old, new)) => old == new || self.floats_equal(old, new, path),
+                    None => old == new,
+                }
+            }
+        }
+    }
+
+    /// Whether two values count as the same type: the same JSON type, or a
+    /// pair a coercion option compares as numbers or booleans.
+    fn same_type(&self, old: &Value, new: &Value) -> bool {
+        std::mem::discriminant(old) == std::mem::discriminant(new)
+            || self.coerced_numbers(old, new).is_some()
+            || self.quantities(old, new).is_some()
+            || self.coerced_bools(old, new).is_some()
+    }
+
+    /// Both values as booleans when `coerce_bools` compares them that way:
+    /// one is a boolean and the other a boolean or one of its spellings.
+    fn coerced_bools(&self, old: &Value, new: &Value) -> Option<(bool, bool)> {
+        let table = self.engine.coerce_bools.as_ref()?;
+        if !(old.is_boolean() || new.is_boolean()) {
+            return None;
+        }
+        Some((table.read(old)?, table.read(new)?))
+    }
+
+    fn numbers_equal(&self, old_number: &Number, new_number: &Number, path: &str) -> bool {
+        if old_number == new_number
+            || (self.engine.ignore_number_format && same_value(old_number, new_number))
+        {
+            return true;
+        }
+        let (Some(old), Some(new)) = (old_number.as_f64(), new_number.as_f64()) else {
+            return false;
+        };
+        self.floats_equal(old, new, path)
+    }
+
+    /// Whether two numbers are within the tolerances at `path`.
+    fn floats_equal(&self, old: f64, new: f64, path: &str) -> bool {
+        let delta = (old - new).abs();
+        // Within either tolerance counts as equal, like math.isclose
+        self.epsilon_at(path)
+            .is_some_and(|epsilon| delta <= epsilon)
+            || self
+                .engine
+                .epsilon_relative
+                .is_some_and(|relative| delta <= relative * old.abs().max(new.abs()))
+    }
+
+    /// Both strings as versions when `compare_versions` reads them that way.
+    fn versions(&self, old: &str, new: &str) -> Option<(Version, Version)> {
+        if !self.engine.compare_versions {
+            return None;
+        }
+        Some((Version::parse(old)?, Version::parse(new)?))
+    }
+
+    /// Both strings in seconds when `compare_durations` reads them as durations.
+    fn durations(&self, old: &str, new: &str) -> Option<(f64, f64)> {
+        if !self.engine.compare_durations {
+            return None;
+        }
+        Some((duration::parse_seconds(old)?, duration::parse_seconds(new)?))
+    }
+
+    /// Both strings in canonical form when `compare_ips` reads them as IP
+    /// addresses or CIDR blocks.
+    fn ip_addresses(&self, old: &str, new: &str) -> Option<(String, String)> {
+        if !self.engine.compare_ips {
+            return None;
+        }
+        Some((ip::normalize(old)?, ip::normalize(new)?))
+    }
+
+    /// Both values as magnitudes when `compare_quantities` compares them: at
+    /// least one is a string, and both are numbers or read as quantities.
+    fn quantities(&self, old: &Value, new: &Value) -> Option<(f64, f64)> {
+        if !self.engine.compare_quantities || !(old.is_string() || new.is_string()) {
+            return None;
+        }
+        let magnitude = |value: &Value| match value {
+            Value::Number(number) => number.as_f64(),
+            Value::String(text) => quantity::parse_kubernetes(text),
+            _ => None,
+        };
+        Some((magnitude(old)?, magnitude(new)?))
+    }
+
+    /// Both values as numbers when `coerce_numeric_strings` compares them
+    /// numerically: at least one is a string, and both are or read as numbers.
+    fn coerced_numbers(&self, old: &Value, new: &Value) -> Option<(Number, Number)> {
+        if !self.engine.coerce_numeric_strings || !(old.is_string() || new.is_string()) {
+            return None;
+        }
+        Some((as_number(old)?, as_number(new)?))
+    }
+}
+
+/// Whether two numbers hold the same value, written as integers or floats.
+/// Integral values compare exactly, so `2**53 + 1` does not equal `2.0**53`.
+fn same_value(a: &Number, b: &Number) -> bool {
+    fn integral(number: &Number) -> Option<i128> {
+        if let Some(integer) = number.as_i64() {
+            return Some(integer.into());
+        }
+        if let Some(integer) = number.as_u64() {
+            return Some(integer.into());
+        }
+        let float = number.as_f64()?;
+        (float.fract() == 0.0 && float.abs() < 1e38).then_some(float as i128)
+    }
+    match (integral(a), integral(b)) {
+        (Some(a), Some(b)) => a == b,
+        _ => a.as_f64() == b.as_f64(),
+    }
+}
+
+/// A quantity's magnitude as a JSON number, an integer when it is integral.
+fn quantity_number(magnitude: f64) -> Option<Number> {
+    if magnitude.fract() == 0.0 && magnitude.abs() < i64::MAX as f64 {
+        Some(Number::from(magnitude as i64))
+    } else {
+        Number::from_f64(magnitude)
+    }
+}
+
+/// A number, or a string holding a JSON number such as `"8080"` or `"0.5"`.
+fn as_number(value: &Value) -> Option<Number> {
+    match value {
+        Value::Number(number) => Some(number.clone()),
+        Value::String(text) => text.parse().ok(),
+        _ => None,
+    }
+}
+
+fn normalize_string(text: &str, options: &DiffOptions) -> String {
+    let mut text = text.to_string();
+    if let Some(diffx_options) = &options.diffx_options {
+        if diffx_options.ignore_whitespace == Some(true) {
+            text.retain(|c| !c.is_whitespace());
+        }
+        if diffx_options.ignore_case == Some(true) {
+            text = text.to_lowercase();
+        }
+    }
+    text
+}
@@ -0,0 +1,105 @@
+//! Python exception types raised by the bindings.
+
+// `create_exception!` expands a `cfg(feature = "gil-refs")` check that this
+// crate does not declare.
+#![allow(unexpected_cfgs)]
+
+use pyo3::exceptions::{PyMemoryError, PyTimeoutError, PyUserWarning, PyValueError};
+use pyo3::prelude::*;
+use regex::Regex;
+
+pyo3::create_exception!(
+    diffx_python,
+    ParseLimitError,
+    PyValueError,
+    "Raised when a document exceeds the configured parse limits."
+);
+
+pyo3::create_exception!(
+    diffx_python,
+    ParseError,
+    PyValueError,
+    "Raised when a document cannot be parsed; `line`, `column` and `snippet` locate the error."
+);
+
+pyo3::create_exception!(
+    diffx_python,
+    DiffTimeoutError,
+    PyTimeoutError,
+    "Raised when a diff runs longer than its `timeout_ms`."
+);
+
+pyo3::create_exception!(
+    diffx_python,
+    MemoryLimitError,
+    PyMemoryError,
+    "Raised when parsing or diffing allocates more than `memory_limit_mb`."
+);
+
+pyo3::create_exception!(
+    diffx_python,
+    CircularReferenceError,
+    PyValueError,
+    "Raised when a Python input contains itself, naming the path of the cycle."
+);
+
+pyo3::create_exception!(
+    diffx_python,
+    ParseWarning,
+    PyUserWarning,
+    "Warning issued for recoverable problems in parsed input, such as records skipped by a \
+     `strict=False` parse or repeated keys."
+);
+
+/// Issue a `ParseWarning` for each message.
+pub fn warn_parse(py: Python, messages: &[String]) -> PyResult<()> {
+    let category = py.get_type_bound::<ParseWarning>();
+    for message in messages {
+        PyErr::warn_bound(py, &category, message, 1)?;
+    }
+    Ok(())
+}
+
+/// A `ParseError` for `message` with the position the parser reported in it.
+///
+/// `line`, `column` (both 1-based) and `snippet` (the text of that line) are
+/// `None` when the message names no position. `line_offset` is added for
+/// parsers that saw only part of `content`.
+pub fn parse_error(message: String, content: &str, line_offset: usize) -> PyErr {
+    let position = reported_position(&message);
+    Python::with_gil(|py| {
+        let error = ParseError::new_err(message);
+        let value = error.value_bound(py);
+        let line = position.map(|(line, _)| line + line_offset);
+        let snippet = line.and_then(|line| content.lines().nth(line.checked_sub(1)?));
+        let attributes = value
+            .setattr("line", line)
+            .and_then(|_| value.setattr("column", position.and_then(|(_, column)| column)))
+            .and_then(|_| value.setattr("snippet", snippet));
+        match attributes {
+            Ok(()) => error,
+            Err(e) => e,
+        }
+    })
+}
+
+/// `(line, column)` from messages such as `at line 3 column 5` (serde,
+/// YAML), `at line 3, column 5` (TOML) or `(line: 3, byte: 40)` (CSV).
+fn reported_position(message: &str) -> Option<(usize, Option<usize>)> {
+    let pattern = Regex::new(r"\bline:? (\d+)(?:,? column (\d+))?").expect("valid pattern");
+    let captures = pattern.captures(message)?;
+    let line = captures[1].parse().ok()?;
+    let column = captures
+        .get(2)
+        .and_then(|column| column.as_str().parse().ok());
+    Some((line, column))
+}
+
+/// `at line L column C` for a byte offset into `content`, for parsers that
+/// report offsets.
+pub fn describe_offset(content: &str, offset: usize) -> String {
+    let before = String::from_utf8_lossy(&content.as_bytes()[..offset.min(content.len())]);
+    let line = before.matches('\n').count() + 1;
+    let column = before.rsplit('\n').next().unwrap_or("").chars().count() + 1;
+    format!("at line {line} column {column}")
+}
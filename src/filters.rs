@@ -0,0 +1,70 @@
+//! Result filters that read only `"type"` and `"path"`, so pruning a large
+//! result list does not touch its values.
+
+use pyo3::prelude::*;
+use pyo3::types::PyList;
+
+use crate::result::{self, KINDS};
+
+/// Keep the results of the given kinds
+///
+/// Args:
+///     results: List of diff results from diff()
+///     types: A result type such as "Added", or a list of them
+///
+/// Returns:
+///     A new list with the matching results, in their original order
+#[pyfunction]
+pub fn filter_by_type<'py>(
+    py: Python<'py>,
+    results: &Bound<'py, PyList>,
+    types: &Bound<'py, PyAny>,
+) -> PyResult<Bound<'py, PyList>> {
+    let types = one_or_many(types)?;
+    if let Some(unknown) = types.iter().find(|kind| !KINDS.contains(&kind.as_str())) {
+        return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+            "Unknown result type '{unknown}' (expected one of: {})",
+            KINDS.join(", ")
+        )));
+    }
+    let kept = PyList::empty_bound(py);
+    for result in results.iter() {
+        if types.contains(&result::str_field(&result, "type")?) {
+            kept.append(result)?;
+        }
+    }
+    Ok(kept)
+}
+
+/// Keep the results at or below paths matching a glob
+///
+/// Args:
+///     results: List of diff results from diff()
+///     pattern: Path glob such as "spec.containers[*].image", or a list of
+///         them; a result matches when its path or one of its ancestors does
+///
+/// Returns:
+///     A new list with the matching results, in their original order
+#[pyfunction]
+pub fn filter_by_path<'py>(
+    py: Python<'py>,
+    results: &Bound<'py, PyList>,
+    pattern: &Bound<'py, PyAny>,
+) -> PyResult<Bound<'py, PyList>> {
+    let patterns = crate::compile_path_patterns(one_or_many(pattern)?)?;
+    let kept = PyList::empty_bound(py);
+    for result in results.iter() {
+        let path = result::str_field(&result, "path")?;
+        if patterns.iter().any(|pattern| pattern.covers(&path)) {
+            kept.append(result)?;
+        }
+    }
+    Ok(kept)
+}
+
+fn one_or_many(value: &Bound<'_, PyAny>) -> PyResult<Vec<String>> {
+    match value.extract::<String>() {
+        Ok(single) => Ok(vec![single]),
+        Err(_) => value.extract(),
+    }
+}
@@ -0,0 +1,189 @@
+//! Path/value mappings of documents, in the path syntax of diff results.
+
+use std::collections::BTreeMap;
+
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+use serde_json::Value;
+
+use crate::segments::{format_escaped, parse_escaped, Segment};
+
+/// Map every leaf of a document to its path
+///
+/// Paths use the syntax of diff results (`spec.ports[0].name`), so a result's
+/// path looks up the value it refers to. Keys holding `.`, `[`, `]` or `\`
+/// are escaped as by `format_path()`, so every path reads back unambiguously.
+/// Scalars and empty containers are leaves; a scalar document maps to the
+/// empty path.
+///
+/// Args:
+///     doc: The document (dict, list, or primitive)
+///
+/// Returns:
+///     A dict of path to value
+///
+/// Raises:
+///     ValueError: If the document has an empty key, which no path can name
+///
+/// Example:
+///     >>> flatten({"a": {"b": [1, 2]}})
+///     {'a.b[0]': 1, 'a.b[1]': 2}
+#[pyfunction]
+pub fn flatten<'py>(py: Python<'py>, doc: &Bound<'py, PyAny>) -> PyResult<Bound<'py, PyDict>> {
+    let doc = crate::python_to_json_value(doc)?;
+    let mut leaves = Vec::new();
+    push_leaves(&doc, &mut Vec::new(), &mut leaves)
+        .map_err(PyErr::new::<pyo3::exceptions::PyValueError, _>)?;
+    let mapping = PyDict::new_bound(py);
+    for (path, value) in leaves {
+        mapping.set_item(path, crate::json_value_to_python(py, value)?)?;
+    }
+    Ok(mapping)
+}
+
+fn push_leaves<'a>(
+    value: &'a Value,
+    segments: &mut Vec<Segment>,
+    leaves: &mut Vec<(String, &'a Value)>,
+) -> Result<(), String> {
+    let children: Vec<(Segment, &Value)> = match value {
+        Value::Object(map) if !map.is_empty() => map
+            .iter()
+            .map(|(key, child)| (Segment::Key(key.clone()), child))
+            .collect(),
+        Value::Array(items) if !items.is_empty() => items
+            .iter()
+            .enumerate()
+            .map(|(i, child)| (Segment::Index(i), child))
+            .collect(),
+        leaf => {
+            leaves.push((format_escaped(segments)?, leaf));
+            return Ok(());
+        }
+    };
+    for (segment, child) in children {
+        segments.push(segment);
+        push_leaves(child, segments, leaves)?;
+        segments.pop();
+    }
+    Ok(())
+}
+
+/// Rebuild a document from a path to value mapping
+///
+/// The inverse of `flatten()`: paths are read as by `parse_path()`, `[n]`
+/// segments become list elements and keys become dict keys.
+///
+/// Args:
+///     mapping: A dict of path to value
+///
+/// Returns:
+///     The document; an empty mapping gives an empty dict
+///
+/// Raises:
+///     ValueError: If a path is malformed, paths conflict (`a` and `a.b`), a
+///         list has gaps, or a path identifies elements by key (`items[id=1]`)
+#[pyfunction]
+pub fn unflatten(py: Python, mapping: &Bound<'_, PyDict>) -> PyResult<PyObject> {
+    let mut root = Slot::Empty;
+    for (path, value) in mapping.iter() {
+        let path = path.extract::<String>()?;
+        let value = crate::python_to_json_value(&value)?;
+        parse_escaped(&path)
+            .and_then(|segments| root.insert(&segments, value))
+            .map_err(|e| {
+                PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+                    "Cannot unflatten '{path}': {e}"
+                ))
+            })?;
+    }
+    let document = match root {
+        Slot::Empty => Value::Object(Default::default()),
+        root => root
+            .finish(&mut Vec::new())
+            .map_err(PyErr::new::<pyo3::exceptions::PyValueError, _>)?,
+    };
+    crate::json_value_to_python(py, &document)
+}
+
+/// A document under construction.
+enum Slot {
+    Empty,
+    Leaf(Value),
+    Object(BTreeMap<String, Slot>),
+    Array(Vec<Slot>),
+}
+
+impl Slot {
+    fn insert(&mut self, segments: &[Segment], value: Value) -> Result<(), String> {
+        let Some((first, rest)) = segments.split_first() else {
+            return match self {
+                Self::Empty => {
+                    *self = Self::Leaf(value);
+                    Ok(())
+                }
+                _ => Err("it conflicts with another path".to_string()),
+            };
+        };
+        match first {
+            Segment::Index(index) => {
+                if let Self::Empty = self {
+                    *self = Self::Array(Vec::new());
+                }
+                let Self::Array(items) = self else {
+                    return Err("it conflicts with another path".to_string());
+                };
+                if items.len() <= *index {
+                    items.resize_with(index + 1, || Self::Empty);
+                }
+                items[*index].insert(rest, value)
+            }
+            Segment::Id(_) => Err(format!(
+                "{} does not name a list position",
+                format_escaped(std::slice::from_ref(first))?
+            )),
+            Segment::Key(key) => {
+                if let Self::Empty = self {
+                    *self = Self::Object(BTreeMap::new());
+                }
+                let Self::Object(map) = self else {
+                    return Err("it conflicts with another path".to_string());
+                };
+                map.entry(key.clone())
+                    .or_insert(Self::Empty)
+                    .insert(rest, value)
+            }
+        }
+    }
+
+    /// The finished value at `path`.
+    fn finish(self, path: &mut Vec<Segment>) -> Result<Value, String> {
+        let mut child_value = |segment: Segment, child: Self| {
+            path.push(segment);
+            let value = child.finish(path);
+            path.pop();
+            value
+        };
+        match self {
+            Self::Empty => Err(format!(
+                "Cannot unflatten: no value for '{}'",
+                format_escaped(path)?
+            )),
+            Self::Leaf(value) => Ok(value),
+            Self::Object(map) => map
+                .into_iter()
+                .map(|(key, child)| {
+                    let value = child_value(Segment::Key(key.clone()), child)?;
+                    Ok((key, value))
+                })
+                .collect::<Result<_, _>>()
+                .map(Value::Object),
+            Self::Array(items) => items
+                .into_iter()
+                .enumerate()
+                .map(|(i, child)| child_value(Segment::Index(i), child))
+                .collect::<Result<_, _>>()
+                .map(Value::Array),
+        }
+    }
+}
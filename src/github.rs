@@ -0,0 +1,97 @@
+//! GitHub Actions workflow commands that surface each change as an annotation.
+
+use serde_json::Value;
+
+use crate::result::Difference;
+
+/// Annotation levels GitHub Actions understands.
+pub const LEVELS: &[&str] = &["notice", "warning", "error"];
+
+/// Render one `::warning file=...,title=...::message` line per difference.
+/// `Unchanged` entries produce no annotation.
+pub fn render(results: &[Difference], file: Option<&str>, level: &str) -> String {
+    let mut output = String::new();
+    for result in results.iter().filter(|result| result.is_change()) {
+        let message = match result {
+            Difference::Added(path, value) => format!("{} added: {value}", display_path(path)),
+            Difference::Removed(path, value) => {
+                format!("{} removed (was {value})", display_path(path))
+            }
+            Difference::Modified(path, old, new) => {
+                format!("{} changed from {old} to {new}", display_path(path))
+            }
+            Difference::TypeChanged(path, old, new) => format!(
+                "{} changed type from {} {old} to {} {new}",
+                display_path(path),
+                type_name(old),
+                type_name(new)
+            ),
+            Difference::Moved(path, from_index, to_index) => format!(
+                "{} moved from index {from_index} to {to_index}",
+                display_path(path)
+            ),
+            Difference::Renamed(path, new_path, _) => {
+                format!("{} renamed to {new_path}", display_path(path))
+            }
+            Difference::Comment(path, old, new) => match (old, new) {
+                (None, Some(new)) => format!("{} comment added: {new}", display_path(path)),
+                (Some(old), None) => format!("{} comment removed (was {old})", display_path(path)),
+                _ => format!(
+                    "{} comment changed from {} to {}",
+                    display_path(path),
+                    old.as_deref().unwrap_or_default(),
+                    new.as_deref().unwrap_or_default()
+                ),
+            },
+            Difference::Unchanged(..) => continue,
+        };
+        let mut properties = Vec::new();
+        if let Some(file) = file {
+            properties.push(format!("file={}", escape_property(file)));
+        }
+        properties.push(format!(
+            "title={}",
+            escape_property(&format!(
+                "{} {}",
+                result.kind(),
+                display_path(result.path())
+            ))
+        ));
+        output.push_str(&format!(
+            "::{level} {}::{}\n",
+            properties.join(","),
+            escape_data(&message)
+        ));
+    }
+    output
+}
+
+fn display_path(path: &str) -> &str {
+    match path {
+        "" => "(root)",
+        path => path,
+    }
+}
+
+fn type_name(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "boolean",
+        Value::Number(_) => "number",
+        Value::String(_) => "string",
+        Value::Array(_) => "array",
+        Value::Object(_) => "object",
+    }
+}
+
+/// Escape a message the way `@actions/core` does.
+fn escape_data(text: &str) -> String {
+    text.replace('%', "%25")
+        .replace('\r', "%0D")
+        .replace('\n', "%0A")
+}
+
+/// Escape a property value; `:` and `,` would end it early.
+fn escape_property(text: &str) -> String {
+    escape_data(text).replace(':', "%3A").replace(',', "%2C")
+}
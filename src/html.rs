@@ -0,0 +1,195 @@
+//! Self-contained HTML report rendering: collapsible sections per path, with
+//! old and new values side by side.
+
+use std::collections::HashMap;
+
+use serde_json::Value;
+
+use crate::paths::{join_path, split_path};
+use crate::result::{Difference, KINDS};
+
+/// Rules are scoped to the `.diffx` container so a fragment embedded in a
+/// notebook leaves the surrounding page alone.
+const STYLE: &str = ".diffx{font-family:system-ui,sans-serif;color:#24292f}\
+.diffx table{border-collapse:collapse;width:100%}\
+.diffx th,.diffx td{border:1px solid #d0d7de;padding:.4rem .6rem;text-align:left;vertical-align:top}\
+.diffx th{background:#f6f8fa}\
+.diffx td.path,.diffx td.value{font-family:ui-monospace,monospace;white-space:pre-wrap;word-break:break-all}\
+.diffx tr.added td.type{color:#1a7f37}\
+.diffx tr.removed td.type{color:#cf222e}\
+.diffx tr.modified td.type{color:#9a6700}\
+.diffx tr.type-changed td.type{color:#8250df}\
+.diffx tr.moved td.type{color:#0969da}\
+.diffx tr.renamed td.type{color:#0969da}\
+.diffx tr.unchanged td.type{color:#57606a}\
+.diffx tr.comment td.type{color:#57606a}\
+.diffx tr.added{background:#dafbe1}\
+.diffx tr.removed{background:#ffebe9}\
+.diffx tr.modified,.diffx tr.type-changed{background:#fff8c5}\
+.diffx details{margin:.5rem 0 .5rem 1rem}\
+.diffx summary{cursor:pointer;font-family:ui-monospace,monospace}\
+.diffx summary .count{background:#eaeef2;border-radius:1rem;padding:0 .5rem;font-size:.85em}\
+.diffx details table{margin:.4rem 0}";
+
+/// Render diff results as a standalone HTML document.
+///
+/// Results are nested by path in collapsible sections; chains of sections
+/// without changes of their own collapse into one (`spec.template`).
+pub fn render(results: &[Difference]) -> String {
+    let mut html = String::new();
+    html.push_str("<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n");
+    html.push_str("<title>diffx report</title>\n");
+    html.push_str(&format!("<style>body{{margin:2rem}}{STYLE}</style>\n"));
+    html.push_str("</head>\n<body>\n<div class=\"diffx\">\n<h1>diffx report</h1>\n");
+    push_report(&mut html, results);
+    html.push_str("</div>\n</body>\n</html>\n");
+    html
+}
+
+/// Render diff results as an HTML fragment with its own scoped styles, for
+/// embedding in another page such as a notebook cell's output.
+pub fn render_fragment(results: &[Difference]) -> String {
+    let mut html = format!("<style>{STYLE}</style>\n<div class=\"diffx\">\n");
+    push_report(&mut html, results);
+    html.push_str("</div>\n");
+    html
+}
+
+/// The summary line followed by the nested sections.
+fn push_report(html: &mut String, results: &[Difference]) {
+    let counts: Vec<String> = KINDS
+        .iter()
+        .filter_map(|kind| {
+            let count = results
+                .iter()
+                .filter(|result| result.kind() == *kind)
+                .count();
+            (count > 0).then(|| format!("{count} {}", kind_label(kind)))
+        })
+        .collect();
+    html.push_str(&format!(
+        "<p class=\"summary\">{} difference(s){}</p>\n",
+        results.iter().filter(|result| result.is_change()).count(),
+        match counts.is_empty() {
+            true => String::new(),
+            false => format!(": {}", counts.join(", ")),
+        }
+    ));
+
+    let mut root = Node::default();
+    for result in results {
+        root.insert(&split_path(result.path()), result);
+    }
+    push_table(html, &root.changes);
+    for (segment, child) in &root.children {
+        push_section(html, segment.to_string(), child);
+    }
+}
+
+/// Results grouped by path segment, children in first-seen order.
+#[derive(Default)]
+struct Node<'a> {
+    changes: Vec<&'a Difference>,
+    children: Vec<(&'a str, Node<'a>)>,
+    index: HashMap<&'a str, usize>,
+    /// Differences in this subtree, `Unchanged` entries excluded.
+    count: usize,
+}
+
+impl<'a> Node<'a> {
+    fn insert(&mut self, segments: &[&'a str], result: &'a Difference) {
+        if result.is_change() {
+            self.count += 1;
+        }
+        let Some((first, rest)) = segments.split_first() else {
+            self.changes.push(result);
+            return;
+        };
+        let position = *self.index.entry(first).or_insert_with(|| {
+            self.children.push((first, Node::default()));
+            self.children.len() - 1
+        });
+        self.children[position].1.insert(rest, result);
+    }
+}
+
+fn push_section(html: &mut String, mut label: String, mut node: &Node) {
+    while let ([(segment, child)], true) = (node.children.as_slice(), node.changes.is_empty()) {
+        label = join_path(&[&label, segment]);
+        node = child;
+    }
+    html.push_str(&format!(
+        "<details open>\n<summary><span class=\"key\">{}</span> <span class=\"count\">{}</span></summary>\n",
+        escape(&label),
+        node.count
+    ));
+    push_table(html, &node.changes);
+    for (segment, child) in &node.children {
+        push_section(html, segment.to_string(), child);
+    }
+    html.push_str("</details>\n");
+}
+
+fn push_table(html: &mut String, results: &[&Difference]) {
+    if results.is_empty() {
+        return;
+    }
+    html.push_str("<table>\n<thead><tr><th>Type</th><th>Path</th><th>Old value</th><th>New value</th></tr></thead>\n<tbody>\n");
+    for result in results {
+        push_row(html, result);
+    }
+    html.push_str("</tbody>\n</table>\n");
+}
+
+fn push_row(html: &mut String, result: &Difference) {
+    let (class, old, new) = match result {
+        Difference::Added(_, value) => ("added", String::new(), render_value(value)),
+        Difference::Removed(_, value) => ("removed", render_value(value), String::new()),
+        Difference::Modified(_, old, new) => ("modified", render_value(old), render_value(new)),
+        Difference::TypeChanged(_, old, new) => {
+            ("type-changed", render_value(old), render_value(new))
+        }
+        Difference::Moved(_, from_index, to_index) => {
+            ("moved", format!("[{from_index}]"), format!("[{to_index}]"))
+        }
+        Difference::Renamed(path, new_path, _) => ("renamed", escape(path), escape(new_path)),
+        Difference::Unchanged(_, value) => ("unchanged", render_value(value), render_value(value)),
+        Difference::Comment(_, old, new) => (
+            "comment",
+            escape(old.as_deref().unwrap_or_default()),
+            escape(new.as_deref().unwrap_or_default()),
+        ),
+    };
+    html.push_str(&format!(
+        "<tr class=\"{class}\"><td class=\"type\">{}</td><td class=\"path\">{}</td><td class=\"value\">{old}</td><td class=\"value\">{new}</td></tr>\n",
+        result.kind(),
+        escape(result.path()),
+    ));
+}
+
+fn kind_label(kind: &str) -> String {
+    match kind {
+        "TypeChanged" => "type changed".to_string(),
+        other => other.to_lowercase(),
+    }
+}
+
+fn render_value(value: &Value) -> String {
+    escape(&value.to_string())
+}
+
+/// Escape text for HTML and XML content and attribute values.
+pub fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
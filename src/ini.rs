@@ -0,0 +1,188 @@
+//! INI parsing with configparser-compatible options.
+
+use serde_json::{Map, Value};
+
+/// Maximum nesting of `%(name)s` references, matching configparser.
+const MAX_INTERPOLATION_DEPTH: usize = 10;
+
+#[derive(Debug, Clone)]
+pub struct IniOptions {
+    /// Lowercase option names (configparser's default `optionxform`).
+    pub case_insensitive_keys: bool,
+    /// Lowercase section names.
+    pub case_insensitive_sections: bool,
+    /// Section receiving keys that appear before any header; its values are
+    /// inherited by every other section, like configparser's DEFAULT.
+    pub default_section: Option<String>,
+    /// Expand `%(name)s` references and `%%` escapes.
+    pub interpolation: bool,
+}
+
+impl Default for IniOptions {
+    /// configparser's defaults, apart from `optionxform`: keys keep their case.
+    fn default() -> Self {
+        Self {
+            case_insensitive_keys: false,
+            case_insensitive_sections: false,
+            default_section: Some("DEFAULT".to_string()),
+            interpolation: false,
+        }
+    }
+}
+
+/// Parse INI into `{section: {key: value}}`.
+///
+/// Without `default_section`, keys before the first header stay at the top level.
+pub fn parse(content: &str, options: &IniOptions) -> Result<Value, String> {
+    let mut sections: Vec<(String, Map<String, Value>)> = Vec::new();
+    let mut top_level = Map::new();
+    let mut current: Option<usize> = None;
+    let mut last_key: Option<String> = None;
+
+    if let Some(default) = &options.default_section {
+        sections.push((default.clone(), Map::new()));
+        current = Some(0);
+    }
+
+    for (number, line) in content.lines().enumerate() {
+        let trimmed = line.trim();
+        if trimmed.is_empty() || trimmed.starts_with('#') || trimmed.starts_with(';') {
+            last_key = None;
+            continue;
+        }
+
+        // Indented lines continue the previous value
+        if line.starts_with(char::is_whitespace) {
+            if let Some(key) = &last_key {
+                let entries = match current {
+                    Some(index) => &mut sections[index].1,
+                    None => &mut top_level,
+                };
+                if let Some(Value::String(value)) = entries.get_mut(key) {
+                    value.push('\n');
+                    value.push_str(trimmed);
+                    continue;
+                }
+            }
+        }
+
+        if trimmed.starts_with('[') && trimmed.ends_with(']') {
+            let mut name = trimmed[1..trimmed.len() - 1].trim().to_string();
+            if options.case_insensitive_sections {
+                name = name.to_lowercase();
+            }
+            current = Some(
+                match sections.iter().position(|(existing, _)| *existing == name) {
+                    Some(index) => index,
+                    None => {
+                        sections.push((name, Map::new()));
+                        sections.len() - 1
+                    }
+                },
+            );
+            last_key = None;
+            continue;
+        }
+
+        let (key, value) = match trimmed.find(['=', ':']) {
+            Some(position) => (
+                trimmed[..position].trim(),
+                Value::String(trimmed[position + 1..].trim().to_string()),
+            ),
+            None => (trimmed, Value::Null),
+        };
+        if key.is_empty() {
+            return Err(format!("line {}: missing key", number + 1));
+        }
+        let key = if options.case_insensitive_keys {
+            key.to_lowercase()
+        } else {
+            key.to_string()
+        };
+
+        let entries = match current {
+            Some(index) => &mut sections[index].1,
+            None => &mut top_level,
+        };
+        entries.insert(key.clone(), value);
+        last_key = Some(key);
+    }
+
+    let defaults = match &options.default_section {
+        Some(_) => sections[0].1.clone(),
+        None => Map::new(),
+    };
+
+    let mut root = top_level;
+    for (index, (name, mut entries)) in sections.into_iter().enumerate() {
+        if index > 0 || options.default_section.is_none() {
+            for (key, value) in &defaults {
+                entries.entry(key.clone()).or_insert_with(|| value.clone());
+            }
+        }
+        if options.interpolation {
+            entries = interpolate_section(&name, &entries)?;
+        }
+        root.insert(name, Value::Object(entries));
+    }
+    Ok(Value::Object(root))
+}
+
+fn interpolate_section(
+    section: &str,
+    entries: &Map<String, Value>,
+) -> Result<Map<String, Value>, String> {
+    let mut interpolated = Map::new();
+    for (key, value) in entries {
+        let value = match value {
+            Value::String(raw) => Value::String(interpolate(section, key, raw, entries, 1)?),
+            other => other.clone(),
+        };
+        interpolated.insert(key.clone(), value);
+    }
+    Ok(interpolated)
+}
+
+fn interpolate(
+    section: &str,
+    key: &str,
+    raw: &str,
+    entries: &Map<String, Value>,
+    depth: usize,
+) -> Result<String, String> {
+    if depth > MAX_INTERPOLATION_DEPTH {
+        return Err(format!(
+            "interpolation too deep in [{section}] {key} (max {MAX_INTERPOLATION_DEPTH})"
+        ));
+    }
+
+    let mut output = String::with_capacity(raw.len());
+    let mut rest = raw;
+    while let Some(position) = rest.find('%') {
+        output.push_str(&rest[..position]);
+        rest = &rest[position..];
+
+        if let Some(after) = rest.strip_prefix("%%") {
+            output.push('%');
+            rest = after;
+        } else if let Some(after) = rest.strip_prefix("%(") {
+            let end = after
+                .find(")s")
+                .ok_or_else(|| format!("bad interpolation syntax in [{section}] {key}: {raw}"))?;
+            let name = &after[..end];
+            let referenced = entries
+                .get(name)
+                .or_else(|| entries.get(&name.to_lowercase()))
+                .and_then(Value::as_str)
+                .ok_or_else(|| format!("[{section}] {key} references missing option '{name}'"))?;
+            output.push_str(&interpolate(section, name, referenced, entries, depth + 1)?);
+            rest = &after[end + 2..];
+        } else {
+            return Err(format!(
+                "'%' must be followed by '%' or '(' in [{section}] {key}: {raw}"
+            ));
+        }
+    }
+    output.push_str(rest);
+    Ok(output)
+}
@@ -0,0 +1,49 @@
+//! IP addresses and CIDR blocks such as `10.0.0.1`, `10.0.0.0/8` or `2001:db8::/32`.
+
+use std::net::Ipv6Addr;
+
+/// Write an address or CIDR block in its canonical form.
+///
+/// IPv4 octets lose their leading zeros (`10.00.000.1` becomes `10.0.0.1`;
+/// they are read as decimal, not octal), and IPv6 addresses are written as
+/// RFC 5952 recommends: lowercase, leading zeros dropped and the longest run
+/// of zero groups compressed. Prefix lengths are kept, so `10.0.0.1` and
+/// `10.0.0.1/32` stay different.
+pub fn normalize(text: &str) -> Option<String> {
+    let (address, prefix) = match text.split_once('/') {
+        Some((address, prefix)) => (address, Some(prefix)),
+        None => (text, None),
+    };
+    let (address, max_prefix) = match ipv4(address) {
+        Some(address) => (address, 32),
+        None => (address.parse::<Ipv6Addr>().ok()?.to_string(), 128),
+    };
+    match prefix {
+        Some(prefix) => {
+            if prefix.is_empty() || !prefix.bytes().all(|b| b.is_ascii_digit()) {
+                return None;
+            }
+            let prefix: u8 = prefix.parse().ok().filter(|&p| p <= max_prefix)?;
+            Some(format!("{address}/{prefix}"))
+        }
+        None => Some(address),
+    }
+}
+
+/// Four decimal octets, each up to three digits.
+fn ipv4(text: &str) -> Option<String> {
+    let octets = text
+        .split('.')
+        .map(|octet| {
+            let digits = !octet.is_empty() && octet.len() <= 3;
+            octet
+                .parse::<u8>()
+                .ok()
+                .filter(|_| digits && octet.bytes().all(|b| b.is_ascii_digit()))
+        })
+        .collect::<Option<Vec<u8>>>()?;
+    let [a, b, c, d] = octets[..] else {
+        return None;
+    };
+    Some(format!("{a}.{b}.{c}.{d}"))
+}
@@ -0,0 +1,113 @@
+//! JSON parsing that enforces parse limits while the value is built.
+
+use std::fmt;
+
+use serde::de::{self, DeserializeSeed, MapAccess, SeqAccess, Visitor};
+use serde_json::{Map, Number, Value};
+
+use crate::limits::ParseGuard;
+
+/// Parse a JSON document into the value `serde_json::from_str` builds,
+/// failing as soon as a value breaks one of `guard`'s limits.
+pub fn parse(content: &str, guard: &ParseGuard) -> Result<Value, serde_json::Error> {
+    let mut deserializer = serde_json::Deserializer::from_str(content);
+    let value = Guarded { guard, depth: 0 }.deserialize(&mut deserializer)?;
+    deserializer.end()?;
+    Ok(value)
+}
+
+/// A value inside `depth` containers.
+#[derive(Clone, Copy)]
+struct Guarded<'a> {
+    guard: &'a ParseGuard<'a>,
+    depth: usize,
+}
+
+impl Guarded<'_> {
+    fn check<E: de::Error>(result: Result<(), String>) -> Result<(), E> {
+        result.map_err(E::custom)
+    }
+
+    /// Count a value.
+    fn count<E: de::Error>(&self) -> Result<(), E> {
+        Self::check(self.guard.add_nodes(1))
+    }
+
+    /// Count a container and return the guard for its contents.
+    fn enter<E: de::Error>(&self) -> Result<Self, E> {
+        self.count::<E>()?;
+        let depth = self.depth + 1;
+        Self::check(self.guard.enter(depth))?;
+        Ok(Self {
+            guard: self.guard,
+            depth,
+        })
+    }
+}
+
+impl<'de> DeserializeSeed<'de> for Guarded<'_> {
+    type Value = Value;
+
+    fn deserialize<D: de::Deserializer<'de>>(self, deserializer: D) -> Result<Value, D::Error> {
+        deserializer.deserialize_any(self)
+    }
+}
+
+impl<'de> Visitor<'de> for Guarded<'_> {
+    type Value = Value;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("any valid JSON value")
+    }
+
+    fn visit_bool<E: de::Error>(self, value: bool) -> Result<Value, E> {
+        self.count::<E>()?;
+        Ok(Value::Bool(value))
+    }
+
+    fn visit_i64<E: de::Error>(self, value: i64) -> Result<Value, E> {
+        self.count::<E>()?;
+        Ok(Value::Number(value.into()))
+    }
+
+    fn visit_u64<E: de::Error>(self, value: u64) -> Result<Value, E> {
+        self.count::<E>()?;
+        Ok(Value::Number(value.into()))
+    }
+
+    fn visit_f64<E: de::Error>(self, value: f64) -> Result<Value, E> {
+        self.count::<E>()?;
+        Ok(Number::from_f64(value).map_or(Value::Null, Value::Number))
+    }
+
+    fn visit_str<E: de::Error>(self, value: &str) -> Result<Value, E> {
+        self.count::<E>()?;
+        Self::check(self.guard.string(value))?;
+        Ok(Value::String(value.to_string()))
+    }
+
+    fn visit_unit<E: de::Error>(self) -> Result<Value, E> {
+        self.count::<E>()?;
+        Ok(Value::Null)
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Value, A::Error> {
+        let inner = self.enter::<A::Error>()?;
+        let mut items = Vec::new();
+        while let Some(item) = seq.next_element_seed(inner)? {
+            items.push(item);
+        }
+        Ok(Value::Array(items))
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Value, A::Error> {
+        let inner = self.enter::<A::Error>()?;
+        let mut entries = Map::new();
+        while let Some(key) = map.next_key::<String>()? {
+            Self::check(self.guard.string(&key))?;
+            let value = map.next_value_seed(inner)?;
+            entries.insert(key, value);
+        }
+        Ok(Value::Object(entries))
+    }
+}
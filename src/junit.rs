@@ -0,0 +1,64 @@
+//! JUnit XML reports: one test case per compared path, failing when it changed.
+
+use crate::html::escape;
+use crate::result::Difference;
+
+/// Render a JUnit XML document with a single `<testsuite>` named `suite`.
+///
+/// Each difference is a failing test case named after its path; `Unchanged`
+/// entries (from `report_unchanged`) are passing ones.
+pub fn render(results: &[Difference], suite: &str) -> String {
+    let failures = results.iter().filter(|result| result.is_change()).count();
+    let suite = escape(suite);
+    let mut xml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    xml.push_str(&format!(
+        "<testsuites name=\"diffx\" tests=\"{}\" failures=\"{failures}\">\n",
+        results.len()
+    ));
+    xml.push_str(&format!(
+        "  <testsuite name=\"{suite}\" tests=\"{}\" failures=\"{failures}\">\n",
+        results.len()
+    ));
+    for result in results {
+        let name = match result.path() {
+            "" => "(root)",
+            path => path,
+        };
+        let open = format!(
+            "    <testcase name=\"{}\" classname=\"{suite}\"",
+            escape(name)
+        );
+        let details = match result {
+            Difference::Unchanged(..) => {
+                xml.push_str(&open);
+                xml.push_str("/>\n");
+                continue;
+            }
+            Difference::Added(_, value) => format!("added: {value}"),
+            Difference::Removed(_, value) => format!("removed: {value}"),
+            Difference::Modified(_, old, new) | Difference::TypeChanged(_, old, new) => {
+                format!("old: {old}\nnew: {new}")
+            }
+            Difference::Moved(_, from_index, to_index) => {
+                format!("moved from index {from_index} to {to_index}")
+            }
+            Difference::Renamed(_, new_path, _) => format!("renamed to {new_path}"),
+            Difference::Comment(_, old, new) => format!(
+                "old comment: {}\nnew comment: {}",
+                old.as_deref().unwrap_or_default(),
+                new.as_deref().unwrap_or_default()
+            ),
+        };
+        xml.push_str(&open);
+        xml.push_str(">\n");
+        xml.push_str(&format!(
+            "      <failure type=\"{kind}\" message=\"{kind}: {}\">{}</failure>\n",
+            escape(name),
+            escape(&details),
+            kind = result.kind(),
+        ));
+        xml.push_str("    </testcase>\n");
+    }
+    xml.push_str("  </testsuite>\n</testsuites>\n");
+    xml
+}
@@ -0,0 +1,175 @@
+//! Known keyword arguments, so misspelled options raise instead of being ignored.
+
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+
+/// Options accepted by `diff()`.
+pub const DIFF: &[&str] = &[
+    "preset",
+    "compare",
+    "epsilon",
+    "epsilons",
+    "epsilon_relative",
+    "coerce_numeric_strings",
+    "ignore_number_format",
+    "coerce_bools",
+    "compare_durations",
+    "compare_quantities",
+    "compare_versions",
+    "compare_ips",
+    "array_id_key",
+    "array_order",
+    "array_strategy",
+    "ignore_keys_regex",
+    "ignore_values_regex",
+    "ignore_paths",
+    "include_paths",
+    "path_filter",
+    "path_filter_regex",
+    "output_format",
+    "ignore_whitespace",
+    "trim_whitespace",
+    "collapse_whitespace",
+    "normalize_line_endings",
+    "ignore_case",
+    "ignore_key_case",
+    "unicode_normalize",
+    "unicode_normalize_keys",
+    "empty_as_missing",
+    "brief_mode",
+    "quiet_mode",
+    "datetime_tolerance",
+    "datetime_timezone",
+    "detect_moves",
+    "detect_renames",
+    "report_unchanged",
+    "sort",
+    "path_style",
+    "big_int",
+    "binary",
+    "enum",
+    "default",
+    "key_policy",
+    "comparators",
+    "normalize",
+    "max_results",
+    "max_depth",
+    "timeout_ms",
+    "memory_limit_mb",
+    "openapi",
+    "classify_breaking",
+    "comments",
+];
+
+/// Options accepted by the parsers. Each parser ignores the format options
+/// of the others, so `diff_files` and `run` can forward the whole set.
+pub const PARSE: &[&str] = &[
+    "hardened",
+    "max_input_bytes",
+    "max_nesting_depth",
+    "max_string_length",
+    "max_nodes",
+    "memory_limit_mb",
+    "yaml_anchors",
+    "case_insensitive_keys",
+    "case_insensitive_sections",
+    "default_section",
+    "interpolation",
+    "attribute_prefix",
+    "text_key",
+    "merge_attributes",
+    "delimiter",
+    "quotechar",
+    "escapechar",
+    "comment",
+    "has_header",
+    "columns",
+    "infer_types",
+    "strict",
+    "duplicate_keys",
+];
+
+/// Options accepted by `format_output()`.
+pub const FORMAT: &[&str] = &[
+    "old", "new", "context", "width", "color", "file", "level", "suite", "fragment",
+];
+
+/// The value of `name` in optional kwargs.
+pub fn get<'py>(
+    kwargs: Option<&Bound<'py, PyDict>>,
+    name: &str,
+) -> PyResult<Option<Bound<'py, PyAny>>> {
+    match kwargs {
+        Some(kwargs) => kwargs.get_item(name),
+        None => Ok(None),
+    }
+}
+
+/// Raise `TypeError` naming every kwarg not in `known`, with close matches.
+pub fn check(
+    function: &str,
+    kwargs: Option<&Bound<'_, PyDict>>,
+    known: &[&[&str]],
+) -> PyResult<()> {
+    let Some(kwargs) = kwargs else {
+        return Ok(());
+    };
+    let known: Vec<&str> = known
+        .iter()
+        .flat_map(|group| group.iter().copied())
+        .collect();
+
+    let mut unknown = Vec::new();
+    for key in kwargs.keys() {
+        let key: String = key.extract()?;
+        if !known.contains(&key.as_str()) {
+            let suggestions = close_matches(&key, &known);
+            unknown.push(match suggestions.as_slice() {
+                [] => format!("'{key}'"),
+                _ => format!("'{key}' (did you mean {}?)", suggestions.join(" or ")),
+            });
+        }
+    }
+
+    match unknown.as_slice() {
+        [] => Ok(()),
+        [name] => Err(PyErr::new::<pyo3::exceptions::PyTypeError, _>(format!(
+            "{function}() got an unexpected keyword argument {name}"
+        ))),
+        names => Err(PyErr::new::<pyo3::exceptions::PyTypeError, _>(format!(
+            "{function}() got unexpected keyword arguments: {}",
+            names.join(", ")
+        ))),
+    }
+}
+
+/// Known names within a small edit distance of `name`, closest first.
+fn close_matches(name: &str, known: &[&str]) -> Vec<String> {
+    let max_distance = (name.chars().count() / 4).clamp(1, 3);
+    let mut matches: Vec<(usize, &str)> = known
+        .iter()
+        .map(|candidate| (edit_distance(name, candidate), *candidate))
+        .filter(|&(distance, _)| distance <= max_distance)
+        .collect();
+    matches.sort();
+    matches
+        .into_iter()
+        .take(3)
+        .map(|(_, candidate)| format!("'{candidate}'"))
+        .collect()
+}
+
+/// Levenshtein distance over characters.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, a_char) in a.chars().enumerate() {
+        let mut current = vec![i + 1; b.len() + 1];
+        for (j, b_char) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(a_char != *b_char);
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
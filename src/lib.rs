@@ -5,16 +5,91 @@
 #![allow(clippy::useless_conversion)]
 #![allow(clippy::uninlined_format_args)]
 
+mod access;
+mod align;
+mod color;
+mod comments;
+mod convert;
+mod datetime;
+mod delimited;
+mod detect;
+mod duplicates;
+mod duration;
+mod engine;
+mod errors;
+mod filters;
+mod flatten;
+mod github;
+mod html;
+mod ini;
+mod ip;
+mod json;
+mod junit;
+mod kwargs;
+mod limits;
+mod memory;
+mod merge_patch;
+mod openapi;
+mod options;
+mod ordering;
+mod patch;
+mod paths;
+mod positions;
+mod presets;
+mod quantity;
+mod report;
+mod result;
+mod scoring;
+mod segments;
+mod serialize;
+mod side_by_side;
+mod storage;
+mod tap;
+mod terraform;
+mod toml_value;
+mod tree;
+mod unified;
+mod version;
+mod xml;
+mod yaml;
+
 use diffx_core::{
     diff as core_diff, format_output as core_format_output, parse_csv as core_parse_csv,
-    parse_ini as core_parse_ini, parse_json as core_parse_json, parse_toml as core_parse_toml,
-    parse_xml as core_parse_xml, parse_yaml as core_parse_yaml, DiffOptions, DiffResult,
-    DiffxSpecificOptions, OutputFormat,
+    parse_ini as core_parse_ini, parse_yaml as core_parse_yaml, DiffOptions, DiffxSpecificOptions,
+    OutputFormat,
 };
 use pyo3::prelude::*;
-use pyo3::types::{PyAny, PyDict, PyList};
+use pyo3::types::{PyAny, PyBool, PyDict, PyFloat, PyList, PyLong, PyString};
 use regex::Regex;
 use serde_json::Value;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use convert::Conversion;
+use delimited::CsvOptions;
+use duplicates::DuplicateKeys;
+use engine::{
+    ArrayOrder, ArrayStrategy, BoolTable, Compare, EngineOptions, IdKey, Normalize, UnicodeForm,
+};
+use errors::{
+    parse_error, warn_parse, CircularReferenceError, DiffTimeoutError, MemoryLimitError,
+    ParseError, ParseLimitError, ParseWarning,
+};
+use ini::IniOptions;
+use limits::{Exceeded, ParseGuard, ParseLimits};
+use memory::{MemoryBudget, TrackingAllocator};
+use openapi::OpenApiMode;
+use ordering::SortOrder;
+use paths::PathPattern;
+use report::ReportFormat;
+use result::Difference;
+use segments::PathStyle;
+use xml::XmlOptions;
+use yaml::AnchorMode;
+
+// Counts Rust allocations per thread so `memory_limit_mb` can be enforced
+#[global_allocator]
+static ALLOCATOR: TrackingAllocator = TrackingAllocator;
 
 // ============================================================================
 // Main diff function
@@ -27,44 +102,447 @@ use serde_json::Value;
 /// Args:
 ///     old: The old value (dict, list, or primitive)
 ///     new: The new value (dict, list, or primitive)
+///     options: A reusable `DiffOptions`; keyword arguments override its fields
 ///     **kwargs: Optional parameters:
+///         preset (str): Named option preset ("strict", "lenient", or one added
+///             with `register_preset`); `options` and keyword arguments override it
+///         compare (str): "values" (default) compares leaf values; "structure"
+///             reports only added and removed keys and type changes; "types"
+///             reports only type changes at paths both sides have; "keys"
+///             reports only keys one side lacks
 ///         epsilon (float): Numerical comparison tolerance
-///         array_id_key (str): Key to use for array element identification
+///         epsilons (dict[str, float]): Per-path tolerances keyed by path glob,
+///             e.g. {"price": 0.01}; the first matching pattern wins
+///         epsilon_relative (float): Relative tolerance; numbers within this
+///             fraction of the larger magnitude are equal (0.01 is 1%)
+///         coerce_numeric_strings (bool): Compare strings holding a JSON number
+///             ("8080", "0.5") as that number, tolerances included
+///         ignore_number_format (bool): Integers and floats holding the same
+///             value are equal (1 and 1.0, 1e3 and 1000)
+///         coerce_bools (bool | dict[str, list]): A boolean equals its spellings:
+///             True uses "true"/"yes"/"y"/"on"/"1"/1 and "false"/"no"/"n"/
+///             "off"/"0"/0, a dict {"true": [...], "false": [...]} its own
+///             strings (case-insensitive) and numbers
+///         compare_durations (bool): Compare duration strings by their length,
+///             so "1h", "3600s", "60m" and "PT1H" are equal
+///         compare_quantities (bool): Compare Kubernetes / SI quantities by
+///             magnitude, so "1Gi" equals "1024Mi" and "500m" equals 0.5
+///         compare_versions (bool): Compare version strings by SemVer
+///             precedence ("v1.2" equals "1.2.0", build metadata is ignored)
+///             and add a "bump" ("major", "minor", "patch" or "prerelease") to
+///             Modified results between two versions
+///         compare_ips (bool): Compare IP addresses and CIDR blocks in their
+///             canonical form, so "10.00.000.1/32" equals "10.0.0.1/32" and
+///             IPv6 compressed and expanded spellings are equal
+///         comparators (dict[str, Callable]): Callables fn(old, new) -> bool
+///             that decide equality for values at paths matching each glob
+///         normalize (Callable | dict[str, Callable]): Applied to both sides
+///             before comparison; a callable normalizes every scalar value, a
+///             dict normalizes values at paths matching each glob
+///         array_id_key (str | list[str]): Key, or keys that together identify
+///             array elements; dotted keys ("metadata.uid") look up nested fields
 ///         ignore_keys_regex (str): Regex pattern for keys to ignore
-///         path_filter (str): Only show differences in paths containing this string
+///         ignore_values_regex (str): Treat two strings as equal when both match
+///             this regex, whatever their keys (timestamps, UUIDs, hashes)
+///         path_filter (str): Only show differences in paths containing this string,
+///             or at or below paths matching a JSONPath starting with `$`
+///         path_filter_regex (str): Only show differences in paths matching this regex
 ///         output_format (str): Output format ("diffx", "json", "yaml")
 ///         ignore_whitespace (bool): Ignore whitespace differences
+///         trim_whitespace (bool): Ignore leading and trailing whitespace
+///         collapse_whitespace (bool): Treat every run of whitespace as a
+///             single space
+///         normalize_line_endings (bool): Treat "\r\n" and "\r" as "\n"
 ///         ignore_case (bool): Ignore case differences
+///         ignore_key_case (bool): Match dict keys case-insensitively, so
+///             "Content-Type" and "content-type" are the same key
+///         unicode_normalize (str): Put strings in this Unicode normalization
+///             form ("NFC", "NFD", "NFKC" or "NFKD") before comparing, so "é"
+///             composed and decomposed are equal
+///         unicode_normalize_keys (bool): Also match dict keys by their
+///             `unicode_normalize` form
+///         empty_as_missing (bool): Treat a key holding [] or {} like an absent
+///             key, so it is not reported as added or removed
 ///         brief_mode (bool): Report only whether files differ
 ///         quiet_mode (bool): Suppress normal output
+///         datetime_tolerance (float | timedelta): Compare datetimes at most
+///             this many seconds apart as equal
+///         datetime_timezone (str | timedelta | tzinfo): Read datetimes without
+///             an offset in this timezone ("UTC", "+09:00"), so they compare
+///             with ones that have one; implies datetime_tolerance=0
+///         array_order (str): "ordered" (default) compares arrays by index;
+///             "unordered" compares them as multisets
+///         array_strategy (str): "index" (default) pairs ordered array elements by
+///             position; "lcs" aligns them so insertions and deletions are
+///             reported minimally
+///         detect_moves (bool): Report array elements that only changed position
+///             as "Moved" results with "from_index" and "to_index"
+///         detect_renames (bool): Report a removed key and an added key with an
+///             equal value as one "Renamed" result with "new_path"
+///         report_unchanged (bool): Also return "Unchanged" entries for values
+///             that compared equal
+///         sort (str): Order of the results: "path" (paths as strings), "type"
+///             (by kind, then document order) or "document" (parents first,
+///             array elements by index); without it results come in traversal
+///             order
+///         big_int (str): Integers outside the 64-bit range compare as their
+///             exact digits, as strings ("string", the default), as the nearest
+///             float ("float"), or raise OverflowError ("error")
+///         binary (str): bytes, bytearray and memoryview values compare as
+///             base64 strings ("base64", the default), as lists of byte values
+///             ("raw"), or as "sha256:<digest>" strings ("hash")
+///         enum (str): enum.Enum members compare as their value ("value", the
+///             default) or their name ("name")
+///         default (Callable): Called with values of unsupported types; its
+///             return value is converted instead, like json.dumps(default=)
+///         key_policy (str): Dict keys that are not strings raise TypeError
+///             ("error", the default), are converted to their JSON text
+///             ("stringify", so 1 becomes "1"), or are left out ("skip")
+///         max_results (int): Stop diffing once this many differences were
+///             found; "Unchanged" entries do not count
+///         timeout_ms (int): Abort with DiffTimeoutError when diffing takes
+///             longer than this many milliseconds
+///         max_depth (int): Compare containers nested deeper than this as
+///             opaque values, reported as a single Modified
+///         memory_limit_mb (int): Raise MemoryLimitError when the Rust side
+///             allocates more than this many megabytes
+///         ignore_paths (list[str]): Glob patterns of subtrees to skip, e.g.
+///             "metadata.annotations.*" or "items[*].timestamps"
+///         include_paths (list[str]): Glob patterns of the only subtrees to diff;
+///             everything else is skipped without being traversed
+///         openapi (bool): Treat the inputs as OpenAPI documents; parameter
+///             arrays align by `name` + `in` unless `array_id_key` is given
+///         classify_breaking (bool): With `openapi`, add a "breaking" flag to
+///             every result
+///         comments (tuple[dict, dict]): Comments on the keys of each side,
+///             as `source_comments()` returns them; keys both sides comment on
+///             differently are reported as "Comment" results
 ///
 /// Returns:
 ///     List[Dict]: List of differences found
 #[pyfunction]
-#[pyo3(signature = (old, new, **kwargs))]
+#[pyo3(signature = (old, new, options=None, **kwargs))]
 fn diff(
     py: Python,
     old: &Bound<'_, PyAny>,
     new: &Bound<'_, PyAny>,
+    options: Option<&Bound<'_, options::DiffOptions>>,
     kwargs: Option<&Bound<'_, PyDict>>,
 ) -> PyResult<PyObject> {
-    let old_json = python_to_json_value(old)?;
-    let new_json = python_to_json_value(new)?;
+    let kwargs = options::resolve(py, options, kwargs)?;
+    let kwargs = Some(&kwargs);
+    kwargs::check("diff", kwargs, &[kwargs::DIFF])?;
+    let memory_budget = match kwargs {
+        Some(kwargs) => extract_memory_limit_mb(kwargs)?.map(MemoryBudget::start),
+        None => None,
+    };
+    let conversion = Conversion::from_kwargs(kwargs)?;
+    let old_json = conversion.to_json(old)?;
+    let new_json = conversion.to_json(new)?;
+    check_memory_budget(memory_budget.as_ref())?;
     let options = build_options_from_kwargs(kwargs)?;
+    let engine_options = build_engine_options_from_kwargs(kwargs)?;
+    let sort_order = extract_sort_order(kwargs)?;
+    let path_style = extract_path_style(kwargs)?;
+    let classify_breaking =
+        OpenApiMode::from_kwargs(kwargs)?.is_some_and(|mode| mode.classify_breaking);
+    let classify_bumps = engine_options
+        .as_ref()
+        .is_some_and(|options| options.compare_versions);
 
-    let results = core_diff(&old_json, &new_json, Some(&options)).map_err(|e| {
-        PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(format!("Diff error: {e}"))
-    })?;
+    let mut results = diff_values(&old_json, &new_json, &options, engine_options.as_ref())?;
+    if let Some(sort_order) = sort_order {
+        ordering::sort(&mut results, sort_order);
+    }
 
     let py_results = PyList::empty_bound(py);
     for result in results {
         let py_result = diff_result_to_python(py, &result)?;
+        if classify_breaking {
+            py_result
+                .bind(py)
+                .set_item("breaking", openapi::is_breaking(&result))?;
+        }
+        if let Some(bump) = classify_bumps.then(|| version::bump(&result)).flatten() {
+            py_result.bind(py).set_item("bump", bump)?;
+        }
+        if path_style == PathStyle::Segments {
+            segments::annotate(
+                py,
+                py_result.bind(py).downcast::<PyDict>()?,
+                &result,
+                &old_json,
+                &new_json,
+            )?;
+        }
         py_results.append(py_result)?;
     }
 
     Ok(py_results.into())
 }
 
+/// Summarize the differences between two Python objects
+///
+/// Args:
+///     old: The old value (dict, list, or primitive)
+///     new: The new value (dict, list, or primitive)
+///     options: A reusable `DiffOptions`; keyword arguments override its fields
+///     **kwargs: The same options as `diff()`
+///
+/// Returns:
+///     Dict with "total" (differences only), counts per kind ("added",
+///     "removed", "modified", "type_changed", "moved", "renamed",
+///     "unchanged"), "by_top_level_key" (differences per first path segment),
+///     "nodes_compared", "max_depth" (deepest level compared, the root being
+///     0), and "truncated"
+#[pyfunction]
+#[pyo3(signature = (old, new, options=None, **kwargs))]
+fn diff_stats(
+    py: Python,
+    old: &Bound<'_, PyAny>,
+    new: &Bound<'_, PyAny>,
+    options: Option<&Bound<'_, options::DiffOptions>>,
+    kwargs: Option<&Bound<'_, PyDict>>,
+) -> PyResult<PyObject> {
+    let kwargs = options::resolve(py, options, kwargs)?;
+    kwargs::check("diff_stats", Some(&kwargs), &[kwargs::DIFF])?;
+    let memory_budget = extract_memory_limit_mb(&kwargs)?.map(MemoryBudget::start);
+    let conversion = Conversion::from_kwargs(Some(&kwargs))?;
+    let old_json = conversion.to_json(old)?;
+    let new_json = conversion.to_json(new)?;
+    check_memory_budget(memory_budget.as_ref())?;
+    let kwargs = Some(&kwargs);
+    let options = build_options_from_kwargs(kwargs)?;
+    let mut engine_options = build_engine_options_from_kwargs(kwargs)?.unwrap_or_default();
+
+    // Collect one extra difference to tell whether max_results cut the diff short
+    let max_results = engine_options.max_results;
+    engine_options.max_results = max_results.map(|max_results| max_results + 1);
+    let (mut results, stats) =
+        engine::diff_with_stats(&old_json, &new_json, &options, &engine_options)?;
+    let truncated =
+        max_results.is_some_and(|max_results| engine::truncate(&mut results, max_results));
+
+    let summary = summarize_results(py, &results)?;
+    let by_top_level_key = PyDict::new_bound(py);
+    for result in results.iter().filter(|result| result.is_change()) {
+        let key = paths::split_path(result.path())
+            .first()
+            .copied()
+            .unwrap_or_default();
+        let count = match by_top_level_key.get_item(key)? {
+            Some(count) => count.extract::<usize>()? + 1,
+            None => 1,
+        };
+        by_top_level_key.set_item(key, count)?;
+    }
+    summary.set_item("by_top_level_key", by_top_level_key)?;
+    summary.set_item("nodes_compared", stats.nodes_compared)?;
+    summary.set_item("max_depth", stats.max_depth)?;
+    summary.set_item("truncated", truncated)?;
+    Ok(summary.into())
+}
+
+/// Counts per result kind, plus "total" counting differences only.
+fn summarize_results<'py>(py: Python<'py>, results: &[Difference]) -> PyResult<Bound<'py, PyDict>> {
+    let summary = PyDict::new_bound(py);
+    let total = results.iter().filter(|result| result.is_change()).count();
+    summary.set_item("total", total)?;
+    for (key, kind) in [
+        ("added", "Added"),
+        ("removed", "Removed"),
+        ("modified", "Modified"),
+        ("type_changed", "TypeChanged"),
+        ("moved", "Moved"),
+        ("renamed", "Renamed"),
+        ("unchanged", "Unchanged"),
+    ] {
+        let count = results
+            .iter()
+            .filter(|result| result.kind() == kind)
+            .count();
+        summary.set_item(key, count)?;
+    }
+    Ok(summary)
+}
+
+/// Check whether two Python objects are semantically equal
+///
+/// Stops at the first difference instead of building a result list, so it is
+/// much cheaper than `len(diff(old, new)) == 0` for large equal documents.
+///
+/// Args:
+///     old: The old value (dict, list, or primitive)
+///     new: The new value (dict, list, or primitive)
+///     options: A reusable `DiffOptions`; keyword arguments override its fields
+///     **kwargs: The same options as `diff()`
+///
+/// Returns:
+///     bool: True when `diff()` would report no differences
+#[pyfunction]
+#[pyo3(signature = (old, new, options=None, **kwargs))]
+fn semantic_equal(
+    py: Python,
+    old: &Bound<'_, PyAny>,
+    new: &Bound<'_, PyAny>,
+    options: Option<&Bound<'_, options::DiffOptions>>,
+    kwargs: Option<&Bound<'_, PyDict>>,
+) -> PyResult<bool> {
+    let kwargs = options::resolve(py, options, kwargs)?;
+    kwargs::check("semantic_equal", Some(&kwargs), &[kwargs::DIFF])?;
+    let memory_budget = extract_memory_limit_mb(&kwargs)?.map(MemoryBudget::start);
+    let kwargs = Some(&kwargs);
+    let options = build_options_from_kwargs(kwargs)?;
+    let mut engine_options = build_engine_options_from_kwargs(kwargs)?.unwrap_or_default();
+
+    // Identical inputs are equal under every option except comparators, which
+    // may call even identical values different, and key folding, which
+    // rejects keys that collide. Checking the Python objects directly skips
+    // converting large equal documents.
+    let shortcut = engine_options.comparators.is_empty()
+        && !engine_options.ignore_key_case
+        && !engine_options.unicode_normalize_keys;
+    if shortcut && python_values_identical(old, new)? {
+        return Ok(true);
+    }
+
+    let conversion = Conversion::from_kwargs(kwargs)?;
+    let old_json = conversion.to_json(old)?;
+    let new_json = conversion.to_json(new)?;
+    check_memory_budget(memory_budget.as_ref())?;
+
+    engine_options.report_unchanged = false;
+    engine_options.max_results = Some(1);
+    let results = engine::diff(&old_json, &new_json, &options, &engine_options)?;
+    Ok(results.is_empty())
+}
+
+/// Score how similar two Python objects are
+///
+/// Args:
+///     old: The old value (dict, list, or primitive)
+///     new: The new value (dict, list, or primitive)
+///     options: A reusable `DiffOptions`; keyword arguments override its fields
+///     **kwargs: The same options as `diff()`; ignored paths do not count
+///
+/// Returns:
+///     float: From 0.0 (no leaf in common) to 1.0 (equal): twice the number of
+///     matching leaves over the leaves of both values
+#[pyfunction]
+#[pyo3(signature = (old, new, options=None, **kwargs))]
+fn similarity(
+    py: Python,
+    old: &Bound<'_, PyAny>,
+    new: &Bound<'_, PyAny>,
+    options: Option<&Bound<'_, options::DiffOptions>>,
+    kwargs: Option<&Bound<'_, PyDict>>,
+) -> PyResult<f64> {
+    let kwargs = options::resolve(py, options, kwargs)?;
+    kwargs::check("similarity", Some(&kwargs), &[kwargs::DIFF])?;
+    let memory_budget = extract_memory_limit_mb(&kwargs)?.map(MemoryBudget::start);
+    let conversion = Conversion::from_kwargs(Some(&kwargs))?;
+    let old_json = conversion.to_json(old)?;
+    let new_json = conversion.to_json(new)?;
+    check_memory_budget(memory_budget.as_ref())?;
+    let kwargs = Some(&kwargs);
+    let options = build_options_from_kwargs(kwargs)?;
+    let mut engine_options = build_engine_options_from_kwargs(kwargs)?.unwrap_or_default();
+
+    // Every leaf has to be accounted for, equal or not
+    engine_options.report_unchanged = true;
+    engine_options.max_results = None;
+    let results = engine::diff(&old_json, &new_json, &options, &engine_options)?;
+    Ok(scoring::similarity(&results))
+}
+
+/// Normalize a document the way `diff()` compares it
+///
+/// Ignored keys and paths are removed, `normalize` callables (and presets)
+/// applied, strings folded by `ignore_whitespace` / `ignore_case`, strings
+/// matching `ignore_values_regex` replaced by "<ignored>", numbers rounded to
+/// the nearest multiple of their epsilon, and arrays the diff compares
+/// regardless of order (`array_order="unordered"`, or elements all
+/// identified by `array_id_key`) sorted. Dict keys come out sorted.
+///
+/// Args:
+///     doc: The document (dict, list, or primitive)
+///     options: A reusable `DiffOptions`; keyword arguments override its fields
+///     **kwargs: The same options as `diff()`; output-only options such as
+///         `sort` have no effect, and `comparators`, `epsilon_relative` and
+///         `coerce_bools` are not applied
+///
+/// Returns:
+///     The canonical document
+///
+/// Example:
+///     >>> canonicalize({"name": " Web ", "updatedAt": 1}, ignore_case=True,
+///     ...              ignore_keys_regex="^updatedAt$")
+///     {'name': ' web '}
+#[pyfunction]
+#[pyo3(signature = (doc, options=None, **kwargs))]
+fn canonicalize(
+    py: Python,
+    doc: &Bound<'_, PyAny>,
+    options: Option<&Bound<'_, options::DiffOptions>>,
+    kwargs: Option<&Bound<'_, PyDict>>,
+) -> PyResult<PyObject> {
+    let canonical = canonical_document(py, "canonicalize", doc, options, kwargs)?;
+    json_value_to_python(py, &canonical)
+}
+
+/// Digest of a document's canonical form
+///
+/// Documents with equal canonical forms (see `canonicalize()`) get equal
+/// hashes, so a cache can skip diffing documents whose hashes match. The
+/// hash is the SHA-256 of the canonical form as compact JSON with sorted
+/// keys, and stays the same across runs and platforms.
+///
+/// Args:
+///     doc: The document (dict, list, or primitive)
+///     options: A reusable `DiffOptions`; keyword arguments override its fields
+///     **kwargs: The same options as `canonicalize()`
+///
+/// Returns:
+///     str: Hex digest
+///
+/// Example:
+///     >>> digest = semantic_hash({"a": 1, "ts": 5}, ignore_keys_regex="^ts$")
+///     >>> digest == semantic_hash({"a": 1})
+///     True
+#[pyfunction]
+#[pyo3(signature = (doc, options=None, **kwargs))]
+fn semantic_hash(
+    py: Python,
+    doc: &Bound<'_, PyAny>,
+    options: Option<&Bound<'_, options::DiffOptions>>,
+    kwargs: Option<&Bound<'_, PyDict>>,
+) -> PyResult<String> {
+    let canonical = canonical_document(py, "semantic_hash", doc, options, kwargs)?;
+    py.import_bound("hashlib")?
+        .call_method1("sha256", (canonical.to_string().as_bytes(),))?
+        .call_method0("hexdigest")?
+        .extract()
+}
+
+/// Convert `doc` and canonicalize it under the resolved options.
+fn canonical_document(
+    py: Python,
+    function: &str,
+    doc: &Bound<'_, PyAny>,
+    options: Option<&Bound<'_, options::DiffOptions>>,
+    kwargs: Option<&Bound<'_, PyDict>>,
+) -> PyResult<Value> {
+    let kwargs = options::resolve(py, options, kwargs)?;
+    kwargs::check(function, Some(&kwargs), &[kwargs::DIFF])?;
+    let memory_budget = extract_memory_limit_mb(&kwargs)?.map(MemoryBudget::start);
+    let value = Conversion::from_kwargs(Some(&kwargs))?.to_json(doc)?;
+    check_memory_budget(memory_budget.as_ref())?;
+    let kwargs = Some(&kwargs);
+    let options = build_options_from_kwargs(kwargs)?;
+    let engine_options = build_engine_options_from_kwargs(kwargs)?.unwrap_or_default();
+    engine::canonicalize(&value, &options, &engine_options)
+}
+
 // ============================================================================
 // Parser functions
 // ============================================================================
@@ -73,14 +551,27 @@ fn diff(
 ///
 /// Args:
 ///     content: JSON string to parse
+///     **kwargs: Parse limits (see `build_parse_limits_from_kwargs`), plus:
+///         duplicate_keys (str): Keys that appear twice in an object keep the
+///             last value ("ignore", the default), also issue a ParseWarning
+///             ("warn"), or raise ParseError ("error")
 ///
 /// Returns:
 ///     Parsed Python object (dict, list, or primitive)
 #[pyfunction]
-fn parse_json(py: Python, content: &str) -> PyResult<PyObject> {
-    let value = core_parse_json(content).map_err(|e| {
-        PyErr::new::<pyo3::exceptions::PyValueError, _>(format!("JSON parse error: {e}"))
-    })?;
+#[pyo3(signature = (content, **kwargs))]
+fn parse_json(py: Python, content: &str, kwargs: Option<&Bound<'_, PyDict>>) -> PyResult<PyObject> {
+    kwargs::check("parse_json", kwargs, &[kwargs::PARSE])?;
+    let limits = build_parse_limits_from_kwargs(kwargs)?;
+    let value = parse_guarded(content, &limits, "JSON", json::parse)?;
+    let mut warnings = Vec::new();
+    check_duplicate_keys(
+        content,
+        "JSON",
+        extract_duplicate_keys(kwargs)?,
+        &mut warnings,
+    )?;
+    warn_parse(py, &warnings)?;
     json_value_to_python(py, &value)
 }
 
@@ -88,29 +579,88 @@ fn parse_json(py: Python, content: &str) -> PyResult<PyObject> {
 ///
 /// Args:
 ///     content: YAML string to parse
+///     **kwargs: Parse limits (see `build_parse_limits_from_kwargs`), plus:
+///         yaml_anchors (str): "resolve" expands aliases and applies `<<` merge keys;
+///             "preserve" keeps aliases as "*name" strings and `<<` as a literal key
+///         duplicate_keys (str): See `parse_json`; repeated `<<` keys are allowed
 ///
 /// Returns:
 ///     Parsed Python object (dict, list, or primitive)
 #[pyfunction]
-fn parse_yaml(py: Python, content: &str) -> PyResult<PyObject> {
-    let value = core_parse_yaml(content).map_err(|e| {
-        PyErr::new::<pyo3::exceptions::PyValueError, _>(format!("YAML parse error: {e}"))
-    })?;
+#[pyo3(signature = (content, **kwargs))]
+fn parse_yaml(py: Python, content: &str, kwargs: Option<&Bound<'_, PyDict>>) -> PyResult<PyObject> {
+    kwargs::check("parse_yaml", kwargs, &[kwargs::PARSE])?;
+    let limits = build_parse_limits_from_kwargs(kwargs)?;
+    let value = match yaml_anchor_mode(extract_anchor_mode(kwargs)?, &limits) {
+        Some(mode) => parse_guarded(content, &limits, "YAML", |content, guard| {
+            yaml::parse_documents(content, mode, guard)
+                .map(|documents| documents.into_iter().next().unwrap_or(Value::Null))
+        })?,
+        None => parse_with_limits(content, &limits, "YAML", parse_core_yaml)?,
+    };
+    let mut warnings = Vec::new();
+    check_duplicate_keys(
+        content,
+        "YAML",
+        extract_duplicate_keys(kwargs)?,
+        &mut warnings,
+    )?;
+    warn_parse(py, &warnings)?;
     json_value_to_python(py, &value)
 }
 
+/// Parse a multi-document YAML stream to a Python list
+///
+/// Args:
+///     content: YAML string containing one or more `---`-separated documents
+///     **kwargs: Parse limits, applied to the whole stream, plus `yaml_anchors`
+///         and `duplicate_keys` (see `parse_yaml`), and:
+///         strict (bool): With False, malformed documents are skipped with a
+///             ParseWarning instead of raising ParseError (default True)
+///
+/// Returns:
+///     List of parsed Python objects, one per non-empty document
+#[pyfunction]
+#[pyo3(signature = (content, **kwargs))]
+fn parse_yaml_all(
+    py: Python,
+    content: &str,
+    kwargs: Option<&Bound<'_, PyDict>>,
+) -> PyResult<PyObject> {
+    kwargs::check("parse_yaml_all", kwargs, &[kwargs::PARSE])?;
+    let limits = build_parse_limits_from_kwargs(kwargs)?;
+    let mut warnings = Vec::new();
+    let documents = parse_yaml_stream(
+        content,
+        &limits,
+        extract_anchor_mode(kwargs)?,
+        extract_strict(kwargs)?,
+        &mut warnings,
+    )?;
+    check_duplicate_keys(
+        content,
+        "YAML",
+        extract_duplicate_keys(kwargs)?,
+        &mut warnings,
+    )?;
+    warn_parse(py, &warnings)?;
+    json_value_to_python(py, &Value::Array(documents))
+}
+
 /// Parse TOML string to Python object
 ///
 /// Args:
 ///     content: TOML string to parse
+///     **kwargs: Parse limits (see `build_parse_limits_from_kwargs`)
 ///
 /// Returns:
 ///     Parsed Python object (dict)
 #[pyfunction]
-fn parse_toml(py: Python, content: &str) -> PyResult<PyObject> {
-    let value = core_parse_toml(content).map_err(|e| {
-        PyErr::new::<pyo3::exceptions::PyValueError, _>(format!("TOML parse error: {e}"))
-    })?;
+#[pyo3(signature = (content, **kwargs))]
+fn parse_toml(py: Python, content: &str, kwargs: Option<&Bound<'_, PyDict>>) -> PyResult<PyObject> {
+    kwargs::check("parse_toml", kwargs, &[kwargs::PARSE])?;
+    let limits = build_parse_limits_from_kwargs(kwargs)?;
+    let value = parse_with_limits(content, &limits, "TOML", toml_value::parse)?;
     json_value_to_python(py, &value)
 }
 
@@ -118,14 +668,58 @@ fn parse_toml(py: Python, content: &str) -> PyResult<PyObject> {
 ///
 /// Args:
 ///     content: CSV string to parse
+///     **kwargs: Parse limits (see `build_parse_limits_from_kwargs`), plus:
+///         delimiter (str): Field separator (default ",")
+///         quotechar (str): Quote character (default '"')
+///         escapechar (str): Escape character inside quoted fields
+///         comment (str): Skip lines starting with this character
+///         has_header (bool): Whether the first row holds column names
+///             (default True, or False when `columns` is given)
+///         columns (list[str]): Column names to key rows by
+///         infer_types (bool): Convert numeric fields to int/float (fields with
+///             leading zeros stay strings)
+///         strict (bool): With False, malformed rows are skipped with a
+///             ParseWarning instead of raising ParseError (default True)
 ///
 /// Returns:
-///     List of dictionaries representing CSV rows
+///     List of dictionaries representing CSV rows (lists of fields when
+///     `has_header=False` and no `columns` are given)
 #[pyfunction]
-fn parse_csv(py: Python, content: &str) -> PyResult<PyObject> {
-    let value = core_parse_csv(content).map_err(|e| {
-        PyErr::new::<pyo3::exceptions::PyValueError, _>(format!("CSV parse error: {e}"))
+#[pyo3(signature = (content, **kwargs))]
+fn parse_csv(py: Python, content: &str, kwargs: Option<&Bound<'_, PyDict>>) -> PyResult<PyObject> {
+    kwargs::check("parse_csv", kwargs, &[kwargs::PARSE])?;
+    let limits = build_parse_limits_from_kwargs(kwargs)?;
+    let mut warnings = Vec::new();
+    let value = match build_csv_options_from_kwargs(kwargs, CsvOptions::default())? {
+        Some(options) => parse_with_limits(content, &limits, "CSV", |content| {
+            delimited::parse(content, &options, &mut warnings)
+        })?,
+        None => parse_with_limits(content, &limits, "CSV", core_parse_csv)?,
+    };
+    warn_parse(py, &warnings)?;
+    json_value_to_python(py, &value)
+}
+
+/// Parse TSV (tab-separated) string to Python list of dicts
+///
+/// Args:
+///     content: TSV string to parse
+///     **kwargs: Same options as `parse_csv`; the delimiter defaults to a tab
+///
+/// Returns:
+///     List of dictionaries representing TSV rows
+#[pyfunction]
+#[pyo3(signature = (content, **kwargs))]
+fn parse_tsv(py: Python, content: &str, kwargs: Option<&Bound<'_, PyDict>>) -> PyResult<PyObject> {
+    kwargs::check("parse_tsv", kwargs, &[kwargs::PARSE])?;
+    let limits = build_parse_limits_from_kwargs(kwargs)?;
+    let options =
+        build_csv_options_from_kwargs(kwargs, CsvOptions::tsv())?.unwrap_or_else(CsvOptions::tsv);
+    let mut warnings = Vec::new();
+    let value = parse_with_limits(content, &limits, "TSV", |content| {
+        delimited::parse(content, &options, &mut warnings)
     })?;
+    warn_parse(py, &warnings)?;
     json_value_to_python(py, &value)
 }
 
@@ -133,14 +727,29 @@ fn parse_csv(py: Python, content: &str) -> PyResult<PyObject> {
 ///
 /// Args:
 ///     content: INI string to parse
+///     **kwargs: Parse limits (see `build_parse_limits_from_kwargs`), plus
+///         configparser-compatible options:
+///         case_insensitive_keys (bool): Lowercase option names
+///         case_insensitive_sections (bool): Lowercase section names
+///         default_section (str | None): Section for keys before any
+///             header; its values are inherited by every other section.
+///             Defaults to "DEFAULT" like configparser; None keeps keys
+///             before any header at the top level
+///         interpolation (bool): Expand `%(name)s` references and `%%`
 ///
 /// Returns:
 ///     Parsed Python dictionary
 #[pyfunction]
-fn parse_ini(py: Python, content: &str) -> PyResult<PyObject> {
-    let value = core_parse_ini(content).map_err(|e| {
-        PyErr::new::<pyo3::exceptions::PyValueError, _>(format!("INI parse error: {e}"))
-    })?;
+#[pyo3(signature = (content, **kwargs))]
+fn parse_ini(py: Python, content: &str, kwargs: Option<&Bound<'_, PyDict>>) -> PyResult<PyObject> {
+    kwargs::check("parse_ini", kwargs, &[kwargs::PARSE])?;
+    let limits = build_parse_limits_from_kwargs(kwargs)?;
+    let value = match build_ini_options_from_kwargs(kwargs)? {
+        Some(options) => parse_with_limits(content, &limits, "INI", |content| {
+            ini::parse(content, &options)
+        })?,
+        None => parse_with_limits(content, &limits, "INI", core_parse_ini)?,
+    };
     json_value_to_python(py, &value)
 }
 
@@ -148,17 +757,72 @@ fn parse_ini(py: Python, content: &str) -> PyResult<PyObject> {
 ///
 /// Args:
 ///     content: XML string to parse
+///     **kwargs: Parse limits (see `build_parse_limits_from_kwargs`), plus:
+///         attribute_prefix (str): Prefix for attribute keys (default "@")
+///         text_key (str): Key for text of elements with attributes or children
+///             (default "#text")
+///         merge_attributes (bool): Store attributes under their bare names
+///             alongside child elements
 ///
 /// Returns:
 ///     Parsed Python dictionary
 #[pyfunction]
-fn parse_xml(py: Python, content: &str) -> PyResult<PyObject> {
-    let value = core_parse_xml(content).map_err(|e| {
-        PyErr::new::<pyo3::exceptions::PyValueError, _>(format!("XML parse error: {e}"))
+#[pyo3(signature = (content, **kwargs))]
+fn parse_xml(py: Python, content: &str, kwargs: Option<&Bound<'_, PyDict>>) -> PyResult<PyObject> {
+    kwargs::check("parse_xml", kwargs, &[kwargs::PARSE])?;
+    let limits = build_parse_limits_from_kwargs(kwargs)?;
+    let options = build_xml_options_from_kwargs(kwargs)?;
+    let value = parse_with_limits(content, &limits, "XML", |content| {
+        xml::parse(content, &options)
     })?;
     json_value_to_python(py, &value)
 }
 
+/// Convert a document from one format to another
+///
+/// Args:
+///     content: Document text in `from_format`
+///     from_format: "json", "yaml", "toml", "ini", "xml", "csv" or "tsv"
+///         ("yml" and "cfg" are accepted too)
+///     to_format: Format to write, with the same names
+///     **kwargs: Parser options for `from_format` (see `parse_json` and the
+///         other parsers)
+///
+/// Returns:
+///     The document in `to_format`. A multi-document YAML stream is
+///     converted as a list of its documents
+///
+/// Raises:
+///     ParseError: If `content` is not valid `from_format`
+///     ValueError: If a format is unknown, or the document holds values
+///         `to_format` cannot represent (e.g. null in TOML, nested tables in INI)
+///
+/// Example:
+///     >>> convert("name: web\nport: 80\n", "yaml", "toml")
+///     'name = "web"\nport = 80\n'
+#[pyfunction]
+#[pyo3(name = "convert", signature = (content, from_format, to_format, **kwargs))]
+fn convert_document(
+    py: Python,
+    content: &str,
+    from_format: &str,
+    to_format: &str,
+    kwargs: Option<&Bound<'_, PyDict>>,
+) -> PyResult<String> {
+    kwargs::check("convert", kwargs, &[kwargs::PARSE])?;
+    let resolve = |name: &str| {
+        detect::from_extension(&name.to_lowercase()).ok_or_else(|| {
+            PyErr::new::<pyo3::exceptions::PyValueError, _>(format!("Unsupported format: {name}"))
+        })
+    };
+    let (from_format, to_format) = (resolve(from_format)?, resolve(to_format)?);
+    let parse_options = build_file_parse_options_from_kwargs(kwargs)?;
+    let mut warnings = Vec::new();
+    let value = parse_content(content, from_format, &parse_options, &mut warnings)?;
+    warn_parse(py, &warnings)?;
+    serialize::dump(&value, to_format).map_err(PyErr::new::<pyo3::exceptions::PyValueError, _>)
+}
+
 // ============================================================================
 // Format output function
 // ============================================================================
@@ -167,59 +831,953 @@ fn parse_xml(py: Python, content: &str) -> PyResult<PyObject> {
 ///
 /// Args:
 ///     results: List of diff results from diff() function
-///     format: Output format ("diffx", "json", "yaml")
+///     format: Output format ("diffx", "json", "yaml", "html", "unified",
+///         "side-by-side", "github", "junit", "tap", "csv", "tsv")
+///     **kwargs: Options of the "unified" format:
+///         old: The old document the results were computed from (required)
+///         new: The new document (required)
+///         context (int): Lines of context around each hunk (default 3)
+///         and of the "side-by-side" format:
+///         width (int): Total line width (default 80)
+///         and of the "diffx" format:
+///         color (bool | str): ANSI colors; "auto" colors when stdout is a
+///             terminal and `NO_COLOR` is unset (default False)
+///         and of the "github" format:
+///         file (str): File the annotations point at
+///         level (str): "notice", "warning" (default) or "error"
+///         and of the "junit" format:
+///         suite (str): Test suite name (default "diffx")
+///         and of the "html" format:
+///         fragment (bool): A `<div>` with scoped styles for embedding
+///             instead of a whole document (default False)
 ///
 /// Returns:
 ///     Formatted string output
 #[pyfunction]
-fn format_output(results: &Bound<'_, PyList>, format: &str) -> PyResult<String> {
+#[pyo3(signature = (results, format, **kwargs))]
+fn format_output(
+    py: Python,
+    results: &Bound<'_, PyList>,
+    format: &str,
+    kwargs: Option<&Bound<'_, PyDict>>,
+) -> PyResult<String> {
+    kwargs::check("format_output", kwargs, &[kwargs::FORMAT])?;
     let rust_results = python_results_to_rust(results)?;
+    let report_format = ReportFormat::parse(format)?;
+    let color = kwargs::get(kwargs, "color")?;
+    let colored = color::enabled(py, color.as_ref())?;
+    let explicit = color
+        .as_ref()
+        .is_some_and(|color| color.downcast::<PyBool>().is_ok());
+    if colored && explicit && !matches!(report_format, ReportFormat::Core(OutputFormat::Diffx)) {
+        return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(
+            "color is only supported by the diffx format",
+        ));
+    }
+
+    match report_format {
+        ReportFormat::Core(output_format) => {
+            let colored = colored && matches!(output_format, OutputFormat::Diffx);
+            let output = core_format_output(&rust_results, output_format).map_err(|e| {
+                PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(format!("Format error: {e}"))
+            })?;
+            Ok(match colored {
+                true => color::colorize(&output, &rust_results),
+                false => output,
+            })
+        }
+        ReportFormat::Html => {
+            let fragment = match kwargs::get(kwargs, "fragment")? {
+                Some(fragment) => fragment.extract::<bool>()?,
+                None => false,
+            };
+            Ok(match fragment {
+                true => html::render_fragment(&rust_results),
+                false => html::render(&rust_results),
+            })
+        }
+        ReportFormat::Junit => {
+            let suite = match kwargs::get(kwargs, "suite")? {
+                Some(suite) => suite.extract::<String>()?,
+                None => "diffx".to_string(),
+            };
+            Ok(junit::render(&rust_results, &suite))
+        }
+        ReportFormat::Tap => Ok(tap::render(&rust_results)),
+        ReportFormat::Delimited(delimiter) => {
+            Ok(delimited::write_results(&rust_results, delimiter))
+        }
+        ReportFormat::Github => {
+            let file = match kwargs::get(kwargs, "file")? {
+                Some(file) => Some(file.extract::<PathBuf>()?.display().to_string()),
+                None => None,
+            };
+            let level = match kwargs::get(kwargs, "level")? {
+                Some(level) => level.extract::<String>()?,
+                None => "warning".to_string(),
+            };
+            if !github::LEVELS.contains(&level.as_str()) {
+                return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+                    "Invalid level: {level:?} (expected \"notice\", \"warning\" or \"error\")"
+                )));
+            }
+            Ok(github::render(&rust_results, file.as_deref(), &level))
+        }
+        ReportFormat::SideBySide => {
+            let width = match kwargs::get(kwargs, "width")? {
+                Some(width) => width.extract()?,
+                None => side_by_side::DEFAULT_WIDTH,
+            };
+            if width < side_by_side::MIN_WIDTH {
+                return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+                    "width must be at least {}",
+                    side_by_side::MIN_WIDTH
+                )));
+            }
+            Ok(side_by_side::render(&rust_results, width))
+        }
+        ReportFormat::Unified => {
+            let document = |name: &str| -> PyResult<Value> {
+                match kwargs::get(kwargs, name)? {
+                    Some(document) => python_to_json_value(&document),
+                    None => Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(
+                        "The unified format needs the compared documents: pass old= and new=",
+                    )),
+                }
+            };
+            let context = match kwargs::get(kwargs, "context")? {
+                Some(context) => context.extract()?,
+                None => unified::DEFAULT_CONTEXT,
+            };
+            Ok(unified::render(
+                &document("old")?,
+                &document("new")?,
+                &rust_results,
+                ("old", "new"),
+                context,
+            ))
+        }
+    }
+}
 
-    let output_format = OutputFormat::parse_format(format).map_err(|e| {
-        PyErr::new::<pyo3::exceptions::PyValueError, _>(format!("Invalid format: {e}"))
+// ============================================================================
+// Workflow function
+// ============================================================================
+
+/// Compare two files and write a formatted report in one call
+///
+/// Parsing, diffing, formatting, and writing all happen in Rust with the GIL
+/// released, so other Python threads keep running.
+///
+/// Args:
+///     old_path: Path to the old file, str or os.PathLike (format detected
+///         from extension)
+///     new_path: Path to the new file
+///     report_path: Where to write the report
+///     format: Report format ("html", "diffx", "json", "yaml", "unified",
+///         "side-by-side", "github", "junit", "tap", "csv", "tsv")
+///     options: A reusable `DiffOptions`; keyword arguments override its fields
+///     **kwargs: diff() options plus any parser options
+///
+/// Returns:
+///     Dict summary with "total", "added", "removed", "modified",
+///     "type_changed", "moved", "renamed", "unchanged", "truncated", and
+///     "report_path"; "total" counts differences only. With
+///     `classify_breaking`, "breaking" counts breaking changes
+#[pyfunction]
+#[pyo3(signature = (old_path, new_path, report_path, format="html", options=None, **kwargs))]
+fn run(
+    py: Python,
+    old_path: PathBuf,
+    new_path: PathBuf,
+    report_path: PathBuf,
+    format: &str,
+    options: Option<&Bound<'_, options::DiffOptions>>,
+    kwargs: Option<&Bound<'_, PyDict>>,
+) -> PyResult<PyObject> {
+    let kwargs = options::resolve(py, options, kwargs)?;
+    let kwargs = Some(&kwargs);
+    kwargs::check("run", kwargs, &[kwargs::DIFF, kwargs::PARSE])?;
+    let options = build_options_from_kwargs(kwargs)?;
+    let mut engine_options = build_engine_options_from_kwargs(kwargs)?;
+    let sort_order = extract_sort_order(kwargs)?;
+    let parse_options = build_file_parse_options_from_kwargs(kwargs)?;
+    let classify_breaking =
+        OpenApiMode::from_kwargs(kwargs)?.is_some_and(|mode| mode.classify_breaking);
+
+    // Collect one extra difference to tell whether max_results cut the diff short
+    let max_results = engine_options
+        .as_ref()
+        .and_then(|engine| engine.max_results);
+    if let (Some(engine), Some(max_results)) = (engine_options.as_mut(), max_results) {
+        engine.max_results = Some(max_results + 1);
+    }
+    let report_format = ReportFormat::parse(format)?;
+
+    let mut warnings = Vec::new();
+    let (results, truncated) = py.allow_threads(|| -> PyResult<(Vec<Difference>, bool)> {
+        let old_json = parse_file(&old_path, &parse_options, &mut warnings)?;
+        let new_json = parse_file(&new_path, &parse_options, &mut warnings)?;
+
+        let mut results = diff_values(&old_json, &new_json, &options, engine_options.as_ref())?;
+        let truncated =
+            max_results.is_some_and(|max_results| engine::truncate(&mut results, max_results));
+        if let Some(sort_order) = sort_order {
+            ordering::sort(&mut results, sort_order);
+        }
+
+        let report = match report_format {
+            ReportFormat::Html => html::render(&results),
+            ReportFormat::Junit => junit::render(&results, &new_path.display().to_string()),
+            ReportFormat::Tap => tap::render(&results),
+            ReportFormat::Delimited(delimiter) => delimited::write_results(&results, delimiter),
+            ReportFormat::Github => {
+                github::render(&results, Some(&new_path.display().to_string()), "warning")
+            }
+            ReportFormat::SideBySide => side_by_side::render(&results, side_by_side::DEFAULT_WIDTH),
+            ReportFormat::Core(output_format) => core_format_output(&results, output_format)
+                .map_err(|e| {
+                    PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(format!("Format error: {e}"))
+                })?,
+            ReportFormat::Unified => unified::render(
+                &old_json,
+                &new_json,
+                &results,
+                (
+                    &old_path.display().to_string(),
+                    &new_path.display().to_string(),
+                ),
+                unified::DEFAULT_CONTEXT,
+            ),
+        };
+        std::fs::write(&report_path, report)?;
+
+        Ok((results, truncated))
     })?;
+    warn_parse(py, &warnings)?;
 
-    core_format_output(&rust_results, output_format).map_err(|e| {
-        PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(format!("Format error: {e}"))
-    })
+    let summary = summarize_results(py, &results)?;
+    if classify_breaking {
+        let breaking = results
+            .iter()
+            .filter(|result| openapi::is_breaking(result))
+            .count();
+        summary.set_item("breaking", breaking)?;
+    }
+    summary.set_item("truncated", truncated)?;
+    summary.set_item("report_path", report_path)?;
+
+    Ok(summary.into())
 }
 
 // ============================================================================
 // Helper functions
 // ============================================================================
 
+/// Diff with diffx-core, or with the binding-side engine when options only it
+/// supports are present or `array_id_key` values repeat within an array.
+fn diff_values(
+    old: &Value,
+    new: &Value,
+    options: &DiffOptions,
+    engine_options: Option<&EngineOptions>,
+) -> PyResult<Vec<Difference>> {
+    let duplicate_ids = options.array_id_key.as_deref().is_some_and(|field| {
+        engine::has_duplicate_ids(old, field) || engine::has_duplicate_ids(new, field)
+    });
+    match engine_options {
+        Some(engine_options) => engine::diff(old, new, options, engine_options),
+        None if duplicate_ids => engine::diff(old, new, options, &EngineOptions::default()),
+        None => core_diff(old, new, Some(options))
+            .map(|results| results.into_iter().map(Difference::from).collect())
+            .map_err(|e| {
+                PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(format!("Diff error: {e}"))
+            }),
+    }
+}
+
+/// `datetime_tolerance` and `datetime_timezone`; either one turns on
+/// comparing datetime strings as instants, the tolerance defaulting to 0.
+fn extract_datetime_tolerance(
+    kwargs: Option<&Bound<'_, PyDict>>,
+) -> PyResult<Option<datetime::Tolerance>> {
+    let timezone = kwargs::get(kwargs, "datetime_timezone")?
+        .map(|timezone| datetime::timezone_offset(&timezone))
+        .transpose()?;
+    let seconds = match kwargs::get(kwargs, "datetime_tolerance")? {
+        Some(tolerance) => {
+            let tolerance = datetime::tolerance_seconds(&tolerance)?;
+            if tolerance.is_nan() || tolerance < 0.0 {
+                return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(
+                    "datetime_tolerance must be a non-negative number of seconds or a timedelta",
+                ));
+            }
+            tolerance
+        }
+        None if timezone.is_some() => 0.0,
+        None => return Ok(None),
+    };
+    Ok(Some(datetime::Tolerance { seconds, timezone }))
+}
+
+fn extract_path_style(kwargs: Option<&Bound<'_, PyDict>>) -> PyResult<PathStyle> {
+    let Some(style) = kwargs::get(kwargs, "path_style")? else {
+        return Ok(PathStyle::String);
+    };
+    PathStyle::parse(&style.extract::<String>()?)
+        .map_err(PyErr::new::<pyo3::exceptions::PyValueError, _>)
+}
+
+fn extract_sort_order(kwargs: Option<&Bound<'_, PyDict>>) -> PyResult<Option<SortOrder>> {
+    let Some(sort) = kwargs::get(kwargs, "sort")? else {
+        return Ok(None);
+    };
+    SortOrder::parse(&sort.extract::<String>()?)
+        .map(Some)
+        .map_err(PyErr::new::<pyo3::exceptions::PyValueError, _>)
+}
+
+/// Parser options gathered from kwargs for file-level helpers.
+struct FileParseOptions {
+    limits: ParseLimits,
+    anchor_mode: Option<AnchorMode>,
+    ini: Option<IniOptions>,
+    xml: XmlOptions,
+    csv: Option<CsvOptions>,
+    tsv: CsvOptions,
+    strict: bool,
+    duplicate_keys: DuplicateKeys,
+}
+
+fn build_file_parse_options_from_kwargs(
+    kwargs: Option<&Bound<'_, PyDict>>,
+) -> PyResult<FileParseOptions> {
+    Ok(FileParseOptions {
+        limits: build_parse_limits_from_kwargs(kwargs)?,
+        anchor_mode: extract_anchor_mode(kwargs)?,
+        ini: build_ini_options_from_kwargs(kwargs)?,
+        xml: build_xml_options_from_kwargs(kwargs)?,
+        csv: build_csv_options_from_kwargs(kwargs, CsvOptions::default())?,
+        tsv: build_csv_options_from_kwargs(kwargs, CsvOptions::tsv())?
+            .unwrap_or_else(CsvOptions::tsv),
+        strict: extract_strict(kwargs)?,
+        duplicate_keys: extract_duplicate_keys(kwargs)?,
+    })
+}
+
+/// Read and parse a file by extension, or by its content when the extension
+/// names no format, mirroring `diff_files` in Python.
+///
+/// Warnings, such as records skipped by a lenient parse, are added to
+/// `warnings` naming the file.
+fn parse_file(
+    path: &Path,
+    options: &FileParseOptions,
+    warnings: &mut Vec<String>,
+) -> PyResult<Value> {
+    if let Some(limit_mb) = options.limits.memory_limit_mb {
+        let size = std::fs::metadata(path)?.len();
+        MemoryBudget::start(limit_mb)
+            .check_input(size)
+            .map_err(MemoryLimitError::new_err)?;
+    }
+    let content = std::fs::read_to_string(path)?;
+    let file_name = path
+        .file_name()
+        .and_then(|name| name.to_str())
+        .unwrap_or_default()
+        .to_lowercase();
+    let extension = match file_name.as_str() {
+        // TOML lockfiles without a .toml extension
+        "cargo.lock" | "poetry.lock" => "toml".to_string(),
+        _ => path
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .unwrap_or_default()
+            .to_lowercase(),
+    };
+    let format = detect::from_extension(&extension)
+        .or_else(|| detect::detect(&content))
+        .ok_or_else(|| {
+            PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+                "Unsupported file format: .{extension}"
+            ))
+        })?;
+    let mut file_warnings = Vec::new();
+    let value = parse_content(&content, format, options, &mut file_warnings)?;
+    warnings.extend(
+        file_warnings
+            .into_iter()
+            .map(|message| format!("{}: {message}", path.display())),
+    );
+    Ok(value)
+}
+
+/// Parse `content` as `format`, one of the names `detect::from_extension`
+/// returns. A multi-document YAML stream becomes a list of its documents.
+fn parse_content(
+    content: &str,
+    format: &str,
+    options: &FileParseOptions,
+    warnings: &mut Vec<String>,
+) -> PyResult<Value> {
+    let limits = &options.limits;
+    match format {
+        "json" => parse_guarded(content, limits, "JSON", json::parse).and_then(|value| {
+            check_duplicate_keys(content, "JSON", options.duplicate_keys, warnings)?;
+            Ok(value)
+        }),
+        "yaml" => {
+            let mut documents = parse_yaml_stream(
+                content,
+                limits,
+                options.anchor_mode,
+                options.strict,
+                warnings,
+            )?;
+            check_duplicate_keys(content, "YAML", options.duplicate_keys, warnings)?;
+            Ok(match documents.len() {
+                0 => Value::Null,
+                1 => documents.remove(0),
+                _ => Value::Array(documents),
+            })
+        }
+        "toml" => parse_with_limits(content, limits, "TOML", toml_value::parse),
+        "ini" => match &options.ini {
+            Some(ini_options) => parse_with_limits(content, limits, "INI", |content| {
+                ini::parse(content, ini_options)
+            }),
+            None => parse_with_limits(content, limits, "INI", core_parse_ini),
+        },
+        "xml" => parse_with_limits(content, limits, "XML", |content| {
+            xml::parse(content, &options.xml)
+        }),
+        "csv" => match &options.csv {
+            Some(csv_options) => parse_with_limits(content, limits, "CSV", |content| {
+                delimited::parse(content, csv_options, warnings)
+            }),
+            None => parse_with_limits(content, limits, "CSV", core_parse_csv),
+        },
+        _ => parse_with_limits(content, limits, "TSV", |content| {
+            delimited::parse(content, &options.tsv, warnings)
+        }),
+    }
+}
+
+fn parse_with_limits<E: std::fmt::Display>(
+    content: &str,
+    limits: &ParseLimits,
+    format_name: &str,
+    parser: impl FnOnce(&str) -> Result<Value, E>,
+) -> PyResult<Value> {
+    parse_guarded(content, limits, format_name, |content, _| parser(content))
+}
+
+/// `parse_with_limits` for a parser that also enforces limits while it
+/// builds the value, through the `ParseGuard` it is given.
+fn parse_guarded<E: std::fmt::Display>(
+    content: &str,
+    limits: &ParseLimits,
+    format_name: &str,
+    parser: impl FnOnce(&str, &ParseGuard) -> Result<Value, E>,
+) -> PyResult<Value> {
+    limits
+        .check_input(content)
+        .map_err(ParseLimitError::new_err)?;
+    let memory_budget = start_memory_budget(limits, content)?;
+    let guard = ParseGuard::new(limits, memory_budget.as_ref());
+    let value = parser(content, &guard).map_err(|e| match guard.exceeded() {
+        Some(exceeded) => limit_error(exceeded),
+        None => parse_error(format!("{format_name} parse error: {e}"), content, 0),
+    })?;
+    check_memory_budget(memory_budget.as_ref())?;
+    limits
+        .check_value(&value)
+        .map_err(ParseLimitError::new_err)?;
+    Ok(value)
+}
+
+/// Budget for parsing `content` under `memory_limit_mb`, rejecting input that
+/// cannot fit before the parser allocates anything.
+fn start_memory_budget(limits: &ParseLimits, content: &str) -> PyResult<Option<MemoryBudget>> {
+    let Some(limit_mb) = limits.memory_limit_mb else {
+        return Ok(None);
+    };
+    let budget = MemoryBudget::start(limit_mb);
+    budget
+        .check_input(content.len() as u64)
+        .map_err(MemoryLimitError::new_err)?;
+    Ok(Some(budget))
+}
+
+/// The error for a limit that stopped a guarded parse.
+fn limit_error(exceeded: Exceeded) -> PyErr {
+    match exceeded {
+        Exceeded::Limit(message) => ParseLimitError::new_err(message),
+        Exceeded::Memory(message) => MemoryLimitError::new_err(message),
+    }
+}
+
+fn check_memory_budget(budget: Option<&MemoryBudget>) -> PyResult<()> {
+    match budget {
+        Some(budget) => budget.check().map_err(MemoryLimitError::new_err),
+        None => Ok(()),
+    }
+}
+
+/// A size limit kwarg: a non-negative int, or None for no limit. Booleans are
+/// ints to Python, but `max_nodes=True` is a mistake, not a limit of 1.
+fn extract_limit(name: &str, value: &Bound<'_, PyAny>) -> PyResult<Option<usize>> {
+    if value.is_instance_of::<PyBool>() {
+        return Err(PyErr::new::<pyo3::exceptions::PyTypeError, _>(format!(
+            "{name} must be an integer or None, not bool"
+        )));
+    }
+    value.extract()
+}
+
+fn extract_memory_limit_mb(kwargs: &Bound<'_, PyDict>) -> PyResult<Option<usize>> {
+    let Some(memory_limit_mb) = kwargs.get_item("memory_limit_mb")? else {
+        return Ok(None);
+    };
+    if memory_limit_mb.is_instance_of::<PyBool>() {
+        return extract_limit("memory_limit_mb", &memory_limit_mb);
+    }
+    memory_limit_mb.extract::<Option<usize>>().map_err(|_| {
+        PyErr::new::<pyo3::exceptions::PyValueError, _>(
+            "memory_limit_mb must be a non-negative integer",
+        )
+    })
+}
+
+fn extract_anchor_mode(kwargs: Option<&Bound<'_, PyDict>>) -> PyResult<Option<AnchorMode>> {
+    if let Some(kwargs) = kwargs {
+        if let Some(mode) = kwargs.get_item("yaml_anchors")? {
+            let mode: String = mode.extract()?;
+            return AnchorMode::parse(&mode)
+                .map(Some)
+                .map_err(PyErr::new::<pyo3::exceptions::PyValueError, _>);
+        }
+    }
+    Ok(None)
+}
+
+/// CSV dialect options applied over `defaults`; `None` when no option is given,
+/// keeping diffx-core's parser for plain CSV.
+fn build_csv_options_from_kwargs(
+    kwargs: Option<&Bound<'_, PyDict>>,
+    defaults: CsvOptions,
+) -> PyResult<Option<CsvOptions>> {
+    let mut options = defaults;
+    let mut has_csv_options = false;
+
+    if let Some(kwargs) = kwargs {
+        if let Some(delimiter) = kwargs.get_item("delimiter")? {
+            options.delimiter = extract_dialect_byte("delimiter", &delimiter)?;
+            has_csv_options = true;
+        }
+
+        if let Some(quotechar) = kwargs.get_item("quotechar")? {
+            options.quotechar = extract_dialect_byte("quotechar", &quotechar)?;
+            has_csv_options = true;
+        }
+
+        if let Some(escapechar) = kwargs.get_item("escapechar")? {
+            if !escapechar.is_none() {
+                options.escapechar = Some(extract_dialect_byte("escapechar", &escapechar)?);
+            }
+            has_csv_options = true;
+        }
+
+        if let Some(comment) = kwargs.get_item("comment")? {
+            if !comment.is_none() {
+                options.comment = Some(extract_dialect_byte("comment", &comment)?);
+            }
+            has_csv_options = true;
+        }
+
+        if let Some(columns) = kwargs.get_item("columns")? {
+            options.columns = columns.extract::<Option<Vec<String>>>()?;
+            // Like csv.DictReader(fieldnames=...), supplied names mean the
+            // first row is data unless has_header says otherwise.
+            options.has_header = options.columns.is_none();
+            has_csv_options = true;
+        }
+
+        if let Some(has_header) = kwargs.get_item("has_header")? {
+            options.has_header = has_header.extract::<bool>()?;
+            has_csv_options = true;
+        }
+
+        if let Some(infer_types) = kwargs.get_item("infer_types")? {
+            options.infer_types = infer_types.extract::<bool>()?;
+            has_csv_options = true;
+        }
+
+        // diffx-core's parser always aborts, so lenient parsing needs ours
+        if !extract_strict(Some(kwargs))? {
+            options.strict = false;
+            has_csv_options = true;
+        }
+    }
+
+    Ok(has_csv_options.then_some(options))
+}
+
+fn extract_duplicate_keys(kwargs: Option<&Bound<'_, PyDict>>) -> PyResult<DuplicateKeys> {
+    match kwargs::get(kwargs, "duplicate_keys")? {
+        Some(mode) => DuplicateKeys::parse(&mode.extract::<String>()?)
+            .map_err(PyErr::new::<pyo3::exceptions::PyValueError, _>),
+        None => Ok(DuplicateKeys::default()),
+    }
+}
+
+/// Apply `duplicate_keys` to a JSON or YAML source that parsed successfully.
+/// `Warn` adds one message per repeated key to `warnings`.
+fn check_duplicate_keys(
+    content: &str,
+    format_name: &str,
+    mode: DuplicateKeys,
+    warnings: &mut Vec<String>,
+) -> PyResult<()> {
+    if mode == DuplicateKeys::Ignore {
+        return Ok(());
+    }
+    let (found, stream) = match format_name {
+        "JSON" => (duplicates::find_json(content), false),
+        // A lenient parse may have skipped a document the scan cannot read
+        _ => (
+            duplicates::find_yaml(content).unwrap_or_default(),
+            split_yaml_documents(content).len() > 1,
+        ),
+    };
+    for duplicate in found {
+        let message = duplicate.describe(stream);
+        match mode {
+            DuplicateKeys::Error => {
+                return Err(parse_error(
+                    format!("{format_name} parse error: {message}"),
+                    content,
+                    0,
+                ))
+            }
+            _ => warnings.push(format!("{format_name} {message}")),
+        }
+    }
+    Ok(())
+}
+
+/// `strict=False` skips malformed CSV/TSV rows and YAML stream documents.
+fn extract_strict(kwargs: Option<&Bound<'_, PyDict>>) -> PyResult<bool> {
+    match kwargs::get(kwargs, "strict")? {
+        Some(strict) => strict.extract::<bool>(),
+        None => Ok(true),
+    }
+}
+
+fn extract_dialect_byte(name: &str, value: &Bound<'_, PyAny>) -> PyResult<u8> {
+    let value: String = value.extract()?;
+    delimited::dialect_byte(name, &value).map_err(PyErr::new::<pyo3::exceptions::PyValueError, _>)
+}
+
+/// INI options in the style of configparser; `None` when no option is given,
+/// keeping diffx-core's parser.
+fn build_ini_options_from_kwargs(
+    kwargs: Option<&Bound<'_, PyDict>>,
+) -> PyResult<Option<IniOptions>> {
+    let mut options = IniOptions::default();
+    let mut has_ini_options = false;
+
+    if let Some(kwargs) = kwargs {
+        if let Some(case_insensitive_keys) = kwargs.get_item("case_insensitive_keys")? {
+            options.case_insensitive_keys = case_insensitive_keys.extract::<bool>()?;
+            has_ini_options = true;
+        }
+
+        if let Some(case_insensitive_sections) = kwargs.get_item("case_insensitive_sections")? {
+            options.case_insensitive_sections = case_insensitive_sections.extract::<bool>()?;
+            has_ini_options = true;
+        }
+
+        if let Some(default_section) = kwargs.get_item("default_section")? {
+            options.default_section = default_section.extract::<Option<String>>()?;
+            has_ini_options = true;
+        }
+
+        if let Some(interpolation) = kwargs.get_item("interpolation")? {
+            options.interpolation = interpolation.extract::<bool>()?;
+            has_ini_options = true;
+        }
+    }
+
+    Ok(has_ini_options.then_some(options))
+}
+
+/// XML mapping options, starting from `XmlOptions::default()` so attributes
+/// and text keep their own keys whether or not an option is given.
+fn build_xml_options_from_kwargs(kwargs: Option<&Bound<'_, PyDict>>) -> PyResult<XmlOptions> {
+    let mut options = XmlOptions::default();
+
+    if let Some(kwargs) = kwargs {
+        if let Some(attribute_prefix) = kwargs.get_item("attribute_prefix")? {
+            options.attribute_prefix = attribute_prefix.extract::<String>()?;
+        }
+
+        if let Some(text_key) = kwargs.get_item("text_key")? {
+            options.text_key = text_key.extract::<String>()?;
+        }
+
+        if let Some(merge_attributes) = kwargs.get_item("merge_attributes")? {
+            options.merge_attributes = merge_attributes.extract::<bool>()?;
+        }
+    }
+
+    Ok(options)
+}
+
+/// Parse limits from kwargs shared by all parser functions:
+///     hardened (bool): Enable conservative default limits
+///     max_input_bytes (int): Maximum input size in bytes
+///     max_nesting_depth (int): Maximum container nesting depth
+///     max_string_length (int): Maximum byte length of any string or key
+///     max_nodes (int): Maximum total number of values in the document
+///     memory_limit_mb (int): Maximum megabytes the parser may allocate;
+///         raises MemoryLimitError instead of ParseLimitError
+fn build_parse_limits_from_kwargs(kwargs: Option<&Bound<'_, PyDict>>) -> PyResult<ParseLimits> {
+    let mut limits = ParseLimits::default();
+
+    if let Some(kwargs) = kwargs {
+        if let Some(hardened) = kwargs.get_item("hardened")? {
+            if hardened.extract::<bool>()? {
+                limits = ParseLimits::hardened();
+            }
+        }
+
+        if let Some(max_input_bytes) = kwargs.get_item("max_input_bytes")? {
+            limits.max_input_bytes = extract_limit("max_input_bytes", &max_input_bytes)?;
+        }
+
+        if let Some(max_nesting_depth) = kwargs.get_item("max_nesting_depth")? {
+            limits.max_nesting_depth = extract_limit("max_nesting_depth", &max_nesting_depth)?;
+        }
+
+        if let Some(max_string_length) = kwargs.get_item("max_string_length")? {
+            limits.max_string_length = extract_limit("max_string_length", &max_string_length)?;
+        }
+
+        if let Some(max_nodes) = kwargs.get_item("max_nodes")? {
+            limits.max_nodes = extract_limit("max_nodes", &max_nodes)?;
+        }
+
+        limits.memory_limit_mb = extract_memory_limit_mb(kwargs)?;
+    }
+
+    Ok(limits)
+}
+
+/// Parse a YAML stream into its documents.
+///
+/// Without `strict`, each document is parsed on its own and malformed ones are
+/// left out and reported in `skipped`.
+fn parse_yaml_stream(
+    content: &str,
+    limits: &ParseLimits,
+    anchor_mode: Option<AnchorMode>,
+    strict: bool,
+    skipped: &mut Vec<String>,
+) -> PyResult<Vec<Value>> {
+    limits
+        .check_input(content)
+        .map_err(ParseLimitError::new_err)?;
+    let memory_budget = start_memory_budget(limits, content)?;
+    let guard = ParseGuard::new(limits, memory_budget.as_ref());
+
+    let anchor_mode = yaml_anchor_mode(anchor_mode, limits);
+    let parse_document = |document: &str| match anchor_mode {
+        Some(mode) => yaml::parse_documents(document, mode, &guard)
+            .map(|documents| documents.into_iter().next().unwrap_or(Value::Null)),
+        None => parse_core_yaml(document),
+    };
+    let documents = match anchor_mode {
+        Some(mode) if strict => {
+            yaml::parse_documents(content, mode, &guard).map_err(|e| match guard.exceeded() {
+                Some(exceeded) => limit_error(exceeded),
+                None => parse_error(format!("YAML parse error: {e}"), content, 0),
+            })?
+        }
+        _ => {
+            let mut documents = Vec::new();
+            for (index, (first_line, document)) in split_yaml_documents(content).iter().enumerate()
+            {
+                let parsed = parse_document(document);
+                // A document over a limit fails the stream even without strict
+                if let Some(exceeded) = guard.exceeded() {
+                    return Err(limit_error(exceeded));
+                }
+                match parsed {
+                    Ok(value) => documents.push(value),
+                    Err(e) if !strict => skipped.push(format!(
+                        "Skipped malformed YAML document {index} (from line {}): {e}",
+                        first_line + 1
+                    )),
+                    Err(e) => {
+                        return Err(parse_error(
+                            format!("YAML parse error in document {index}: {e}"),
+                            content,
+                            *first_line,
+                        ))
+                    }
+                }
+            }
+            documents
+        }
+    };
+
+    check_memory_budget(memory_budget.as_ref())?;
+    limits
+        .check_documents(&documents)
+        .map_err(ParseLimitError::new_err)?;
+    Ok(documents)
+}
+
+/// The loader for `anchor_mode`. Without one, a node limit still needs the
+/// counting loader, so aliases cannot expand past `max_nodes`.
+fn yaml_anchor_mode(anchor_mode: Option<AnchorMode>, limits: &ParseLimits) -> Option<AnchorMode> {
+    anchor_mode.or(limits.max_nodes.map(|_| AnchorMode::Core))
+}
+
+/// diffx-core's YAML parser, without the "YAML parse error: " prefix its
+/// errors carry; callers add their own.
+fn parse_core_yaml(content: &str) -> Result<Value, String> {
+    core_parse_yaml(content).map_err(|e| {
+        let message = e.to_string();
+        match message.strip_prefix("YAML parse error: ") {
+            Some(rest) => rest.to_string(),
+            None => message,
+        }
+    })
+}
+
+/// Split a YAML stream on `---` / `...` markers, dropping empty documents.
+///
+/// Each document comes with the number of stream lines before its first line.
+fn split_yaml_documents(content: &str) -> Vec<(usize, String)> {
+    let mut documents = Vec::new();
+    let mut current = String::new();
+    let mut first_line = 0;
+
+    for (number, line) in content.lines().enumerate() {
+        let is_start = line == "---" || line.starts_with("--- ") || line.starts_with("---\t");
+        let is_end = line == "..." || line.starts_with("... ");
+        if is_start || is_end {
+            documents.push((first_line, std::mem::take(&mut current)));
+            if is_start {
+                // Content may follow the marker on the same line (e.g. `--- |`)
+                current.push_str(&line[3..]);
+                current.push('\n');
+                first_line = number;
+            } else {
+                first_line = number + 1;
+            }
+            continue;
+        }
+        current.push_str(line);
+        current.push('\n');
+    }
+    documents.push((first_line, current));
+
+    documents
+        .into_iter()
+        .filter(|(_, document)| {
+            document.lines().any(|line| {
+                let line = line.trim();
+                !line.is_empty() && !line.starts_with('#') && !line.starts_with('%')
+            })
+        })
+        .collect()
+}
+
 fn python_to_json_value(py_obj: &Bound<'_, PyAny>) -> PyResult<Value> {
-    if py_obj.is_none() {
-        Ok(Value::Null)
-    } else if let Ok(b) = py_obj.extract::<bool>() {
-        Ok(Value::Bool(b))
-    } else if let Ok(i) = py_obj.extract::<i64>() {
-        Ok(Value::Number(i.into()))
-    } else if let Ok(f) = py_obj.extract::<f64>() {
-        Ok(Value::Number(
-            serde_json::Number::from_f64(f).unwrap_or(0.into()),
-        ))
-    } else if let Ok(s) = py_obj.extract::<String>() {
-        Ok(Value::String(s))
-    } else if let Ok(list) = py_obj.downcast::<PyList>() {
-        let mut vec = Vec::new();
-        for item in list.iter() {
-            vec.push(python_to_json_value(&item)?);
-        }
-        Ok(Value::Array(vec))
-    } else if let Ok(dict) = py_obj.downcast::<PyDict>() {
-        let mut map = serde_json::Map::new();
-        for (key, value) in dict.iter() {
-            let key_str = key.extract::<String>()?;
-            let json_value = python_to_json_value(&value)?;
-            map.insert(key_str, json_value);
-        }
-        Ok(Value::Object(map))
-    } else {
-        Err(PyErr::new::<pyo3::exceptions::PyTypeError, _>(
-            "Unsupported Python type",
-        ))
+    Conversion::default().to_json(py_obj)
+}
+
+/// Whether two Python values convert to the same JSON value, checked without
+/// converting them. Types must match exactly (`1` and `1.0` differ); `false`
+/// means "not proven", e.g. for values `python_to_json_value` rejects.
+/// Self-containing and very deeply nested values are never proven, leaving
+/// them to the conversion, which rejects them with an error.
+fn python_values_identical(old: &Bound<'_, PyAny>, new: &Bound<'_, PyAny>) -> PyResult<bool> {
+    values_identical_within(old, new, &mut Vec::new())
+}
+
+/// Deepest nesting `python_values_identical` walks before giving up.
+const MAX_IDENTICAL_DEPTH: usize = 256;
+
+/// `python_values_identical` below the containers of `old` listed in
+/// `ancestors` by address.
+fn values_identical_within(
+    old: &Bound<'_, PyAny>,
+    new: &Bound<'_, PyAny>,
+    ancestors: &mut Vec<usize>,
+) -> PyResult<bool> {
+    if old.is_none() || new.is_none() {
+        return Ok(old.is_none() && new.is_none());
+    }
+    if old.is_instance_of::<PyBool>() || new.is_instance_of::<PyBool>() {
+        return Ok(old.is_instance_of::<PyBool>()
+            && new.is_instance_of::<PyBool>()
+            && old.eq(new)?);
+    }
+    let is_container = old.is_instance_of::<PyDict>() || old.is_instance_of::<PyList>();
+    if is_container {
+        let address = old.as_ptr() as usize;
+        if ancestors.len() >= MAX_IDENTICAL_DEPTH || ancestors.contains(&address) {
+            return Ok(false);
+        }
+        ancestors.push(address);
+        let identical = containers_identical(old, new, ancestors);
+        ancestors.pop();
+        return identical;
+    }
+    let same_scalar_type = (old.is_instance_of::<PyString>() && new.is_instance_of::<PyString>())
+        || (old.is_instance_of::<PyLong>() && new.is_instance_of::<PyLong>())
+        || (old.is_instance_of::<PyFloat>() && new.is_instance_of::<PyFloat>());
+    Ok(same_scalar_type && old.eq(new)?)
+}
+
+/// `python_values_identical` for a dict or list `old`.
+fn containers_identical(
+    old: &Bound<'_, PyAny>,
+    new: &Bound<'_, PyAny>,
+    ancestors: &mut Vec<usize>,
+) -> PyResult<bool> {
+    if let (Ok(old), Ok(new)) = (old.downcast::<PyDict>(), new.downcast::<PyDict>()) {
+        if old.len() != new.len() {
+            return Ok(false);
+        }
+        for (key, old_value) in old.iter() {
+            if !key.is_instance_of::<PyString>() {
+                return Ok(false);
+            }
+            match new.get_item(&key)? {
+                Some(new_value) if values_identical_within(&old_value, &new_value, ancestors)? => {}
+                _ => return Ok(false),
+            }
+        }
+        return Ok(true);
+    }
+    if let (Ok(old), Ok(new)) = (old.downcast::<PyList>(), new.downcast::<PyList>()) {
+        if old.len() != new.len() {
+            return Ok(false);
+        }
+        for (old_item, new_item) in old.iter().zip(new.iter()) {
+            if !values_identical_within(&old_item, &new_item, ancestors)? {
+                return Ok(false);
+            }
+        }
+        return Ok(true);
     }
+    Ok(false)
 }
 
 fn json_value_to_python(py: Python, value: &Value) -> PyResult<PyObject> {
@@ -229,6 +1787,8 @@ fn json_value_to_python(py: Python, value: &Value) -> PyResult<PyObject> {
         Value::Number(n) => {
             if let Some(i) = n.as_i64() {
                 Ok(i.to_object(py))
+            } else if let Some(u) = n.as_u64() {
+                Ok(u.to_object(py))
             } else if let Some(f) = n.as_f64() {
                 Ok(f.to_object(py))
             } else {
@@ -255,38 +1815,61 @@ fn json_value_to_python(py: Python, value: &Value) -> PyResult<PyObject> {
     }
 }
 
-fn diff_result_to_python(py: Python, result: &DiffResult) -> PyResult<PyObject> {
+fn diff_result_to_python(py: Python, result: &Difference) -> PyResult<PyObject> {
     let py_dict = PyDict::new_bound(py);
 
     match result {
-        DiffResult::Added(path, value) => {
+        Difference::Added(path, value) => {
             py_dict.set_item("type", "Added")?;
             py_dict.set_item("path", path)?;
             py_dict.set_item("value", json_value_to_python(py, value)?)?;
         }
-        DiffResult::Removed(path, value) => {
+        Difference::Removed(path, value) => {
             py_dict.set_item("type", "Removed")?;
             py_dict.set_item("path", path)?;
             py_dict.set_item("value", json_value_to_python(py, value)?)?;
         }
-        DiffResult::Modified(path, old_val, new_val) => {
+        Difference::Modified(path, old_val, new_val) => {
             py_dict.set_item("type", "Modified")?;
             py_dict.set_item("path", path)?;
             py_dict.set_item("old_value", json_value_to_python(py, old_val)?)?;
             py_dict.set_item("new_value", json_value_to_python(py, new_val)?)?;
         }
-        DiffResult::TypeChanged(path, old_val, new_val) => {
+        Difference::TypeChanged(path, old_val, new_val) => {
             py_dict.set_item("type", "TypeChanged")?;
             py_dict.set_item("path", path)?;
             py_dict.set_item("old_value", json_value_to_python(py, old_val)?)?;
             py_dict.set_item("new_value", json_value_to_python(py, new_val)?)?;
         }
+        Difference::Moved(path, from_index, to_index) => {
+            py_dict.set_item("type", "Moved")?;
+            py_dict.set_item("path", path)?;
+            py_dict.set_item("from_index", from_index)?;
+            py_dict.set_item("to_index", to_index)?;
+        }
+        Difference::Renamed(path, new_path, value) => {
+            py_dict.set_item("type", "Renamed")?;
+            py_dict.set_item("path", path)?;
+            py_dict.set_item("new_path", new_path)?;
+            py_dict.set_item("value", json_value_to_python(py, value)?)?;
+        }
+        Difference::Unchanged(path, value) => {
+            py_dict.set_item("type", "Unchanged")?;
+            py_dict.set_item("path", path)?;
+            py_dict.set_item("value", json_value_to_python(py, value)?)?;
+        }
+        Difference::Comment(path, old_comment, new_comment) => {
+            py_dict.set_item("type", "Comment")?;
+            py_dict.set_item("path", path)?;
+            py_dict.set_item("old_comment", old_comment)?;
+            py_dict.set_item("new_comment", new_comment)?;
+        }
     }
 
     Ok(py_dict.into())
 }
 
-fn python_results_to_rust(results: &Bound<'_, PyList>) -> PyResult<Vec<DiffResult>> {
+fn python_results_to_rust(results: &Bound<'_, PyList>) -> PyResult<Vec<Difference>> {
     let mut rust_results = Vec::new();
 
     for item in results.iter() {
@@ -307,13 +1890,13 @@ fn python_results_to_rust(results: &Bound<'_, PyList>) -> PyResult<Vec<DiffResul
                 let value = dict.get_item("value")?.ok_or_else(|| {
                     PyErr::new::<pyo3::exceptions::PyValueError, _>("Missing 'value' field")
                 })?;
-                DiffResult::Added(path, python_to_json_value(&value)?)
+                Difference::Added(path, python_to_json_value(&value)?)
             }
             "Removed" => {
                 let value = dict.get_item("value")?.ok_or_else(|| {
                     PyErr::new::<pyo3::exceptions::PyValueError, _>("Missing 'value' field")
                 })?;
-                DiffResult::Removed(path, python_to_json_value(&value)?)
+                Difference::Removed(path, python_to_json_value(&value)?)
             }
             "Modified" => {
                 let old_value = dict.get_item("old_value")?.ok_or_else(|| {
@@ -322,7 +1905,7 @@ fn python_results_to_rust(results: &Bound<'_, PyList>) -> PyResult<Vec<DiffResul
                 let new_value = dict.get_item("new_value")?.ok_or_else(|| {
                     PyErr::new::<pyo3::exceptions::PyValueError, _>("Missing 'new_value' field")
                 })?;
-                DiffResult::Modified(
+                Difference::Modified(
                     path,
                     python_to_json_value(&old_value)?,
                     python_to_json_value(&new_value)?,
@@ -335,12 +1918,45 @@ fn python_results_to_rust(results: &Bound<'_, PyList>) -> PyResult<Vec<DiffResul
                 let new_value = dict.get_item("new_value")?.ok_or_else(|| {
                     PyErr::new::<pyo3::exceptions::PyValueError, _>("Missing 'new_value' field")
                 })?;
-                DiffResult::TypeChanged(
+                Difference::TypeChanged(
                     path,
                     python_to_json_value(&old_value)?,
                     python_to_json_value(&new_value)?,
                 )
             }
+            "Moved" => {
+                let from_index = dict.get_item("from_index")?.ok_or_else(|| {
+                    PyErr::new::<pyo3::exceptions::PyValueError, _>("Missing 'from_index' field")
+                })?;
+                let to_index = dict.get_item("to_index")?.ok_or_else(|| {
+                    PyErr::new::<pyo3::exceptions::PyValueError, _>("Missing 'to_index' field")
+                })?;
+                Difference::Moved(path, from_index.extract()?, to_index.extract()?)
+            }
+            "Renamed" => {
+                let new_path = dict.get_item("new_path")?.ok_or_else(|| {
+                    PyErr::new::<pyo3::exceptions::PyValueError, _>("Missing 'new_path' field")
+                })?;
+                let value = dict.get_item("value")?.ok_or_else(|| {
+                    PyErr::new::<pyo3::exceptions::PyValueError, _>("Missing 'value' field")
+                })?;
+                Difference::Renamed(path, new_path.extract()?, python_to_json_value(&value)?)
+            }
+            "Unchanged" => {
+                let value = dict.get_item("value")?.ok_or_else(|| {
+                    PyErr::new::<pyo3::exceptions::PyValueError, _>("Missing 'value' field")
+                })?;
+                Difference::Unchanged(path, python_to_json_value(&value)?)
+            }
+            "Comment" => {
+                let old_comment = dict.get_item("old_comment")?.ok_or_else(|| {
+                    PyErr::new::<pyo3::exceptions::PyValueError, _>("Missing 'old_comment' field")
+                })?;
+                let new_comment = dict.get_item("new_comment")?.ok_or_else(|| {
+                    PyErr::new::<pyo3::exceptions::PyValueError, _>("Missing 'new_comment' field")
+                })?;
+                Difference::Comment(path, old_comment.extract()?, new_comment.extract()?)
+            }
             _ => {
                 return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
                     "Invalid diff type: {}",
@@ -363,29 +1979,31 @@ fn build_options_from_kwargs(kwargs: Option<&Bound<'_, PyDict>>) -> PyResult<Dif
             options.epsilon = Some(epsilon.extract::<f64>()?);
         }
 
-        if let Some(array_id_key) = kwargs.get_item("array_id_key")? {
-            options.array_id_key = Some(array_id_key.extract::<String>()?);
+        if let Some(mut fields) = extract_array_id_key(kwargs)? {
+            // Composite and nested keys are handled by the binding-side engine
+            if IdKey::is_simple(&fields) {
+                options.array_id_key = fields.pop();
+            }
         }
 
         if let Some(ignore_keys_regex) = kwargs.get_item("ignore_keys_regex")? {
-            let pattern: String = ignore_keys_regex.extract()?;
-            let regex = Regex::new(&pattern).map_err(|e| {
-                PyErr::new::<pyo3::exceptions::PyValueError, _>(format!("Invalid regex: {e}"))
-            })?;
-            options.ignore_keys_regex = Some(regex);
+            options.ignore_keys_regex =
+                Some(compile_regex(&ignore_keys_regex.extract::<String>()?)?);
         }
 
         if let Some(path_filter) = kwargs.get_item("path_filter")? {
-            options.path_filter = Some(path_filter.extract::<String>()?);
+            let path_filter = path_filter.extract::<String>()?;
+            // JSONPath filters are applied by the engine
+            if !path_filter.starts_with('$') {
+                options.path_filter = Some(path_filter);
+            }
         }
 
         if let Some(output_format) = kwargs.get_item("output_format")? {
             let format_str: String = output_format.extract()?;
-            let format = OutputFormat::parse_format(&format_str).map_err(|e| {
-                PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
-                    "Invalid output format: {e}"
-                ))
-            })?;
+            // diffx-core's message already reads "Invalid output format: ..."
+            let format = OutputFormat::parse_format(&format_str)
+                .map_err(|e| PyErr::new::<pyo3::exceptions::PyValueError, _>(e.to_string()))?;
             options.output_format = Some(format);
         }
 
@@ -421,6 +2039,378 @@ fn build_options_from_kwargs(kwargs: Option<&Bound<'_, PyDict>>) -> PyResult<Dif
     Ok(options)
 }
 
+fn compile_path_patterns(patterns: Vec<String>) -> PyResult<Vec<PathPattern>> {
+    patterns
+        .iter()
+        .map(|pattern| PathPattern::new(pattern))
+        .collect::<Result<_, _>>()
+        .map_err(PyErr::new::<pyo3::exceptions::PyValueError, _>)
+}
+
+fn compile_regex(pattern: &str) -> PyResult<Regex> {
+    Regex::new(pattern)
+        .map_err(|e| PyErr::new::<pyo3::exceptions::PyValueError, _>(format!("Invalid regex: {e}")))
+}
+
+/// `epsilons` mapping of path patterns to tolerances, in dict order.
+fn extract_epsilons(epsilons: &Bound<'_, PyAny>) -> PyResult<Vec<(PathPattern, f64)>> {
+    let epsilons = epsilons.downcast::<PyDict>()?;
+    epsilons
+        .iter()
+        .map(|(pattern, epsilon)| {
+            let pattern: String = pattern.extract()?;
+            let epsilon: f64 = epsilon.extract()?;
+            if epsilon.is_nan() || epsilon < 0.0 {
+                return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+                    "epsilons[{pattern:?}] must be a non-negative number"
+                )));
+            }
+            let pattern = PathPattern::new(&pattern)
+                .map_err(PyErr::new::<pyo3::exceptions::PyValueError, _>)?;
+            Ok((pattern, epsilon))
+        })
+        .collect()
+}
+
+/// `coerce_bools` as a flag for the default spellings, or a dict of
+/// spellings keyed by "true" and "false".
+fn extract_bool_table(coerce_bools: &Bound<'_, PyAny>) -> PyResult<Option<BoolTable>> {
+    if let Ok(enabled) = coerce_bools.downcast::<PyBool>() {
+        return Ok(enabled.is_true().then(BoolTable::default));
+    }
+    let table = coerce_bools.downcast::<PyDict>()?;
+    let (mut truthy, mut falsy) = (Vec::new(), Vec::new());
+    for (key, spellings) in table.iter() {
+        let key: String = key.extract()?;
+        let target = match key.as_str() {
+            "true" => &mut truthy,
+            "false" => &mut falsy,
+            other => {
+                return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+                    "coerce_bools keys must be \"true\" or \"false\", not {other:?}"
+                )))
+            }
+        };
+        if spellings.is_instance_of::<PyString>() {
+            return Err(PyErr::new::<pyo3::exceptions::PyTypeError, _>(format!(
+                "coerce_bools[{key:?}] must be a list of spellings"
+            )));
+        }
+        for spelling in spellings.iter()? {
+            target.push(python_to_json_value(&spelling?)?);
+        }
+    }
+    BoolTable::new(truthy, falsy)
+        .map(Some)
+        .map_err(PyErr::new::<pyo3::exceptions::PyValueError, _>)
+}
+
+/// `comparators` mapping of path patterns to Python callables, in dict order.
+fn extract_comparators(comparators: &Bound<'_, PyAny>) -> PyResult<Vec<(PathPattern, PyObject)>> {
+    let comparators = comparators.downcast::<PyDict>()?;
+    comparators
+        .iter()
+        .map(|(pattern, comparator)| {
+            let pattern: String = pattern.extract()?;
+            if !comparator.is_callable() {
+                return Err(PyErr::new::<pyo3::exceptions::PyTypeError, _>(format!(
+                    "comparators[{pattern:?}] must be callable"
+                )));
+            }
+            let pattern = PathPattern::new(&pattern)
+                .map_err(PyErr::new::<pyo3::exceptions::PyValueError, _>)?;
+            Ok((pattern, comparator.unbind()))
+        })
+        .collect()
+}
+
+/// `normalize` as a single callable or a mapping of path patterns to callables.
+fn extract_normalize(normalize: &Bound<'_, PyAny>) -> PyResult<Normalize> {
+    if normalize.is_callable() {
+        return Ok(Normalize::All(normalize.clone().unbind()));
+    }
+    let normalizers = normalize.downcast::<PyDict>().map_err(|_| {
+        PyErr::new::<pyo3::exceptions::PyTypeError, _>(
+            "normalize must be a callable or a dict of path patterns to callables",
+        )
+    })?;
+    normalizers
+        .iter()
+        .map(|(pattern, normalizer)| {
+            let pattern: String = pattern.extract()?;
+            if !normalizer.is_callable() {
+                return Err(PyErr::new::<pyo3::exceptions::PyTypeError, _>(format!(
+                    "normalize[{pattern:?}] must be callable"
+                )));
+            }
+            let pattern = PathPattern::new(&pattern)
+                .map_err(PyErr::new::<pyo3::exceptions::PyValueError, _>)?;
+            Ok((pattern, normalizer.unbind()))
+        })
+        .collect::<PyResult<_>>()
+        .map(Normalize::ByPath)
+}
+
+/// `array_id_key` as a list of fields; accepts a single name or a list of names.
+fn extract_array_id_key(kwargs: &Bound<'_, PyDict>) -> PyResult<Option<Vec<String>>> {
+    let Some(array_id_key) = kwargs.get_item("array_id_key")? else {
+        return Ok(None);
+    };
+    let fields = match array_id_key.extract::<String>() {
+        Ok(field) => vec![field],
+        Err(_) => array_id_key.extract::<Vec<String>>()?,
+    };
+    if fields.is_empty() {
+        return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(
+            "array_id_key must name at least one field",
+        ));
+    }
+    Ok(Some(fields))
+}
+
+/// Options only the binding-side engine supports; `None` keeps diffx-core.
+fn build_engine_options_from_kwargs(
+    kwargs: Option<&Bound<'_, PyDict>>,
+) -> PyResult<Option<EngineOptions>> {
+    let mut options = EngineOptions::default();
+    let mut has_engine_options = false;
+
+    if let Some(kwargs) = kwargs {
+        if let Some(fields) = extract_array_id_key(kwargs)? {
+            if !IdKey::is_simple(&fields) {
+                options.array_id_key = Some(IdKey::new(fields));
+                has_engine_options = true;
+            }
+        }
+
+        if let Some(compare) = kwargs.get_item("compare")? {
+            let compare: String = compare.extract()?;
+            options.compare = Compare::parse(&compare)
+                .map_err(PyErr::new::<pyo3::exceptions::PyValueError, _>)?;
+            has_engine_options |= options.compare != Compare::Values;
+        }
+
+        if let Some(array_order) = kwargs.get_item("array_order")? {
+            let array_order: String = array_order.extract()?;
+            options.array_order = ArrayOrder::parse(&array_order)
+                .map_err(PyErr::new::<pyo3::exceptions::PyValueError, _>)?;
+            has_engine_options |= options.array_order != ArrayOrder::Ordered;
+        }
+
+        if let Some(array_strategy) = kwargs.get_item("array_strategy")? {
+            let array_strategy: String = array_strategy.extract()?;
+            options.array_strategy = ArrayStrategy::parse(&array_strategy)
+                .map_err(PyErr::new::<pyo3::exceptions::PyValueError, _>)?;
+            has_engine_options |= options.array_strategy != ArrayStrategy::Index;
+        }
+
+        if let Some(ignore_paths) = kwargs.get_item("ignore_paths")? {
+            options.ignore_paths = compile_path_patterns(ignore_paths.extract()?)?;
+            has_engine_options |= !options.ignore_paths.is_empty();
+        }
+
+        if let Some(path_filter) = kwargs.get_item("path_filter")? {
+            let path_filter = path_filter.extract::<String>()?;
+            if path_filter.starts_with('$') {
+                options.path_filter_jsonpath = Some(
+                    PathPattern::from_jsonpath(&path_filter)
+                        .map_err(PyErr::new::<pyo3::exceptions::PyValueError, _>)?,
+                );
+                has_engine_options = true;
+            }
+        }
+
+        if let Some(path_filter_regex) = kwargs.get_item("path_filter_regex")? {
+            options.path_filter_regex =
+                Some(compile_regex(&path_filter_regex.extract::<String>()?)?);
+            has_engine_options = true;
+        }
+
+        if let Some(ignore_values_regex) = kwargs.get_item("ignore_values_regex")? {
+            options.ignore_values_regex =
+                Some(compile_regex(&ignore_values_regex.extract::<String>()?)?);
+            has_engine_options = true;
+        }
+
+        if let Some(epsilons) = kwargs.get_item("epsilons")? {
+            options.epsilons = extract_epsilons(&epsilons)?;
+            has_engine_options |= !options.epsilons.is_empty();
+        }
+
+        if let Some(epsilon_relative) = kwargs.get_item("epsilon_relative")? {
+            let epsilon_relative: f64 = epsilon_relative.extract()?;
+            if epsilon_relative.is_nan() || epsilon_relative < 0.0 {
+                return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(
+                    "epsilon_relative must be a non-negative number",
+                ));
+            }
+            options.epsilon_relative = Some(epsilon_relative);
+            has_engine_options = true;
+        }
+
+        if let Some(coerce) = kwargs.get_item("coerce_numeric_strings")? {
+            options.coerce_numeric_strings = coerce.extract::<bool>()?;
+            has_engine_options |= options.coerce_numeric_strings;
+        }
+
+        if let Some(ignore_number_format) = kwargs.get_item("ignore_number_format")? {
+            options.ignore_number_format = ignore_number_format.extract::<bool>()?;
+            has_engine_options |= options.ignore_number_format;
+        }
+
+        if let Some(coerce_bools) = kwargs.get_item("coerce_bools")? {
+            options.coerce_bools = extract_bool_table(&coerce_bools)?;
+            has_engine_options |= options.coerce_bools.is_some();
+        }
+
+        if let Some(compare_durations) = kwargs.get_item("compare_durations")? {
+            options.compare_durations = compare_durations.extract::<bool>()?;
+            has_engine_options |= options.compare_durations;
+        }
+
+        if let Some(compare_quantities) = kwargs.get_item("compare_quantities")? {
+            options.compare_quantities = compare_quantities.extract::<bool>()?;
+            has_engine_options |= options.compare_quantities;
+        }
+
+        if let Some(compare_versions) = kwargs.get_item("compare_versions")? {
+            options.compare_versions = compare_versions.extract::<bool>()?;
+            has_engine_options |= options.compare_versions;
+        }
+
+        if let Some(compare_ips) = kwargs.get_item("compare_ips")? {
+            options.compare_ips = compare_ips.extract::<bool>()?;
+            has_engine_options |= options.compare_ips;
+        }
+
+        options.datetimes = extract_datetime_tolerance(Some(kwargs))?;
+        has_engine_options |= options.datetimes.is_some();
+
+        if let Some(comparators) = kwargs.get_item("comparators")? {
+            options.comparators = extract_comparators(&comparators)?;
+            has_engine_options |= !options.comparators.is_empty();
+        }
+
+        if let Some(normalize) = kwargs.get_item("normalize")? {
+            options.normalize = Some(extract_normalize(&normalize)?);
+            has_engine_options = true;
+        }
+
+        if let Some(comments) = kwargs.get_item("comments")? {
+            let (old, new) = comments.extract().map_err(|_| {
+                PyErr::new::<pyo3::exceptions::PyTypeError, _>(
+                    "comments must be a pair of dicts of path to comment, as source_comments() returns them",
+                )
+            })?;
+            options.comments = Some(engine::Comments { old, new });
+            has_engine_options = true;
+        }
+
+        if let Some(include_paths) = kwargs.get_item("include_paths")? {
+            options.include_paths = compile_path_patterns(include_paths.extract()?)?;
+            has_engine_options |= !options.include_paths.is_empty();
+        }
+
+        if let Some(detect_moves) = kwargs.get_item("detect_moves")? {
+            options.detect_moves = detect_moves.extract::<bool>()?;
+            has_engine_options |= options.detect_moves;
+        }
+
+        if let Some(detect_renames) = kwargs.get_item("detect_renames")? {
+            options.detect_renames = detect_renames.extract::<bool>()?;
+            has_engine_options |= options.detect_renames;
+        }
+
+        if let Some(trim) = kwargs.get_item("trim_whitespace")? {
+            options.whitespace.trim = trim.extract::<bool>()?;
+            has_engine_options |= options.whitespace.trim;
+        }
+
+        if let Some(collapse) = kwargs.get_item("collapse_whitespace")? {
+            options.whitespace.collapse = collapse.extract::<bool>()?;
+            has_engine_options |= options.whitespace.collapse;
+        }
+
+        if let Some(line_endings) = kwargs.get_item("normalize_line_endings")? {
+            options.whitespace.line_endings = line_endings.extract::<bool>()?;
+            has_engine_options |= options.whitespace.line_endings;
+        }
+
+        if let Some(ignore_key_case) = kwargs.get_item("ignore_key_case")? {
+            options.ignore_key_case = ignore_key_case.extract::<bool>()?;
+            has_engine_options |= options.ignore_key_case;
+        }
+
+        // None is the default: no normalization
+        if let Some(unicode_normalize) = kwargs
+            .get_item("unicode_normalize")?
+            .filter(|form| !form.is_none())
+        {
+            let unicode_normalize: String = unicode_normalize.extract()?;
+            options.unicode_normalize = Some(
+                UnicodeForm::parse(&unicode_normalize)
+                    .map_err(PyErr::new::<pyo3::exceptions::PyValueError, _>)?,
+            );
+            has_engine_options = true;
+        }
+
+        if let Some(unicode_normalize_keys) = kwargs.get_item("unicode_normalize_keys")? {
+            options.unicode_normalize_keys = unicode_normalize_keys.extract::<bool>()?;
+            if options.unicode_normalize_keys && options.unicode_normalize.is_none() {
+                return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(
+                    "unicode_normalize_keys requires unicode_normalize",
+                ));
+            }
+            has_engine_options |= options.unicode_normalize_keys;
+        }
+
+        if let Some(empty_as_missing) = kwargs.get_item("empty_as_missing")? {
+            options.empty_as_missing = empty_as_missing.extract::<bool>()?;
+            has_engine_options |= options.empty_as_missing;
+        }
+
+        if let Some(max_results) = kwargs.get_item("max_results")? {
+            options.max_results = Some(max_results.extract::<usize>().map_err(|_| {
+                PyErr::new::<pyo3::exceptions::PyValueError, _>(
+                    "max_results must be a non-negative integer",
+                )
+            })?);
+            has_engine_options = true;
+        }
+
+        if let Some(timeout_ms) = kwargs.get_item("timeout_ms")? {
+            let timeout_ms = timeout_ms.extract::<u64>().map_err(|_| {
+                PyErr::new::<pyo3::exceptions::PyValueError, _>(
+                    "timeout_ms must be a non-negative integer",
+                )
+            })?;
+            options.timeout = Some(Duration::from_millis(timeout_ms));
+            has_engine_options = true;
+        }
+
+        if let Some(max_depth) = kwargs.get_item("max_depth")? {
+            options.max_depth = Some(max_depth.extract::<usize>().map_err(|_| {
+                PyErr::new::<pyo3::exceptions::PyValueError, _>(
+                    "max_depth must be a non-negative integer",
+                )
+            })?);
+            has_engine_options = true;
+        }
+
+        if let Some(memory_limit_mb) = extract_memory_limit_mb(kwargs)? {
+            options.memory_limit_mb = Some(memory_limit_mb);
+            has_engine_options = true;
+        }
+
+        if let Some(report_unchanged) = kwargs.get_item("report_unchanged")? {
+            options.report_unchanged = report_unchanged.extract::<bool>()?;
+            has_engine_options |= options.report_unchanged;
+        }
+    }
+
+    Ok(has_engine_options.then_some(options))
+}
+
 // ============================================================================
 // Python module
 // ============================================================================
@@ -433,17 +2423,78 @@ fn build_options_from_kwargs(kwargs: Option<&Bound<'_, PyDict>>) -> PyResult<Dif
 fn diffx_python(m: &Bound<'_, PyModule>) -> PyResult<()> {
     // Main diff function
     m.add_function(wrap_pyfunction!(diff, m)?)?;
+    m.add_function(wrap_pyfunction!(diff_stats, m)?)?;
+    m.add_function(wrap_pyfunction!(semantic_equal, m)?)?;
+    m.add_function(wrap_pyfunction!(similarity, m)?)?;
+    m.add_function(wrap_pyfunction!(canonicalize, m)?)?;
+    m.add_function(wrap_pyfunction!(semantic_hash, m)?)?;
+
+    m.add_class::<options::DiffOptions>()?;
+    m.add_class::<patch::PatchIssue>()?;
+    m.add_function(wrap_pyfunction!(presets::register_preset, m)?)?;
+    m.add_function(wrap_pyfunction!(presets::get_preset, m)?)?;
+    m.add_function(wrap_pyfunction!(presets::list_presets, m)?)?;
 
     // Parser functions
     m.add_function(wrap_pyfunction!(parse_json, m)?)?;
     m.add_function(wrap_pyfunction!(parse_yaml, m)?)?;
+    m.add_function(wrap_pyfunction!(parse_yaml_all, m)?)?;
     m.add_function(wrap_pyfunction!(parse_toml, m)?)?;
     m.add_function(wrap_pyfunction!(parse_csv, m)?)?;
+    m.add_function(wrap_pyfunction!(parse_tsv, m)?)?;
     m.add_function(wrap_pyfunction!(parse_ini, m)?)?;
     m.add_function(wrap_pyfunction!(parse_xml, m)?)?;
+    m.add_function(wrap_pyfunction!(convert_document, m)?)?;
+    m.add_function(wrap_pyfunction!(serialize::dump_json, m)?)?;
+    m.add_function(wrap_pyfunction!(serialize::dump_yaml, m)?)?;
+    m.add_function(wrap_pyfunction!(serialize::dump_toml, m)?)?;
+    m.add_function(wrap_pyfunction!(serialize::dump_xml, m)?)?;
+    m.add_function(wrap_pyfunction!(serialize::dump_ini, m)?)?;
+    m.add_function(wrap_pyfunction!(serialize::dump_csv, m)?)?;
+
+    // Exceptions
+    m.add(
+        "ParseLimitError",
+        m.py().get_type_bound::<ParseLimitError>(),
+    )?;
+    m.add("ParseError", m.py().get_type_bound::<ParseError>())?;
+    m.add("ParseWarning", m.py().get_type_bound::<ParseWarning>())?;
+    m.add(
+        "DiffTimeoutError",
+        m.py().get_type_bound::<DiffTimeoutError>(),
+    )?;
+    m.add(
+        "MemoryLimitError",
+        m.py().get_type_bound::<MemoryLimitError>(),
+    )?;
+    m.add(
+        "CircularReferenceError",
+        m.py().get_type_bound::<CircularReferenceError>(),
+    )?;
 
     // Format output function
     m.add_function(wrap_pyfunction!(format_output, m)?)?;
+    m.add_function(wrap_pyfunction!(tree::to_tree, m)?)?;
+    m.add_function(wrap_pyfunction!(tree::group_by_prefix, m)?)?;
+    m.add_function(wrap_pyfunction!(ordering::sort_results, m)?)?;
+    m.add_function(wrap_pyfunction!(filters::filter_by_type, m)?)?;
+    m.add_function(wrap_pyfunction!(filters::filter_by_path, m)?)?;
+    m.add_function(wrap_pyfunction!(flatten::flatten, m)?)?;
+    m.add_function(wrap_pyfunction!(flatten::unflatten, m)?)?;
+    m.add_function(wrap_pyfunction!(segments::parse_path, m)?)?;
+    m.add_function(wrap_pyfunction!(segments::format_path, m)?)?;
+    m.add_function(wrap_pyfunction!(access::get_at_path, m)?)?;
+    m.add_function(wrap_pyfunction!(access::set_at_path, m)?)?;
+    m.add_function(wrap_pyfunction!(storage::results_to_json, m)?)?;
+    m.add_function(wrap_pyfunction!(storage::results_from_json, m)?)?;
+    m.add_function(wrap_pyfunction!(merge_patch::to_merge_patch, m)?)?;
+    m.add_function(wrap_pyfunction!(patch::validate_patch, m)?)?;
+    m.add_function(wrap_pyfunction!(positions::source_lines, m)?)?;
+    m.add_function(wrap_pyfunction!(comments::source_comments, m)?)?;
+    m.add_function(wrap_pyfunction!(detect::detect_format, m)?)?;
+
+    // Workflow function
+    m.add_function(wrap_pyfunction!(run, m)?)?;
 
     // Version
     m.add("__version__", "0.6.1")?;
@@ -0,0 +1,179 @@
+//! Resource limits for parsing untrusted documents.
+
+use std::cell::{Cell, RefCell};
+
+use serde_json::Value;
+
+use crate::memory::MemoryBudget;
+
+/// Limits checked around every parser call, and by `ParseGuard` during the
+/// parsers that support it.
+///
+/// `None` means unlimited. `ParseLimits::hardened()` provides conservative
+/// defaults for public-facing services.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ParseLimits {
+    pub max_input_bytes: Option<usize>,
+    pub max_nesting_depth: Option<usize>,
+    pub max_string_length: Option<usize>,
+    pub max_nodes: Option<usize>,
+    /// Enforced with a `MemoryBudget` around the parser call.
+    pub memory_limit_mb: Option<usize>,
+}
+
+impl ParseLimits {
+    pub fn hardened() -> Self {
+        Self {
+            max_input_bytes: Some(16 * 1024 * 1024),
+            max_nesting_depth: Some(64),
+            max_string_length: Some(1024 * 1024),
+            max_nodes: Some(1_000_000),
+            memory_limit_mb: None,
+        }
+    }
+
+    /// Reject oversized input before the parser allocates anything.
+    pub fn check_input(&self, content: &str) -> Result<(), String> {
+        match self.max_input_bytes {
+            Some(max) if content.len() > max => Err(format!(
+                "input is {} bytes, exceeding max_input_bytes={max}",
+                content.len()
+            )),
+            _ => Ok(()),
+        }
+    }
+
+    /// Check depth, string sizes, and node count of a parsed document.
+    pub fn check_value(&self, value: &Value) -> Result<(), String> {
+        self.check_documents(std::slice::from_ref(value))
+    }
+
+    /// Like `check_value`, counting nodes across every document of a stream.
+    pub fn check_documents(&self, documents: &[Value]) -> Result<(), String> {
+        let mut nodes = 0;
+        for document in documents {
+            self.check_node(document, 0, &mut nodes)?;
+        }
+        Ok(())
+    }
+
+    fn check_node(&self, value: &Value, depth: usize, nodes: &mut usize) -> Result<(), String> {
+        *nodes += 1;
+        if let Some(max) = self.max_nodes {
+            if *nodes > max {
+                return Err(format!("document exceeds max_nodes={max}"));
+            }
+        }
+
+        match value {
+            Value::String(s) => self.check_string(s),
+            Value::Array(items) => {
+                self.check_depth(depth + 1)?;
+                for item in items {
+                    self.check_node(item, depth + 1, nodes)?;
+                }
+                Ok(())
+            }
+            Value::Object(map) => {
+                self.check_depth(depth + 1)?;
+                for (key, item) in map {
+                    self.check_string(key)?;
+                    self.check_node(item, depth + 1, nodes)?;
+                }
+                Ok(())
+            }
+            _ => Ok(()),
+        }
+    }
+
+    fn check_depth(&self, depth: usize) -> Result<(), String> {
+        match self.max_nesting_depth {
+            Some(max) if depth > max => {
+                Err(format!("document nesting exceeds max_nesting_depth={max}"))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    fn check_string(&self, s: &str) -> Result<(), String> {
+        match self.max_string_length {
+            Some(max) if s.len() > max => Err(format!(
+                "string of {} bytes exceeds max_string_length={max}",
+                s.len()
+            )),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Limits a parser enforces while it builds a document, so a hostile input is
+/// rejected before it is fully built instead of after.
+#[derive(Debug)]
+pub struct ParseGuard<'a> {
+    limits: &'a ParseLimits,
+    memory_budget: Option<&'a MemoryBudget>,
+    nodes: Cell<usize>,
+    exceeded: RefCell<Option<Exceeded>>,
+}
+
+/// The limit that stopped a guarded parse.
+#[derive(Debug)]
+pub enum Exceeded {
+    /// One of the `ParseLimits`, raised as `ParseLimitError`.
+    Limit(String),
+    /// The `memory_limit_mb` budget, raised as `MemoryLimitError`.
+    Memory(String),
+}
+
+impl<'a> ParseGuard<'a> {
+    pub fn new(limits: &'a ParseLimits, memory_budget: Option<&'a MemoryBudget>) -> Self {
+        Self {
+            limits,
+            memory_budget,
+            nodes: Cell::new(0),
+            exceeded: RefCell::new(None),
+        }
+    }
+
+    /// Count `count` more values, failing past `max_nodes` or once the
+    /// memory budget is spent.
+    pub fn add_nodes(&self, count: usize) -> Result<(), String> {
+        let nodes = self.nodes.get().saturating_add(count);
+        self.nodes.set(nodes);
+        if let Some(max) = self.limits.max_nodes.filter(|&max| nodes > max) {
+            return Err(self.exceed(format!("document exceeds max_nodes={max}")));
+        }
+        match self.memory_budget.map(MemoryBudget::check) {
+            Some(Err(message)) => Err(self.record(Exceeded::Memory(message))),
+            _ => Ok(()),
+        }
+    }
+
+    /// Check a container `depth` levels deep, the root container being 1.
+    pub fn enter(&self, depth: usize) -> Result<(), String> {
+        self.limits.check_depth(depth).map_err(|e| self.exceed(e))
+    }
+
+    /// Check the length of a string or key.
+    pub fn string(&self, s: &str) -> Result<(), String> {
+        self.limits.check_string(s).map_err(|e| self.exceed(e))
+    }
+
+    /// Record `message` as the limit that stopped the parse, and return it.
+    pub fn exceed(&self, message: String) -> String {
+        self.record(Exceeded::Limit(message))
+    }
+
+    fn record(&self, exceeded: Exceeded) -> String {
+        let message = match &exceeded {
+            Exceeded::Limit(message) | Exceeded::Memory(message) => message.clone(),
+        };
+        *self.exceeded.borrow_mut() = Some(exceeded);
+        message
+    }
+
+    /// The limit that stopped the parse, if one did.
+    pub fn exceeded(&self) -> Option<Exceeded> {
+        self.exceeded.borrow_mut().take()
+    }
+}
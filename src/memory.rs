@@ -0,0 +1,117 @@
+//! Allocation tracking for `memory_limit_mb`.
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// System allocator that keeps a per-thread count of live bytes while any
+/// `MemoryBudget` exists; otherwise it only forwards to the system allocator.
+pub struct TrackingAllocator;
+
+thread_local! {
+    static ALLOCATED: Cell<isize> = const { Cell::new(0) };
+}
+
+/// Number of live `MemoryBudget`s.
+static ACTIVE_BUDGETS: AtomicUsize = AtomicUsize::new(0);
+
+fn record(delta: isize) {
+    // A budget only looks at the change in the count while it exists, so
+    // allocations outside every budget need not be counted
+    if ACTIVE_BUDGETS.load(Ordering::Relaxed) == 0 {
+        return;
+    }
+    // Fails only while the thread is being torn down
+    let _ = ALLOCATED.try_with(|allocated| allocated.set(allocated.get().wrapping_add(delta)));
+}
+
+fn allocated() -> isize {
+    ALLOCATED.try_with(Cell::get).unwrap_or_default()
+}
+
+unsafe impl GlobalAlloc for TrackingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let ptr = System.alloc(layout);
+        if !ptr.is_null() {
+            record(layout.size() as isize);
+        }
+        ptr
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        let ptr = System.alloc_zeroed(layout);
+        if !ptr.is_null() {
+            record(layout.size() as isize);
+        }
+        ptr
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout);
+        record(-(layout.size() as isize));
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        let new_ptr = System.realloc(ptr, layout, new_size);
+        if !new_ptr.is_null() {
+            record(new_size as isize - layout.size() as isize);
+        }
+        new_ptr
+    }
+}
+
+const MB: usize = 1024 * 1024;
+
+/// Limit on the bytes the current thread allocates after `start`.
+///
+/// Only Rust allocations are counted; Python objects are not. Parsers that
+/// take a `ParseGuard` check it as they build values; elsewhere it is
+/// checked after each step.
+#[derive(Debug)]
+pub struct MemoryBudget {
+    baseline: isize,
+    limit_mb: usize,
+}
+
+impl MemoryBudget {
+    pub fn start(limit_mb: usize) -> Self {
+        ACTIVE_BUDGETS.fetch_add(1, Ordering::Relaxed);
+        Self {
+            baseline: allocated(),
+            limit_mb,
+        }
+    }
+
+    fn limit_bytes(&self) -> usize {
+        self.limit_mb.saturating_mul(MB)
+    }
+
+    /// Reject input that cannot fit in the budget before parsing it.
+    pub fn check_input(&self, input_bytes: u64) -> Result<(), String> {
+        if input_bytes > self.limit_bytes() as u64 {
+            return Err(format!(
+                "input is {input_bytes} bytes, exceeding memory_limit_mb={}",
+                self.limit_mb
+            ));
+        }
+        Ok(())
+    }
+
+    pub fn check(&self) -> Result<(), String> {
+        let used = allocated().saturating_sub(self.baseline).max(0) as usize;
+        if used > self.limit_bytes() {
+            return Err(format!(
+                "allocated {} MB, exceeding memory_limit_mb={}",
+                used.div_ceil(MB),
+                self.limit_mb
+            ));
+        }
+        Ok(())
+    }
+}
+
+impl Drop for MemoryBudget {
+    fn drop(&mut self) {
+        ACTIVE_BUDGETS.fetch_sub(1, Ordering::Relaxed);
+    }
+}
@@ -0,0 +1,120 @@
+//! JSON Merge Patch (RFC 7386) documents built from diff results.
+
+use pyo3::prelude::*;
+use pyo3::types::PyList;
+use serde_json::{Map, Value};
+
+use crate::paths::split_path;
+use crate::result::Difference;
+
+/// Convert diff results to a JSON Merge Patch (RFC 7386)
+///
+/// Removed keys become `null`, added and modified values are set at their
+/// path inside nested objects, and renamed keys are removed under the old
+/// name and set under the new one. Unchanged results are skipped.
+///
+/// A merge patch replaces lists whole and uses `null` to delete, so changes
+/// inside a list and new `None` values cannot be expressed.
+///
+/// Args:
+///     results: List of diff results from diff()
+///
+/// Returns:
+///     The merge patch document (a dict unless the root itself changed)
+///
+/// Raises:
+///     ValueError: If a change cannot be expressed as a merge patch
+///
+/// Example:
+///     >>> to_merge_patch(diff({"a": 1, "b": 2}, {"a": 3}))
+///     {'a': 3, 'b': None}
+#[pyfunction]
+pub fn to_merge_patch(py: Python, results: &Bound<'_, PyList>) -> PyResult<PyObject> {
+    let results = crate::python_results_to_rust(results)?;
+    let patch = build(&results).map_err(PyErr::new::<pyo3::exceptions::PyValueError, _>)?;
+    crate::json_value_to_python(py, &patch)
+}
+
+fn build(results: &[Difference]) -> Result<Value, String> {
+    let mut patch = Value::Object(Map::new());
+    for result in results {
+        match result {
+            Difference::Added(path, value)
+            | Difference::Modified(path, _, value)
+            | Difference::TypeChanged(path, _, value) => {
+                set(&mut patch, path, checked(path, value)?)?
+            }
+            Difference::Removed(path, _) => set(&mut patch, path, &Value::Null)?,
+            Difference::Renamed(path, new_path, value) => {
+                set(&mut patch, path, &Value::Null)?;
+                set(&mut patch, new_path, checked(new_path, value)?)?;
+            }
+            Difference::Moved(path, _, _) => return Err(inside_list(path)),
+            // Comments are not part of the data a merge patch carries
+            Difference::Unchanged(..) | Difference::Comment(..) => {}
+        }
+    }
+    Ok(patch)
+}
+
+/// `value` if applying it through a merge patch keeps all of it.
+fn checked<'v>(path: &str, value: &'v Value) -> Result<&'v Value, String> {
+    if has_null_member(value) {
+        return Err(format!(
+            "Cannot express the change at '{path}' as a merge patch: null values mean removal"
+        ));
+    }
+    Ok(value)
+}
+
+/// Place `value` at `path` in `patch`; `null` marks a removal.
+fn set(patch: &mut Value, path: &str, value: &Value) -> Result<(), String> {
+    let segments = split_path(path);
+    if segments.iter().any(|segment| segment.starts_with('[')) {
+        return Err(inside_list(path));
+    }
+    let Some((last, parents)) = segments.split_last() else {
+        if patch.as_object().is_some_and(|map| !map.is_empty()) {
+            return Err(conflict(path));
+        }
+        *patch = value.clone();
+        return Ok(());
+    };
+    let mut node = patch;
+    for segment in parents {
+        let Value::Object(map) = node else {
+            return Err(conflict(path));
+        };
+        node = map
+            .entry(segment.to_string())
+            .or_insert_with(|| Value::Object(Map::new()));
+    }
+    let Value::Object(map) = node else {
+        return Err(conflict(path));
+    };
+    if map.insert(last.to_string(), value.clone()).is_some() {
+        return Err(conflict(path));
+    }
+    Ok(())
+}
+
+/// Whether `value` is `null` or holds a `null` object member outside a list.
+fn has_null_member(value: &Value) -> bool {
+    match value {
+        Value::Null => true,
+        Value::Object(map) => map.values().any(has_null_member),
+        _ => false,
+    }
+}
+
+fn inside_list(path: &str) -> String {
+    format!(
+        "Cannot express the change at '{path}' as a merge patch: lists can only be replaced whole"
+    )
+}
+
+fn conflict(path: &str) -> String {
+    format!(
+        "Cannot express the change at '{path}' as a merge patch: it conflicts with another change"
+    )
+}
@@ -0,0 +1,105 @@
+//! OpenAPI-aware diffing: parameter alignment and breaking-change classification.
+
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+use serde_json::Value;
+
+use crate::paths::split_path;
+use crate::result::Difference;
+
+/// Keys that document an API without changing its contract.
+const DOC_KEYS: &[&str] = &[
+    "description",
+    "summary",
+    "title",
+    "example",
+    "examples",
+    "externalDocs",
+    "tags",
+    "deprecated",
+];
+
+/// `openapi=True` options gathered from kwargs.
+#[derive(Debug, Clone, Copy)]
+pub struct OpenApiMode {
+    /// Add a `"breaking"` flag to every result.
+    pub classify_breaking: bool,
+}
+
+impl OpenApiMode {
+    pub fn from_kwargs(kwargs: Option<&Bound<'_, PyDict>>) -> PyResult<Option<Self>> {
+        let Some(kwargs) = kwargs else {
+            return Ok(None);
+        };
+        let enabled = match kwargs.get_item("openapi")? {
+            Some(openapi) => openapi.extract::<bool>()?,
+            None => false,
+        };
+        let classify_breaking = match kwargs.get_item("classify_breaking")? {
+            Some(classify) => classify.extract::<bool>()?,
+            None => false,
+        };
+        if classify_breaking && !enabled {
+            return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(
+                "classify_breaking requires openapi=True",
+            ));
+        }
+        Ok(enabled.then_some(Self { classify_breaking }))
+    }
+}
+
+/// Align parameter arrays by `name` + `in` unless `array_id_key` is given.
+pub fn apply_defaults(kwargs: &Bound<'_, PyDict>) -> PyResult<()> {
+    let enabled = match kwargs.get_item("openapi")? {
+        Some(openapi) => openapi.is_truthy()?,
+        None => false,
+    };
+    if enabled && !kwargs.contains("array_id_key")? {
+        kwargs.set_item("array_id_key", ["name", "in"])?;
+    }
+    Ok(())
+}
+
+/// Whether a change can break existing clients of the API.
+///
+/// Removals, renames and type changes break; additions do not, except new
+/// required parameters or properties and parameters that became required.
+/// Documentation-only keys (`description`, `example`, `x-*` extensions, ...)
+/// never break.
+pub fn is_breaking(result: &Difference) -> bool {
+    let segments = split_path(result.path());
+    if is_documentation(&segments) {
+        return false;
+    }
+    let in_required_list = matches!(
+        segments.as_slice(),
+        [.., "required", index] if index.starts_with('[')
+    );
+    let is_required_flag = segments.last() == Some(&"required");
+    let required = Value::Bool(true);
+    match result {
+        Difference::Added(_, value) if is_required_flag => value == &required,
+        Difference::Added(_, value) => in_required_list || is_required_parameter(value),
+        Difference::Removed(..) => !in_required_list && !is_required_flag,
+        Difference::Modified(_, old, new) if is_required_flag => {
+            old != &required && new == &required
+        }
+        Difference::Modified(..) => true,
+        Difference::TypeChanged(..) | Difference::Renamed(..) => true,
+        Difference::Moved(..) | Difference::Unchanged(..) | Difference::Comment(..) => false,
+    }
+}
+
+/// Whether the path runs through a documentation key. Keys directly under
+/// `properties` are schema property names, not documentation.
+fn is_documentation(segments: &[&str]) -> bool {
+    segments.first() == Some(&"info")
+        || segments.iter().enumerate().any(|(i, segment)| {
+            let is_property_name = i > 0 && segments[i - 1] == "properties";
+            !is_property_name && (DOC_KEYS.contains(segment) || segment.starts_with("x-"))
+        })
+}
+
+fn is_required_parameter(value: &Value) -> bool {
+    value.get("in").is_some() && value.get("required") == Some(&Value::Bool(true))
+}
@@ -0,0 +1,138 @@
+//! `DiffOptions`: a validated, reusable set of `diff()` keyword arguments.
+
+use pyo3::prelude::*;
+use pyo3::types::{PyDict, PyTuple};
+
+use crate::convert::Conversion;
+use crate::openapi::{self, OpenApiMode};
+use crate::{kwargs, presets};
+
+/// Reusable diff options, validated when constructed.
+///
+/// Accepts the same keyword arguments as `diff()`; `None` leaves an option at
+/// its default. Pass it as `diff(old, new, options=opts)`; explicit keyword
+/// arguments take precedence over it, and it takes precedence over its preset.
+#[pyclass(frozen, module = "diffx_python")]
+pub struct DiffOptions {
+    kwargs: Py<PyDict>,
+}
+
+#[pymethods]
+impl DiffOptions {
+    #[new]
+    #[pyo3(signature = (**kwargs))]
+    fn new(py: Python<'_>, kwargs: Option<&Bound<'_, PyDict>>) -> PyResult<Self> {
+        match kwargs {
+            Some(kwargs) => Self::from_dict(py, kwargs),
+            None => Self::from_dict(py, &PyDict::new_bound(py)),
+        }
+    }
+
+    /// Return a copy with the given options changed (`None` resets one).
+    #[pyo3(signature = (**changes))]
+    fn replace(&self, py: Python<'_>, changes: Option<&Bound<'_, PyDict>>) -> PyResult<Self> {
+        let merged = self.kwargs.bind(py).copy()?;
+        if let Some(changes) = changes {
+            merged.update(changes.as_mapping())?;
+        }
+        Self::from_dict(py, &merged)
+    }
+
+    /// The options as a dict of keyword arguments for `diff()`.
+    pub fn to_dict<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyDict>> {
+        self.kwargs.bind(py).copy()
+    }
+
+    fn __getattr__(&self, py: Python<'_>, name: &str) -> PyResult<PyObject> {
+        if !kwargs::DIFF.contains(&name) {
+            return Err(PyErr::new::<pyo3::exceptions::PyAttributeError, _>(
+                format!("'DiffOptions' object has no attribute '{name}'"),
+            ));
+        }
+        let options = self.kwargs.bind(py);
+        let value = match (options.get_item(name)?, options.get_item("preset")?) {
+            (Some(value), _) => Some(value),
+            (None, Some(preset)) => {
+                presets::lookup(py, &preset.extract::<String>()?)?.get_item(name)?
+            }
+            (None, None) => None,
+        };
+        Ok(value.map_or_else(|| py.None(), Bound::unbind))
+    }
+
+    /// Pickle as a call with the same keyword arguments, so options can be
+    /// sent to worker processes.
+    fn __reduce__<'py>(
+        slf: &Bound<'py, Self>,
+    ) -> PyResult<(Bound<'py, PyAny>, Bound<'py, PyTuple>)> {
+        let py = slf.py();
+        let constructor = py
+            .import_bound("functools")?
+            .getattr("partial")?
+            .call((slf.get_type(),), Some(&slf.get().to_dict(py)?))?;
+        Ok((constructor, PyTuple::empty_bound(py)))
+    }
+
+    fn __eq__(&self, py: Python<'_>, other: &Bound<'_, Self>) -> PyResult<bool> {
+        self.kwargs.bind(py).eq(other.get().kwargs.bind(py))
+    }
+
+    fn __repr__(&self, py: Python<'_>) -> PyResult<String> {
+        let fields = self
+            .kwargs
+            .bind(py)
+            .iter()
+            .map(|(name, value)| Ok(format!("{name}={}", value.repr()?)))
+            .collect::<PyResult<Vec<_>>>()?;
+        Ok(format!("DiffOptions({})", fields.join(", ")))
+    }
+}
+
+impl DiffOptions {
+    pub fn from_dict(py: Python<'_>, kwargs: &Bound<'_, PyDict>) -> PyResult<Self> {
+        let options = PyDict::new_bound(py);
+        for (name, value) in kwargs.iter() {
+            if !value.is_none() {
+                options.set_item(name, value)?;
+            }
+        }
+        validate(&options)?;
+        Ok(Self {
+            kwargs: options.unbind(),
+        })
+    }
+}
+
+/// Build every option once so bad values fail at construction, not at diff time.
+fn validate(kwargs: &Bound<'_, PyDict>) -> PyResult<()> {
+    kwargs::check("DiffOptions", Some(kwargs), &[kwargs::DIFF])?;
+    let kwargs = &presets::expand(kwargs.copy()?)?;
+    crate::build_options_from_kwargs(Some(kwargs))?;
+    crate::build_engine_options_from_kwargs(Some(kwargs))?;
+    crate::extract_datetime_tolerance(Some(kwargs))?;
+    crate::extract_sort_order(Some(kwargs))?;
+    crate::extract_path_style(Some(kwargs))?;
+    Conversion::from_kwargs(Some(kwargs))?;
+    crate::extract_memory_limit_mb(kwargs)?;
+    OpenApiMode::from_kwargs(Some(kwargs))?;
+    Ok(())
+}
+
+/// Combine a preset, `options=` and explicit keyword arguments, later ones
+/// taking precedence.
+pub fn resolve<'py>(
+    py: Python<'py>,
+    options: Option<&Bound<'py, DiffOptions>>,
+    kwargs: Option<&Bound<'py, PyDict>>,
+) -> PyResult<Bound<'py, PyDict>> {
+    let merged = PyDict::new_bound(py);
+    if let Some(options) = options {
+        merged.update(options.get().kwargs.bind(py).as_mapping())?;
+    }
+    if let Some(kwargs) = kwargs {
+        merged.update(kwargs.as_mapping())?;
+    }
+    let resolved = presets::expand(merged)?;
+    openapi::apply_defaults(&resolved)?;
+    Ok(resolved)
+}
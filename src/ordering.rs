@@ -0,0 +1,106 @@
+//! Stable result orderings for `sort=` and `sort_results()`.
+
+use std::cmp::Ordering;
+
+use pyo3::prelude::*;
+use pyo3::types::PyList;
+
+use crate::paths::{array_index, split_path};
+use crate::result::{self, Difference, KINDS};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SortOrder {
+    /// Paths compared as plain strings.
+    Path,
+    /// Grouped by kind in `KINDS` order, then in document order.
+    Type,
+    /// Parents before children, keys alphabetically, array elements by index.
+    Document,
+}
+
+impl SortOrder {
+    pub fn parse(order: &str) -> Result<Self, String> {
+        match order {
+            "path" => Ok(Self::Path),
+            "type" => Ok(Self::Type),
+            "document" => Ok(Self::Document),
+            other => Err(format!(
+                "Invalid sort: {other:?} (expected \"path\", \"type\" or \"document\")"
+            )),
+        }
+    }
+
+    /// Compare two results by `(kind, path)`; ties fall back to the other key
+    /// so the order never depends on the input order.
+    fn compare(self, a: (&str, &str), b: (&str, &str)) -> Ordering {
+        let (a_kind, a_path) = a;
+        let (b_kind, b_path) = b;
+        let by_kind = kind_rank(a_kind).cmp(&kind_rank(b_kind));
+        match self {
+            Self::Path => a_path.cmp(b_path).then(by_kind),
+            Self::Type => by_kind.then_with(|| compare_document(a_path, b_path)),
+            Self::Document => compare_document(a_path, b_path).then(by_kind),
+        }
+    }
+}
+
+pub fn sort(results: &mut [Difference], order: SortOrder) {
+    results.sort_by(|a, b| order.compare((a.kind(), a.path()), (b.kind(), b.path())));
+}
+
+fn kind_rank(kind: &str) -> usize {
+    KINDS
+        .iter()
+        .position(|known| *known == kind)
+        .unwrap_or(KINDS.len())
+}
+
+/// Segment-wise comparison: numeric array indices compare as numbers and a
+/// path sorts before the paths below it.
+fn compare_document(a: &str, b: &str) -> Ordering {
+    let (a, b) = (split_path(a), split_path(b));
+    for (a, b) in a.iter().zip(&b) {
+        let ordering = match (array_index(a), array_index(b)) {
+            (Some(a), Some(b)) => a.cmp(&b),
+            _ => a.cmp(b),
+        };
+        if ordering != Ordering::Equal {
+            return ordering;
+        }
+    }
+    a.len().cmp(&b.len())
+}
+
+/// Sort diff results into a stable order
+///
+/// Args:
+///     results: List of diff results from diff()
+///     by: "path" (paths as strings), "type" (by kind, then document order),
+///         or "document" (parents first, array elements by index)
+///
+/// Returns:
+///     A new sorted list; `results` is left as is
+#[pyfunction]
+#[pyo3(signature = (results, by="path"))]
+pub fn sort_results<'py>(
+    py: Python<'py>,
+    results: &Bound<'py, PyList>,
+    by: &str,
+) -> PyResult<Bound<'py, PyList>> {
+    let order = SortOrder::parse(by).map_err(PyErr::new::<pyo3::exceptions::PyValueError, _>)?;
+    let mut keyed = results
+        .iter()
+        .map(|result| {
+            Ok((
+                result::str_field(&result, "type")?,
+                result::str_field(&result, "path")?,
+                result,
+            ))
+        })
+        .collect::<PyResult<Vec<_>>>()?;
+    keyed.sort_by(|a, b| order.compare((&a.0, &a.1), (&b.0, &b.1)));
+    Ok(PyList::new_bound(
+        py,
+        keyed.into_iter().map(|(_, _, result)| result),
+    ))
+}
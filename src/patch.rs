@@ -0,0 +1,176 @@
+//! Dry-run checks of whether stored diff results still apply to a document.
+
+use pyo3::prelude::*;
+use pyo3::types::PyList;
+
+use crate::access;
+use crate::result::Difference;
+use crate::segments::Segment;
+
+/// A change that would not apply cleanly, as reported by `validate_patch()`.
+///
+/// `reason` is `"missing"` (nothing at the path, or no parent for an
+/// addition), `"mismatch"` (the value differs from the old value the change
+/// expects) or `"exists"` (an addition or rename target is already taken).
+#[pyclass(frozen, get_all, module = "diffx_python")]
+pub struct PatchIssue {
+    /// Position of the change in the results.
+    index: usize,
+    #[pyo3(name = "type")]
+    kind: String,
+    path: String,
+    reason: String,
+    message: String,
+    /// The value the change expects, or `None`.
+    expected: PyObject,
+    /// The value found in the document, or `None`.
+    actual: PyObject,
+}
+
+#[pymethods]
+impl PatchIssue {
+    fn __repr__(&self) -> String {
+        format!(
+            "PatchIssue(index={}, type={:?}, path={:?}, reason={:?})",
+            self.index, self.kind, self.path, self.reason
+        )
+    }
+
+    fn __str__(&self) -> String {
+        self.message.clone()
+    }
+}
+
+/// Check whether diff results still apply cleanly to a document
+///
+/// Nothing is modified. Each change is checked against `document` as it is:
+/// removed, modified and renamed values must be present with their old
+/// value, moved elements must be present, and additions need their parent
+/// and a free key (an addition at a list position inserts there). Paths are
+/// traced through the document like `get_at_path()`. Unchanged results are
+/// skipped.
+///
+/// Args:
+///     document: The document the changes would be applied to
+///     results: List of diff results, e.g. from results_from_json()
+///
+/// Returns:
+///     A list of PatchIssue, in result order; empty when every change applies
+///
+/// Example:
+///     >>> results = diff({"port": 80}, {"port": 8080})
+///     >>> validate_patch({"port": 81}, results)
+///     [PatchIssue(index=0, type="Modified", path="port", reason="mismatch")]
+#[pyfunction]
+pub fn validate_patch(
+    py: Python,
+    document: &Bound<'_, PyAny>,
+    results: &Bound<'_, PyList>,
+) -> PyResult<Vec<PatchIssue>> {
+    let mut issues = Vec::new();
+    for (index, result) in crate::python_results_to_rust(results)?
+        .into_iter()
+        .enumerate()
+    {
+        let issue = |path: &str, reason: &str, message: String, expected, actual| PatchIssue {
+            index,
+            kind: result.kind().to_string(),
+            path: path.to_string(),
+            reason: reason.to_string(),
+            message,
+            expected,
+            actual,
+        };
+        match &result {
+            Difference::Removed(path, old)
+            | Difference::Modified(path, old, _)
+            | Difference::TypeChanged(path, old, _)
+            | Difference::Renamed(path, _, old) => match find(document, path)? {
+                None => issues.push(issue(
+                    path,
+                    "missing",
+                    format!("No value at '{path}'"),
+                    crate::json_value_to_python(py, old)?,
+                    py.None(),
+                )),
+                Some(actual) if crate::python_to_json_value(&actual)? != *old => {
+                    issues.push(issue(
+                        path,
+                        "mismatch",
+                        format!("Value at '{path}' is not the expected old value {old}"),
+                        crate::json_value_to_python(py, old)?,
+                        actual.unbind(),
+                    ))
+                }
+                Some(_) => {}
+            },
+            Difference::Moved(path, _, _) => {
+                if find(document, path)?.is_none() {
+                    issues.push(issue(
+                        path,
+                        "missing",
+                        format!("No value at '{path}'"),
+                        py.None(),
+                        py.None(),
+                    ));
+                }
+            }
+            Difference::Added(..) | Difference::Unchanged(..) | Difference::Comment(..) => {}
+        }
+        let target = match &result {
+            Difference::Added(path, _) => Some(path),
+            Difference::Renamed(_, new_path, _) => Some(new_path),
+            _ => None,
+        };
+        if let Some(path) = target {
+            if let Some(actual) = occupied(document, path)? {
+                issues.push(issue(
+                    path,
+                    "exists",
+                    format!("A value already exists at '{path}'"),
+                    py.None(),
+                    actual.unbind(),
+                ));
+            } else if access::walk(path, document, true)?.is_none() {
+                issues.push(issue(
+                    path,
+                    "missing",
+                    format!("Cannot add '{path}': its parent is missing or the list is too short"),
+                    py.None(),
+                    py.None(),
+                ));
+            }
+        }
+    }
+    Ok(issues)
+}
+
+/// The value at `path`, or `None` when nothing is there.
+fn find<'py>(document: &Bound<'py, PyAny>, path: &str) -> PyResult<Option<Bound<'py, PyAny>>> {
+    let Some(segments) = access::walk(path, document, false)? else {
+        return Ok(None);
+    };
+    let mut value = document.clone();
+    for segment in &segments {
+        match access::child(&value, segment)? {
+            Some(next) => value = next,
+            None => return Ok(None),
+        }
+    }
+    Ok(Some(value))
+}
+
+/// The value an addition at `path` would overwrite; list positions are
+/// never occupied because additions insert there.
+fn occupied<'py>(document: &Bound<'py, PyAny>, path: &str) -> PyResult<Option<Bound<'py, PyAny>>> {
+    let inserts = matches!(
+        access::walk(path, document, false)?
+            .as_deref()
+            .and_then(<[Segment]>::last),
+        Some(Segment::Index(_))
+    );
+    if inserts {
+        return Ok(None);
+    }
+    find(document, path)
+}
@@ -0,0 +1,293 @@
+//! Glob-style patterns over diff paths such as `items[*].metadata.*`, and the
+//! JSONPath subset accepted by `path_filter`.
+
+use regex::Regex;
+
+/// Split a diff path into segments: `a.b[0].c` becomes `["a", "b", "[0]", "c"]`.
+///
+/// Dots and brackets inside `[...]` (including quoted id values) do not split.
+pub fn split_path(path: &str) -> Vec<&str> {
+    let mut segments = Vec::new();
+    let mut start = 0;
+    let mut depth = 0usize;
+    let mut in_quotes = false;
+    let mut escaped = false;
+
+    for (i, c) in path.char_indices() {
+        if in_quotes {
+            match c {
+                _ if escaped => escaped = false,
+                '\\' => escaped = true,
+                '"' => in_quotes = false,
+                _ => {}
+            }
+            continue;
+        }
+        match c {
+            '"' if depth > 0 => in_quotes = true,
+            '[' => {
+                if depth == 0 && i > start {
+                    segments.push(&path[start..i]);
+                    start = i;
+                }
+                depth += 1;
+            }
+            ']' if depth > 0 => {
+                depth -= 1;
+                if depth == 0 {
+                    segments.push(&path[start..=i]);
+                    start = i + 1;
+                }
+            }
+            '.' if depth == 0 => {
+                if i > start {
+                    segments.push(&path[start..i]);
+                }
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    if start < path.len() {
+        segments.push(&path[start..]);
+    }
+    segments
+}
+
+/// Join path segments back into a diff path: `["a", "[0]", "b"]` becomes `a[0].b`.
+pub fn join_path(segments: &[&str]) -> String {
+    let mut path = String::new();
+    for segment in segments {
+        if !path.is_empty() && !segment.starts_with('[') {
+            path.push('.');
+        }
+        path.push_str(segment);
+    }
+    path
+}
+
+/// The position named by an index segment such as `[3]`.
+pub fn array_index(segment: &str) -> Option<usize> {
+    segment.strip_prefix('[')?.strip_suffix(']')?.parse().ok()
+}
+
+#[derive(Debug, Clone)]
+enum Segment {
+    /// `**`: any number of segments, including none.
+    AnyDepth,
+    /// Any single segment, key or index (JSONPath `*`).
+    AnyOne,
+    /// A single segment; `is_index` for `[...]` segments.
+    Glob { regex: Regex, is_index: bool },
+}
+
+impl Segment {
+    fn matches(&self, segment: &str) -> bool {
+        match self {
+            Self::AnyDepth | Self::AnyOne => true,
+            Self::Glob { regex, is_index } => {
+                segment.starts_with('[') == *is_index && regex.is_match(segment)
+            }
+        }
+    }
+}
+
+/// A compiled path glob.
+///
+/// `*` matches within one segment (a key, or one `[...]` index), `**` matches
+/// any number of segments, and `?` matches a single character. Brackets are
+/// literal, so `items[*]` matches any array element.
+#[derive(Debug, Clone)]
+pub struct PathPattern {
+    segments: Vec<Segment>,
+}
+
+impl PathPattern {
+    pub fn new(pattern: &str) -> Result<Self, String> {
+        let segments = split_path(pattern)
+            .into_iter()
+            .map(|segment| {
+                if segment == "**" {
+                    return Ok(Segment::AnyDepth);
+                }
+                let mut source = String::from("^");
+                for c in segment.chars() {
+                    match c {
+                        '*' => source.push_str(".*"),
+                        '?' => source.push('.'),
+                        _ => source.push_str(&regex::escape(&c.to_string())),
+                    }
+                }
+                source.push('$');
+                Regex::new(&source)
+                    .map(|regex| Segment::Glob {
+                        regex,
+                        is_index: segment.starts_with('['),
+                    })
+                    .map_err(|e| format!("Invalid path pattern {pattern:?}: {e}"))
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(Self { segments })
+    }
+
+    /// Compile a JSONPath expression such as `$.spec.containers[*].image`.
+    ///
+    /// Supported are child names (`.name`, `['name']`), indices (`[0]`),
+    /// unions (`[0,1]`, `['a','b']`), wildcards (`.*`, `[*]`) and recursive
+    /// descent (`..name`); filters and slices are rejected.
+    pub fn from_jsonpath(expr: &str) -> Result<Self, String> {
+        let invalid = |reason: &str| format!("Invalid JSONPath {expr:?}: {reason}");
+        let mut rest = expr
+            .strip_prefix('$')
+            .ok_or_else(|| invalid("it must start with '$'"))?;
+        let mut segments = Vec::new();
+        while !rest.is_empty() {
+            if let Some(after) = rest.strip_prefix("..") {
+                segments.push(Segment::AnyDepth);
+                rest = after;
+                if rest.starts_with('[') {
+                    continue;
+                }
+            } else if let Some(after) = rest.strip_prefix('.') {
+                rest = after;
+            } else if !rest.starts_with('[') {
+                return Err(invalid("expected '.' or '['"));
+            }
+
+            if let Some(after) = rest.strip_prefix('[') {
+                let end = bracket_end(after).ok_or_else(|| invalid("unclosed '['"))?;
+                segments.push(jsonpath_selector(&after[..end]).map_err(|e| invalid(&e))?);
+                rest = &after[end + 1..];
+            } else {
+                let end = rest.find(['.', '[']).unwrap_or(rest.len());
+                let name = &rest[..end];
+                segments.push(match name {
+                    "" => return Err(invalid("empty name")),
+                    "*" => Segment::AnyOne,
+                    name => key_segment(&[name.to_string()]),
+                });
+                rest = &rest[end..];
+            }
+        }
+        if segments.is_empty() {
+            segments.push(Segment::AnyDepth);
+        }
+        Ok(Self { segments })
+    }
+
+    /// Whether the pattern matches `path` exactly.
+    pub fn matches(&self, path: &str) -> bool {
+        match_segments(&self.segments, &split_path(path), false)
+    }
+
+    /// Whether the pattern matches `path` or one of its ancestors.
+    pub fn covers(&self, path: &str) -> bool {
+        let segments = split_path(path);
+        (1..=segments.len()).any(|len| match_segments(&self.segments, &segments[..len], false))
+    }
+
+    /// Whether `path` or something below it could match.
+    pub fn may_match_below(&self, path: &str) -> bool {
+        match_segments(&self.segments, &split_path(path), true)
+    }
+}
+
+/// Position of the `]` closing a bracket whose contents start `inner`,
+/// skipping quoted names.
+fn bracket_end(inner: &str) -> Option<usize> {
+    let mut quote = None;
+    let mut escaped = false;
+    for (i, c) in inner.char_indices() {
+        match (quote, c) {
+            (Some(_), _) if escaped => escaped = false,
+            (Some(_), '\\') => escaped = true,
+            (Some(open), c) if c == open => quote = None,
+            (None, '\'' | '"') => quote = Some(c),
+            (None, ']') => return Some(i),
+            _ => {}
+        }
+    }
+    None
+}
+
+/// The segment for a JSONPath bracket selector: `*`, indices or quoted names.
+fn jsonpath_selector(selector: &str) -> Result<Segment, String> {
+    let selector = selector.trim();
+    if selector == "*" {
+        return Ok(Segment::AnyOne);
+    }
+    if selector.starts_with('?') || selector.starts_with('(') || selector.contains(':') {
+        return Err("filters, scripts and slices are not supported".to_string());
+    }
+    let parts = split_union(selector);
+    if parts.iter().all(|part| part.parse::<usize>().is_ok()) {
+        let alternatives: Vec<String> = parts.iter().map(|part| regex::escape(part)).collect();
+        let regex = Regex::new(&format!(r"^\[(?:{})\]$", alternatives.join("|")))
+            .map_err(|e| e.to_string())?;
+        return Ok(Segment::Glob {
+            regex,
+            is_index: true,
+        });
+    }
+    let names = parts
+        .iter()
+        .map(|part| {
+            let quote = part.chars().next().filter(|c| matches!(c, '\'' | '"'));
+            let name = quote
+                .and_then(|quote| part.strip_prefix(quote)?.strip_suffix(quote))
+                .ok_or_else(|| format!("expected an index or a quoted name, got {part}"))?;
+            Ok(name.replace("\\'", "'").replace("\\\"", "\""))
+        })
+        .collect::<Result<Vec<_>, String>>()?;
+    Ok(key_segment(&names))
+}
+
+/// Split a union selector on the commas outside quoted names.
+fn split_union(selector: &str) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let mut start = 0;
+    let mut quote = None;
+    let mut escaped = false;
+    for (i, c) in selector.char_indices() {
+        match (quote, c) {
+            (Some(_), _) if escaped => escaped = false,
+            (Some(_), '\\') => escaped = true,
+            (Some(open), c) if c == open => quote = None,
+            (None, '\'' | '"') => quote = Some(c),
+            (None, ',') => {
+                parts.push(selector[start..i].trim());
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    parts.push(selector[start..].trim());
+    parts
+}
+
+/// A segment matching any of the given keys literally.
+fn key_segment(names: &[String]) -> Segment {
+    let alternatives: Vec<String> = names.iter().map(|name| regex::escape(name)).collect();
+    Segment::Glob {
+        regex: Regex::new(&format!("^(?:{})$", alternatives.join("|")))
+            .expect("escaped names form a valid regex"),
+        is_index: false,
+    }
+}
+
+/// Match pattern segments against path segments. With `partial`, running out
+/// of path segments first counts as a match (the path is a possible ancestor).
+fn match_segments(pattern: &[Segment], path: &[&str], partial: bool) -> bool {
+    match pattern.split_first() {
+        None => path.is_empty(),
+        Some((Segment::AnyDepth, rest)) => {
+            partial || (0..=path.len()).any(|skip| match_segments(rest, &path[skip..], partial))
+        }
+        Some((segment, rest)) => match path.split_first() {
+            None => partial,
+            Some((first, remaining)) => {
+                segment.matches(first) && match_segments(rest, remaining, partial)
+            }
+        },
+    }
+}
@@ -0,0 +1,319 @@
+//! Source positions of values in JSON, YAML, TOML and INI text.
+//!
+//! The parsers only produce values, so these scans walk the source again to
+//! find the line of each key and array element (by diff path) and the keys
+//! that appear twice in an object.
+
+use std::collections::HashMap;
+
+use pyo3::prelude::*;
+use yaml_rust2::parser::{Event, MarkedEventReceiver, Parser};
+use yaml_rust2::scanner::Marker;
+
+use crate::comments;
+use crate::duplicates::Duplicate;
+
+/// What a scan found; `lines` has one map per document.
+#[derive(Debug, Default)]
+pub struct Scan {
+    /// Line (1-based) of each key and array element, by diff path.
+    pub lines: Vec<HashMap<String, usize>>,
+    pub duplicates: Vec<Duplicate>,
+}
+
+/// Line numbers of keys and array elements in JSON, YAML, TOML or INI source
+///
+/// Paths use the syntax of diff results (`a.b[0]`); a key's line is where
+/// the key is written, an element's line is where it starts. For repeated
+/// keys the last occurrence counts, as in the parsed value. TOML tables and
+/// INI sections are at their header line.
+///
+/// Args:
+///     content: JSON, YAML, TOML or INI text that parses successfully
+///     format: "json", "yaml", "toml" or "ini"
+///
+/// Returns:
+///     One dict of path to line (1-based) per document
+///
+/// Example:
+///     >>> source_lines('{\n  "port": 80\n}', "json")
+///     [{'port': 2}]
+#[pyfunction]
+pub fn source_lines(content: &str, format: &str) -> PyResult<Vec<HashMap<String, usize>>> {
+    let scan = match format.to_lowercase().as_str() {
+        "json" => scan_json(content),
+        "yaml" | "yml" => {
+            scan_yaml(content).map_err(PyErr::new::<pyo3::exceptions::PyValueError, _>)?
+        }
+        "toml" => return Ok(vec![comments::toml_lines(content).1.into_iter().collect()]),
+        "ini" | "cfg" => return Ok(vec![comments::ini_lines(content).1.into_iter().collect()]),
+        other => {
+            return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+                "Line numbers are only available for JSON, YAML, TOML and INI, not {other:?}"
+            )))
+        }
+    };
+    Ok(scan.lines)
+}
+
+pub fn child_path(parent: &str, key: &str) -> String {
+    if parent.is_empty() {
+        key.to_string()
+    } else {
+        format!("{parent}.{key}")
+    }
+}
+
+enum Frame {
+    Object {
+        path: String,
+        /// Line of the first occurrence of each key seen so far.
+        keys: HashMap<String, usize>,
+        key: Option<String>,
+        expecting_key: bool,
+    },
+    Array {
+        path: String,
+        index: usize,
+        /// Whether the next element has not started yet (JSON only).
+        pending: bool,
+    },
+}
+
+impl Frame {
+    fn object(path: String) -> Self {
+        Frame::Object {
+            path,
+            keys: HashMap::new(),
+            key: None,
+            expecting_key: true,
+        }
+    }
+
+    fn array(path: String) -> Self {
+        Frame::Array {
+            path,
+            index: 0,
+            pending: true,
+        }
+    }
+
+    /// Path of the value about to be read inside this container.
+    fn child(&self) -> String {
+        match self {
+            Frame::Object { path, key, .. } => child_path(path, key.as_deref().unwrap_or("")),
+            Frame::Array { path, index, .. } => format!("{path}[{index}]"),
+        }
+    }
+
+    fn expects_key(&self) -> bool {
+        matches!(
+            self,
+            Frame::Object {
+                expecting_key: true,
+                ..
+            }
+        )
+    }
+
+    /// Record key `name` at `line`, returning the line of an earlier
+    /// occurrence.
+    fn add_key(&mut self, name: String, line: usize) -> Option<usize> {
+        let Frame::Object {
+            keys,
+            key,
+            expecting_key,
+            ..
+        } = self
+        else {
+            return None;
+        };
+        *expecting_key = false;
+        let first = keys.get(&name).copied();
+        keys.entry(name.clone()).or_insert(line);
+        *key = Some(name);
+        first
+    }
+}
+
+/// Scan a JSON document that has already parsed successfully.
+pub fn scan_json(content: &str) -> Scan {
+    let mut lines = HashMap::new();
+    let mut duplicates = Vec::new();
+    let mut stack: Vec<Frame> = Vec::new();
+    let (mut line, mut column) = (1, 0);
+    let mut chars = content.char_indices();
+
+    while let Some((start, c)) = chars.next() {
+        column += 1;
+        if !c.is_whitespace() && !matches!(c, ',' | ']' | '}') {
+            if let Some(frame @ Frame::Array { pending: true, .. }) = stack.last_mut() {
+                lines.insert(frame.child(), line);
+                if let Frame::Array { pending, .. } = frame {
+                    *pending = false;
+                }
+            }
+        }
+        match c {
+            '\n' => {
+                line += 1;
+                column = 0;
+            }
+            '{' | '[' => {
+                let path = stack.last().map(Frame::child).unwrap_or_default();
+                stack.push(if c == '{' {
+                    Frame::object(path)
+                } else {
+                    Frame::array(path)
+                });
+            }
+            '}' | ']' => {
+                stack.pop();
+            }
+            ',' => match stack.last_mut() {
+                Some(Frame::Array { index, pending, .. }) => {
+                    *index += 1;
+                    *pending = true;
+                }
+                Some(Frame::Object { expecting_key, .. }) => *expecting_key = true,
+                None => {}
+            },
+            '"' => {
+                let (key_line, key_column) = (line, column);
+                let mut escaped = false;
+                let mut end = start;
+                for (index, c) in chars.by_ref() {
+                    column += 1;
+                    end = index;
+                    match c {
+                        '\\' if !escaped => escaped = true,
+                        '"' if !escaped => break,
+                        _ => escaped = false,
+                    }
+                }
+                let Some(frame) = stack.last_mut().filter(|frame| frame.expects_key()) else {
+                    continue;
+                };
+                let name = serde_json::from_str::<String>(&content[start..=end])
+                    .unwrap_or_else(|_| content[start + 1..end].to_string());
+                let Frame::Object { path, .. } = &*frame else {
+                    continue;
+                };
+                let path = child_path(path, &name);
+                lines.insert(path.clone(), key_line);
+                if let Some(first_line) = frame.add_key(name, key_line) {
+                    duplicates.push(Duplicate {
+                        document: 0,
+                        path,
+                        line: key_line,
+                        column: key_column,
+                        first_line,
+                    });
+                }
+            }
+            _ => {}
+        }
+    }
+    Scan {
+        lines: vec![lines],
+        duplicates,
+    }
+}
+
+/// Scan every document of a YAML stream.
+pub fn scan_yaml(content: &str) -> Result<Scan, String> {
+    let mut scanner = YamlScanner {
+        scan: Scan::default(),
+        lines: HashMap::new(),
+        stack: Vec::new(),
+    };
+    Parser::new_from_str(content)
+        .load(&mut scanner, true)
+        .map_err(|e| e.to_string())?;
+    Ok(scanner.scan)
+}
+
+struct YamlScanner {
+    scan: Scan,
+    /// Lines of the current document.
+    lines: HashMap<String, usize>,
+    stack: Vec<Frame>,
+}
+
+impl MarkedEventReceiver for YamlScanner {
+    fn on_event(&mut self, event: Event, mark: Marker) {
+        if let Some(frame @ Frame::Array { .. }) = self.stack.last() {
+            if matches!(
+                event,
+                Event::Scalar(..)
+                    | Event::MappingStart(..)
+                    | Event::SequenceStart(..)
+                    | Event::Alias(_)
+            ) {
+                self.lines.insert(frame.child(), mark.line());
+            }
+        }
+        match event {
+            Event::DocumentEnd => {
+                let lines = std::mem::take(&mut self.lines);
+                self.scan.lines.push(lines);
+            }
+            Event::MappingStart(..) | Event::SequenceStart(..) => {
+                let path = self.stack.last().map(Frame::child).unwrap_or_default();
+                self.stack.push(match event {
+                    Event::MappingStart(..) => Frame::object(path),
+                    _ => Frame::array(path),
+                });
+            }
+            Event::MappingEnd | Event::SequenceEnd => {
+                self.stack.pop();
+                self.complete();
+            }
+            Event::Scalar(text, ..) => {
+                let Some(frame) = self.stack.last_mut().filter(|frame| frame.expects_key()) else {
+                    self.complete();
+                    return;
+                };
+                let Frame::Object { path, .. } = &*frame else {
+                    return;
+                };
+                let path = child_path(path, &text);
+                self.lines.insert(path.clone(), mark.line());
+                // Several `<<` merge keys are a common idiom, not a mistake
+                let is_merge = text == "<<";
+                if let Some(first_line) = frame.add_key(text, mark.line()) {
+                    if !is_merge {
+                        self.scan.duplicates.push(Duplicate {
+                            document: self.scan.lines.len(),
+                            path,
+                            line: mark.line(),
+                            column: mark.col() + 1,
+                            first_line,
+                        });
+                    }
+                }
+            }
+            Event::Alias(_) => self.complete(),
+            _ => {}
+        }
+    }
+}
+
+impl YamlScanner {
+    /// A key (other than a plain scalar) or value finished in the parent.
+    fn complete(&mut self) {
+        match self.stack.last_mut() {
+            Some(Frame::Array { index, .. }) => *index += 1,
+            Some(Frame::Object {
+                expecting_key, key, ..
+            }) => {
+                if *expecting_key {
+                    // A complex key, which is never compared
+                    *key = Some(String::new());
+                }
+                *expecting_key = !*expecting_key;
+            }
+            None => {}
+        }
+    }
+}
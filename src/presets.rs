@@ -0,0 +1,182 @@
+//! Named option presets: built-in policies plus presets registered at runtime.
+
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+
+use crate::options::DiffOptions;
+use crate::{quantity, terraform};
+
+/// Names of the presets shipped with the binding.
+const BUILTIN: &[&str] = &[
+    "cargo-lock",
+    "kubernetes",
+    "lenient",
+    "package-lock",
+    "poetry-lock",
+    "strict",
+    "terraform",
+];
+
+/// Presets added with `register_preset`, already validated and expanded.
+static CUSTOM: Mutex<BTreeMap<String, Py<DiffOptions>>> = Mutex::new(BTreeMap::new());
+
+/// Options for a built-in preset.
+fn builtin<'py>(py: Python<'py>, name: &str) -> PyResult<Option<Bound<'py, PyDict>>> {
+    let options = PyDict::new_bound(py);
+    match name {
+        // Exact comparison: the defaults, spelled out for policy files
+        "strict" => {}
+        "lenient" => {
+            options.set_item("ignore_case", true)?;
+            options.set_item("ignore_whitespace", true)?;
+            options.set_item("array_order", "unordered")?;
+            options.set_item("epsilon_relative", 1e-9)?;
+        }
+        // Live vs. desired manifests: drop server-populated fields
+        "kubernetes" => {
+            options.set_item(
+                "ignore_paths",
+                [
+                    "**.metadata.managedFields",
+                    "**.metadata.resourceVersion",
+                    "**.metadata.generation",
+                    "**.metadata.uid",
+                    "**.metadata.creationTimestamp",
+                    "status",
+                    "[*].status",
+                    "items[*].status",
+                ],
+            )?;
+            options.set_item("array_id_key", "metadata.name")?;
+            let normalize_quantity =
+                wrap_pyfunction_bound!(quantity::normalize_kubernetes_quantity, py)?;
+            let normalize = PyDict::new_bound(py);
+            for pattern in [
+                "**.resources.limits.*",
+                "**.resources.requests.*",
+                "**.hard.*",
+                "**.capacity.*",
+            ] {
+                normalize.set_item(pattern, &normalize_quantity)?;
+            }
+            options.set_item("normalize", normalize)?;
+        }
+        // State and plan JSON: align resources by address so each resource's
+        // changes share a path prefix
+        "terraform" => {
+            options.set_item("ignore_paths", ["serial", "lineage", "timestamp"])?;
+            options.set_item("array_id_key", "address")?;
+            let normalize = PyDict::new_bound(py);
+            normalize.set_item(
+                "resources",
+                wrap_pyfunction_bound!(terraform::address_resources, py)?,
+            )?;
+            options.set_item("normalize", normalize)?;
+        }
+        // Lockfiles: align packages by name so upgrades show as version changes;
+        // hashes and download URLs follow the version and are dropped
+        "package-lock" => {
+            options.set_item("ignore_paths", ["**.resolved", "**.integrity"])?;
+        }
+        "poetry-lock" => {
+            options.set_item(
+                "ignore_paths",
+                ["package[*].files", "metadata.content-hash"],
+            )?;
+            options.set_item("array_id_key", "name")?;
+        }
+        "cargo-lock" => {
+            options.set_item("ignore_paths", ["package[*].checksum"])?;
+            // A crate locked at several versions appears once per version;
+            // entries sharing a name are paired one-to-one, equal ones first
+            options.set_item("array_id_key", "name")?;
+            // Dependency lists are sorted, so compare them as sets
+            options.set_item("array_order", "unordered")?;
+        }
+        _ => return Ok(None),
+    }
+    Ok(Some(options))
+}
+
+/// A fresh dict of the options bundled under `name`.
+pub fn lookup<'py>(py: Python<'py>, name: &str) -> PyResult<Bound<'py, PyDict>> {
+    if let Some(options) = builtin(py, name)? {
+        return Ok(options);
+    }
+    let custom = CUSTOM.lock().unwrap_or_else(|e| e.into_inner());
+    match custom.get(name) {
+        Some(options) => options.get().to_dict(py),
+        None => Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+            "Unknown preset '{name}' (available: {})",
+            names(&custom).join(", ")
+        ))),
+    }
+}
+
+/// Replace a `preset` entry with the preset's options; the other entries win.
+pub fn expand<'py>(options: Bound<'py, PyDict>) -> PyResult<Bound<'py, PyDict>> {
+    let Some(name) = options.get_item("preset")? else {
+        return Ok(options);
+    };
+    options.del_item("preset")?;
+    if name.is_none() {
+        return Ok(options);
+    }
+    let expanded = lookup(options.py(), &name.extract::<String>()?)?;
+    expanded.update(options.as_mapping())?;
+    Ok(expanded)
+}
+
+fn names(custom: &BTreeMap<String, Py<DiffOptions>>) -> Vec<String> {
+    let mut names: Vec<String> = BUILTIN.iter().map(|name| name.to_string()).collect();
+    names.extend(custom.keys().cloned());
+    names.sort();
+    names
+}
+
+/// Register a named preset usable as `diff(..., preset=name)`
+///
+/// Args:
+///     name: Preset name; built-in presets cannot be replaced
+///     options: A `DiffOptions` to start from
+///     **kwargs: diff() options, overriding `options`; `preset` builds on
+///         another preset
+#[pyfunction]
+#[pyo3(signature = (name, options=None, **kwargs))]
+pub fn register_preset(
+    py: Python,
+    name: &str,
+    options: Option<&Bound<'_, DiffOptions>>,
+    kwargs: Option<&Bound<'_, PyDict>>,
+) -> PyResult<()> {
+    if BUILTIN.contains(&name) {
+        return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+            "Cannot replace built-in preset '{name}'"
+        )));
+    }
+    let merged = crate::options::resolve(py, options, kwargs)?;
+    let preset = Py::new(py, DiffOptions::from_dict(py, &merged)?)?;
+    CUSTOM
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .insert(name.to_string(), preset);
+    Ok(())
+}
+
+/// The options bundled under a preset name
+///
+/// Returns:
+///     DiffOptions holding the preset's options
+#[pyfunction]
+pub fn get_preset(py: Python, name: &str) -> PyResult<DiffOptions> {
+    DiffOptions::from_dict(py, &lookup(py, name)?)
+}
+
+/// Names of the built-in and registered presets, sorted
+#[pyfunction]
+pub fn list_presets() -> Vec<String> {
+    names(&CUSTOM.lock().unwrap_or_else(|e| e.into_inner()))
+}
@@ -0,0 +1,72 @@
+//! Kubernetes resource quantities such as `500m`, `1Gi` or `2e3`.
+
+use pyo3::prelude::*;
+use pyo3::types::PyString;
+
+/// Parse a quantity string into its numeric value.
+///
+/// Binary suffixes (`Ki`..`Ei`) are powers of 1024, decimal suffixes (`n`, `u`,
+/// `m`, `k`, `M`..`E`) powers of ten, and `e3` / `E-2` are decimal exponents.
+pub fn parse_kubernetes(quantity: &str) -> Option<f64> {
+    let quantity = quantity.trim();
+    let number_len = quantity
+        .char_indices()
+        .find(|&(i, c)| !(c.is_ascii_digit() || c == '.' || (i == 0 && (c == '+' || c == '-'))))
+        .map_or(quantity.len(), |(i, _)| i);
+    let (number, suffix) = quantity.split_at(number_len);
+    if !number.chars().any(|c| c.is_ascii_digit()) {
+        return None;
+    }
+    let value: f64 = number.parse().ok()?;
+
+    let exponent_digits = suffix
+        .strip_prefix(['e', 'E'])
+        .map(|rest| rest.strip_prefix(['+', '-']).unwrap_or(rest));
+    if exponent_digits
+        .is_some_and(|digits| !digits.is_empty() && digits.chars().all(|c| c.is_ascii_digit()))
+    {
+        return quantity.parse().ok();
+    }
+
+    let binary = |power: i32| Some(value * 1024f64.powi(power));
+    // Dividing keeps `100m` exactly equal to `0.1`
+    let decimal = |power: i32| match power {
+        0.. => Some(value * 10f64.powi(power)),
+        _ => Some(value / 10f64.powi(-power)),
+    };
+    match suffix {
+        "" => Some(value),
+        "Ki" => binary(1),
+        "Mi" => binary(2),
+        "Gi" => binary(3),
+        "Ti" => binary(4),
+        "Pi" => binary(5),
+        "Ei" => binary(6),
+        "n" => decimal(-9),
+        "u" => decimal(-6),
+        "m" => decimal(-3),
+        "k" => decimal(3),
+        "M" => decimal(6),
+        "G" => decimal(9),
+        "T" => decimal(12),
+        "P" => decimal(15),
+        "E" => decimal(18),
+        _ => None,
+    }
+}
+
+/// Normalize a Kubernetes quantity string to a number; other values pass through.
+#[pyfunction]
+pub fn normalize_kubernetes_quantity(py: Python, value: &Bound<'_, PyAny>) -> PyObject {
+    let parsed = value
+        .downcast::<PyString>()
+        .ok()
+        .and_then(|s| s.to_str().ok().and_then(parse_kubernetes));
+    match parsed {
+        Some(number) if number.fract() == 0.0 && number.abs() < i64::MAX as f64 => {
+            (number as i64).into_py(py)
+        }
+        Some(number) => number.into_py(py),
+        None => value.clone().unbind(),
+    }
+}
@@ -0,0 +1,47 @@
+//! Report formats shared by `format_output()` and `run()`.
+
+use diffx_core::OutputFormat;
+use pyo3::prelude::*;
+
+pub enum ReportFormat {
+    /// Formats rendered by diffx-core ("diffx", "json", "yaml").
+    Core(OutputFormat),
+    /// A standalone HTML report.
+    Html,
+    /// A unified text diff of the canonicalized documents.
+    Unified,
+    /// Old and new values in two terminal columns.
+    SideBySide,
+    /// GitHub Actions annotation commands.
+    Github,
+    /// A JUnit XML test report.
+    Junit,
+    /// A Test Anything Protocol stream.
+    Tap,
+    /// One row per result, separated by the given delimiter ("csv", "tsv").
+    Delimited(u8),
+}
+
+impl ReportFormat {
+    pub fn parse(format: &str) -> PyResult<Self> {
+        match format {
+            "html" => Ok(Self::Html),
+            "unified" => Ok(Self::Unified),
+            "side-by-side" => Ok(Self::SideBySide),
+            "github" => Ok(Self::Github),
+            "junit" => Ok(Self::Junit),
+            "tap" => Ok(Self::Tap),
+            "csv" => Ok(Self::Delimited(b',')),
+            "tsv" => Ok(Self::Delimited(b'\t')),
+            other => OutputFormat::parse_format(other)
+                .map(Self::Core)
+                .map_err(|_| {
+                    PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+                        "Invalid format: {other:?} (expected \"diffx\", \"json\", \"yaml\", \
+                     \"html\", \"unified\", \"side-by-side\", \"github\", \"junit\", \
+                     \"tap\", \"csv\" or \"tsv\")"
+                    ))
+                }),
+        }
+    }
+}
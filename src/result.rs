@@ -0,0 +1,95 @@
+//! Diff result model shared by both engines.
+
+use diffx_core::DiffResult;
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+use serde::Serialize;
+use serde_json::Value;
+
+/// Every value of the `"type"` field, in declaration order.
+pub const KINDS: &[&str] = &[
+    "Added",
+    "Removed",
+    "Modified",
+    "TypeChanged",
+    "Moved",
+    "Renamed",
+    "Unchanged",
+    "Comment",
+];
+
+/// diffx-core's `DiffResult` plus the kinds only the binding-side engine
+/// produces. Serializes in the same shape so `format_output` accepts it.
+#[derive(Debug, Serialize)]
+pub enum Difference {
+    Added(String, Value),
+    Removed(String, Value),
+    Modified(String, Value, Value),
+    TypeChanged(String, Value, Value),
+    /// An array element that changed position: `(path, from_index, to_index)`.
+    Moved(String, usize, usize),
+    /// An object key renamed with its value unchanged: `(path, new_path, value)`.
+    Renamed(String, String, Value),
+    /// A value compared equal, reported only with `report_unchanged`.
+    Unchanged(String, Value),
+    /// A comment on a key added, removed or edited, reported only with
+    /// `comments=True`: `(path, old_comment, new_comment)`.
+    Comment(String, Option<String>, Option<String>),
+}
+
+impl Difference {
+    pub fn path(&self) -> &str {
+        match self {
+            Self::Added(path, _)
+            | Self::Removed(path, _)
+            | Self::Modified(path, _, _)
+            | Self::TypeChanged(path, _, _)
+            | Self::Moved(path, _, _)
+            | Self::Renamed(path, _, _)
+            | Self::Unchanged(path, _)
+            | Self::Comment(path, _, _) => path,
+        }
+    }
+
+    /// Name used for the `"type"` field of Python result dicts.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Self::Added(..) => "Added",
+            Self::Removed(..) => "Removed",
+            Self::Modified(..) => "Modified",
+            Self::TypeChanged(..) => "TypeChanged",
+            Self::Moved(..) => "Moved",
+            Self::Renamed(..) => "Renamed",
+            Self::Unchanged(..) => "Unchanged",
+            Self::Comment(..) => "Comment",
+        }
+    }
+
+    /// Whether this is an actual difference rather than an `Unchanged` entry.
+    pub fn is_change(&self) -> bool {
+        !matches!(self, Self::Unchanged(..))
+    }
+}
+
+impl From<DiffResult> for Difference {
+    fn from(result: DiffResult) -> Self {
+        match result {
+            DiffResult::Added(path, value) => Self::Added(path, value),
+            DiffResult::Removed(path, value) => Self::Removed(path, value),
+            DiffResult::Modified(path, old, new) => Self::Modified(path, old, new),
+            DiffResult::TypeChanged(path, old, new) => Self::TypeChanged(path, old, new),
+        }
+    }
+}
+
+/// A string field (`"type"`, `"path"`) of a Python result dict, read without
+/// converting its values.
+pub fn str_field(result: &Bound<'_, PyAny>, name: &str) -> PyResult<String> {
+    result
+        .downcast::<PyDict>()?
+        .get_item(name)?
+        .ok_or_else(|| {
+            PyErr::new::<pyo3::exceptions::PyValueError, _>(format!("Missing '{name}' field"))
+        })?
+        .extract()
+}
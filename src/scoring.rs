@@ -0,0 +1,47 @@
+//! Structural scores computed from diff results.
+
+use serde_json::Value;
+
+use crate::result::Difference;
+
+/// Score `results` of a diff run with `report_unchanged` from 0.0 (nothing in
+/// common) to 1.0 (equal): twice the matching leaves over all leaves of both
+/// sides. Empty containers count as one leaf; ignored paths do not count.
+pub fn similarity(results: &[Difference]) -> f64 {
+    let (mut matched, mut old_leaves, mut new_leaves) = (0, 0, 0);
+    for result in results {
+        match result {
+            Difference::Unchanged(_, value) => {
+                let leaves = count_leaves(value);
+                matched += leaves;
+                old_leaves += leaves;
+                new_leaves += leaves;
+            }
+            Difference::Added(_, value) => new_leaves += count_leaves(value),
+            Difference::Removed(_, value) => old_leaves += count_leaves(value),
+            Difference::Modified(_, old, new) | Difference::TypeChanged(_, old, new) => {
+                old_leaves += count_leaves(old);
+                new_leaves += count_leaves(new);
+            }
+            Difference::Renamed(_, _, value) => {
+                let leaves = count_leaves(value);
+                old_leaves += leaves;
+                new_leaves += leaves;
+            }
+            // Moved elements are equal but carry no value, so they are left out
+            Difference::Moved(..) | Difference::Comment(..) => {}
+        }
+    }
+    match old_leaves + new_leaves {
+        0 => 1.0,
+        total => (2 * matched) as f64 / total as f64,
+    }
+}
+
+fn count_leaves(value: &Value) -> usize {
+    match value {
+        Value::Array(items) if !items.is_empty() => items.iter().map(count_leaves).sum(),
+        Value::Object(map) if !map.is_empty() => map.values().map(count_leaves).sum(),
+        _ => 1,
+    }
+}
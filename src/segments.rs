@@ -0,0 +1,380 @@
+//! Structured path segments for `path_style="segments"`.
+//!
+//! Result paths are plain strings, so a key such as `"a.b"` cannot be told
+//! apart from two nested keys. Segments are recovered by walking the compared
+//! documents and matching the path against the keys that actually exist.
+
+use pyo3::prelude::*;
+use pyo3::types::{PyBool, PyDict, PyList, PyLong, PyString};
+use serde_json::Value;
+
+use crate::engine;
+use crate::paths::{array_index, split_path};
+use crate::result::Difference;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PathStyle {
+    /// Only the dotted `path` string.
+    String,
+    /// A `segments` list alongside the `path` string.
+    Segments,
+}
+
+impl PathStyle {
+    pub fn parse(style: &str) -> Result<Self, String> {
+        match style {
+            "string" => Ok(Self::String),
+            "segments" => Ok(Self::Segments),
+            other => Err(format!(
+                "Invalid path_style: {other:?} (expected \"string\" or \"segments\")"
+            )),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Segment {
+    /// An object key.
+    Key(String),
+    /// An array position.
+    Index(usize),
+    /// An array element identified by `array_id_key` fields, `[id=1]`.
+    Id(Vec<(String, Value)>),
+}
+
+impl Segment {
+    /// Keys become `str`, positions `int` and identities a dict of field to value.
+    pub fn to_python(&self, py: Python<'_>) -> PyResult<PyObject> {
+        match self {
+            Self::Key(key) => Ok(key.to_object(py)),
+            Self::Index(index) => Ok(index.to_object(py)),
+            Self::Id(fields) => {
+                let identity = PyDict::new_bound(py);
+                for (field, value) in fields {
+                    identity.set_item(field, crate::json_value_to_python(py, value)?)?;
+                }
+                Ok(identity.into())
+            }
+        }
+    }
+
+    pub fn from_python(segment: &Bound<'_, PyAny>) -> PyResult<Self> {
+        if let Ok(key) = segment.downcast::<PyString>() {
+            return Ok(Self::Key(key.to_cow()?.into_owned()));
+        }
+        if segment.is_instance_of::<PyLong>() && !segment.is_instance_of::<PyBool>() {
+            return segment.extract::<usize>().map(Self::Index).map_err(|_| {
+                PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+                    "Array indices cannot be negative: {segment}"
+                ))
+            });
+        }
+        if let Ok(identity) = segment.downcast::<PyDict>() {
+            return identity
+                .iter()
+                .map(|(field, value)| {
+                    Ok((
+                        field.extract::<String>()?,
+                        crate::python_to_json_value(&value)?,
+                    ))
+                })
+                .collect::<PyResult<_>>()
+                .map(Self::Id);
+        }
+        Err(PyErr::new::<pyo3::exceptions::PyTypeError, _>(format!(
+            "Path segments must be str, int or dict, not {}",
+            segment.get_type().name()?
+        )))
+    }
+
+    /// Read a single segment as written in a path, without a document.
+    pub fn parse(segment: &str) -> Self {
+        if let Some(index) = array_index(segment) {
+            return Self::Index(index);
+        }
+        match segment
+            .strip_prefix('[')
+            .and_then(|inner| inner.strip_suffix(']'))
+            .and_then(parse_identity)
+        {
+            Some(fields) => Self::Id(fields),
+            None => Self::Key(segment.to_string()),
+        }
+    }
+}
+
+/// Add `segments` (and `new_segments` for renames) to a converted result.
+pub fn annotate(
+    py: Python<'_>,
+    py_result: &Bound<'_, PyDict>,
+    result: &Difference,
+    old: &Value,
+    new: &Value,
+) -> PyResult<()> {
+    // Where each path should lead, and the value expected there
+    let locations: Vec<(&Value, Option<&Value>)> = match result {
+        Difference::Added(_, value) => vec![(new, Some(value))],
+        Difference::Removed(_, value)
+        | Difference::Renamed(_, _, value)
+        | Difference::Unchanged(_, value) => vec![(old, Some(value))],
+        Difference::Modified(_, old_value, new_value)
+        | Difference::TypeChanged(_, old_value, new_value) => {
+            vec![(old, Some(old_value)), (new, Some(new_value))]
+        }
+        Difference::Moved(..) | Difference::Comment(..) => vec![(old, None)],
+    };
+    let segments = resolve(result.path(), &locations);
+    py_result.set_item("segments", to_python(py, &segments)?)?;
+    if let Difference::Renamed(_, new_path, value) = result {
+        let segments = resolve(new_path, &[(new, Some(value))]);
+        py_result.set_item("new_segments", to_python(py, &segments)?)?;
+    }
+    Ok(())
+}
+
+/// Segments of `path`, traced through the first document where it leads to
+/// the expected value, then through any document where it exists at all;
+/// otherwise the path is split on its separators.
+fn resolve(path: &str, locations: &[(&Value, Option<&Value>)]) -> Vec<Segment> {
+    locations
+        .iter()
+        .find_map(|(document, expected)| walk(path, document, *expected))
+        .or_else(|| {
+            locations
+                .iter()
+                .find_map(|(document, _)| walk(path, document, None))
+        })
+        .unwrap_or_else(|| split_path(path).into_iter().map(Segment::parse).collect())
+}
+
+/// A list of the segments as Python objects.
+pub fn to_python<'py>(py: Python<'py>, segments: &[Segment]) -> PyResult<Bound<'py, PyList>> {
+    let items = segments
+        .iter()
+        .map(|segment| segment.to_python(py))
+        .collect::<PyResult<Vec<_>>>()?;
+    Ok(PyList::new_bound(py, items))
+}
+
+/// Match `rest` against `value`, trying longer keys first and backtracking,
+/// so `a.b` resolves to one key only where the document has such a key and,
+/// given `expected`, the value there is the reported one.
+fn walk(rest: &str, value: &Value, expected: Option<&Value>) -> Option<Vec<Segment>> {
+    if rest.is_empty() {
+        return expected
+            .is_none_or(|expected| expected == value)
+            .then(Vec::new);
+    }
+    let (segment, child, len) = match value {
+        Value::Object(map) => {
+            let mut keys: Vec<&String> = map
+                .keys()
+                .filter(|key| {
+                    rest.strip_prefix(key.as_str())
+                        .is_some_and(|after| after.is_empty() || after.starts_with(['.', '[']))
+                })
+                .collect();
+            keys.sort_by_key(|key| std::cmp::Reverse(key.len()));
+            return keys.into_iter().find_map(|key| {
+                let mut segments = walk(advance(rest, key.len()), &map[key], expected)?;
+                segments.insert(0, Segment::Key(key.clone()));
+                Some(segments)
+            });
+        }
+        Value::Array(items) => {
+            let first = *split_path(rest).first()?;
+            match Segment::parse(first) {
+                Segment::Index(index) => (Segment::Index(index), items.get(index)?, first.len()),
+                Segment::Id(fields) => {
+                    let item = items.iter().find(|item| {
+                        fields
+                            .iter()
+                            .all(|(field, value)| engine::lookup(item, field) == Some(value))
+                    })?;
+                    (Segment::Id(fields), item, first.len())
+                }
+                Segment::Key(_) => return None,
+            }
+        }
+        _ => return None,
+    };
+    let mut segments = walk(advance(rest, len), child, expected)?;
+    segments.insert(0, segment);
+    Some(segments)
+}
+
+/// The path after its first `len` bytes, without the `.` separator.
+pub fn advance(path: &str, len: usize) -> &str {
+    let rest = &path[len..];
+    rest.strip_prefix('.').unwrap_or(rest)
+}
+
+/// Parse `field=value,field=value` where each value is JSON.
+fn parse_identity(inner: &str) -> Option<Vec<(String, Value)>> {
+    let mut fields = Vec::new();
+    let mut rest = inner;
+    loop {
+        let (field, after) = rest.split_once('=')?;
+        let mut values = serde_json::Deserializer::from_str(after).into_iter::<Value>();
+        let value = values.next()?.ok()?;
+        fields.push((field.to_string(), value));
+        rest = &after[values.byte_offset()..];
+        if rest.is_empty() {
+            return Some(fields);
+        }
+        rest = rest.strip_prefix(',')?;
+    }
+}
+
+/// Split a path into segments, honoring backslash escapes
+///
+/// The inverse of `format_path()`. Keys are separated by `.`; `[3]` is an
+/// array position and `[id=1]` an element identified by `array_id_key`. In
+/// keys, `\.`, `\[`, `\]` and `\\` stand for the literal characters.
+///
+/// Args:
+///     path: A path such as `spec.ports[0].name` or `labels.app\.kubernetes\.io/name`
+///
+/// Returns:
+///     List of segments: keys as str, positions as int, identities as dict
+///
+/// Raises:
+///     ValueError: If the path is malformed, e.g. has an empty key or an
+///         unclosed `[`
+#[pyfunction]
+pub fn parse_path<'py>(py: Python<'py>, path: &str) -> PyResult<Bound<'py, PyList>> {
+    let segments = parse_escaped(path).map_err(|e| {
+        PyErr::new::<pyo3::exceptions::PyValueError, _>(format!("Invalid path {path:?}: {e}"))
+    })?;
+    to_python(py, &segments)
+}
+
+/// Join segments into a path, escaping keys so `parse_path()` reads them back
+///
+/// Args:
+///     segments: Keys (str), array positions (int) and identities (dict of
+///         field to value), e.g. the `segments` of a result
+///
+/// Returns:
+///     The path; keys without `.`, `[`, `]` or `\` read as in diff results
+#[pyfunction]
+pub fn format_path(segments: &Bound<'_, PyList>) -> PyResult<String> {
+    let segments = segments
+        .iter()
+        .map(|segment| Segment::from_python(&segment))
+        .collect::<PyResult<Vec<_>>>()?;
+    format_escaped(&segments).map_err(PyErr::new::<pyo3::exceptions::PyValueError, _>)
+}
+
+/// Segments of a path written with backslash escapes.
+pub fn parse_escaped(path: &str) -> Result<Vec<Segment>, String> {
+    #[derive(PartialEq)]
+    enum Last {
+        Start,
+        Dot,
+        Bracket,
+        Key,
+    }
+
+    let mut segments = Vec::new();
+    let mut key = String::new();
+    let mut last = Last::Start;
+    let mut chars = path.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '.' => {
+                if !key.is_empty() {
+                    segments.push(Segment::Key(std::mem::take(&mut key)));
+                } else if last != Last::Bracket {
+                    return Err("empty key".to_string());
+                }
+                last = Last::Dot;
+            }
+            '[' => {
+                if !key.is_empty() {
+                    segments.push(Segment::Key(std::mem::take(&mut key)));
+                } else if last == Last::Dot {
+                    return Err("empty key".to_string());
+                }
+                // Copy up to the closing bracket; quoted id values may hold `]`
+                let mut bracket = String::from('[');
+                let mut in_quotes = false;
+                loop {
+                    let c = chars.next().ok_or("unclosed '['")?;
+                    bracket.push(c);
+                    match c {
+                        ']' if !in_quotes => break,
+                        '"' => in_quotes = !in_quotes,
+                        '\\' if in_quotes => bracket.extend(chars.next()),
+                        _ => {}
+                    }
+                }
+                match Segment::parse(&bracket) {
+                    Segment::Key(_) => {
+                        return Err(format!(
+                            "{bracket} is neither an index nor field=value pairs"
+                        ));
+                    }
+                    segment => segments.push(segment),
+                }
+                last = Last::Bracket;
+            }
+            ']' => return Err("unexpected ']'".to_string()),
+            _ if last == Last::Bracket => {
+                return Err("expected '.' or '[' after ']'".to_string());
+            }
+            '\\' => {
+                key.push(chars.next().ok_or("path ends with a backslash")?);
+                last = Last::Key;
+            }
+            c => {
+                key.push(c);
+                last = Last::Key;
+            }
+        }
+    }
+    if !key.is_empty() {
+        segments.push(Segment::Key(key));
+    } else if last == Last::Dot {
+        return Err("empty key".to_string());
+    }
+    Ok(segments)
+}
+
+/// A path naming `segments`, with keys escaped for `parse_escaped()`.
+pub fn format_escaped(segments: &[Segment]) -> Result<String, String> {
+    let mut path = String::new();
+    for segment in segments {
+        match segment {
+            Segment::Key(key) => {
+                if key.is_empty() {
+                    return Err("Empty keys cannot be written in a path".to_string());
+                }
+                if !path.is_empty() {
+                    path.push('.');
+                }
+                for c in key.chars() {
+                    if matches!(c, '.' | '[' | ']' | '\\') {
+                        path.push('\\');
+                    }
+                    path.push(c);
+                }
+            }
+            Segment::Index(index) => path.push_str(&format!("[{index}]")),
+            Segment::Id(fields) => {
+                if fields.is_empty() {
+                    return Err("Identity segments need at least one field".to_string());
+                }
+                let mut parts = Vec::new();
+                for (field, value) in fields {
+                    if field.is_empty() || field.contains(['=', ',', '[', ']']) {
+                        return Err(format!("Invalid identity field {field:?}"));
+                    }
+                    parts.push(format!("{field}={value}"));
+                }
+                path.push_str(&format!("[{}]", parts.join(",")));
+            }
+        }
+    }
+    Ok(path)
+}
@@ -0,0 +1,637 @@
+//! Writing values back out as JSON, YAML, TOML, INI, XML, CSV or TSV.
+
+use std::cell::RefCell;
+
+use pyo3::prelude::*;
+use serde::{Serialize, Serializer};
+use serde_json::{Map, Value};
+
+use crate::convert::{Conversion, KeyOrder};
+use crate::delimited;
+use crate::xml::XmlOptions;
+
+/// Indentation of nested values unless `indent` is given.
+pub const DEFAULT_INDENT: usize = 2;
+
+/// Serialize `value` in `format`, one of the names `detect::from_extension`
+/// returns, with default options.
+///
+/// Each format round-trips through its parser (with default options) to an
+/// equal value where the format can hold it; values it cannot hold, such as
+/// nulls in TOML or nested tables in INI, are an error naming their path.
+/// Keys come out sorted, as the value model keeps them.
+pub fn dump(value: &Value, format: &str) -> Result<String, String> {
+    let sorted = KeyOrder::sorted();
+    match format {
+        "json" => Ok(json(value, sorted, Some(DEFAULT_INDENT))),
+        "yaml" => yaml(value, sorted, DEFAULT_INDENT),
+        "toml" => toml(value, sorted),
+        "ini" => ini(value, sorted),
+        "xml" => xml(value, sorted, &XmlOptions::default(), DEFAULT_INDENT),
+        "csv" => rows(value, sorted, b',', None),
+        "tsv" => rows(value, sorted, b'\t', None),
+        _ => Err(format!("Unsupported format: {format}")),
+    }
+}
+
+/// A value whose objects serialize with their keys in `order`.
+struct Ordered<'a>(&'a Value, &'a KeyOrder);
+
+impl Serialize for Ordered<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let Ordered(value, order) = self;
+        match value {
+            Value::Object(map) => serializer.collect_map(
+                order
+                    .keys(map)
+                    .into_iter()
+                    .map(|key| (key, Ordered(&map[key], order.key(key)))),
+            ),
+            Value::Array(items) => serializer.collect_seq(
+                items
+                    .iter()
+                    .enumerate()
+                    .map(|(index, item)| Ordered(item, order.item(index))),
+            ),
+            scalar => scalar.serialize(serializer),
+        }
+    }
+}
+
+/// JSON indented by `indent` spaces, or on one line without it.
+pub fn json(value: &Value, order: &KeyOrder, indent: Option<usize>) -> String {
+    let value = Ordered(value, order);
+    let Some(indent) = indent else {
+        return serde_json::to_string(&value).expect("JSON values always serialize");
+    };
+    let indent = " ".repeat(indent);
+    let mut serializer = serde_json::Serializer::with_formatter(
+        Vec::new(),
+        serde_json::ser::PrettyFormatter::with_indent(indent.as_bytes()),
+    );
+    value
+        .serialize(&mut serializer)
+        .expect("JSON values always serialize");
+    let mut out = String::from_utf8(serializer.into_inner()).expect("JSON is UTF-8");
+    out.push('\n');
+    out
+}
+
+/// Block-style YAML, quoting strings that would read back as another type.
+pub fn yaml(value: &Value, order: &KeyOrder, indent: usize) -> Result<String, String> {
+    if indent == 0 {
+        return Err("YAML indent must be at least 1".to_string());
+    }
+    let mut out = String::new();
+    match value {
+        Value::Object(map) if !map.is_empty() => {
+            yaml_mapping(&mut out, map, order, 0, indent, false)
+        }
+        Value::Array(items) if !items.is_empty() => {
+            yaml_sequence(&mut out, items, order, 0, indent)
+        }
+        scalar => {
+            out.push_str(&yaml_scalar(scalar));
+            out.push('\n');
+        }
+    }
+    Ok(out)
+}
+
+/// Write `map` with its keys at `column`; with `inline_first` the first key
+/// continues the current line (after a sequence's `- `).
+fn yaml_mapping(
+    out: &mut String,
+    map: &Map<String, Value>,
+    order: &KeyOrder,
+    column: usize,
+    indent: usize,
+    inline_first: bool,
+) {
+    for (index, key) in order.keys(map).into_iter().enumerate() {
+        if index > 0 || !inline_first {
+            out.push_str(&" ".repeat(column));
+        }
+        out.push_str(&yaml_string(key));
+        out.push(':');
+        yaml_nested(out, &map[key], order.key(key), column + indent, indent);
+    }
+}
+
+fn yaml_sequence(
+    out: &mut String,
+    items: &[Value],
+    order: &KeyOrder,
+    column: usize,
+    indent: usize,
+) {
+    for (index, item) in items.iter().enumerate() {
+        let order = order.item(index);
+        out.push_str(&" ".repeat(column));
+        match item {
+            // Compact `- key: value`, with the other keys under the first
+            Value::Object(map) if !map.is_empty() => {
+                out.push_str("- ");
+                yaml_mapping(out, map, order, column + 2, indent, true);
+            }
+            _ => {
+                out.push('-');
+                yaml_nested(out, item, order, column + indent, indent);
+            }
+        }
+    }
+}
+
+/// A value after `key:` or `-`: scalars and empty collections on the same
+/// line, everything else below at `column`.
+fn yaml_nested(out: &mut String, value: &Value, order: &KeyOrder, column: usize, indent: usize) {
+    match value {
+        Value::Object(map) if !map.is_empty() => {
+            out.push('\n');
+            yaml_mapping(out, map, order, column, indent, false);
+        }
+        Value::Array(items) if !items.is_empty() => {
+            out.push('\n');
+            yaml_sequence(out, items, order, column, indent);
+        }
+        scalar => {
+            out.push(' ');
+            out.push_str(&yaml_scalar(scalar));
+            out.push('\n');
+        }
+    }
+}
+
+fn yaml_scalar(value: &Value) -> String {
+    match value {
+        Value::Null => "null".to_string(),
+        Value::String(s) => yaml_string(s),
+        Value::Object(_) => "{}".to_string(),
+        Value::Array(_) => "[]".to_string(),
+        other => other.to_string(),
+    }
+}
+
+/// `s` plain when it reads back as the same string, else double-quoted
+/// (JSON string syntax is valid YAML).
+fn yaml_string(s: &str) -> String {
+    let plain = !s.is_empty()
+        && s.trim() == s
+        && !s.starts_with([
+            '-', '?', ':', ',', '[', ']', '{', '}', '#', '&', '*', '!', '|', '>', '\'', '"', '%',
+            '@', '`', '.', '+', '~',
+        ])
+        && !s.ends_with(':')
+        && !s.contains(": ")
+        && !s.contains(" #")
+        && !s.contains(|c: char| c.is_control())
+        && !s.starts_with(|c: char| c.is_ascii_digit())
+        && !matches!(
+            s.to_ascii_lowercase().as_str(),
+            "null" | "true" | "false" | "yes" | "no" | "on" | "off" | "y" | "n"
+        );
+    if plain {
+        s.to_string()
+    } else {
+        Value::from(s).to_string()
+    }
+}
+
+/// TOML, which needs a table at the top and has no null.
+pub fn toml(value: &Value, order: &KeyOrder) -> Result<String, String> {
+    if !value.is_object() {
+        return Err("TOML documents must be tables".to_string());
+    }
+    if let Some(path) = find_null(value, String::new()) {
+        return Err(format!("TOML cannot represent null (at '{path}')"));
+    }
+    toml::to_string(&Ordered(value, order)).map_err(|e| format!("TOML serialization error: {e}"))
+}
+
+/// Path of the first null in `value`, if any.
+fn find_null(value: &Value, path: String) -> Option<String> {
+    match value {
+        Value::Null => Some(path),
+        Value::Object(map) => map.iter().find_map(|(key, value)| {
+            let path = if path.is_empty() {
+                key.clone()
+            } else {
+                format!("{path}.{key}")
+            };
+            find_null(value, path)
+        }),
+        Value::Array(items) => items
+            .iter()
+            .enumerate()
+            .find_map(|(index, item)| find_null(item, format!("{path}[{index}]"))),
+        _ => None,
+    }
+}
+
+/// INI with top-level scalars before the first section. Multi-line values
+/// continue on indented lines and null values are written as bare keys.
+pub fn ini(value: &Value, order: &KeyOrder) -> Result<String, String> {
+    let Value::Object(map) = value else {
+        return Err("INI documents must be tables".to_string());
+    };
+    let keys = order.keys(map);
+    let mut out = String::new();
+    for key in keys.iter().filter(|key| !map[key.as_str()].is_object()) {
+        ini_entry(&mut out, key, &map[key.as_str()], key)?;
+    }
+    for section in keys {
+        let Value::Object(entries) = &map[section] else {
+            continue;
+        };
+        if !out.is_empty() {
+            out.push('\n');
+        }
+        out.push_str(&format!("[{section}]\n"));
+        for key in order.key(section).keys(entries) {
+            ini_entry(&mut out, key, &entries[key], &format!("{section}.{key}"))?;
+        }
+    }
+    Ok(out)
+}
+
+fn ini_entry(out: &mut String, key: &str, value: &Value, path: &str) -> Result<(), String> {
+    if value.is_null() {
+        out.push_str(key);
+        out.push('\n');
+        return Ok(());
+    }
+    let text =
+        scalar_text(value).ok_or_else(|| format!("INI values must be scalars (at '{path}')"))?;
+    out.push_str(&format!("{key} = {}\n", text.replace('\n', "\n    ")));
+    Ok(())
+}
+
+/// XML from `{root_name: element}`, the shape `xml::parse` produces with
+/// `options`. `merge_attributes` is ignored: every key becomes an element
+/// unless it carries the attribute prefix.
+pub fn xml(
+    value: &Value,
+    order: &KeyOrder,
+    options: &XmlOptions,
+    indent: usize,
+) -> Result<String, String> {
+    let (name, root) = match value {
+        Value::Object(map) if map.len() == 1 => map.iter().next().expect("one entry"),
+        _ => return Err("XML documents must be a table with exactly one root element".to_string()),
+    };
+    let mut out = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    let order = order.key(name);
+    xml_element(&mut out, name, root, order, options, &" ".repeat(indent), 0)?;
+    Ok(out)
+}
+
+fn xml_element(
+    out: &mut String,
+    name: &str,
+    value: &Value,
+    order: &KeyOrder,
+    options: &XmlOptions,
+    indent: &str,
+    depth: usize,
+) -> Result<(), String> {
+    if !is_xml_name(name) {
+        return Err(format!("'{name}' is not a valid XML element name"));
+    }
+    let margin = indent.repeat(depth);
+    match value {
+        // Repeated elements
+        Value::Array(items) => {
+            for (index, item) in items.iter().enumerate() {
+                if item.is_array() {
+                    return Err(format!("XML cannot represent nested lists (at '{name}')"));
+                }
+                xml_element(out, name, item, order.item(index), options, indent, depth)?;
+            }
+        }
+        Value::Object(fields) => {
+            let mut attributes = String::new();
+            let mut text = String::new();
+            let mut children = Vec::new();
+            for key in order.keys(fields) {
+                let value = &fields[key];
+                let attribute = match key.strip_prefix(&options.attribute_prefix) {
+                    Some(attribute) if !options.attribute_prefix.is_empty() => attribute,
+                    _ if *key == options.text_key => {
+                        text = scalar_text(value).ok_or_else(|| {
+                            format!("XML text must be a scalar (at '{name}.{key}')")
+                        })?;
+                        continue;
+                    }
+                    _ => {
+                        children.push((key, value));
+                        continue;
+                    }
+                };
+                if !is_xml_name(attribute) {
+                    return Err(format!("'{attribute}' is not a valid XML attribute name"));
+                }
+                let value = scalar_text(value)
+                    .ok_or_else(|| format!("XML attributes must be scalars (at '{name}.{key}')"))?;
+                attributes.push_str(&format!(" {attribute}=\"{}\"", xml_escape(&value)));
+            }
+            out.push_str(&format!("{margin}<{name}{attributes}"));
+            match (children.is_empty(), text.is_empty()) {
+                (true, true) => out.push_str("/>\n"),
+                (true, false) => out.push_str(&format!(">{}</{name}>\n", xml_escape(&text))),
+                (false, _) => {
+                    out.push_str(&format!(">{}\n", xml_escape(&text)));
+                    for (key, value) in children {
+                        let order = order.key(key);
+                        xml_element(out, key, value, order, options, indent, depth + 1)?;
+                    }
+                    out.push_str(&format!("{margin}</{name}>\n"));
+                }
+            }
+        }
+        Value::Null => out.push_str(&format!("{margin}<{name}/>\n")),
+        scalar => {
+            let text = scalar_text(scalar).expect("scalar");
+            out.push_str(&format!("{margin}<{name}>{}</{name}>\n", xml_escape(&text)));
+        }
+    }
+    Ok(())
+}
+
+fn is_xml_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars
+        .next()
+        .is_some_and(|first| first.is_alphabetic() || first == '_')
+        && chars.all(|c| c.is_alphanumeric() || matches!(c, '_' | '-' | '.' | ':'))
+}
+
+fn xml_escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Delimited text from a list of rows: dicts under a header of `columns`
+/// (every key in order of first appearance by default), or lists written
+/// as headerless records.
+pub fn rows(
+    value: &Value,
+    order: &KeyOrder,
+    delimiter: u8,
+    columns: Option<&[String]>,
+) -> Result<String, String> {
+    let Value::Array(rows) = value else {
+        return Err("CSV documents must be a list of rows".to_string());
+    };
+    let mut writer = csv::WriterBuilder::new()
+        .delimiter(delimiter)
+        .flexible(true)
+        .from_writer(Vec::new());
+    let cell = |value: &Value, index: usize| {
+        scalar_text(value).ok_or_else(|| format!("CSV fields must be scalars (at '[{index}]')"))
+    };
+
+    if rows.iter().all(Value::is_object) {
+        let columns: Vec<&str> = match columns {
+            Some(columns) => columns.iter().map(String::as_str).collect(),
+            None => {
+                let mut columns: Vec<&str> = Vec::new();
+                for (index, row) in rows.iter().enumerate() {
+                    for key in order.item(index).keys(row.as_object().expect("object")) {
+                        if !columns.contains(&key.as_str()) {
+                            columns.push(key);
+                        }
+                    }
+                }
+                columns
+            }
+        };
+        if !columns.is_empty() {
+            writer.write_record(&columns).map_err(|e| e.to_string())?;
+        }
+        for (index, row) in rows.iter().enumerate() {
+            let row = row.as_object().expect("object");
+            if let Some(key) = row.keys().find(|key| !columns.contains(&key.as_str())) {
+                return Err(format!(
+                    "Row {index} has key '{key}', which is not a column"
+                ));
+            }
+            let record = columns
+                .iter()
+                .map(|column| {
+                    row.get(*column)
+                        .map_or(Ok(String::new()), |v| cell(v, index))
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            writer.write_record(&record).map_err(|e| e.to_string())?;
+        }
+    } else if rows.iter().all(Value::is_array) {
+        if let Some(columns) = columns {
+            writer.write_record(columns).map_err(|e| e.to_string())?;
+        }
+        for (index, row) in rows.iter().enumerate() {
+            let record = row
+                .as_array()
+                .expect("array")
+                .iter()
+                .map(|value| cell(value, index))
+                .collect::<Result<Vec<_>, _>>()?;
+            writer.write_record(&record).map_err(|e| e.to_string())?;
+        }
+    } else {
+        return Err("CSV rows must all be dicts or all be lists".to_string());
+    }
+
+    let bytes = writer.into_inner().map_err(|e| e.to_string())?;
+    Ok(String::from_utf8(bytes).expect("fields are UTF-8"))
+}
+
+/// Text of a scalar as the text-based formats write it; None for containers.
+fn scalar_text(value: &Value) -> Option<String> {
+    match value {
+        Value::Null => Some(String::new()),
+        Value::String(s) => Some(s.clone()),
+        Value::Bool(_) | Value::Number(_) => Some(value.to_string()),
+        Value::Array(_) | Value::Object(_) => None,
+    }
+}
+
+fn value_error(message: String) -> PyErr {
+    PyErr::new::<pyo3::exceptions::PyValueError, _>(message)
+}
+
+/// `doc` converted, with the order of its keys unless they are to be sorted.
+fn document(doc: &Bound<'_, PyAny>, sort_keys: bool) -> PyResult<(Value, KeyOrder)> {
+    if sort_keys {
+        return Ok((crate::python_to_json_value(doc)?, KeyOrder::default()));
+    }
+    let conversion = Conversion {
+        key_order: Some(RefCell::default()),
+        ..Conversion::default()
+    };
+    let value = conversion.to_json(doc)?;
+    let order = conversion.key_order.unwrap_or_default().into_inner();
+    Ok((value, order))
+}
+
+/// Write a document as JSON
+///
+/// Args:
+///     doc: The document (dict, list, or primitive)
+///     indent: Spaces per nesting level, or None for a single line (default 2)
+///     sort_keys: Write keys sorted (default True); False keeps the order of
+///         the dicts
+///
+/// Returns:
+///     JSON text, ending in a newline unless on one line
+///
+/// Example:
+///     >>> dump_json({"b": 1, "a": [1, 2]}, indent=None)
+///     '{"a":[1,2],"b":1}'
+///     >>> dump_json({"b": 1, "a": [1, 2]}, indent=None, sort_keys=False)
+///     '{"b":1,"a":[1,2]}'
+#[pyfunction]
+#[pyo3(signature = (doc, *, indent=Some(DEFAULT_INDENT), sort_keys=true))]
+pub fn dump_json(
+    doc: &Bound<'_, PyAny>,
+    indent: Option<usize>,
+    sort_keys: bool,
+) -> PyResult<String> {
+    let (value, order) = document(doc, sort_keys)?;
+    Ok(json(&value, &order, indent))
+}
+
+/// Write a document as block-style YAML
+///
+/// Strings that would read back as another type ("80", "true", "") are
+/// double-quoted.
+///
+/// Args:
+///     doc: The document (dict, list, or primitive)
+///     indent: Spaces per nesting level (default 2)
+///     sort_keys: Write keys sorted (default True); False keeps the order of
+///         the dicts
+///
+/// Returns:
+///     YAML text
+///
+/// Raises:
+///     ValueError: If `indent` is 0
+#[pyfunction]
+#[pyo3(signature = (doc, *, indent=DEFAULT_INDENT, sort_keys=true))]
+pub fn dump_yaml(doc: &Bound<'_, PyAny>, indent: usize, sort_keys: bool) -> PyResult<String> {
+    let (value, order) = document(doc, sort_keys)?;
+    yaml(&value, &order, indent).map_err(value_error)
+}
+
+/// Write a document as TOML
+///
+/// Args:
+///     doc: A dict; nested dicts become tables
+///     sort_keys: Write keys sorted (default True); False keeps the order of
+///         the dicts, though tables still follow the plain keys of their
+///         parent
+///
+/// Returns:
+///     TOML text
+///
+/// Raises:
+///     ValueError: If `doc` is not a dict or holds None
+#[pyfunction]
+#[pyo3(signature = (doc, *, sort_keys=true))]
+pub fn dump_toml(doc: &Bound<'_, PyAny>, sort_keys: bool) -> PyResult<String> {
+    let (value, order) = document(doc, sort_keys)?;
+    toml(&value, &order).map_err(value_error)
+}
+
+/// Write a document as XML
+///
+/// The inverse of `parse_xml`: keys starting with `attribute_prefix` become
+/// attributes, `text_key` the element text, lists repeated elements and
+/// other keys child elements. Pass the same `attribute_prefix` to
+/// `parse_xml` to read attributes back under their prefixed keys.
+///
+/// Args:
+///     doc: A dict with a single key, the root element
+///     indent: Spaces per nesting level (default 2)
+///     attribute_prefix: Prefix of attribute keys (default "@")
+///     text_key: Key of element text (default "#text")
+///     sort_keys: Write attributes and child elements sorted by key (default
+///         True); False keeps the order of the dicts
+///
+/// Returns:
+///     XML text with an XML declaration
+///
+/// Raises:
+///     ValueError: If `doc` has no single root, a name is not a valid XML
+///         name, or an attribute or text value is a container
+#[pyfunction]
+#[pyo3(signature = (
+    doc, *, indent=DEFAULT_INDENT, attribute_prefix="@", text_key="#text", sort_keys=true
+))]
+pub fn dump_xml(
+    doc: &Bound<'_, PyAny>,
+    indent: usize,
+    attribute_prefix: &str,
+    text_key: &str,
+    sort_keys: bool,
+) -> PyResult<String> {
+    let options = XmlOptions {
+        attribute_prefix: attribute_prefix.to_string(),
+        text_key: text_key.to_string(),
+        merge_attributes: false,
+    };
+    let (value, order) = document(doc, sort_keys)?;
+    xml(&value, &order, &options, indent).map_err(value_error)
+}
+
+/// Write a document as INI
+///
+/// Args:
+///     doc: A dict of sections (dicts of scalars); scalar values at the top
+///         level are written before the first section
+///     sort_keys: Write sections and keys sorted (default True); False keeps
+///         the order of the dicts
+///
+/// Returns:
+///     INI text
+///
+/// Raises:
+///     ValueError: If `doc` is not a dict or nests deeper than sections
+#[pyfunction]
+#[pyo3(signature = (doc, *, sort_keys=true))]
+pub fn dump_ini(doc: &Bound<'_, PyAny>, sort_keys: bool) -> PyResult<String> {
+    let (value, order) = document(doc, sort_keys)?;
+    ini(&value, &order).map_err(value_error)
+}
+
+/// Write rows as CSV
+///
+/// Args:
+///     doc: A list of dicts, written under a header row, or of lists,
+///         written as they are
+///     delimiter: Field separator (default ",")
+///     columns: Header and column order; by default every key of the
+///         dicts, in order of first appearance
+///     sort_keys: Read the keys of each dict sorted when collecting the
+///         default columns (default True); False keeps the order of the dicts
+///
+/// Returns:
+///     CSV text
+///
+/// Raises:
+///     ValueError: If `doc` is not a list of rows, rows mix dicts and lists,
+///         a field is a container, or a dict has a key missing from `columns`
+#[pyfunction]
+#[pyo3(signature = (doc, *, delimiter=",", columns=None, sort_keys=true))]
+pub fn dump_csv(
+    doc: &Bound<'_, PyAny>,
+    delimiter: &str,
+    columns: Option<Vec<String>>,
+    sort_keys: bool,
+) -> PyResult<String> {
+    let delimiter = delimited::dialect_byte("delimiter", delimiter).map_err(value_error)?;
+    let (value, order) = document(doc, sort_keys)?;
+    rows(&value, &order, delimiter, columns.as_deref()).map_err(value_error)
+}
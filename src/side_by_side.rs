@@ -0,0 +1,92 @@
+//! Two-column terminal rendering in the style of `diff --side-by-side`.
+
+use serde_json::Value;
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
+
+use crate::result::Difference;
+
+/// Total line width unless `width` is given.
+pub const DEFAULT_WIDTH: usize = 80;
+
+/// Narrowest accepted `width`.
+pub const MIN_WIDTH: usize = 11;
+
+/// Render each result as one row: `path: old` on the left, `path: new` on the
+/// right, separated by `|` (changed), `<` (removed), `>` (added) or a blank
+/// (unchanged). Cells longer than their column wrap onto further lines.
+/// Widths are terminal columns, so wide characters such as CJK take two.
+pub fn render(results: &[Difference], width: usize) -> String {
+    let column = (width - 3) / 2;
+    let mut output = String::new();
+    for result in results {
+        let cell = |path: &str, value: &Value| format!("{}: {value}", display_path(path));
+        let (left, marker, right) = match result {
+            Difference::Added(path, value) => (String::new(), '>', cell(path, value)),
+            Difference::Removed(path, value) => (cell(path, value), '<', String::new()),
+            Difference::Modified(path, old, new) | Difference::TypeChanged(path, old, new) => {
+                (cell(path, old), '|', cell(path, new))
+            }
+            Difference::Moved(path, from_index, to_index) => (
+                format!("{}: at [{from_index}]", display_path(path)),
+                '|',
+                format!("{}: at [{to_index}]", display_path(path)),
+            ),
+            Difference::Renamed(path, new_path, value) => {
+                (cell(path, value), '|', cell(new_path, value))
+            }
+            Difference::Unchanged(path, value) => (cell(path, value), ' ', cell(path, value)),
+            Difference::Comment(path, old, new) => {
+                let comment = |comment: &Option<String>| match comment {
+                    Some(comment) => format!("{}: # {comment}", display_path(path)),
+                    None => String::new(),
+                };
+                let marker = match (old, new) {
+                    (None, _) => '>',
+                    (_, None) => '<',
+                    _ => '|',
+                };
+                (comment(old), marker, comment(new))
+            }
+        };
+        let left = wrap(&left, column);
+        let right = wrap(&right, column);
+        for row in 0..left.len().max(right.len()) {
+            let left = left.get(row).map_or("", String::as_str);
+            let right = right.get(row).map_or("", String::as_str);
+            let marker = if row == 0 { marker } else { ' ' };
+            let padding = column.saturating_sub(left.width());
+            let line = format!("{left}{:padding$} {marker} {right}", "");
+            output.push_str(line.trim_end());
+            output.push('\n');
+        }
+    }
+    output
+}
+
+/// The root path is empty; show it as `.` so the cell still names a location.
+fn display_path(path: &str) -> &str {
+    match path {
+        "" => ".",
+        path => path,
+    }
+}
+
+/// Split `text` into chunks at most `column` terminal columns wide.
+fn wrap(text: &str, column: usize) -> Vec<String> {
+    let mut lines = Vec::new();
+    let mut line = String::new();
+    let mut line_width = 0;
+    for c in text.chars() {
+        let width = c.width().unwrap_or(0);
+        if line_width + width > column && !line.is_empty() {
+            lines.push(std::mem::take(&mut line));
+            line_width = 0;
+        }
+        line.push(c);
+        line_width += width;
+    }
+    if !line.is_empty() {
+        lines.push(line);
+    }
+    lines
+}
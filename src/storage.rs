@@ -0,0 +1,102 @@
+//! A versioned JSON format for storing diff results and reading them back.
+
+use pyo3::prelude::*;
+use pyo3::types::{PyDict, PyList};
+use serde_json::{json, Value};
+
+/// Marks a JSON document as stored diff results.
+const FORMAT: &str = "diffx-results";
+
+/// Version of the stored format; readers reject newer versions.
+pub const VERSION: u64 = 1;
+
+/// Serialize diff results to JSON for storage
+///
+/// The document is an object `{"format": "diffx-results", "version": 1,
+/// "truncated": bool, "results": [...]}` whose results have the same fields as
+/// the dicts `diff()` returns. Extra fields such as `segments` are kept.
+///
+/// Args:
+///     results: List of diff results; `truncated` is read from a DiffResults
+///
+/// Returns:
+///     The JSON document as a string
+///
+/// Raises:
+///     ValueError: If a result is malformed
+#[pyfunction]
+pub fn results_to_json(py: Python, results: &Bound<'_, PyList>) -> PyResult<String> {
+    let truncated = match results.getattr("truncated") {
+        Ok(truncated) => truncated.is_truthy()?,
+        Err(_) => false,
+    };
+    let entries = results
+        .iter()
+        .enumerate()
+        .map(|(index, result)| {
+            check_result(py, index, &result)?;
+            crate::python_to_json_value(&result)
+        })
+        .collect::<PyResult<Vec<_>>>()?;
+    let document = json!({
+        "format": FORMAT,
+        "version": VERSION,
+        "truncated": truncated,
+        "results": entries,
+    });
+    Ok(document.to_string())
+}
+
+/// Read results stored by `results_to_json()`
+///
+/// Returns `(results, truncated)`; the Python package wraps this to return a
+/// DiffResults.
+#[pyfunction]
+pub fn results_from_json(py: Python, content: &str) -> PyResult<(PyObject, bool)> {
+    let invalid = |reason: String| PyErr::new::<pyo3::exceptions::PyValueError, _>(reason);
+    let document: Value =
+        serde_json::from_str(content).map_err(|e| invalid(format!("Invalid results JSON: {e}")))?;
+    if document.get("format").and_then(Value::as_str) != Some(FORMAT) {
+        return Err(invalid(format!(
+            "Not a diffx results document (expected \"format\": \"{FORMAT}\")"
+        )));
+    }
+    match document.get("version").and_then(Value::as_u64) {
+        Some(version) if version <= VERSION => {}
+        Some(version) => {
+            return Err(invalid(format!(
+                "Unsupported results version {version} (this diffx-python reads up to {VERSION})"
+            )));
+        }
+        None => return Err(invalid("Missing results version".to_string())),
+    }
+    let Some(entries) = document.get("results").and_then(Value::as_array) else {
+        return Err(invalid("Missing 'results' list".to_string()));
+    };
+    let truncated = document
+        .get("truncated")
+        .and_then(Value::as_bool)
+        .unwrap_or(false);
+
+    let results = PyList::empty_bound(py);
+    for (index, entry) in entries.iter().enumerate() {
+        let result = crate::json_value_to_python(py, entry)?;
+        check_result(py, index, result.bind(py))?;
+        results.append(result)?;
+    }
+    Ok((results.into(), truncated))
+}
+
+/// Check that `result` is a well-formed result dict, naming its position.
+fn check_result(py: Python, index: usize, result: &Bound<'_, PyAny>) -> PyResult<()> {
+    let message = match result.downcast::<PyDict>() {
+        Ok(_) => match crate::python_results_to_rust(&PyList::new_bound(py, [result])) {
+            Ok(_) => return Ok(()),
+            Err(e) => e.value_bound(py).to_string(),
+        },
+        Err(_) => "expected a dict".to_string(),
+    };
+    Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+        "Invalid result {index}: {message}"
+    )))
+}
@@ -0,0 +1,56 @@
+//! Test Anything Protocol output: `ok`/`not ok` per compared path.
+
+use serde_json::Value;
+
+use crate::result::Difference;
+
+/// Render a TAP version 13 stream with one test point per result.
+///
+/// Differences are `not ok` with a YAML diagnostic block holding the values;
+/// `Unchanged` entries (from `report_unchanged`) are `ok`.
+pub fn render(results: &[Difference]) -> String {
+    let mut output = format!("TAP version 13\n1..{}\n", results.len());
+    for (number, result) in results.iter().enumerate() {
+        let number = number + 1;
+        let description = escape(match result.path() {
+            "" => "(root)",
+            path => path,
+        });
+        let diagnostics: Vec<(&str, String)> = match result {
+            Difference::Unchanged(..) => {
+                output.push_str(&format!("ok {number} - {description}\n"));
+                continue;
+            }
+            Difference::Added(_, value) | Difference::Removed(_, value) => {
+                vec![("value", value.to_string())]
+            }
+            Difference::Modified(_, old, new) | Difference::TypeChanged(_, old, new) => {
+                vec![("old", old.to_string()), ("new", new.to_string())]
+            }
+            Difference::Moved(_, from_index, to_index) => vec![
+                ("from_index", from_index.to_string()),
+                ("to_index", to_index.to_string()),
+            ],
+            Difference::Renamed(_, new_path, value) => vec![
+                ("new_path", Value::from(new_path.as_str()).to_string()),
+                ("value", value.to_string()),
+            ],
+            Difference::Comment(_, old, new) => vec![
+                ("old_comment", Value::from(old.clone()).to_string()),
+                ("new_comment", Value::from(new.clone()).to_string()),
+            ],
+        };
+        output.push_str(&format!("not ok {number} - {description}\n  ---\n"));
+        output.push_str(&format!("  type: {}\n", result.kind()));
+        for (key, value) in diagnostics {
+            output.push_str(&format!("  {key}: {value}\n"));
+        }
+        output.push_str("  ...\n");
+    }
+    output
+}
+
+/// Backslash-escape `\` and `#` so a path is never read as a directive.
+fn escape(description: &str) -> String {
+    description.replace('\\', "\\\\").replace('#', "\\#")
+}
@@ -0,0 +1,52 @@
+//! Terraform state helpers for the `terraform` preset.
+
+use pyo3::prelude::*;
+use pyo3::types::{PyDict, PyList};
+
+/// Give each resource of a raw state file its address, such as
+/// `module.network.data.aws_vpc.main`, so resources align by address.
+///
+/// `terraform show -json` and plan output already carry `address`; those
+/// resources, and anything that is not a list of resources, pass through.
+#[pyfunction]
+pub fn address_resources(py: Python, value: &Bound<'_, PyAny>) -> PyResult<PyObject> {
+    let Ok(resources) = value.downcast::<PyList>() else {
+        return Ok(value.clone().unbind());
+    };
+    let addressed = PyList::empty_bound(py);
+    for resource in resources.iter() {
+        match resource.downcast::<PyDict>() {
+            Ok(fields) if !fields.contains("address")? => match address(fields)? {
+                Some(address) => {
+                    let fields = fields.copy()?;
+                    fields.set_item("address", address)?;
+                    addressed.append(fields)?;
+                }
+                None => addressed.append(resource)?,
+            },
+            _ => addressed.append(resource)?,
+        }
+    }
+    Ok(addressed.into_any().unbind())
+}
+
+fn address(resource: &Bound<'_, PyDict>) -> PyResult<Option<String>> {
+    let field = |name: &str| -> PyResult<Option<String>> {
+        Ok(resource
+            .get_item(name)?
+            .and_then(|value| value.extract::<String>().ok()))
+    };
+    let (Some(resource_type), Some(name)) = (field("type")?, field("name")?) else {
+        return Ok(None);
+    };
+    let mut address = String::new();
+    if let Some(module) = field("module")? {
+        address.push_str(&module);
+        address.push('.');
+    }
+    if field("mode")?.as_deref() == Some("data") {
+        address.push_str("data.");
+    }
+    address.push_str(&format!("{resource_type}.{name}"));
+    Ok(Some(address))
+}
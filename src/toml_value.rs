@@ -0,0 +1,40 @@
+//! TOML to value conversion with normalized datetimes.
+
+use serde_json::{Map, Value};
+
+use crate::datetime;
+
+/// Parse a TOML document, normalizing datetimes (see `datetime::normalize_toml`).
+pub fn parse(content: &str) -> Result<Value, String> {
+    let table: toml::Table = toml::from_str(content).map_err(|e| e.to_string())?;
+    convert_table(table)
+}
+
+fn convert_table(table: toml::Table) -> Result<Value, String> {
+    let mut map = Map::new();
+    for (key, value) in table {
+        map.insert(key, convert(value)?);
+    }
+    Ok(Value::Object(map))
+}
+
+fn convert(value: toml::Value) -> Result<Value, String> {
+    Ok(match value {
+        toml::Value::String(s) => Value::String(s),
+        toml::Value::Integer(i) => Value::Number(i.into()),
+        toml::Value::Float(f) => match serde_json::Number::from_f64(f) {
+            Some(number) => Value::Number(number),
+            // JSON numbers cannot hold inf/nan
+            None => Value::String(f.to_string()),
+        },
+        toml::Value::Boolean(b) => Value::Bool(b),
+        toml::Value::Datetime(dt) => Value::String(datetime::normalize_toml(&dt)?),
+        toml::Value::Array(items) => Value::Array(
+            items
+                .into_iter()
+                .map(convert)
+                .collect::<Result<Vec<_>, _>>()?,
+        ),
+        toml::Value::Table(table) => convert_table(table)?,
+    })
+}
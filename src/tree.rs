@@ -0,0 +1,91 @@
+//! Hierarchical views of diff results: nested trees and prefix groups.
+
+use pyo3::prelude::*;
+use pyo3::types::{PyDict, PyList};
+
+use crate::paths::{join_path, split_path};
+use crate::result;
+
+/// Nest results by path, mirroring the document structure
+///
+/// Every node is a dict with "changes" (results located exactly at that node)
+/// and "children" (child nodes keyed by path segment: object keys as-is,
+/// array elements as `"[0]"`). The returned root node holds changes to the
+/// whole document.
+///
+/// Args:
+///     results: List of diff results from diff()
+///
+/// Returns:
+///     The root node
+#[pyfunction]
+pub fn to_tree(py: Python, results: &Bound<'_, PyList>) -> PyResult<PyObject> {
+    let root = node(py)?;
+    for result in results.iter() {
+        let path = result::str_field(&result, "path")?;
+        let mut current = root.clone();
+        for segment in split_path(&path) {
+            let children = current
+                .get_item("children")?
+                .expect("nodes have children")
+                .downcast_into::<PyDict>()?;
+            current = match children.get_item(segment)? {
+                Some(child) => child.downcast_into::<PyDict>()?,
+                None => {
+                    let child = node(py)?;
+                    children.set_item(segment, &child)?;
+                    child
+                }
+            };
+        }
+        current
+            .get_item("changes")?
+            .expect("nodes have changes")
+            .downcast_into::<PyList>()?
+            .append(result)?;
+    }
+    Ok(root.into())
+}
+
+/// Group results by the first `depth` segments of their path
+///
+/// Results shallower than `depth` are grouped under their full path; a change
+/// to the whole document is grouped under `""`.
+///
+/// Args:
+///     results: List of diff results from diff()
+///     depth: Number of path segments in each group key (at least 1)
+///
+/// Returns:
+///     Dict of path prefix -> list of results, in first-seen order
+#[pyfunction]
+#[pyo3(signature = (results, depth=1))]
+pub fn group_by_prefix(
+    py: Python,
+    results: &Bound<'_, PyList>,
+    depth: usize,
+) -> PyResult<PyObject> {
+    if depth == 0 {
+        return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(
+            "depth must be at least 1",
+        ));
+    }
+    let groups = PyDict::new_bound(py);
+    for result in results.iter() {
+        let path = result::str_field(&result, "path")?;
+        let segments = split_path(&path);
+        let prefix = join_path(&segments[..depth.min(segments.len())]);
+        match groups.get_item(&prefix)? {
+            Some(group) => group.downcast_into::<PyList>()?.append(result)?,
+            None => groups.set_item(prefix, PyList::new_bound(py, [result]))?,
+        }
+    }
+    Ok(groups.into())
+}
+
+fn node(py: Python<'_>) -> PyResult<Bound<'_, PyDict>> {
+    let node = PyDict::new_bound(py);
+    node.set_item("changes", PyList::empty_bound(py))?;
+    node.set_item("children", PyDict::new_bound(py))?;
+    Ok(node)
+}
@@ -0,0 +1,233 @@
+//! Unified text diffs of canonicalized documents.
+
+use std::collections::HashMap;
+
+use serde_json::Value;
+
+use crate::align::{self, Edit};
+use crate::result::Difference;
+
+/// Lines of context around each hunk unless `context` is given.
+pub const DEFAULT_CONTEXT: usize = 3;
+
+/// A line of a canonical document and the diff path it belongs to.
+struct Line {
+    text: String,
+    path: String,
+}
+
+/// Render a unified diff between the canonical (sorted, 2-space indented
+/// JSON) forms of `old` and `new`.
+///
+/// Hunk headers carry the structural path of their first changed line after
+/// the line ranges, like the function context of `git diff`. Only hunks that
+/// touch a path in `results` are kept, so ignored paths stay out of the diff.
+/// Comments of `Comment` results appear as `// comment` lines above the
+/// first line of their key.
+pub fn render(
+    old: &Value,
+    new: &Value,
+    results: &[Difference],
+    labels: (&str, &str),
+    context: usize,
+) -> String {
+    let mut old_comments = HashMap::new();
+    let mut new_comments = HashMap::new();
+    for result in results {
+        if let Difference::Comment(path, old_comment, new_comment) = result {
+            old_comments.insert(path.as_str(), old_comment.as_deref());
+            new_comments.insert(path.as_str(), new_comment.as_deref());
+        }
+    }
+    let old_lines = canonical_lines(old, &old_comments);
+    let new_lines = canonical_lines(new, &new_comments);
+    let edits = align::edit_script(old_lines.len(), new_lines.len(), |i, j| {
+        old_lines[i].text == new_lines[j].text
+    });
+    let changed_paths: Vec<&str> = results
+        .iter()
+        .filter(|result| result.is_change())
+        .flat_map(|result| match result {
+            Difference::Renamed(path, new_path, _) => vec![path.as_str(), new_path.as_str()],
+            other => vec![other.path()],
+        })
+        .collect();
+    let relevant = |edit: &Edit| {
+        let path = match *edit {
+            Edit::Keep(..) => return false,
+            Edit::Delete(i) => &old_lines[i].path,
+            Edit::Insert(j) => &new_lines[j].path,
+        };
+        changed_paths.iter().any(|changed| related(path, changed))
+    };
+
+    // Line positions before each edit, so hunk ranges can be read off directly
+    let mut positions = Vec::with_capacity(edits.len() + 1);
+    let (mut old_pos, mut new_pos) = (0, 0);
+    for edit in &edits {
+        positions.push((old_pos, new_pos));
+        match edit {
+            Edit::Keep(..) => (old_pos, new_pos) = (old_pos + 1, new_pos + 1),
+            Edit::Delete(_) => old_pos += 1,
+            Edit::Insert(_) => new_pos += 1,
+        }
+    }
+    positions.push((old_pos, new_pos));
+
+    let changes: Vec<usize> = (0..edits.len())
+        .filter(|&k| !matches!(edits[k], Edit::Keep(..)))
+        .collect();
+    let mut output = String::new();
+    let mut next = 0;
+    while next < changes.len() {
+        let first = changes[next];
+        let mut last = first;
+        next += 1;
+        while next < changes.len() && changes[next] - last <= 2 * context + 1 {
+            last = changes[next];
+            next += 1;
+        }
+        let hunk = &edits[first..=last];
+        if !hunk.iter().any(relevant) {
+            continue;
+        }
+
+        let start = first.saturating_sub(context);
+        let end = (last + 1 + context).min(edits.len());
+        let (old_start, new_start) = positions[start];
+        let (old_end, new_end) = positions[end];
+        let path = match edits[first] {
+            Edit::Delete(i) => &old_lines[i].path,
+            Edit::Insert(j) => &new_lines[j].path,
+            Edit::Keep(..) => unreachable!("hunks start at a change"),
+        };
+        if output.is_empty() {
+            output.push_str(&format!("--- {}\n+++ {}\n", labels.0, labels.1));
+        }
+        output.push_str(&format!(
+            "@@ -{} +{} @@{}\n",
+            range(old_start, old_end - old_start),
+            range(new_start, new_end - new_start),
+            if path.is_empty() {
+                String::new()
+            } else {
+                format!(" {path}")
+            }
+        ));
+        for edit in &edits[start..end] {
+            let (marker, text) = match *edit {
+                Edit::Keep(i, _) => (' ', &old_lines[i].text),
+                Edit::Delete(i) => ('-', &old_lines[i].text),
+                Edit::Insert(j) => ('+', &new_lines[j].text),
+            };
+            output.push(marker);
+            output.push_str(text);
+            output.push('\n');
+        }
+    }
+    output
+}
+
+/// `start,len` with 1-based `start`; an empty range names the line before it.
+fn range(start: usize, len: usize) -> String {
+    match len {
+        0 => format!("{start},0"),
+        _ => format!("{},{len}", start + 1),
+    }
+}
+
+/// Whether `path` is `changed`, lies below it, or encloses it.
+fn related(path: &str, changed: &str) -> bool {
+    let below = |inner: &str, outer: &str| {
+        outer.is_empty()
+            || inner == outer
+            || inner
+                .strip_prefix(outer)
+                .is_some_and(|rest| rest.starts_with(['.', '[']))
+    };
+    below(path, changed) || below(changed, path)
+}
+
+/// Comment text by path, for one side of the diff.
+type CommentMap<'a> = HashMap<&'a str, Option<&'a str>>;
+
+fn canonical_lines(value: &Value, comments: &CommentMap) -> Vec<Line> {
+    let mut lines = Vec::new();
+    push_value(&mut lines, comments, value, "", 0, String::new(), "");
+    lines
+}
+
+/// Append `value` at `path`; `prefix` is its `"key": ` and `suffix` its comma.
+fn push_value(
+    lines: &mut Vec<Line>,
+    comments: &CommentMap,
+    value: &Value,
+    path: &str,
+    indent: usize,
+    prefix: String,
+    suffix: &str,
+) {
+    let pad = "  ".repeat(indent);
+    if let Some(Some(comment)) = comments.get(path) {
+        lines.push(Line {
+            text: format!("{pad}// {comment}"),
+            path: path.to_string(),
+        });
+    }
+    let (open, close, children): (char, char, Vec<(String, String, &Value)>) = match value {
+        Value::Object(map) if !map.is_empty() => (
+            '{',
+            '}',
+            map.iter()
+                .map(|(key, child)| {
+                    let child_path = match path {
+                        "" => key.clone(),
+                        _ => format!("{path}.{key}"),
+                    };
+                    (
+                        child_path,
+                        format!("{}: ", Value::from(key.as_str())),
+                        child,
+                    )
+                })
+                .collect(),
+        ),
+        Value::Array(items) if !items.is_empty() => (
+            '[',
+            ']',
+            items
+                .iter()
+                .enumerate()
+                .map(|(i, child)| (format!("{path}[{i}]"), String::new(), child))
+                .collect(),
+        ),
+        scalar => {
+            lines.push(Line {
+                text: format!("{pad}{prefix}{scalar}{suffix}"),
+                path: path.to_string(),
+            });
+            return;
+        }
+    };
+    lines.push(Line {
+        text: format!("{pad}{prefix}{open}"),
+        path: path.to_string(),
+    });
+    let count = children.len();
+    for (i, (child_path, child_prefix, child)) in children.into_iter().enumerate() {
+        let comma = if i + 1 < count { "," } else { "" };
+        push_value(
+            lines,
+            comments,
+            child,
+            &child_path,
+            indent + 1,
+            child_prefix,
+            comma,
+        );
+    }
+    lines.push(Line {
+        text: format!("{pad}{close}{suffix}"),
+        path: path.to_string(),
+    });
+}
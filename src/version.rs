@@ -0,0 +1,109 @@
+//! Semantic version strings such as `1.2.3`, `v2.0.0-rc.1` or `1.4`.
+
+use std::fmt;
+
+use crate::result::Difference;
+use serde_json::Value;
+
+/// A version with SemVer precedence: build metadata is dropped, so two
+/// versions are equal when their precedence is.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Version {
+    major: u64,
+    minor: u64,
+    patch: u64,
+    prerelease: Vec<String>,
+}
+
+impl Version {
+    /// Parse `[v]MAJOR.MINOR[.PATCH][-PRERELEASE][+BUILD]`. A missing patch
+    /// reads as 0, as in Cargo and PEP 440; numbers may not have leading
+    /// zeros, so `"1.05"` is not a version.
+    pub fn parse(text: &str) -> Option<Self> {
+        let text = text.strip_prefix(['v', 'V']).unwrap_or(text);
+        let (text, build) = match text.split_once('+') {
+            Some((text, build)) => (text, Some(build)),
+            None => (text, None),
+        };
+        if build.is_some_and(|build| !build.split('.').all(is_identifier)) {
+            return None;
+        }
+        let (core, prerelease) = match text.split_once('-') {
+            Some((core, prerelease)) => (core, Some(prerelease)),
+            None => (text, None),
+        };
+        let prerelease: Vec<String> = match prerelease {
+            Some(prerelease) => prerelease.split('.').map(str::to_string).collect(),
+            None => Vec::new(),
+        };
+        let valid_prerelease = prerelease.iter().all(|identifier| {
+            is_identifier(identifier)
+                && (!identifier.bytes().all(|b| b.is_ascii_digit()) || is_number(identifier))
+        });
+        if !valid_prerelease {
+            return None;
+        }
+
+        let numbers = core
+            .split('.')
+            .map(|number| number.parse().ok().filter(|_| is_number(number)))
+            .collect::<Option<Vec<u64>>>()?;
+        let (major, minor, patch) = match numbers[..] {
+            [major, minor] => (major, minor, 0),
+            [major, minor, patch] => (major, minor, patch),
+            _ => return None,
+        };
+        Some(Self {
+            major,
+            minor,
+            patch,
+            prerelease,
+        })
+    }
+
+    /// The most significant part that differs: `"major"`, `"minor"`,
+    /// `"patch"` or `"prerelease"`.
+    pub fn bump(&self, other: &Self) -> Option<&'static str> {
+        if self.major != other.major {
+            Some("major")
+        } else if self.minor != other.minor {
+            Some("minor")
+        } else if self.patch != other.patch {
+            Some("patch")
+        } else if self.prerelease != other.prerelease {
+            Some("prerelease")
+        } else {
+            None
+        }
+    }
+}
+
+impl fmt::Display for Version {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}.{}", self.major, self.minor, self.patch)?;
+        if !self.prerelease.is_empty() {
+            write!(f, "-{}", self.prerelease.join("."))?;
+        }
+        Ok(())
+    }
+}
+
+/// The `"bump"` of a `Modified` result whose sides are both versions.
+pub fn bump(result: &Difference) -> Option<&'static str> {
+    let Difference::Modified(_, Value::String(old), Value::String(new)) = result else {
+        return None;
+    };
+    Version::parse(old)?.bump(&Version::parse(new)?)
+}
+
+/// A non-empty run of ASCII alphanumerics and hyphens.
+fn is_identifier(text: &str) -> bool {
+    !text.is_empty() && text.bytes().all(|b| b.is_ascii_alphanumeric() || b == b'-')
+}
+
+/// Digits without a leading zero, or a lone `0`.
+fn is_number(text: &str) -> bool {
+    !text.is_empty()
+        && text.bytes().all(|b| b.is_ascii_digit())
+        && (text == "0" || !text.starts_with('0'))
+}
@@ -0,0 +1,165 @@
+//! XML to value conversion with configurable attribute and text keys.
+
+use quick_xml::events::{BytesStart, Event};
+use quick_xml::Reader;
+use serde_json::{Map, Value};
+
+use crate::errors::describe_offset;
+
+/// How attributes and text nodes are keyed in the parsed model.
+#[derive(Debug, Clone)]
+pub struct XmlOptions {
+    /// Prefix prepended to attribute names (ignored when `merge_attributes` is set).
+    pub attribute_prefix: String,
+    /// Key holding an element's text when it also has attributes or children.
+    pub text_key: String,
+    /// Store attributes under their bare names alongside child elements.
+    pub merge_attributes: bool,
+}
+
+impl Default for XmlOptions {
+    fn default() -> Self {
+        Self {
+            attribute_prefix: "@".to_string(),
+            text_key: "#text".to_string(),
+            merge_attributes: false,
+        }
+    }
+}
+
+struct Element {
+    name: String,
+    fields: Map<String, Value>,
+    text: String,
+}
+
+/// Parse an XML document into `{root_name: element}`.
+///
+/// Elements with neither attributes nor children become their text (or null
+/// when empty); repeated child elements become lists.
+pub fn parse(content: &str, options: &XmlOptions) -> Result<Value, String> {
+    let mut reader = Reader::from_str(content);
+    reader.config_mut().trim_text(true);
+
+    let mut stack: Vec<Element> = Vec::new();
+    let mut root = Map::new();
+
+    loop {
+        let event = reader.read_event().map_err(|e| {
+            format!(
+                "{e} {}",
+                describe_offset(content, reader.error_position() as usize)
+            )
+        })?;
+        // Start of the event, for errors found after reading it
+        let end = reader.buffer_position() as usize;
+        let position = match &event {
+            Event::Start(tag) => end.saturating_sub(tag.len() + 2),
+            Event::Empty(tag) => end.saturating_sub(tag.len() + 3),
+            Event::End(tag) => end.saturating_sub(tag.len() + 3),
+            Event::Text(text) => end.saturating_sub(text.len()),
+            _ => end,
+        };
+        let located = |e: String| format!("{e} {}", describe_offset(content, position));
+        match event {
+            Event::Start(start) => stack.push(open_element(&start, options).map_err(located)?),
+            Event::Empty(start) => {
+                let element = open_element(&start, options).map_err(located)?;
+                attach(&mut stack, &mut root, element, options);
+            }
+            Event::End(_) => {
+                let element = stack
+                    .pop()
+                    .ok_or_else(|| located("unexpected closing tag".to_string()))?;
+                attach(&mut stack, &mut root, element, options);
+            }
+            Event::Text(text) => {
+                if let Some(element) = stack.last_mut() {
+                    let text = text.unescape().map_err(|e| located(e.to_string()))?;
+                    element.text.push_str(&text);
+                }
+            }
+            Event::CData(data) => {
+                if let Some(element) = stack.last_mut() {
+                    element
+                        .text
+                        .push_str(&String::from_utf8_lossy(&data.into_inner()));
+                }
+            }
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+
+    if !stack.is_empty() {
+        return Err("unexpected end of document: unclosed elements".to_string());
+    }
+    if root.is_empty() {
+        return Err("XML document has no root element".to_string());
+    }
+    Ok(Value::Object(root))
+}
+
+fn open_element(start: &BytesStart, options: &XmlOptions) -> Result<Element, String> {
+    let name = String::from_utf8_lossy(start.name().as_ref()).into_owned();
+    let mut fields = Map::new();
+
+    for attribute in start.attributes() {
+        let attribute = attribute.map_err(|e| e.to_string())?;
+        let key = String::from_utf8_lossy(attribute.key.as_ref()).into_owned();
+        let value = attribute.unescape_value().map_err(|e| e.to_string())?;
+        let key = if options.merge_attributes {
+            key
+        } else {
+            format!("{}{key}", options.attribute_prefix)
+        };
+        fields.insert(key, Value::String(value.into_owned()));
+    }
+
+    Ok(Element {
+        name,
+        fields,
+        text: String::new(),
+    })
+}
+
+fn attach(
+    stack: &mut [Element],
+    root: &mut Map<String, Value>,
+    element: Element,
+    options: &XmlOptions,
+) {
+    let Element {
+        name,
+        mut fields,
+        text,
+    } = element;
+
+    let value = if fields.is_empty() {
+        if text.is_empty() {
+            Value::Null
+        } else {
+            Value::String(text)
+        }
+    } else {
+        if !text.is_empty() {
+            fields.insert(options.text_key.clone(), Value::String(text));
+        }
+        Value::Object(fields)
+    };
+
+    let siblings = match stack.last_mut() {
+        Some(parent) => &mut parent.fields,
+        None => root,
+    };
+    match siblings.get_mut(&name) {
+        Some(Value::Array(items)) => items.push(value),
+        Some(existing) => {
+            let first = existing.take();
+            *existing = Value::Array(vec![first, value]);
+        }
+        None => {
+            siblings.insert(name, value);
+        }
+    }
+}
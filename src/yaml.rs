@@ -0,0 +1,306 @@
+//! Event-based YAML loading with explicit anchor, alias, and merge-key handling.
+
+use std::collections::HashMap;
+
+use serde_json::{Map, Number, Value};
+use yaml_rust2::parser::{Event, MarkedEventReceiver, Parser, Tag};
+use yaml_rust2::scanner::{Marker, TScalarStyle};
+use yaml_rust2::Yaml;
+
+use crate::limits::ParseGuard;
+
+const MERGE_KEY: &str = "<<";
+
+/// Aliases may expand to at most this many times the nodes written in the
+/// document, like serde_yaml's repetition limit, so a "billion laughs"
+/// document fails instead of exhausting memory.
+const MAX_ALIAS_EXPANSION: usize = 100;
+
+/// How anchors (`&name`), aliases (`*name`), and merge keys (`<<:`) are loaded.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AnchorMode {
+    /// Expand aliases and apply merge keys, so only resolved values are compared.
+    Resolve,
+    /// Keep aliases as `"*name"` strings and merge keys as literal `<<` entries.
+    Preserve,
+    /// Expand aliases but keep merge keys as literal `<<` entries, like
+    /// diffx-core's loader. Not selectable by name: used to enforce node
+    /// limits while loading when `yaml_anchors` is not given.
+    Core,
+}
+
+impl AnchorMode {
+    pub fn parse(mode: &str) -> Result<Self, String> {
+        match mode {
+            "resolve" => Ok(Self::Resolve),
+            "preserve" => Ok(Self::Preserve),
+            other => Err(format!(
+                "Invalid yaml_anchors mode '{other}' (expected \"resolve\" or \"preserve\")"
+            )),
+        }
+    }
+}
+
+/// Load every document of a YAML stream, checking values against `guard` as
+/// they are loaded; aliases count as the nodes they expand to.
+pub fn parse_documents(
+    content: &str,
+    mode: AnchorMode,
+    guard: &ParseGuard,
+) -> Result<Vec<Value>, String> {
+    let mut loader = Loader {
+        source: content.chars().collect(),
+        mode,
+        guard,
+        documents: Vec::new(),
+        stack: Vec::new(),
+        anchors: HashMap::new(),
+        written: 0,
+        expanded: 0,
+        error: None,
+    };
+    Parser::new_from_str(content)
+        .load(&mut loader, true)
+        .map_err(|e| e.to_string())?;
+    match loader.error {
+        Some(error) => Err(error),
+        None => Ok(loader.documents),
+    }
+}
+
+/// A container being loaded; `start` is the loaded node count before it.
+enum Frame {
+    Sequence {
+        anchor: usize,
+        start: usize,
+        items: Vec<Value>,
+    },
+    Mapping {
+        anchor: usize,
+        start: usize,
+        entries: Map<String, Value>,
+        key: Option<String>,
+        merges: Vec<Value>,
+    },
+}
+
+struct Loader<'a> {
+    source: Vec<char>,
+    mode: AnchorMode,
+    guard: &'a ParseGuard<'a>,
+    documents: Vec<Value>,
+    stack: Vec<Frame>,
+    /// Resolved anchors with the number of nodes in each.
+    anchors: HashMap<usize, (Value, usize)>,
+    /// Nodes written in the document.
+    written: usize,
+    /// Nodes added by expanding aliases.
+    expanded: usize,
+    error: Option<String>,
+}
+
+impl MarkedEventReceiver for Loader<'_> {
+    fn on_event(&mut self, event: Event, mark: Marker) {
+        if self.error.is_some() {
+            return;
+        }
+        if let Err(error) = self.on_node_event(event, mark) {
+            self.error = Some(error);
+        }
+    }
+}
+
+impl Loader<'_> {
+    fn on_node_event(&mut self, event: Event, mark: Marker) -> Result<(), String> {
+        let start = self.loaded();
+        match event {
+            Event::SequenceStart(anchor, _) => {
+                self.write_node()?;
+                self.guard.enter(self.stack.len() + 1)?;
+                self.stack.push(Frame::Sequence {
+                    anchor,
+                    start,
+                    items: Vec::new(),
+                });
+            }
+            Event::MappingStart(anchor, _) => {
+                self.write_node()?;
+                self.guard.enter(self.stack.len() + 1)?;
+                self.stack.push(Frame::Mapping {
+                    anchor,
+                    start,
+                    entries: Map::new(),
+                    key: None,
+                    merges: Vec::new(),
+                });
+            }
+            Event::SequenceEnd => {
+                if let Some(Frame::Sequence {
+                    anchor,
+                    start,
+                    items,
+                }) = self.stack.pop()
+                {
+                    self.complete(Value::Array(items), anchor, start);
+                }
+            }
+            Event::MappingEnd => {
+                if let Some(Frame::Mapping {
+                    anchor,
+                    start,
+                    mut entries,
+                    merges,
+                    ..
+                }) = self.stack.pop()
+                {
+                    apply_merges(&mut entries, merges);
+                    self.complete(Value::Object(entries), anchor, start);
+                }
+            }
+            Event::Scalar(text, style, anchor, tag) => {
+                // Keys are not values, as in `ParseLimits::check_value`
+                if !self.expecting_key() {
+                    self.write_node()?;
+                }
+                self.guard.string(&text)?;
+                let value = scalar_to_value(text, style, tag.as_ref());
+                self.complete(value, anchor, start);
+            }
+            Event::Alias(id) => {
+                let value = match self.mode {
+                    AnchorMode::Resolve | AnchorMode::Core => self.expand(id)?,
+                    AnchorMode::Preserve => {
+                        self.write_node()?;
+                        Value::String(format!("*{}", self.alias_name(&mark)))
+                    }
+                };
+                self.complete(value, 0, start);
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    /// Nodes loaded so far, counting each alias as the nodes it expands to.
+    fn loaded(&self) -> usize {
+        self.written + self.expanded
+    }
+
+    fn expecting_key(&self) -> bool {
+        matches!(self.stack.last(), Some(Frame::Mapping { key: None, .. }))
+    }
+
+    fn write_node(&mut self) -> Result<(), String> {
+        self.written += 1;
+        self.guard.add_nodes(1)
+    }
+
+    /// The value of anchor `id`, counted before it is copied.
+    fn expand(&mut self, id: usize) -> Result<Value, String> {
+        let Some((value, nodes)) = self.anchors.get(&id) else {
+            self.write_node()?;
+            return Ok(Value::Null);
+        };
+        self.expanded = self.expanded.saturating_add(*nodes);
+        self.guard.add_nodes(*nodes)?;
+        if self.expanded > self.written.saturating_mul(MAX_ALIAS_EXPANSION) {
+            return Err(self.guard.exceed(format!(
+                "YAML aliases expand to more than {MAX_ALIAS_EXPANSION} times the nodes \
+                 written in the document"
+            )));
+        }
+        Ok(value.clone())
+    }
+
+    /// Attach a finished node to its parent container (or emit it as a
+    /// document); `start` is the loaded node count before the node.
+    fn complete(&mut self, value: Value, anchor: usize, start: usize) {
+        if anchor > 0 && self.mode != AnchorMode::Preserve {
+            let nodes = self.loaded() - start;
+            self.anchors.insert(anchor, (value.clone(), nodes));
+        }
+
+        let resolve = self.mode == AnchorMode::Resolve;
+        match self.stack.last_mut() {
+            None => self.documents.push(value),
+            Some(Frame::Sequence { items, .. }) => items.push(value),
+            Some(Frame::Mapping {
+                entries,
+                key,
+                merges,
+                ..
+            }) => match key.take() {
+                None => *key = Some(key_to_string(value)),
+                Some(name) if resolve && name == MERGE_KEY => merges.push(value),
+                Some(name) => {
+                    entries.insert(name, value);
+                }
+            },
+        }
+    }
+
+    /// Read the alias name following `*` at the alias marker.
+    fn alias_name(&self, mark: &Marker) -> String {
+        self.source
+            .iter()
+            .skip(mark.index() + 1)
+            .take_while(|c| !c.is_whitespace() && !matches!(c, ',' | '[' | ']' | '{' | '}'))
+            .collect()
+    }
+}
+
+/// Merge `<<` sources into a mapping; explicit keys and earlier sources win.
+fn apply_merges(entries: &mut Map<String, Value>, merges: Vec<Value>) {
+    for merge in merges {
+        let sources = match merge {
+            Value::Array(items) => items,
+            other => vec![other],
+        };
+        for source in sources {
+            if let Value::Object(source) = source {
+                for (key, value) in source {
+                    entries.entry(key).or_insert(value);
+                }
+            }
+        }
+    }
+}
+
+fn scalar_to_value(text: String, style: TScalarStyle, tag: Option<&Tag>) -> Value {
+    let core_tag = tag
+        .filter(|tag| tag.handle == "tag:yaml.org,2002:" || tag.handle == "!!")
+        .map(|tag| tag.suffix.as_str());
+
+    match core_tag {
+        Some("str") => return Value::String(text),
+        Some("null") => return Value::Null,
+        _ => {}
+    }
+    if style != TScalarStyle::Plain && core_tag.is_none() {
+        return Value::String(text);
+    }
+
+    match Yaml::from_str(&text) {
+        Yaml::Null => Value::Null,
+        Yaml::Boolean(b) => Value::Bool(b),
+        Yaml::Integer(i) => Value::Number(i.into()),
+        // Integers above i64::MAX are read as reals; keep those that fit u64 exact
+        Yaml::Real(real) => match real.parse::<u64>() {
+            Ok(integer) => Value::Number(integer.into()),
+            Err(_) => real
+                .parse::<f64>()
+                .ok()
+                .and_then(Number::from_f64)
+                .map(Value::Number)
+                .unwrap_or(Value::String(text)),
+        },
+        _ => Value::String(text),
+    }
+}
+
+fn key_to_string(key: Value) -> String {
+    match key {
+        Value::String(s) => s,
+        other => other.to_string(),
+    }
+}
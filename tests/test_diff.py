@@ -1,6 +1,16 @@
+import copy
+import dataclasses
+import datetime
+import decimal
+import enum
+import hashlib
 import json
+import os
+import pickle
 import sys
+import uuid
 from pathlib import Path
+from typing import ClassVar, NamedTuple
 
 import pytest
 
@@ -197,6 +207,48 @@ class TestOptionsHandling:
         results = diffx_python.diff(old, new, epsilon=0.0001)
         assert len(results) == 1
 
+    def test_diff_with_per_path_epsilons(self):
+        old = {"price": 10.00, "metrics": {"latency_p99": 120.0}, "ratio": 0.5}
+        new = {"price": 10.02, "metrics": {"latency_p99": 124.0}, "ratio": 0.51}
+
+        results = diffx_python.diff(
+            old,
+            new,
+            epsilon=0.1,
+            epsilons={"metrics.latency_p99": 5.0, "price": 0.01},
+        )
+
+        assert [r["path"] for r in results] == ["price"]
+
+    def test_diff_with_per_path_epsilons_glob(self):
+        old = {"items": [{"price": 1.0}, {"price": 2.0}]}
+        new = {"items": [{"price": 1.005}, {"price": 2.5}]}
+
+        results = diffx_python.diff(old, new, epsilons={"items[*].price": 0.01})
+
+        assert [r["path"] for r in results] == ["items[1].price"]
+
+    def test_diff_with_negative_per_path_epsilon(self):
+        with pytest.raises(ValueError, match="non-negative"):
+            diffx_python.diff({"a": 1}, {"a": 2}, epsilons={"a": -1.0})
+
+    def test_diff_with_relative_epsilon(self):
+        old = {"small": 1.0, "large": 1_000_000, "changed": 100}
+        new = {"small": 1.005, "large": 1_005_000, "changed": 120}
+
+        results = diffx_python.diff(old, new, epsilon_relative=0.01)
+
+        assert [r["path"] for r in results] == ["changed"]
+
+    def test_diff_with_relative_and_absolute_epsilon(self):
+        old = {"zero": 0.0, "large": 1_000_000}
+        new = {"zero": 0.001, "large": 1_005_000}
+
+        # Near zero only the absolute tolerance can match
+        results = diffx_python.diff(old, new, epsilon=0.01, epsilon_relative=0.01)
+
+        assert results == []
+
     def test_diff_with_array_id_key(self):
         old = {"users": [{"id": 1, "name": "Alice"}, {"id": 2, "name": "Bob"}]}
         new = {"users": [{"id": 2, "name": "Bob"}, {"id": 1, "name": "Alice Updated"}]}
@@ -218,6 +270,31 @@ class TestOptionsHandling:
         results = diffx_python.diff(old, new, ignore_keys_regex=r"^(timestamp|debug_)")
         assert len(results) == 0  # All changes ignored
 
+    def test_diff_with_ignore_values_regex(self):
+        uuid = r"^[0-9a-f]{8}-[0-9a-f]{4}-[0-9a-f]{4}-[0-9a-f]{4}-[0-9a-f]{12}$"
+        old = {
+            "id": "123e4567-e89b-12d3-a456-426614174000",
+            "items": [{"ref": "00000000-0000-0000-0000-000000000001"}],
+            "name": "a",
+        }
+        new = {
+            "id": "9b2f6c1e-0d4a-4c3e-8f7b-1a2b3c4d5e6f",
+            "items": [{"ref": "00000000-0000-0000-0000-000000000002"}],
+            "name": "b",
+        }
+
+        results = diffx_python.diff(old, new, ignore_values_regex=uuid)
+
+        assert [r["path"] for r in results] == ["name"]
+
+    def test_diff_with_ignore_values_regex_requires_both_to_match(self):
+        old = {"build": "sha-1a2b3c"}
+        new = {"build": "unknown"}
+
+        results = diffx_python.diff(old, new, ignore_values_regex=r"^sha-[0-9a-f]+$")
+
+        assert len(results) == 1
+
     def test_diff_with_path_filter(self):
         old = {"config": {"value": 1}, "metadata": {"value": 2}}
         new = {"config": {"value": 10}, "metadata": {"value": 20}}
@@ -238,6 +315,55 @@ class TestOptionsHandling:
         assert len(exact_results) == 1  # Exact match
         assert exact_results[0]["path"] == "config.value"
 
+    def test_diff_with_path_filter_regex(self):
+        old = {"services": [{"ports": [80], "name": "a"}], "ports": [1]}
+        new = {"services": [{"ports": [8080], "name": "b"}], "ports": [2]}
+
+        results = diffx_python.diff(
+            old, new, path_filter_regex=r"^services\[\d+\]\.ports"
+        )
+
+        assert [r["path"] for r in results] == ["services[0].ports[0]"]
+
+    def test_diff_with_invalid_path_filter_regex(self):
+        with pytest.raises(ValueError, match="Invalid regex"):
+            diffx_python.diff({"a": 1}, {"a": 2}, path_filter_regex="(")
+
+    def test_diff_with_jsonpath_filter(self):
+        old = {
+            "spec": {"containers": [{"image": "v1", "env": 1}], "replicas": 1},
+            "meta": {"image": "x"},
+        }
+        new = {
+            "spec": {"containers": [{"image": "v2", "env": 2}], "replicas": 2},
+            "meta": {"image": "y"},
+        }
+
+        images = diffx_python.diff(old, new, path_filter="$.spec.containers[*].image")
+        anywhere = diffx_python.diff(old, new, path_filter="$..image")
+        quoted = diffx_python.diff(old, new, path_filter="$['spec']['replicas']")
+
+        assert [r["path"] for r in images] == ["spec.containers[0].image"]
+        assert [r["path"] for r in anywhere] == [
+            "meta.image",
+            "spec.containers[0].image",
+        ]
+        assert [r["path"] for r in quoted] == ["spec.replicas"]
+
+    def test_jsonpath_filter_skips_other_subtrees(self):
+        old = {"a": {"x": 1}, "b": {str(i): i for i in range(50)}}
+        new = {"a": {"x": 2}, "b": {str(i): -i for i in range(50)}}
+
+        full = diffx_python.diff_stats(old, new)
+        filtered = diffx_python.diff_stats(old, new, path_filter="$.a.x")
+
+        assert filtered["total"] == 1
+        assert filtered["nodes_compared"] < full["nodes_compared"] / 10
+
+    def test_unsupported_jsonpath(self):
+        with pytest.raises(ValueError, match="Invalid JSONPath"):
+            diffx_python.diff({}, {}, path_filter="$.items[?(@.id > 1)]")
+
     def test_diff_with_output_format(self):
         old = {"name": "Alice"}
         new = {"name": "Bob"}
@@ -271,6 +397,43 @@ class TestOptionsHandling:
         results = diffx_python.diff(old, new, ignore_whitespace=False)
         assert len(results) == 1
 
+    def test_diff_with_normalize_line_endings(self):
+        old = {"script": "set -e\r\necho ok\r\n", "mac": "a\rb"}
+        new = {"script": "set -e\necho ok\n", "mac": "a\nb"}
+
+        assert diffx_python.diff(old, new, normalize_line_endings=True) == []
+        # Other whitespace still counts
+        results = diffx_python.diff(
+            {"a": "x\r\n"}, {"a": "x \n"}, normalize_line_endings=True
+        )
+        assert len(results) == 1
+
+    def test_diff_with_trim_whitespace(self):
+        old = {"a": "  value\n", "b": "two  words"}
+        new = {"a": "value", "b": "two words"}
+
+        results = diffx_python.diff(old, new, trim_whitespace=True)
+
+        assert [r["path"] for r in results] == ["b"]
+
+    def test_diff_with_collapse_whitespace(self):
+        old = {"a": "two  words", "b": "line\n\tbreak", "c": "ab"}
+        new = {"a": "two words", "b": "line break", "c": "a b"}
+
+        results = diffx_python.diff(old, new, collapse_whitespace=True)
+
+        assert [r["path"] for r in results] == ["c"]
+
+    def test_diff_with_combined_whitespace_options(self):
+        results = diffx_python.diff(
+            " SELECT *\r\n  FROM t ",
+            "SELECT * FROM t",
+            trim_whitespace=True,
+            collapse_whitespace=True,
+        )
+
+        assert results == []
+
 
 # ============================================================================
 # PYTHON TYPE CONVERSION TESTS
@@ -319,6 +482,299 @@ class TestPythonTypeConversion:
         results = diffx_python.diff(old, new)
         assert len(results) == 2  # Both should be detected as changes
 
+    def test_unsigned_64_bit_integers(self):
+        """Ints between 2**63 and 2**64 - 1 stay exact ints"""
+        largest = 2**64 - 1
+
+        results = diffx_python.diff({"id": largest}, {"id": largest - 1})
+
+        assert results[0]["old_value"] == largest
+        assert results[0]["new_value"] == largest - 1
+        assert type(results[0]["old_value"]) is int
+        assert diffx_python.diff([2**63], [2**63]) == []
+        assert diffx_python.flatten({"id": largest}) == {"id": largest}
+
+    def test_integers_beyond_64_bits(self):
+        """Big ints compare exactly, as their digits"""
+        big = 2**70
+
+        assert diffx_python.diff({"id": big}, {"id": big}) == []
+        results = diffx_python.diff({"id": big}, {"id": big + 1})
+        assert results == [
+            {
+                "type": "Modified",
+                "path": "id",
+                "old_value": str(big),
+                "new_value": str(big + 1),
+            }
+        ]
+        assert not diffx_python.semantic_equal([-big], [-big - 1])
+
+    def test_big_int_policies(self):
+        big = 2**70
+
+        assert diffx_python.diff({"id": big}, {"id": big + 1}, big_int="float") == []
+        with pytest.raises(OverflowError, match="does not fit in 64 bits"):
+            diffx_python.diff({"id": big}, {"id": 1}, big_int="error")
+        with pytest.raises(ValueError, match="Invalid big_int"):
+            diffx_python.DiffOptions(big_int="bigint")
+
+    def test_datetime_values(self):
+        """datetime, date and time compare as ISO 8601 strings"""
+        utc = datetime.timezone.utc
+        tokyo = datetime.timezone(datetime.timedelta(hours=9))
+        start = datetime.datetime(2024, 5, 1, tzinfo=utc)
+
+        # Aware datetimes compare as instants, normalized to UTC
+        same_instant = datetime.datetime(2024, 5, 1, 9, tzinfo=tokyo)
+        assert diffx_python.diff({"at": start}, {"at": same_instant}) == []
+        results = diffx_python.diff(
+            {"at": start, "day": datetime.date(2024, 1, 1)},
+            {"at": start.replace(second=3), "day": datetime.date(2024, 1, 2)},
+        )
+        assert [(r["old_value"], r["new_value"]) for r in results] == [
+            ("2024-05-01T00:00:00Z", "2024-05-01T00:00:03Z"),
+            ("2024-01-01", "2024-01-02"),
+        ]
+        naive = datetime.datetime(2024, 1, 1, 12, 30)
+        assert diffx_python.diff([naive], ["2024-01-01T12:30:00"]) == []
+        assert diffx_python.diff([datetime.time(8, 0)], ["08:00:00"]) == []
+
+    def test_datetime_tolerance_as_timedelta(self):
+        start = datetime.datetime(2024, 5, 1, tzinfo=datetime.timezone.utc)
+        later = start + datetime.timedelta(seconds=3)
+        tolerance = datetime.timedelta(seconds=5)
+
+        assert diffx_python.diff([start], [later], datetime_tolerance=tolerance) == []
+        assert diffx_python.diff([start], [later], datetime_tolerance=1) != []
+        with pytest.raises(ValueError, match="non-negative"):
+            diffx_python.diff([start], [later], datetime_tolerance=-tolerance)
+
+    def test_datetime_strings_compare_as_instants(self):
+        old = {"at": "2024-05-01T00:00:00Z"}
+        new = {"at": "2024-04-30T17:00:00-07:00"}
+
+        assert diffx_python.diff(old, new, datetime_tolerance=0) == []
+        assert len(diffx_python.diff(old, new)) == 1
+
+    def test_datetime_strings_without_seconds(self):
+        results = diffx_python.diff(
+            ["2024-05-01T09:00+09:00"], ["2024-05-01 00:00Z"], datetime_tolerance=0
+        )
+
+        assert results == []
+
+    def test_datetime_timezone_reads_naive_datetimes(self):
+        old = {"at": "2024-05-01T09:00:00"}
+        new = {"at": "2024-05-01T00:00:00Z"}
+
+        assert len(diffx_python.diff(old, new, datetime_tolerance=0)) == 1
+        for timezone in [
+            "+09:00",
+            datetime.timedelta(hours=9),
+            datetime.timezone(datetime.timedelta(hours=9)),
+        ]:
+            assert diffx_python.diff(old, new, datetime_timezone=timezone) == []
+
+    def test_datetime_timezone_with_tolerance(self):
+        results = diffx_python.diff(
+            ["2024-05-01 00:00:30"],
+            ["2024-05-01T00:00:00Z"],
+            datetime_timezone="UTC",
+            datetime_tolerance=60,
+        )
+
+        assert results == []
+
+    def test_datetime_tolerance_when_matching_array_elements(self):
+        old = {"events": ["2024-05-01T00:00:00Z", "2024-05-02T00:00:00Z"]}
+        new = {"events": ["2024-05-02T00:00:01Z", "2024-05-01T00:00:01Z"]}
+
+        assert (
+            diffx_python.diff(old, new, datetime_tolerance=5, array_order="unordered")
+            == []
+        )
+        new = {"events": ["2024-04-30T00:00:00Z"] + old["events"][:1]}
+        new["events"].append("2024-05-02T00:00:01Z")
+        results = diffx_python.diff(
+            old, new, datetime_tolerance=5, array_strategy="lcs"
+        )
+        assert [(r["type"], r["path"]) for r in results] == [("Added", "events[0]")]
+
+    def test_datetime_timezone_in_canonical_forms(self):
+        old = {"at": "2024-05-01T09:00:00"}
+        new = {"at": "2024-05-01T00:00:00Z"}
+        options = {"datetime_timezone": "+09:00"}
+
+        assert diffx_python.canonicalize(old, **options) == {
+            "at": "2024-05-01T00:00:00Z"
+        }
+        assert diffx_python.semantic_hash(old, **options) == diffx_python.semantic_hash(
+            new, **options
+        )
+        assert diffx_python.canonicalize(
+            {"at": "2024-05-01T00:00:02Z"}, datetime_tolerance=5
+        ) == {"at": "2024-05-01T00:00:00Z"}
+
+    def test_invalid_datetime_timezone(self):
+        with pytest.raises(ValueError, match="datetime_timezone"):
+            diffx_python.diff([], [], datetime_timezone="Asia/Tokyo")
+
+    def test_uuid_values(self):
+        """UUIDs compare as their canonical string form"""
+        key = uuid.UUID("12345678-1234-5678-1234-567812345678")
+
+        assert diffx_python.diff({"id": key}, {"id": str(key)}) == []
+        results = diffx_python.diff({"id": key}, {"id": uuid.UUID(int=0)})
+        assert results[0]["old_value"] == "12345678-1234-5678-1234-567812345678"
+        assert results[0]["new_value"] == "00000000-0000-0000-0000-000000000000"
+
+    def test_binary_values(self):
+        """bytes, bytearray and memoryview compare by content"""
+        assert diffx_python.diff([b"ab"], [bytearray(b"ab")]) == []
+        assert diffx_python.diff([b"ab"], [memoryview(b"ab")]) == []
+
+        results = diffx_python.diff({"blob": b"ab"}, {"blob": b"ac"})
+        assert (results[0]["old_value"], results[0]["new_value"]) == ("YWI=", "YWM=")
+
+    def test_binary_policies(self):
+        old, new = {"blob": b"ab"}, {"blob": b"ac"}
+
+        raw = diffx_python.diff(old, new, binary="raw")
+        assert raw == [
+            {"type": "Modified", "path": "blob[1]", "old_value": 98, "new_value": 99}
+        ]
+        hashed = diffx_python.diff(old, new, binary="hash")
+        expected = "sha256:" + hashlib.sha256(b"ab").hexdigest()
+        assert hashed[0]["old_value"] == expected
+        with pytest.raises(ValueError, match="Invalid binary"):
+            diffx_python.diff(old, new, binary="hex")
+
+    def test_tuples_and_sets(self):
+        """Tuples compare as lists, sets as sorted lists"""
+        assert diffx_python.diff({"t": (1, "a")}, {"t": [1, "a"]}) == []
+        assert diffx_python.diff({"s": {10, 9, 1}}, {"s": frozenset([1, 9, 10])}) == []
+        assert diffx_python.diff([{3, 1, 2}], [[1, 2, 3]]) == []
+
+        results = diffx_python.diff(
+            {"tags": {"a", "b"}}, {"tags": {"a", "c"}}, array_order="unordered"
+        )
+        assert [(r["type"], r["path"]) for r in results] == [
+            ("Removed", "tags[1]"),
+            ("Added", "tags[1]"),
+        ]
+
+    def test_dataclasses_and_named_tuples(self):
+        """Records convert field by field, like dicts"""
+
+        @dataclasses.dataclass
+        class Port:
+            number: int
+            protocol: str = "tcp"
+            kind: ClassVar[str] = "port"
+
+        class Service(NamedTuple):
+            name: str
+            ports: list
+
+        old = Service("web", [Port(80)])
+        new = Service("web", [Port(80, "udp")])
+
+        assert diffx_python.diff(old, new) == [
+            {
+                "type": "Modified",
+                "path": "ports[0].protocol",
+                "old_value": "tcp",
+                "new_value": "udp",
+            }
+        ]
+        assert diffx_python.diff(Port(80), {"number": 80, "protocol": "tcp"}) == []
+
+    def test_attrs_classes(self):
+        attrs = pytest.importorskip("attrs")
+
+        @attrs.define
+        class Limit:
+            cpu: str
+            memory: str
+
+        results = diffx_python.diff(Limit("1", "1Gi"), Limit("2", "1Gi"))
+        assert [r["path"] for r in results] == ["cpu"]
+
+    def test_pydantic_models(self):
+        pydantic = pytest.importorskip("pydantic")
+
+        class Item(pydantic.BaseModel):
+            sku: str
+            qty: int
+
+        class Order(pydantic.BaseModel):
+            id: int
+            items: list[Item]
+
+        old = Order(id=1, items=[Item(sku="a", qty=1)])
+        new = Order(id=1, items=[Item(sku="a", qty=2)])
+
+        results = diffx_python.diff(old, new)
+        assert [(r["path"], r["new_value"]) for r in results] == [("items[0].qty", 2)]
+        plain = {"id": 1, "items": [{"sku": "a", "qty": 1}]}
+        assert diffx_python.diff({"order": old}, {"order": plain}) == []
+
+    def test_enum_values(self):
+        class Color(enum.Enum):
+            RED = "red"
+            BLUE = "blue"
+
+        class Level(enum.IntEnum):
+            LOW = 1
+            HIGH = 2
+
+        old = {"color": Color.RED, "level": Level.LOW}
+        new = {"color": Color.BLUE, "level": Level.HIGH}
+
+        results = diffx_python.diff(old, new)
+        assert [(r["old_value"], r["new_value"]) for r in results] == [
+            ("red", "blue"),
+            (1, 2),
+        ]
+        by_name = diffx_python.diff(old, new, enum="name")
+        assert [(r["old_value"], r["new_value"]) for r in by_name] == [
+            ("RED", "BLUE"),
+            ("LOW", "HIGH"),
+        ]
+        assert diffx_python.diff([Color.RED], ["red"]) == []
+        with pytest.raises(ValueError, match="Invalid enum"):
+            diffx_python.diff(old, new, enum="label")
+
+    def test_path_values(self):
+        """os.PathLike values compare as their path strings"""
+        old = {"config": Path("etc") / "app.yaml"}
+
+        assert diffx_python.diff(old, {"config": os.path.join("etc", "app.yaml")}) == []
+        results = diffx_python.diff(old, {"config": Path("etc/app.toml")})
+        assert results[0]["new_value"] == os.path.join("etc", "app.toml")
+
+    def test_default_converts_unsupported_values(self):
+        """default= works like json.dumps(default=)"""
+
+        class Money:
+            def __init__(self, cents):
+                self.cents = cents
+
+        old = {"price": Money(100), "tax": decimal.Decimal("0.1")}
+        new = {"price": Money(120), "tax": decimal.Decimal("0.1")}
+
+        with pytest.raises(TypeError, match="Unsupported Python type: Money"):
+            diffx_python.diff(old, new)
+        results = diffx_python.diff(
+            old, new, default=lambda value: getattr(value, "__dict__", str(value))
+        )
+        assert [(r["path"], r["new_value"]) for r in results] == [("price.cents", 120)]
+        with pytest.raises(TypeError, match="unchanged"):
+            diffx_python.diff(old, new, default=lambda value: value)
+        with pytest.raises(TypeError, match="default must be callable"):
+            diffx_python.DiffOptions(default="str")
+
     def test_python_dict_keys_types(self):
         """Test that only string keys are supported in dicts"""
         # Python dicts with non-string keys should be converted to string keys
@@ -329,6 +785,58 @@ class TestPythonTypeConversion:
         assert len(results) == 1
         assert results[0]["type"] == "Added"
 
+    def test_non_string_keys_raise_with_path(self):
+        old = {"a": {"b": [{1: "x"}]}}
+        with pytest.raises(TypeError, match=r"Dict key 1 at 'a\.b\[0\]'"):
+            diffx_python.diff(old, old)
+        with pytest.raises(TypeError, match="at the root"):
+            diffx_python.diff({(1, 2): "x"}, {})
+
+    def test_key_policy_stringify(self):
+        old = {1: "a", (1, 2): "b", None: "c", 2.5: "d"}
+        new = {"1": "a", "[1,2]": "b", "null": "c", "2.5": "e"}
+        results = diffx_python.diff(old, new, key_policy="stringify")
+        assert results == [
+            {"type": "Modified", "path": "2.5", "old_value": "d", "new_value": "e"}
+        ]
+
+    def test_key_policy_stringify_collision(self):
+        with pytest.raises(ValueError, match="collides"):
+            diffx_python.diff({1: "a", "1": "b"}, {}, key_policy="stringify")
+
+    def test_key_policy_skip(self):
+        old = {"a": 1, 2: "dropped"}
+        assert diffx_python.diff(old, {"a": 1}, key_policy="skip") == []
+
+    def test_key_policy_invalid(self):
+        with pytest.raises(ValueError, match="Invalid key_policy"):
+            diffx_python.diff({}, {}, key_policy="drop")
+
+    def test_circular_reference(self):
+        data = {"a": {"b": []}}
+        data["a"]["b"].append(data["a"])
+        with pytest.raises(
+            diffx_python.CircularReferenceError,
+            match=r"Circular reference at 'a\.b\[0\]' \(the value at 'a' contains",
+        ):
+            diffx_python.diff(data, {})
+        assert issubclass(diffx_python.CircularReferenceError, ValueError)
+
+    def test_circular_reference_at_root(self):
+        data = []
+        data.append(data)
+        with pytest.raises(diffx_python.CircularReferenceError, match="at the root"):
+            diffx_python.diff({}, data)
+
+    def test_shared_values_are_not_cycles(self):
+        shared = {"x": 1}
+        old = {"a": shared, "b": [shared, shared]}
+        assert diffx_python.diff(old, old) == []
+
+    def test_unsupported_type_error_names_path(self):
+        with pytest.raises(TypeError, match=r"object at 'items\[1\]'"):
+            diffx_python.diff({"items": [1, object()]}, {})
+
 
 # ============================================================================
 # ARRAY HANDLING TESTS
@@ -373,130 +881,3350 @@ class TestArrayHandling:
         assert len(results) > 0
 
 
-# ============================================================================
-# COMPLEX DATA STRUCTURES WITH FIXTURES
-# ============================================================================
+class TestIgnorePaths:
+    """ignore_paths excludes subtrees matched by glob patterns"""
+
+    OLD = {
+        "metadata": {"name": "web", "annotations": {"a": "1", "b": "2"}},
+        "items": [
+            {"name": "x", "timestamps": {"created": 1}},
+            {"name": "y", "timestamps": {"created": 2}},
+        ],
+    }
+    NEW = {
+        "metadata": {"name": "web", "annotations": {"a": "9", "c": "3"}},
+        "items": [
+            {"name": "x", "timestamps": {"created": 10}},
+            {"name": "z", "timestamps": {"created": 20}},
+        ],
+    }
+
+    def test_ignores_children_under_parent(self):
+        results = diffx_python.diff(
+            self.OLD, self.NEW, ignore_paths=["metadata.annotations.*"]
+        )
+        paths = {r["path"] for r in results}
+
+        assert not any(p.startswith("metadata.annotations") for p in paths)
+        assert "items[1].name" in paths
+
+    def test_array_wildcard(self):
+        results = diffx_python.diff(
+            self.OLD,
+            self.NEW,
+            ignore_paths=["metadata.annotations", "items[*].timestamps"],
+        )
+
+        assert [r["path"] for r in results] == ["items[1].name"]
+
+    def test_double_star_matches_across_segments(self):
+        results = diffx_python.diff(self.OLD, self.NEW, ignore_paths=["**.created"])
+
+        assert not any(r["path"].endswith("created") for r in results)
+
+    def test_scoped_to_parent(self):
+        old = {"a": {"id": 1}, "b": {"id": 1}}
+        new = {"a": {"id": 2}, "b": {"id": 2}}
+
+        results = diffx_python.diff(old, new, ignore_paths=["a.id"])
+
+        assert [r["path"] for r in results] == ["b.id"]
+
+
+class TestIncludePaths:
+    """include_paths restricts the diff to subtrees matched by glob patterns"""
+
+    OLD = {
+        "metadata": {"name": "web", "labels": {"app": "web"}},
+        "spec": {
+            "replicas": 1,
+            "containers": [{"name": "app", "image": "app:1", "ports": [80]}],
+        },
+    }
+    NEW = {
+        "metadata": {"name": "api", "labels": {"app": "api"}},
+        "spec": {
+            "replicas": 2,
+            "containers": [{"name": "app", "image": "app:2", "ports": [8080]}],
+        },
+    }
+
+    def test_only_included_subtree(self):
+        results = diffx_python.diff(self.OLD, self.NEW, include_paths=["spec"])
+
+        assert [r["path"] for r in results] == [
+            "spec.containers[0].image",
+            "spec.containers[0].ports[0]",
+            "spec.replicas",
+        ]
 
+    def test_array_wildcard(self):
+        results = diffx_python.diff(
+            self.OLD, self.NEW, include_paths=["spec.containers[*].image"]
+        )
 
-class TestComplexStructures:
-    """Test complex data structures"""
+        assert [r["path"] for r in results] == ["spec.containers[0].image"]
 
-    def test_diff_nested_objects(self):
-        old = TestFixtures.nested_object_old()
-        new = TestFixtures.nested_object_new()
+    def test_multiple_patterns(self):
+        results = diffx_python.diff(
+            self.OLD, self.NEW, include_paths=["metadata.name", "spec.replicas"]
+        )
 
-        results = diffx_python.diff(old, new)
+        assert [r["path"] for r in results] == ["metadata.name", "spec.replicas"]
 
-        # Should find multiple changes in nested structure
-        assert len(results) > 1
+    def test_double_star(self):
+        results = diffx_python.diff(self.OLD, self.NEW, include_paths=["**.app"])
 
-        # Verify some expected changes
-        paths = [result["path"] for result in results]
-        assert any("database.host" in path for path in paths)
-        assert any("monitoring" in path for path in paths)
+        assert [r["path"] for r in results] == ["metadata.labels.app"]
 
-    def test_diff_large_dataset(self):
-        """Test performance with larger dataset"""
-        # Create large dataset (smaller than Rust version to avoid timeout)
-        old_data = {}
-        new_data = {}
+    def test_combined_with_ignore_paths(self):
+        results = diffx_python.diff(
+            self.OLD,
+            self.NEW,
+            include_paths=["spec"],
+            ignore_paths=["spec.containers"],
+        )
 
-        for i in range(100):  # Smaller dataset for Python test
-            old_data[f"key_{i}"] = i
-            new_data[f"key_{i}"] = i + 1
+        assert [r["path"] for r in results] == ["spec.replicas"]
 
-        results = diffx_python.diff(old_data, new_data)
-        assert len(results) == 100
+    def test_no_match_reports_nothing(self):
+        results = diffx_python.diff(self.OLD, self.NEW, include_paths=["status"])
 
+        assert results == []
 
-# ============================================================================
-# ERROR HANDLING TESTS
-# ============================================================================
+    def test_parent_added_or_removed(self):
+        doc = {"metadata": {"name": "x", "other": 1}}
 
+        assert diffx_python.diff({}, doc, include_paths=["metadata.name"]) == [
+            {"type": "Added", "path": "metadata.name", "value": "x"}
+        ]
+        assert diffx_python.diff(doc, {}, include_paths=["metadata.name"]) == [
+            {"type": "Removed", "path": "metadata.name", "value": "x"}
+        ]
+        results = diffx_python.diff(
+            {"items": [{"a": 1, "b": 2}]}, {"items": []}, include_paths=["items[*].a"]
+        )
+        assert [(r["type"], r["path"]) for r in results] == [("Removed", "items[0].a")]
 
-class TestErrorHandling:
-    """Test error handling in Python bindings"""
 
-    def test_invalid_regex_pattern(self):
-        old = {"test": "value"}
-        new = {"test": "value2"}
+class TestCoerceNumericStrings:
+    """coerce_numeric_strings=True compares "8080" and 8080 as numbers"""
 
-        with pytest.raises(Exception):  # Should raise ValueError for invalid regex
-            diffx_python.diff(old, new, ignore_keys_regex="[invalid_regex")
+    def test_numeric_string_equals_number(self):
+        old = {"port": "8080", "timeout": "30"}
+        new = {"port": 8080, "timeout": 30}
 
-    def test_invalid_output_format(self):
-        old = {"test": "value"}
-        new = {"test": "value2"}
+        assert diffx_python.diff(old, new, coerce_numeric_strings=True) == []
+        assert [r["type"] for r in diffx_python.diff(old, new)] == [
+            "TypeChanged",
+            "TypeChanged",
+        ]
 
-        with pytest.raises(Exception):  # Should raise ValueError for invalid format
-            diffx_python.diff(old, new, output_format="invalid_format")
+    def test_epsilon_applies_after_coercion(self):
+        results = diffx_python.diff(
+            {"ratio": "0.5"},
+            {"ratio": 0.501},
+            coerce_numeric_strings=True,
+            epsilon=0.01,
+        )
 
-    def test_unsupported_python_types(self):
-        """Test handling of complex Python types that can't be converted"""
-        # Most complex types should be convertible or raise clear errors
-        # This test ensures we handle edge cases gracefully
+        assert results == []
 
-        old = {"simple": "value"}
-        new = {"simple": "value"}
+    def test_changed_number_is_modified(self):
+        results = diffx_python.diff(
+            {"port": "8080"}, {"port": 9090}, coerce_numeric_strings=True
+        )
 
-        # These should work fine
-        results = diffx_python.diff(old, new)
-        assert len(results) == 0
+        assert [(r["type"], r["old_value"], r["new_value"]) for r in results] == [
+            ("Modified", "8080", 9090)
+        ]
 
+    def test_non_numeric_strings_are_not_coerced(self):
+        old = {"a": "8080/tcp", "b": "0x1F", "c": " 1"}
+        new = {"a": 8080, "b": 31, "c": 1}
 
-# ============================================================================
-# INTEGRATION TESTS
-# ============================================================================
+        results = diffx_python.diff(old, new, coerce_numeric_strings=True)
 
+        assert {r["type"] for r in results} == {"TypeChanged"}
+        assert len(results) == 3
 
-class TestIntegration:
-    """Integration tests that mirror Rust test scenarios"""
+    def test_two_numeric_strings_compare_numerically(self):
+        assert (
+            diffx_python.diff({"v": "1.50"}, {"v": "1.5"}, coerce_numeric_strings=True)
+            == []
+        )
 
-    def test_unified_api_comprehensive(self):
-        """Comprehensive test using multiple options together"""
-        old = TestFixtures.array_with_ids_old()
-        new = TestFixtures.array_with_ids_new()
+    def test_canonicalize_reads_numeric_strings(self):
+        assert diffx_python.canonicalize(
+            {"port": "8080", "name": "web"}, coerce_numeric_strings=True
+        ) == {"name": "web", "port": 8080}
 
-        results = diffx_python.diff(old, new, array_id_key="id", output_format="json")
 
-        # Should detect changes in the array with ID-based comparison
-        assert len(results) > 0
+class TestIgnoreNumberFormat:
+    """ignore_number_format=True equates integers and floats of equal value"""
 
-        # Verify result structure
-        for result in results:
-            assert "type" in result
-            assert "path" in result
-            assert result["type"] in ["Added", "Removed", "Modified", "TypeChanged"]
+    def test_int_and_float_are_equal(self):
+        old = {"replicas": 1, "ratio": 1000}
+        new = {"replicas": 1.0, "ratio": 1e3}
 
-    def test_type_conversion_fidelity(self):
-        """Test that data roundtrips through Rust conversion correctly"""
-        test_cases = [
-            TestFixtures.simple_object_old(),
-            TestFixtures.numeric_precision_old(),
-            TestFixtures.type_changes_old(),
+        assert diffx_python.diff(old, new, ignore_number_format=True) == []
+        assert len(diffx_python.diff(old, new)) == 2
+
+    def test_different_values_are_still_reported(self):
+        results = diffx_python.diff({"a": 1}, {"a": 1.5}, ignore_number_format=True)
+
+        assert [r["type"] for r in results] == ["Modified"]
+
+    def test_large_integers_compare_exactly(self):
+        results = diffx_python.diff(
+            {"n": 2**53 + 1}, {"n": float(2**53)}, ignore_number_format=True
+        )
+
+        assert len(results) == 1
+
+    def test_exponent_notation_in_parsed_documents(self):
+        old = diffx_python.parse_json('{"limit": 1e3}')
+        new = diffx_python.parse_yaml("limit: 1000")
+
+        assert diffx_python.diff(old, new, ignore_number_format=True) == []
+
+    def test_canonicalize_writes_integral_floats_as_integers(self):
+        assert diffx_python.canonicalize(
+            {"a": 2.0, "b": 2.5}, ignore_number_format=True
+        ) == {"a": 2, "b": 2.5}
+        assert isinstance(
+            diffx_python.canonicalize(3.0, ignore_number_format=True), int
+        )
+
+
+class TestCoerceBools:
+    """coerce_bools equates booleans with their string and number spellings"""
+
+    def test_default_spellings(self):
+        old = {"debug": True, "tls": False, "cache": True}
+        new = {"debug": "yes", "tls": "OFF", "cache": 1}
+
+        assert diffx_python.diff(old, new, coerce_bools=True) == []
+        assert len(diffx_python.diff(old, new)) == 3
+
+    def test_opposite_value_is_modified(self):
+        results = diffx_python.diff({"debug": True}, {"debug": "no"}, coerce_bools=True)
+
+        assert [r["type"] for r in results] == ["Modified"]
+
+    def test_unknown_spelling_is_type_changed(self):
+        results = diffx_python.diff(
+            {"debug": True}, {"debug": "maybe"}, coerce_bools=True
+        )
+
+        assert [r["type"] for r in results] == ["TypeChanged"]
+
+    def test_only_pairs_with_a_boolean_are_coerced(self):
+        results = diffx_python.diff({"a": "yes"}, {"a": 1}, coerce_bools=True)
+
+        assert len(results) == 1
+
+    def test_custom_table(self):
+        table = {"true": ["enabled", 1], "false": ["disabled", 0]}
+        old = {"a": True, "b": False, "c": True}
+        new = {"a": "Enabled", "b": 0, "c": "yes"}
+
+        results = diffx_python.diff(old, new, coerce_bools=table)
+
+        assert [r["path"] for r in results] == ["c"]
+
+    def test_invalid_tables(self):
+        with pytest.raises(ValueError, match="both true and false"):
+            diffx_python.diff(True, "x", coerce_bools={"true": ["x"], "false": ["X"]})
+        with pytest.raises(ValueError, match="keys"):
+            diffx_python.diff(True, "x", coerce_bools={"yes": ["x"]})
+        with pytest.raises(ValueError, match="strings or numbers"):
+            diffx_python.diff(True, "x", coerce_bools={"true": [None]})
+        with pytest.raises(TypeError, match="list"):
+            diffx_python.diff(True, "x", coerce_bools={"true": "on"})
+
+
+class TestCompareDurations:
+    """compare_durations=True compares duration strings by length"""
+
+    def test_equal_durations_in_different_units(self):
+        old = {"timeout": "1h", "interval": "90s", "ttl": "P1DT2H", "delay": "1.1h"}
+        new = {"timeout": "PT1H", "interval": "1m30s", "ttl": "26h", "delay": "66m"}
+
+        assert diffx_python.diff(old, new, compare_durations=True) == []
+        assert len(diffx_python.diff(old, new)) == 4
+
+    def test_changed_duration_is_modified(self):
+        results = diffx_python.diff(
+            {"timeout": "30s"}, {"timeout": "1m"}, compare_durations=True
+        )
+
+        assert [(r["old_value"], r["new_value"]) for r in results] == [("30s", "1m")]
+
+    def test_epsilon_applies_in_seconds(self):
+        results = diffx_python.diff(
+            {"timeout": "500ms"},
+            {"timeout": "0.501s"},
+            compare_durations=True,
+            epsilon=0.01,
+        )
+
+        assert results == []
+
+    def test_other_strings_compare_as_text(self):
+        old = {"a": "1", "b": "P1M", "c": "5x"}
+        new = {"a": "1s", "b": "P30D", "c": "5s"}
+
+        results = diffx_python.diff(old, new, compare_durations=True)
+
+        assert len(results) == 3
+
+    def test_canonicalize_writes_seconds(self):
+        assert diffx_python.canonicalize(
+            {"timeout": "PT1M30S", "name": "web"}, compare_durations=True
+        ) == {"name": "web", "timeout": "90s"}
+
+
+class TestCompareQuantities:
+    """compare_quantities=True compares Kubernetes / SI quantities by magnitude"""
+
+    def test_equal_quantities_in_different_units(self):
+        old = {"memory": "1Gi", "cpu": "500m", "storage": "1.5Gi", "limit": "2k"}
+        new = {"memory": "1024Mi", "cpu": "0.5", "storage": "1536Mi", "limit": "2e3"}
+
+        assert diffx_python.diff(old, new, compare_quantities=True) == []
+        assert len(diffx_python.diff(old, new)) == 4
+
+    def test_quantity_equals_number(self):
+        old = {"cpu": 1, "replicas": 3}
+        new = {"cpu": "1000m", "replicas": "3"}
+
+        assert diffx_python.diff(old, new, compare_quantities=True) == []
+
+    def test_changed_quantity_is_modified(self):
+        results = diffx_python.diff(
+            {"cpu": 2}, {"cpu": "1500m"}, compare_quantities=True
+        )
+
+        assert [(r["type"], r["old_value"], r["new_value"]) for r in results] == [
+            ("Modified", 2, "1500m")
         ]
 
-        for test_data in test_cases:
-            # Diff with itself should produce no changes
-            results = diffx_python.diff(test_data, test_data)
-            assert len(results) == 0, f"Self-diff should be empty for {test_data}"
+    def test_other_strings_compare_as_text(self):
+        old = {"a": "1Gb", "b": "abc", "c": True}
+        new = {"a": "1G", "b": "ABC", "c": "1"}
 
-    def test_backwards_compatibility_workflow(self):
-        """Test that common usage patterns work correctly"""
-        # Simulate common usage: load JSON, compare, get results
-        old_json_str = '{"name": "old", "version": 1}'
-        new_json_str = '{"name": "new", "version": 2}'
+        results = diffx_python.diff(old, new, compare_quantities=True)
 
-        old_data = json.loads(old_json_str)
-        new_data = json.loads(new_json_str)
+        assert len(results) == 3
 
-        results = diffx_python.diff(old_data, new_data)
+    def test_canonicalize_writes_numbers(self):
+        assert diffx_python.canonicalize(
+            {"memory": "1Ki", "cpu": "250m", "name": "web"}, compare_quantities=True
+        ) == {"cpu": 0.25, "memory": 1024, "name": "web"}
 
-        assert len(results) == 2  # name and version changed
 
-        # Results should be easily convertible back to JSON
-        results_json = json.dumps(results, default=str)
-        assert len(results_json) > 0
+class TestCompareVersions:
+    """compare_versions=True compares versions by SemVer and classifies bumps"""
+
+    def test_equal_versions_in_different_spellings(self):
+        old = {"requests": "v2.31", "numpy": "1.26.0+cpu", "click": "8.1.7"}
+        new = {"requests": "2.31.0", "numpy": "1.26.0", "click": "V8.1.7"}
+
+        assert diffx_python.diff(old, new, compare_versions=True) == []
+        assert len(diffx_python.diff(old, new)) == 3
+
+    def test_modified_versions_carry_bump(self):
+        old = {"a": "1.2.3", "b": "1.2.3", "c": "1.2.3", "d": "2.0.0-rc.1"}
+        new = {"a": "2.0.0", "b": "1.3", "c": "1.2.2", "d": "2.0.0"}
+
+        results = diffx_python.diff(old, new, compare_versions=True)
+
+        assert {r["path"]: r["bump"] for r in results} == {
+            "a": "major",
+            "b": "minor",
+            "c": "patch",
+            "d": "prerelease",
+        }
+
+    def test_non_versions_have_no_bump(self):
+        old = {"name": "web", "version": "1.2.3", "port": "1.10"}
+        new = {"name": "api", "version": "latest", "port": "1.1"}
+
+        results = diffx_python.diff(old, new, compare_versions=True)
+
+        assert len(results) == 3
+        assert {r["path"]: r["bump"] for r in results if "bump" in r} == {
+            "port": "minor"
+        }
+
+    def test_versions_win_over_numeric_strings(self):
+        results = diffx_python.diff(
+            {"version": "1.10"},
+            {"version": "1.1"},
+            compare_versions=True,
+            coerce_numeric_strings=True,
+        )
+
+        assert len(results) == 1
+
+    def test_no_bump_without_option(self):
+        results = diffx_python.diff({"v": "1.2.3"}, {"v": "2.0.0"})
+
+        assert "bump" not in results[0]
+
+    def test_canonicalize_writes_full_version(self):
+        assert diffx_python.canonicalize(
+            {"a": "v1.2", "b": "1.2.3-rc.1+build.5", "c": "web"}, compare_versions=True
+        ) == {"a": "1.2.0", "b": "1.2.3-rc.1", "c": "web"}
+
+
+class TestCompareIps:
+    """compare_ips=True compares IP addresses and CIDR blocks canonically"""
+
+    def test_equal_addresses_in_different_spellings(self):
+        old = {
+            "host": "10.00.000.1/32",
+            "v6": "2001:0DB8:0000:0000:0000:0000:0000:0001",
+            "net": "2001:db8:0:0::/48",
+        }
+        new = {"host": "10.0.0.1/32", "v6": "2001:db8::1", "net": "2001:DB8::/48"}
+
+        assert diffx_python.diff(old, new, compare_ips=True) == []
+        assert len(diffx_python.diff(old, new)) == 3
+
+    def test_different_addresses_and_prefixes_are_modified(self):
+        old = {"a": "10.0.0.1", "b": "10.0.0.0/8", "c": "10.0.0.1"}
+        new = {"a": "10.0.0.2", "b": "10.0.0.0/16", "c": "10.0.0.1/32"}
+
+        results = diffx_python.diff(old, new, compare_ips=True)
+
+        assert [r["path"] for r in results] == ["a", "b", "c"]
+
+    def test_other_strings_compare_as_text(self):
+        old = {"a": "10.0.0.256", "b": "host-01"}
+        new = {"a": "10.0.0.0256", "b": "HOST-01"}
+
+        assert len(diffx_python.diff(old, new, compare_ips=True)) == 2
+
+    def test_canonicalize_writes_canonical_form(self):
+        assert diffx_python.canonicalize(
+            {"a": "010.001.000.001/24", "b": "FE80:0:0:0:0:0:0:1"}, compare_ips=True
+        ) == {"a": "10.1.0.1/24", "b": "fe80::1"}
+
+
+class TestComparators:
+    """comparators decide equality with Python callables for matching paths"""
+
+    def test_callable_decides_equality(self):
+        old = {"checksum": "ABC123", "name": "a"}
+        new = {"checksum": "abc123", "name": "b"}
+
+        results = diffx_python.diff(
+            old, new, comparators={"checksum": lambda a, b: a.lower() == b.lower()}
+        )
+
+        assert [r["path"] for r in results] == ["name"]
+
+    def test_unequal_container_reported_as_whole(self):
+        old = {"url": {"host": "a", "query": ["x=1", "y=2"]}}
+        new = {"url": {"host": "b", "query": ["y=2", "x=1"]}}
+
+        def same_url(a, b):
+            return a["host"] == b["host"] and sorted(a["query"]) == sorted(b["query"])
+
+        results = diffx_python.diff(old, new, comparators={"url": same_url})
+
+        assert results == [
+            {
+                "type": "Modified",
+                "path": "url",
+                "old_value": old["url"],
+                "new_value": new["url"],
+            }
+        ]
+
+    def test_glob_pattern(self):
+        old = {"items": [{"ts": 100}, {"ts": 200}]}
+        new = {"items": [{"ts": 120}, {"ts": 900}]}
+
+        results = diffx_python.diff(
+            old, new, comparators={"items[*].ts": lambda a, b: abs(a - b) <= 60}
+        )
+
+        assert [r["path"] for r in results] == ["items[1].ts"]
+
+    def test_exception_propagates(self):
+        def broken(a, b):
+            raise RuntimeError("comparator failed")
+
+        with pytest.raises(RuntimeError, match="comparator failed"):
+            diffx_python.diff({"a": 1}, {"a": 2}, comparators={"a": broken})
+
+    def test_non_callable_rejected(self):
+        with pytest.raises(TypeError, match="must be callable"):
+            diffx_python.diff({"a": 1}, {"a": 2}, comparators={"a": 1})
+
+
+class TestNormalize:
+    """normalize applies callables to both sides before comparison"""
+
+    def test_callable_applies_to_every_scalar(self):
+        old = {"name": "Alice", "tags": ["A", "B"], "age": 30}
+        new = {"name": "ALICE", "tags": ["a", "b"], "age": 31}
+
+        results = diffx_python.diff(
+            old, new, normalize=lambda v: v.lower() if isinstance(v, str) else v
+        )
+
+        assert [r["path"] for r in results] == ["age"]
+
+    def test_per_path_mapping(self):
+        old = {"width": "100px", "price": 9.999, "label": "x"}
+        new = {"width": "100", "price": 10.0, "label": "y"}
+
+        results = diffx_python.diff(
+            old,
+            new,
+            normalize={
+                "width": lambda v: v.replace("px", ""),
+                "price": lambda v: round(v, 2),
+            },
+        )
+
+        assert [r["path"] for r in results] == ["label"]
+
+    def test_results_show_normalized_values(self):
+        results = diffx_python.diff(
+            {"name": " Alice "}, {"name": "Bob"}, normalize=lambda v: v.strip()
+        )
+
+        assert results[0]["old_value"] == "Alice"
+
+    def test_exception_propagates(self):
+        with pytest.raises(AttributeError):
+            diffx_python.diff({"a": 1}, {"a": 2}, normalize=lambda v: v.lower())
+
+    def test_invalid_normalize_rejected(self):
+        with pytest.raises(TypeError, match="normalize"):
+            diffx_python.diff({"a": 1}, {"a": 2}, normalize="lower")
+
+
+class TestCompositeIdKey:
+    """array_id_key accepts several fields that together identify elements"""
+
+    OLD = [
+        {"region": "us", "name": "web", "size": 1},
+        {"region": "eu", "name": "web", "size": 2},
+    ]
+
+    def test_matches_by_all_fields(self):
+        new = [
+            {"region": "eu", "name": "web", "size": 3},
+            {"region": "us", "name": "web", "size": 1},
+        ]
+
+        results = diffx_python.diff(self.OLD, new, array_id_key=["region", "name"])
+
+        assert results == [
+            {
+                "type": "Modified",
+                "path": '[region="eu",name="web"].size',
+                "old_value": 2,
+                "new_value": 3,
+            }
+        ]
+
+    def test_added_and_removed_elements(self):
+        new = [{"region": "us", "name": "web", "size": 1}]
+
+        results = diffx_python.diff(self.OLD, new, array_id_key=["region", "name"])
+
+        assert [(r["type"], r["path"]) for r in results] == [
+            ("Removed", '[region="eu",name="web"]')
+        ]
+
+    def test_single_item_list_matches_string_key(self):
+        old = [{"id": 1, "v": "a"}]
+        new = [{"id": 1, "v": "b"}]
+
+        assert diffx_python.diff(old, new, array_id_key=["id"]) == diffx_python.diff(
+            old, new, array_id_key="id"
+        )
+
+    def test_empty_key_list_raises(self):
+        with pytest.raises(ValueError):
+            diffx_python.diff([], [], array_id_key=[])
+
+    def test_duplicate_ids_paired_one_to_one(self):
+        items = [{"id": 1, "v": 1}, {"id": 1, "v": 2}]
+
+        for id_key in ("id", ["id"]):
+            assert (
+                diffx_python.diff(
+                    items, items, array_id_key=id_key, array_order="unordered"
+                )
+                == []
+            )
+
+    def test_extra_duplicate_id_reported(self):
+        old = [{"id": 1, "v": 1}]
+        new = [{"id": 1, "v": 1}, {"id": 1, "v": 2}]
+
+        assert diffx_python.diff(old, new, array_id_key=["id"]) == [
+            {"type": "Added", "path": "[id=1]", "value": {"id": 1, "v": 2}}
+        ]
+        assert diffx_python.diff(new, old, array_id_key=["id"]) == [
+            {"type": "Removed", "path": "[id=1]", "value": {"id": 1, "v": 2}}
+        ]
+
+
+class TestNestedIdKey:
+    """Dotted array_id_key looks up identity fields inside each element"""
+
+    def test_kubernetes_style_uid(self):
+        old = [
+            {"metadata": {"uid": "a1"}, "spec": {"replicas": 1}},
+            {"metadata": {"uid": "b2"}, "spec": {"replicas": 2}},
+        ]
+        new = [
+            {"metadata": {"uid": "b2"}, "spec": {"replicas": 3}},
+            {"metadata": {"uid": "a1"}, "spec": {"replicas": 1}},
+        ]
+
+        results = diffx_python.diff(old, new, array_id_key="metadata.uid")
+
+        assert [(r["path"], r["new_value"]) for r in results] == [
+            ('[metadata.uid="b2"].spec.replicas', 3)
+        ]
+
+    def test_literal_dotted_key_wins(self):
+        old = [{"a.b": 1, "v": "x"}]
+        new = [{"a.b": 1, "v": "y"}]
+
+        results = diffx_python.diff(old, new, array_id_key="a.b")
+
+        assert results[0]["path"] == "[a.b=1].v"
+
+    def test_nested_in_composite_key(self):
+        old = [{"meta": {"ns": "x", "name": "web"}, "v": 1}]
+        new = [{"meta": {"ns": "y", "name": "web"}, "v": 1}]
+
+        results = diffx_python.diff(old, new, array_id_key=["meta.ns", "meta.name"])
+
+        assert sorted(r["type"] for r in results) == ["Added", "Removed"]
+
+
+class TestUnorderedArrays:
+    """array_order="unordered" compares arrays as multisets"""
+
+    def test_reordered_elements_produce_no_diff(self):
+        old = {"tags": ["web", "prod", "eu"]}
+        new = {"tags": ["eu", "web", "prod"]}
+
+        assert diffx_python.diff(old, new, array_order="unordered") == []
+        assert len(diffx_python.diff(old, new)) > 0
+
+    def test_reports_only_added_and_removed(self):
+        old = ["a", "b", "c"]
+        new = ["c", "d", "a"]
+
+        results = diffx_python.diff(old, new, array_order="unordered")
+
+        assert [(r["type"], r["path"], r["value"]) for r in results] == [
+            ("Removed", "[1]", "b"),
+            ("Added", "[1]", "d"),
+        ]
+
+    def test_duplicates_are_counted(self):
+        results = diffx_python.diff([1, 1, 2], [1, 2], array_order="unordered")
+
+        assert len(results) == 1
+        assert results[0]["type"] == "Removed"
+        assert results[0]["value"] == 1
+
+    def test_nested_objects(self):
+        old = {"rules": [{"port": 80, "cidr": "0.0.0.0/0"}, {"port": 443}]}
+        new = {"rules": [{"port": 443}, {"port": 80, "cidr": "0.0.0.0/0"}]}
+
+        assert diffx_python.diff(old, new, array_order="unordered") == []
+
+    def test_respects_epsilon(self):
+        results = diffx_python.diff(
+            [1.0, 2.0], [2.0001, 1.0], array_order="unordered", epsilon=0.001
+        )
+
+        assert results == []
+
+    def test_id_key_takes_precedence(self):
+        old = [{"id": 1, "v": "a"}, {"id": 2, "v": "b"}]
+        new = [{"id": 2, "v": "b"}, {"id": 1, "v": "z"}]
+
+        results = diffx_python.diff(
+            old, new, array_id_key="id", array_order="unordered"
+        )
+
+        assert len(results) == 1
+        assert results[0]["type"] == "Modified"
+
+    def test_invalid_array_order(self):
+        with pytest.raises(ValueError):
+            diffx_python.diff([1], [1], array_order="sorted")
+
+
+class TestLcsArrayStrategy:
+    """array_strategy="lcs" aligns arrays so edits are reported minimally"""
+
+    def test_insert_at_front(self):
+        old = {"items": ["b", "c", "d"]}
+        new = {"items": ["a", "b", "c", "d"]}
+
+        results = diffx_python.diff(old, new, array_strategy="lcs")
+
+        assert results == [{"type": "Added", "path": "items[0]", "value": "a"}]
+        assert len(diffx_python.diff(old, new)) == 4
+
+    def test_delete_in_middle(self):
+        results = diffx_python.diff([1, 2, 3, 4], [1, 2, 4], array_strategy="lcs")
+
+        assert results == [{"type": "Removed", "path": "[2]", "value": 3}]
+
+    def test_replacement_is_reported_as_modification(self):
+        results = diffx_python.diff([1, 2, 3], [1, 9, 3], array_strategy="lcs")
+
+        assert len(results) == 1
+        assert results[0]["type"] == "Modified"
+        assert results[0]["path"] == "[1]"
+
+    def test_nested_changes_inside_replaced_element(self):
+        old = [{"name": "a", "v": 1}, {"name": "b", "v": 2}]
+        new = [{"name": "x"}, {"name": "a", "v": 1}, {"name": "b", "v": 3}]
+
+        results = diffx_python.diff(old, new, array_strategy="lcs")
+        paths = sorted(r["path"] for r in results)
+
+        assert paths == ["[0]", "[1].v"]
+
+    def test_invalid_array_strategy(self):
+        with pytest.raises(ValueError):
+            diffx_python.diff([1], [1], array_strategy="patience")
+
+
+class TestMoveDetection:
+    """detect_moves reports repositioned array elements as Moved"""
+
+    def test_moved_scalar(self):
+        results = diffx_python.diff(["a", "b", "c"], ["b", "c", "a"], detect_moves=True)
+
+        assert results == [
+            {"type": "Moved", "path": "[0]", "from_index": 0, "to_index": 2}
+        ]
+
+    def test_moved_by_id(self):
+        old = {"users": [{"id": 1, "n": "a"}, {"id": 2, "n": "b"}, {"id": 3}]}
+        new = {"users": [{"id": 2, "n": "b"}, {"id": 3}, {"id": 1, "n": "z"}]}
+
+        results = diffx_python.diff(old, new, array_id_key="id", detect_moves=True)
+        by_type = {r["type"]: r for r in results}
+
+        assert len(results) == 2
+        assert by_type["Modified"]["path"] == "users[id=1].n"
+        assert by_type["Moved"]["path"] == "users[id=1]"
+        assert by_type["Moved"]["from_index"] == 0
+        assert by_type["Moved"]["to_index"] == 2
+
+    def test_insertion_does_not_report_moves(self):
+        results = diffx_python.diff(
+            [{"id": 1}, {"id": 2}],
+            [{"id": 0}, {"id": 1}, {"id": 2}],
+            array_id_key="id",
+            detect_moves=True,
+        )
+
+        assert [r["type"] for r in results] == ["Added"]
+
+    def test_unmatched_elements_stay_added_and_removed(self):
+        results = diffx_python.diff([1, 2], [2, 3], detect_moves=True)
+
+        assert [(r["type"], r["path"]) for r in results] == [
+            ("Removed", "[0]"),
+            ("Added", "[1]"),
+        ]
+
+    def test_format_output_accepts_moved(self):
+        results = diffx_python.diff([1, 2], [2, 1], detect_moves=True)
+
+        output = diffx_python.format_output(results, "json")
+
+        assert "Moved" in output
+
+    def test_without_option_no_moved_results(self):
+        results = diffx_python.diff(["a", "b"], ["b", "a"])
+
+        assert all(r["type"] != "Moved" for r in results)
+
+
+class TestRenameDetection:
+    """detect_renames reports a key whose value moved to a new key as Renamed"""
+
+    def test_renamed_key(self):
+        old = {"server": {"hostname": "db1", "port": 5432}}
+        new = {"server": {"host": "db1", "port": 5432}}
+
+        results = diffx_python.diff(old, new, detect_renames=True)
+
+        assert results == [
+            {
+                "type": "Renamed",
+                "path": "server.hostname",
+                "new_path": "server.host",
+                "value": "db1",
+            }
+        ]
+
+    def test_without_option_reports_removed_and_added(self):
+        old = {"hostname": "db1"}
+        new = {"host": "db1"}
+
+        results = diffx_python.diff(old, new)
+
+        assert sorted(r["type"] for r in results) == ["Added", "Removed"]
+
+    def test_different_values_are_not_renames(self):
+        old = {"hostname": "db1"}
+        new = {"host": "db2"}
+
+        results = diffx_python.diff(old, new, detect_renames=True)
+
+        assert sorted(r["type"] for r in results) == ["Added", "Removed"]
+
+    def test_nested_value_renamed(self):
+        old = {"db": {"user": "app", "pool": {"size": 5}}}
+        new = {"database": {"user": "app", "pool": {"size": 5}}}
+
+        results = diffx_python.diff(old, new, detect_renames=True)
+
+        assert [(r["type"], r["path"], r["new_path"]) for r in results] == [
+            ("Renamed", "db", "database")
+        ]
+
+    def test_run_summary_counts_renames(self, tmp_path):
+        old_file = tmp_path / "old.json"
+        new_file = tmp_path / "new.json"
+        old_file.write_text(json.dumps({"hostname": "db1"}))
+        new_file.write_text(json.dumps({"host": "db1"}))
+
+        summary = diffx_python.run(
+            str(old_file),
+            str(new_file),
+            str(tmp_path / "report.html"),
+            detect_renames=True,
+        )
+
+        assert summary["renamed"] == 1
+        assert "Renamed" in (tmp_path / "report.html").read_text()
+
+
+class TestIgnoreKeyCase:
+    """ignore_key_case=True matches dict keys case-insensitively"""
+
+    def test_keys_differing_in_case_match(self):
+        old = {"headers": {"Content-Type": "text/html", "X-Id": "1"}}
+        new = {"headers": {"content-type": "text/html", "x-id": "1"}}
+
+        assert diffx_python.diff(old, new, ignore_key_case=True) == []
+        assert len(diffx_python.diff(old, new)) == 4
+
+    def test_paths_use_old_spelling(self):
+        old = {"Headers": {"Content-Type": "text/html"}}
+        new = {"headers": {"content-type": "application/json"}}
+
+        results = diffx_python.diff(old, new, ignore_key_case=True)
+
+        assert [(r["type"], r["path"]) for r in results] == [
+            ("Modified", "Headers.Content-Type")
+        ]
+
+    def test_added_keys_use_new_spelling(self):
+        results = diffx_python.diff(
+            {"Host": "a"}, {"host": "a", "Accept": "*/*"}, ignore_key_case=True
+        )
+
+        assert [(r["type"], r["path"]) for r in results] == [("Added", "Accept")]
+
+    def test_values_keep_their_case(self):
+        results = diffx_python.diff({"Key": "A"}, {"key": "a"}, ignore_key_case=True)
+
+        assert [r["type"] for r in results] == ["Modified"]
+
+    def test_canonicalize_lowercases_keys(self):
+        assert diffx_python.canonicalize(
+            {"Content-Type": "text/html"}, ignore_key_case=True
+        ) == {"content-type": "text/html"}
+
+    def test_keys_folding_together_rejected(self):
+        old = {"headers": {"Accept": "a", "accept": "b"}}
+        new = {"headers": {"accept": "b"}}
+
+        with pytest.raises(
+            ValueError, match="Keys \"Accept\" and \"accept\" at 'headers' are the same"
+        ):
+            diffx_python.diff(old, new, ignore_key_case=True)
+        with pytest.raises(ValueError, match="at 'headers'"):
+            diffx_python.canonicalize(old, ignore_key_case=True)
+        assert len(diffx_python.diff(old, new)) == 1
+
+    def test_semantic_equal_rejects_folding_keys_of_identical_inputs(self):
+        doc = {"headers": {"Accept": "a", "accept": "b"}}
+
+        with pytest.raises(ValueError, match="at 'headers'"):
+            diffx_python.semantic_equal(doc, doc, ignore_key_case=True)
+        with pytest.raises(ValueError, match="at 'headers'"):
+            diffx_python.semantic_equal(doc, dict(doc), ignore_key_case=True)
+        assert diffx_python.semantic_equal(doc, doc)
+
+
+class TestUnicodeNormalize:
+    """unicode_normalize compares strings in a Unicode normalization form"""
+
+    COMPOSED = "caf\u00e9"
+    DECOMPOSED = "cafe\u0301"
+
+    def test_composed_and_decomposed_strings_match(self):
+        old = {"name": self.COMPOSED}
+        new = {"name": self.DECOMPOSED}
+
+        assert diffx_python.diff(old, new, unicode_normalize="NFC") == []
+        assert len(diffx_python.diff(old, new)) == 1
+
+    def test_compatibility_forms(self):
+        old = {"unit": "\uff21\u2460"}  # fullwidth A, circled one
+        new = {"unit": "A1"}
+
+        assert len(diffx_python.diff(old, new, unicode_normalize="NFC")) == 1
+        assert diffx_python.diff(old, new, unicode_normalize="NFKC") == []
+
+    def test_combines_with_ignore_case(self):
+        results = diffx_python.diff(
+            "\u00c9T\u00c9",
+            "e\u0301te\u0301",
+            unicode_normalize="NFC",
+            ignore_case=True,
+        )
+
+        assert results == []
+
+    def test_reported_values_are_unchanged(self):
+        results = diffx_python.diff(
+            {"a": self.COMPOSED}, {"a": "tea"}, unicode_normalize="NFD"
+        )
+
+        assert results[0]["old_value"] == self.COMPOSED
+
+    def test_keys_only_with_unicode_normalize_keys(self):
+        old = {self.COMPOSED: 1}
+        new = {self.DECOMPOSED: 1}
+
+        assert len(diffx_python.diff(old, new, unicode_normalize="NFC")) == 2
+        assert (
+            diffx_python.diff(
+                old, new, unicode_normalize="NFC", unicode_normalize_keys=True
+            )
+            == []
+        )
+
+    def test_none_disables(self):
+        results = diffx_python.diff(
+            self.COMPOSED, self.DECOMPOSED, unicode_normalize=None
+        )
+
+        assert len(results) == 1
+
+    def test_invalid_options(self):
+        with pytest.raises(ValueError, match="NFKC"):
+            diffx_python.diff("a", "a", unicode_normalize="nfc")
+        with pytest.raises(ValueError, match="requires unicode_normalize"):
+            diffx_python.diff("a", "a", unicode_normalize_keys=True)
+
+    def test_canonicalize(self):
+        assert diffx_python.canonicalize(
+            {self.DECOMPOSED: self.DECOMPOSED},
+            unicode_normalize="NFC",
+            unicode_normalize_keys=True,
+        ) == {self.COMPOSED: self.COMPOSED}
+
+
+class TestEmptyAsMissing:
+    """empty_as_missing=True treats [] and {} like absent keys"""
+
+    def test_empty_collections_match_missing_keys(self):
+        old = {"name": "web", "env": [], "labels": {}}
+        new = {"name": "web", "volumes": []}
+
+        assert diffx_python.diff(old, new, empty_as_missing=True) == []
+        assert len(diffx_python.diff(old, new)) == 3
+
+    def test_non_empty_values_are_still_reported(self):
+        old = {"env": [], "ports": [80]}
+        new = {"tags": {"a": 1}}
+
+        results = diffx_python.diff(old, new, empty_as_missing=True)
+
+        assert sorted((r["type"], r["path"]) for r in results) == [
+            ("Added", "tags"),
+            ("Removed", "ports"),
+        ]
+
+    def test_null_and_empty_string_are_not_empty_collections(self):
+        results = diffx_python.diff({"a": None, "b": ""}, {}, empty_as_missing=True)
+
+        assert len(results) == 2
+
+    def test_empty_collection_versus_present_value(self):
+        results = diffx_python.diff(
+            {"env": []}, {"env": ["A=1"]}, empty_as_missing=True
+        )
+
+        assert [r["type"] for r in results] == ["Added"]
+
+    def test_not_paired_as_renames(self):
+        results = diffx_python.diff(
+            {"a": []}, {"b": []}, empty_as_missing=True, detect_renames=True
+        )
+
+        assert results == []
+
+    def test_canonicalize_drops_empty_collections(self):
+        assert diffx_python.canonicalize(
+            {"a": 1, "b": [], "c": {"d": {}}}, empty_as_missing=True
+        ) == {"a": 1, "c": {}}
+
+
+class TestReportUnchanged:
+    """report_unchanged adds Unchanged entries for values that compared equal"""
+
+    def test_unchanged_leaves_reported(self):
+        old = {"name": "web", "config": {"port": 80, "debug": False}}
+        new = {"name": "web", "config": {"port": 8080, "debug": False}}
+
+        results = diffx_python.diff(old, new, report_unchanged=True)
+
+        assert [(r["type"], r["path"]) for r in results] == [
+            ("Unchanged", "config.debug"),
+            ("Modified", "config.port"),
+            ("Unchanged", "name"),
+        ]
+        assert results[0]["value"] is False
+
+    def test_equal_under_tolerance_is_unchanged(self):
+        results = diffx_python.diff(
+            {"x": 1.0}, {"x": 1.0001}, epsilon=0.01, report_unchanged=True
+        )
+
+        assert results == [{"type": "Unchanged", "path": "x", "value": 1.0}]
+
+    def test_unordered_array_elements(self):
+        results = diffx_python.diff(
+            [1, 2], [2, 3], array_order="unordered", report_unchanged=True
+        )
+
+        assert [(r["type"], r["path"]) for r in results] == [
+            ("Removed", "[0]"),
+            ("Unchanged", "[1]"),
+            ("Added", "[1]"),
+        ]
+
+    def test_default_omits_unchanged(self):
+        results = diffx_python.diff({"a": 1, "b": 2}, {"a": 1, "b": 3})
+
+        assert all(r["type"] != "Unchanged" for r in results)
+
+    def test_run_total_excludes_unchanged(self, tmp_path):
+        old_file = tmp_path / "old.json"
+        new_file = tmp_path / "new.json"
+        old_file.write_text(json.dumps({"a": 1, "b": 2}))
+        new_file.write_text(json.dumps({"a": 1, "b": 3}))
+
+        summary = diffx_python.run(
+            str(old_file),
+            str(new_file),
+            str(tmp_path / "report.json"),
+            format="json",
+            report_unchanged=True,
+        )
+
+        assert summary["total"] == 1
+        assert summary["unchanged"] == 1
+
+
+class TestMaxResults:
+    """max_results stops collecting differences and flags truncation"""
+
+    OLD = {f"key{i}": i for i in range(10)}
+    NEW = {f"key{i}": i + 1 for i in range(10)}
+
+    def test_truncated(self):
+        results = diffx_python.diff(self.OLD, self.NEW, max_results=3)
+
+        assert len(results) == 3
+        assert results.truncated is True
+
+    def test_not_truncated_when_under_limit(self):
+        results = diffx_python.diff(self.OLD, self.NEW, max_results=10)
+
+        assert len(results) == 10
+        assert results.truncated is False
+
+    def test_results_are_a_list(self):
+        results = diffx_python.diff({"a": 1}, {"a": 2})
+
+        assert isinstance(results, list)
+        assert isinstance(results, diffx_python.DiffResults)
+        assert results.truncated is False
+
+    def test_limit_applies_after_path_filter(self):
+        old = {"a": {"x": 1, "y": 2}, "b": {"x": 1, "y": 2}}
+        new = {"a": {"x": 9, "y": 9}, "b": {"x": 9, "y": 9}}
+
+        results = diffx_python.diff(old, new, path_filter="b.", max_results=1)
+
+        assert [r["path"] for r in results] == ["b.x"]
+        assert results.truncated is True
+
+    def test_unchanged_entries_not_counted(self):
+        results = diffx_python.diff(
+            {"a": 1, "b": 1, "c": 1},
+            {"a": 1, "b": 2, "c": 2},
+            report_unchanged=True,
+            max_results=1,
+        )
+
+        assert [(r["type"], r["path"]) for r in results] == [
+            ("Unchanged", "a"),
+            ("Modified", "b"),
+        ]
+        assert results.truncated is True
+
+    def test_limit_applies_after_datetime_tolerance(self):
+        old = {"a": "2024-05-01T00:00:00Z", "b": 1, "c": 1}
+        new = {"a": "2024-05-01T00:00:01Z", "b": 2, "c": 2}
+
+        results = diffx_python.diff(old, new, datetime_tolerance=5, max_results=1)
+
+        assert [r["path"] for r in results] == ["b"]
+        assert results.truncated is True
+        stats = diffx_python.diff_stats(old, new, datetime_tolerance=5, max_results=1)
+        assert stats["total"] == 1
+        assert stats["truncated"] is True
+
+    def test_negative_rejected(self):
+        with pytest.raises(ValueError, match="max_results"):
+            diffx_python.diff({"a": 1}, {"a": 2}, max_results=-1)
+
+    def test_run_summary_flags_truncation(self, tmp_path):
+        old_file = tmp_path / "old.json"
+        new_file = tmp_path / "new.json"
+        old_file.write_text(json.dumps(self.OLD))
+        new_file.write_text(json.dumps(self.NEW))
+
+        summary = diffx_python.run(
+            str(old_file),
+            str(new_file),
+            str(tmp_path / "report.json"),
+            format="json",
+            max_results=3,
+        )
+
+        assert summary["total"] == 3
+        assert summary["truncated"] is True
+
+
+class TestMaxDepth:
+    """max_depth compares deeper subtrees as opaque values"""
+
+    OLD = {"a": {"b": {"c": 1, "d": 2}}, "top": 1}
+    NEW = {"a": {"b": {"c": 9, "d": 9}}, "top": 2}
+
+    def test_deeper_subtree_reported_once(self):
+        results = diffx_python.diff(self.OLD, self.NEW, max_depth=1)
+
+        assert results == [
+            {
+                "type": "Modified",
+                "path": "a",
+                "old_value": self.OLD["a"],
+                "new_value": self.NEW["a"],
+            },
+            {"type": "Modified", "path": "top", "old_value": 1, "new_value": 2},
+        ]
+
+    def test_depth_counts_levels(self):
+        results = diffx_python.diff(self.OLD, self.NEW, max_depth=2)
+
+        assert [r["path"] for r in results] == ["a.b", "top"]
+
+    def test_equal_deep_subtree_not_reported(self):
+        old = {"a": {"b": {"c": [1, 2]}}, "x": 1}
+        new = {"a": {"b": {"c": [1, 2]}}, "x": 2}
+
+        results = diffx_python.diff(old, new, max_depth=1)
+
+        assert [r["path"] for r in results] == ["x"]
+
+    def test_arrays_count_as_levels(self):
+        results = diffx_python.diff([[1, [2]]], [[1, [3]]], max_depth=1)
+
+        assert [r["path"] for r in results] == ["[0]"]
+
+    def test_deeply_nested_input_rejected(self):
+        deep = []
+        for _ in range(200_000):
+            deep = [deep]
+
+        with pytest.raises(RecursionError, match="more than 1000 containers"):
+            diffx_python.diff(deep, [1], max_depth=10)
+
+    def test_negative_rejected(self):
+        with pytest.raises(ValueError, match="max_depth"):
+            diffx_python.diff({"a": 1}, {"a": 2}, max_depth=-1)
+
+
+class TestCompareStructure:
+    """compare="structure" reports shape changes and ignores leaf values"""
+
+    def test_changed_values_produce_no_diff(self):
+        old = {"user": {"name": "Ann", "age": 31, "active": True}}
+        new = {"user": {"name": "Bob", "age": 42, "active": False}}
+
+        assert diffx_python.diff(old, new, compare="structure") == []
+
+    def test_reports_added_removed_and_type_changes(self):
+        old = {"id": 1, "name": "web", "tags": None}
+        new = {"id": "1", "title": "web", "tags": None}
+
+        results = diffx_python.diff(old, new, compare="structure")
+
+        assert sorted((r["type"], r["path"]) for r in results) == [
+            ("Added", "title"),
+            ("Removed", "name"),
+            ("TypeChanged", "id"),
+        ]
+
+    def test_array_length_is_not_reported(self):
+        old = {"items": [{"id": 1}]}
+        new = {"items": [{"id": 2}, {"id": 3}]}
+
+        assert diffx_python.diff(old, new, compare="structure") == []
+
+    def test_array_elements_compared_by_position(self):
+        old = {"items": [{"id": 1, "price": 9.5}]}
+        new = {"items": [{"id": 1, "price": "9.50"}, {"id": 2}]}
+
+        results = diffx_python.diff(old, new, compare="structure")
+
+        assert [(r["type"], r["path"]) for r in results] == [
+            ("TypeChanged", "items[0].price")
+        ]
+
+    def test_container_replaced_by_scalar(self):
+        results = diffx_python.diff({"a": {"b": 1}}, {"a": 1}, compare="structure")
+
+        assert [r["type"] for r in results] == ["TypeChanged"]
+
+    def test_semantic_equal(self):
+        assert diffx_python.semantic_equal({"a": 1}, {"a": 2}, compare="structure")
+        assert not diffx_python.semantic_equal(
+            {"a": 1}, {"a": [1]}, compare="structure"
+        )
+
+    def test_invalid_compare(self):
+        with pytest.raises(ValueError, match="types"):
+            diffx_python.diff({}, {}, compare="shape")
+
+
+class TestCompareTypes:
+    """compare="types" reports only JSON type changes"""
+
+    def test_same_type_changes_produce_no_diff(self):
+        old = {"name": "web", "replicas": 2, "ports": [80]}
+        new = {"name": "db", "replicas": 5.5, "ports": [443, 8443]}
+
+        assert diffx_python.diff(old, new, compare="types") == []
+
+    def test_added_and_removed_keys_are_not_reported(self):
+        old = {"id": 1, "name": "web"}
+        new = {"id": 1, "owner": "ops"}
+
+        assert diffx_python.diff(old, new, compare="types") == []
+
+    def test_reports_type_changes(self):
+        old = {"id": 1, "spec": {"ports": {"http": 80}}, "tags": "a"}
+        new = {"id": "1", "spec": {"ports": [80]}, "tags": "b"}
+
+        results = diffx_python.diff(old, new, compare="types")
+
+        assert sorted((r["type"], r["path"]) for r in results) == [
+            ("TypeChanged", "id"),
+            ("TypeChanged", "spec.ports"),
+        ]
+
+    def test_null_is_a_type(self):
+        results = diffx_python.diff({"a": None}, {"a": 0}, compare="types")
+
+        assert [r["type"] for r in results] == ["TypeChanged"]
+
+
+class TestCompareKeys:
+    """compare="keys" reports only keys present on one side"""
+
+    def test_reports_missing_keys(self):
+        prod = {"db": {"host": "prod", "pool": 20}, "cache": "redis"}
+        dev = {"db": {"host": "localhost"}, "debug": True}
+
+        results = diffx_python.diff(prod, dev, compare="keys")
+
+        assert sorted((r["type"], r["path"]) for r in results) == [
+            ("Added", "debug"),
+            ("Removed", "cache"),
+            ("Removed", "db.pool"),
+        ]
+
+    def test_value_and_type_changes_are_ignored(self):
+        old = {"port": 80, "hosts": ["a"], "tls": {"on": True}}
+        new = {"port": "8080", "hosts": ["b", "c"], "tls": False}
+
+        assert diffx_python.diff(old, new, compare="keys") == []
+
+    def test_array_elements_compared_by_position(self):
+        old = {"servers": [{"name": "a", "port": 1}]}
+        new = {"servers": [{"name": "b"}]}
+
+        results = diffx_python.diff(old, new, compare="keys")
+
+        assert [(r["type"], r["path"]) for r in results] == [
+            ("Removed", "servers[0].port")
+        ]
+
+
+class TestDiffOptions:
+    """DiffOptions bundles validated, reusable diff options"""
+
+    def test_options_are_applied(self):
+        options = diffx_python.DiffOptions(epsilon=0.1)
+
+        assert diffx_python.diff({"a": 1.0}, {"a": 1.05}, options=options) == []
+
+    def test_keyword_arguments_override_options(self):
+        options = diffx_python.DiffOptions(epsilon=0.1)
+
+        results = diffx_python.diff(
+            {"a": 1.0}, {"a": 1.05}, options=options, epsilon=0.01
+        )
+
+        assert len(results) == 1
+
+    def test_invalid_values_rejected_on_construction(self):
+        with pytest.raises(ValueError, match="regex"):
+            diffx_python.DiffOptions(ignore_keys_regex="[unclosed")
+        with pytest.raises(ValueError, match="^Invalid output format: invalid_format$"):
+            diffx_python.DiffOptions(output_format="invalid_format")
+
+    def test_unknown_option_rejected(self):
+        with pytest.raises(TypeError, match="did you mean 'epsilon'"):
+            diffx_python.DiffOptions(epsilion=0.1)
+
+    def test_replace_returns_validated_copy(self):
+        options = diffx_python.DiffOptions(epsilon=0.1, array_id_key="id")
+
+        replaced = options.replace(epsilon=None, ignore_case=True)
+
+        assert replaced == diffx_python.DiffOptions(array_id_key="id", ignore_case=True)
+        assert options.epsilon == 0.1
+        with pytest.raises(ValueError, match="timeout_ms"):
+            options.replace(timeout_ms=-1)
+
+    def test_fields_and_repr(self):
+        options = diffx_python.DiffOptions(epsilon=0.1, array_id_key="id")
+
+        assert options.array_id_key == "id"
+        assert options.ignore_paths is None
+        assert options.to_dict() == {"epsilon": 0.1, "array_id_key": "id"}
+        assert repr(options) == "DiffOptions(epsilon=0.1, array_id_key='id')"
+
+    def test_max_results_from_options_sets_truncated(self):
+        options = diffx_python.DiffOptions(max_results=1)
+
+        results = diffx_python.diff({"a": 1, "b": 2}, {"a": 2, "b": 3}, options=options)
+
+        assert len(results) == 1
+        assert results.truncated
+
+    def test_run_accepts_options(self, tmp_path):
+        old_file = tmp_path / "old.json"
+        new_file = tmp_path / "new.json"
+        old_file.write_text('{"price": 1.0}')
+        new_file.write_text('{"price": 1.05}')
+        options = diffx_python.DiffOptions(epsilon=0.1)
+
+        summary = diffx_python.run(
+            str(old_file),
+            str(new_file),
+            str(tmp_path / "report.json"),
+            "json",
+            options=options,
+        )
+
+        assert summary["total"] == 0
+
+
+class TestPresets:
+    """Named presets bundle a comparison policy"""
+
+    def test_lenient_preset(self):
+        old = [1, "Hello "]
+        new = ["hello", 1]
+
+        assert diffx_python.diff(old, new, preset="lenient") == []
+        assert len(diffx_python.diff(old, new, preset="strict")) == 2
+
+    def test_keyword_arguments_override_preset(self):
+        assert diffx_python.diff(["a", "B"], ["b", "A"], preset="lenient") == []
+
+        results = diffx_python.diff(
+            ["a", "B"], ["b", "A"], preset="lenient", array_order="ordered"
+        )
+        assert len(results) == 2
+        results = diffx_python.diff(
+            ["a", "B"], ["b", "A"], preset="lenient", ignore_case=False
+        )
+        assert results != []
+
+    def test_unknown_preset_lists_available(self):
+        with pytest.raises(ValueError, match="available: .*lenient"):
+            diffx_python.diff({}, {}, preset="lenientt")
+
+    def test_register_custom_preset(self):
+        diffx_python.register_preset(
+            "test_custom", preset="lenient", ignore_keys_regex="^updated$"
+        )
+
+        old = {"name": "Alice", "updated": 1}
+        new = {"name": "alice", "updated": 2}
+        assert diffx_python.diff(old, new, preset="test_custom") == []
+        assert "test_custom" in diffx_python.list_presets()
+        assert diffx_python.get_preset("test_custom").ignore_case is True
+
+    def test_register_from_diff_options(self):
+        options = diffx_python.DiffOptions(epsilon=0.5, max_results=1)
+        diffx_python.register_preset("test_from_options", options)
+
+        results = diffx_python.diff(
+            {"a": 1.0, "b": 1, "c": 1},
+            {"a": 1.2, "b": 2, "c": 2},
+            preset="test_from_options",
+        )
+
+        assert [r["path"] for r in results] == ["b"]
+        assert results.truncated
+
+    def test_invalid_preset_rejected_on_registration(self):
+        with pytest.raises(ValueError, match="regex"):
+            diffx_python.register_preset("test_invalid", ignore_keys_regex="[")
+        assert "test_invalid" not in diffx_python.list_presets()
+
+    def test_builtin_presets_cannot_be_replaced(self):
+        with pytest.raises(ValueError, match="built-in"):
+            diffx_python.register_preset("strict", epsilon=0.1)
+
+    def test_diff_options_with_preset(self):
+        options = diffx_python.DiffOptions(preset="lenient", epsilon=0.1)
+
+        assert options.ignore_whitespace is True
+        assert options.epsilon == 0.1
+        assert diffx_python.diff({"a": " x"}, {"a": "x"}, options=options) == []
+
+
+class TestKubernetesPreset:
+    """preset="kubernetes" compares live manifests against desired ones"""
+
+    LIVE = {
+        "kind": "Deployment",
+        "metadata": {
+            "name": "web",
+            "uid": "3f1c",
+            "resourceVersion": "4711",
+            "generation": 3,
+            "managedFields": [{"manager": "kubectl"}],
+        },
+        "spec": {
+            "replicas": 2,
+            "template": {
+                "spec": {
+                    "containers": [
+                        {
+                            "name": "web",
+                            "resources": {
+                                "limits": {"cpu": "500m", "memory": "1Gi"},
+                                "requests": {"cpu": "100m", "memory": "1e9"},
+                            },
+                        }
+                    ]
+                }
+            },
+        },
+        "status": {"readyReplicas": 2},
+    }
+    DESIRED = {
+        "kind": "Deployment",
+        "metadata": {"name": "web"},
+        "spec": {
+            "replicas": 2,
+            "template": {
+                "spec": {
+                    "containers": [
+                        {
+                            "name": "web",
+                            "resources": {
+                                "limits": {"cpu": 0.5, "memory": "1024Mi"},
+                                "requests": {"cpu": "0.1", "memory": "1G"},
+                            },
+                        }
+                    ]
+                }
+            },
+        },
+    }
+
+    def test_server_fields_and_equivalent_quantities_ignored(self):
+        assert diffx_python.diff(self.LIVE, self.DESIRED, preset="kubernetes") == []
+
+    def test_quantity_changes_reported_normalized(self):
+        desired = {"resources": {"limits": {"memory": "2Gi"}}}
+        live = {"resources": {"limits": {"memory": "1Gi"}}}
+
+        results = diffx_python.diff(live, desired, preset="kubernetes")
+
+        assert results == [
+            {
+                "type": "Modified",
+                "path": "resources.limits.memory",
+                "old_value": 1073741824,
+                "new_value": 2147483648,
+            }
+        ]
+
+    def test_list_items_aligned_by_name(self):
+        old = {"items": [self.LIVE, {"metadata": {"name": "db"}, "spec": {}}]}
+        new = {
+            "items": [
+                {"metadata": {"name": "db"}, "spec": {"replicas": 1}},
+                self.DESIRED,
+            ]
+        }
+
+        results = diffx_python.diff(old, new, preset="kubernetes")
+
+        assert results == [
+            {
+                "type": "Added",
+                "path": 'items[metadata.name="db"].spec.replicas',
+                "value": 1,
+            }
+        ]
+
+
+class TestTerraformPreset:
+    """preset="terraform" aligns state and plan resources by address"""
+
+    @staticmethod
+    def state(serial, ami, resources=()):
+        return {
+            "version": 4,
+            "serial": serial,
+            "lineage": f"lineage-{serial}",
+            "resources": [
+                *resources,
+                {
+                    "mode": "managed",
+                    "type": "aws_instance",
+                    "name": "web",
+                    "instances": [{"attributes": {"ami": ami}}],
+                },
+                {
+                    "module": "module.network",
+                    "mode": "data",
+                    "type": "aws_vpc",
+                    "name": "main",
+                    "instances": [{"attributes": {"cidr_block": "10.0.0.0/16"}}],
+                },
+            ],
+        }
+
+    def test_state_metadata_ignored(self):
+        results = diffx_python.diff(
+            self.state(1, "ami-1"), self.state(2, "ami-1"), preset="terraform"
+        )
+
+        assert results == []
+
+    def test_state_resources_aligned_by_address(self):
+        bucket = {
+            "mode": "managed",
+            "type": "aws_s3_bucket",
+            "name": "logs",
+            "instances": [],
+        }
+
+        results = diffx_python.diff(
+            self.state(1, "ami-1"),
+            self.state(2, "ami-2", [bucket]),
+            preset="terraform",
+        )
+
+        assert [(r["type"], r["path"]) for r in results] == [
+            (
+                "Modified",
+                'resources[address="aws_instance.web"].instances[0].attributes.ami',
+            ),
+            ("Added", 'resources[address="aws_s3_bucket.logs"]'),
+        ]
+
+    def test_module_and_data_sources_in_address(self):
+        old = self.state(1, "ami-1")
+        new = self.state(1, "ami-1")
+        new["resources"][1]["instances"][0]["attributes"]["cidr_block"] = "10.1.0.0/16"
+
+        results = diffx_python.diff(old, new, preset="terraform")
+
+        assert [r["path"] for r in results] == [
+            'resources[address="module.network.data.aws_vpc.main"]'
+            ".instances[0].attributes.cidr_block"
+        ]
+
+    def test_plan_resource_changes_aligned_by_address(self):
+        old = {
+            "timestamp": "2024-01-01T00:00:00Z",
+            "resource_changes": [
+                {"address": "aws_instance.web", "change": {"actions": ["no-op"]}},
+            ],
+        }
+        new = {
+            "timestamp": "2024-01-02T00:00:00Z",
+            "resource_changes": [
+                {"address": "aws_s3_bucket.logs", "change": {"actions": ["create"]}},
+                {"address": "aws_instance.web", "change": {"actions": ["update"]}},
+            ],
+        }
+
+        results = diffx_python.diff(old, new, preset="terraform")
+
+        assert [r["path"] for r in results] == [
+            'resource_changes[address="aws_instance.web"].change.actions[0]',
+            'resource_changes[address="aws_s3_bucket.logs"]',
+        ]
+
+
+class TestOpenApi:
+    """openapi=True aligns parameters and classifies breaking changes"""
+
+    OLD = {
+        "openapi": "3.0.0",
+        "info": {"version": "1.0"},
+        "paths": {
+            "/users": {
+                "get": {
+                    "summary": "List",
+                    "parameters": [
+                        {"name": "limit", "in": "query"},
+                        {"name": "X-Id", "in": "header"},
+                    ],
+                },
+                "delete": {"responses": {}},
+            }
+        },
+        "components": {
+            "schemas": {
+                "User": {
+                    "required": ["id"],
+                    "properties": {
+                        "id": {"type": "integer"},
+                        "description": {"type": "string"},
+                    },
+                }
+            }
+        },
+    }
+
+    @staticmethod
+    def changes(old, new):
+        results = diffx_python.diff(old, new, openapi=True, classify_breaking=True)
+        return {r["path"]: (r["type"], r["breaking"]) for r in results}
+
+    def test_parameters_aligned_by_name_and_location(self):
+        new = copy.deepcopy(self.OLD)
+        new["paths"]["/users"]["get"]["parameters"].reverse()
+
+        assert diffx_python.diff(self.OLD, new, openapi=True) == []
+        assert diffx_python.diff(self.OLD, new) != []
+
+    def test_parameter_changes(self):
+        new = copy.deepcopy(self.OLD)
+        parameters = new["paths"]["/users"]["get"]["parameters"]
+        parameters[0]["required"] = True
+        parameters.append({"name": "page", "in": "query"})
+        parameters.append({"name": "tenant", "in": "query", "required": True})
+
+        prefix = "paths./users.get.parameters"
+        assert self.changes(self.OLD, new) == {
+            f'{prefix}[name="limit",in="query"].required': ("Added", True),
+            f'{prefix}[name="page",in="query"]': ("Added", False),
+            f'{prefix}[name="tenant",in="query"]': ("Added", True),
+        }
+
+    def test_removals_break_and_documentation_does_not(self):
+        new = copy.deepcopy(self.OLD)
+        del new["paths"]["/users"]["delete"]
+        new["paths"]["/users"]["get"]["summary"] = "List users"
+        new["info"]["version"] = "2.0"
+
+        assert self.changes(self.OLD, new) == {
+            "info.version": ("Modified", False),
+            "paths./users.delete": ("Removed", True),
+            "paths./users.get.summary": ("Modified", False),
+        }
+
+    def test_schema_changes(self):
+        new = copy.deepcopy(self.OLD)
+        user = new["components"]["schemas"]["User"]
+        user["required"].append("email")
+        user["properties"]["email"] = {"type": "string"}
+        user["properties"]["id"]["type"] = "string"
+        del user["properties"]["description"]
+
+        prefix = "components.schemas.User"
+        assert self.changes(self.OLD, new) == {
+            f"{prefix}.properties.description": ("Removed", True),
+            f"{prefix}.properties.email": ("Added", False),
+            f"{prefix}.properties.id.type": ("Modified", True),
+            f"{prefix}.required[1]": ("Added", True),
+        }
+
+    def test_classify_breaking_requires_openapi(self):
+        with pytest.raises(ValueError, match="openapi=True"):
+            diffx_python.diff({}, {}, classify_breaking=True)
+
+
+class TestLockfilePresets:
+    """Lockfile presets report version transitions per package"""
+
+    def test_package_lock(self):
+        def lock(version, digest):
+            return {
+                "lockfileVersion": 3,
+                "packages": {
+                    "node_modules/lodash": {
+                        "version": version,
+                        "resolved": f"https://registry.npmjs.org/lodash-{version}.tgz",
+                        "integrity": f"sha512-{digest}",
+                    }
+                },
+            }
+
+        results = diffx_python.diff(
+            lock("4.17.20", "a"), lock("4.17.21", "b"), preset="package-lock"
+        )
+
+        assert results == [
+            {
+                "type": "Modified",
+                "path": "packages.node_modules/lodash.version",
+                "old_value": "4.17.20",
+                "new_value": "4.17.21",
+            }
+        ]
+
+    def test_poetry_lock(self):
+        old = {
+            "package": [
+                {"name": "certifi", "version": "2023.7.22", "files": [{"hash": "a"}]},
+                {"name": "requests", "version": "2.31.0", "files": [{"hash": "b"}]},
+            ],
+            "metadata": {"content-hash": "111"},
+        }
+        new = {
+            "package": [
+                {"name": "idna", "version": "3.6", "files": [{"hash": "c"}]},
+                {"name": "certifi", "version": "2023.11.17", "files": [{"hash": "d"}]},
+                {"name": "requests", "version": "2.31.0", "files": [{"hash": "b"}]},
+            ],
+            "metadata": {"content-hash": "222"},
+        }
+
+        results = diffx_python.diff(old, new, preset="poetry-lock")
+
+        assert [(r["type"], r["path"]) for r in results] == [
+            ("Modified", 'package[name="certifi"].version'),
+            ("Added", 'package[name="idna"]'),
+        ]
+
+    def test_cargo_lock_file(self, tmp_path):
+        package = '[[package]]\nname = "{}"\nversion = "{}"\nchecksum = "{}"\n\n'
+        app = '[[package]]\nname = "app"\nversion = "0.1.0"\ndependencies = [{}]\n\n'
+        old_dir = tmp_path / "old"
+        new_dir = tmp_path / "new"
+        old_dir.mkdir()
+        new_dir.mkdir()
+        (old_dir / "Cargo.lock").write_text(
+            "version = 3\n\n"
+            + app.format('"serde"')
+            + package.format("serde", "1.0.190", "aaa")
+        )
+        (new_dir / "Cargo.lock").write_text(
+            "version = 3\n\n"
+            + package.format("anyhow", "1.0.75", "bbb")
+            + app.format('"anyhow", "serde"')
+            + package.format("serde", "1.0.193", "ccc")
+        )
+
+        results = diffx_python.diff_files(
+            str(old_dir / "Cargo.lock"),
+            str(new_dir / "Cargo.lock"),
+            preset="cargo-lock",
+        )
+
+        assert [(r["type"], r["path"]) for r in results] == [
+            ("Added", 'package[name="app"].dependencies[0]'),
+            ("Modified", 'package[name="serde"].version'),
+            ("Added", 'package[name="anyhow"]'),
+        ]
+
+    def test_cargo_lock_crate_at_several_versions(self, tmp_path):
+        package = '[[package]]\nname = "{}"\nversion = "{}"\nchecksum = "{}"\n\n'
+        (tmp_path / "old").mkdir()
+        (tmp_path / "new").mkdir()
+        old_path = tmp_path / "old" / "Cargo.lock"
+        new_path = tmp_path / "new" / "Cargo.lock"
+        old_path.write_text(
+            "version = 3\n\n"
+            + package.format("syn", "1.0.109", "aaa")
+            + package.format("syn", "2.0.38", "bbb")
+        )
+        new_path.write_text(
+            "version = 3\n\n"
+            + package.format("syn", "1.0.109", "aaa")
+            + package.format("syn", "2.0.39", "ccc")
+        )
+
+        def diff(old, new):
+            return diffx_python.diff_files(str(old), str(new), preset="cargo-lock")
+
+        assert diff(old_path, old_path) == []
+        assert diff(old_path, new_path) == [
+            {
+                "type": "Modified",
+                "path": 'package[name="syn"].version',
+                "old_value": "2.0.38",
+                "new_value": "2.0.39",
+            }
+        ]
+
+
+class TestSemanticEqual:
+    """semantic_equal() answers whether diff() would find no differences"""
+
+    def test_equal_documents(self):
+        old = {"users": [{"id": 1, "tags": ["a", "b"]}], "active": True}
+        new = {"users": [{"id": 1, "tags": ["a", "b"]}], "active": True}
+
+        assert diffx_python.semantic_equal(old, new) is True
+
+    def test_different_documents(self):
+        old = {"users": [{"id": 1}], "active": True}
+
+        changed = {"users": [{"id": 2}], "active": True}
+
+        assert diffx_python.semantic_equal(old, changed) is False
+        assert diffx_python.semantic_equal(old, {"users": [{"id": 1}]}) is False
+
+    def test_agrees_with_diff_on_types(self):
+        for old, new in [(1, 1.0), (True, 1), ({"a": None}, {"a": 0})]:
+            expected = len(diffx_python.diff(old, new)) == 0
+            assert diffx_python.semantic_equal(old, new) is expected
+
+    def test_options_apply(self):
+        assert diffx_python.semantic_equal({"a": 1.0}, {"a": 1.05}, epsilon=0.1)
+        assert diffx_python.semantic_equal(["X", "y"], ["Y", "x"], preset="lenient")
+        assert diffx_python.semantic_equal(
+            {"a": 1, "ts": 1}, {"a": 1, "ts": 2}, ignore_keys_regex="^ts$"
+        )
+
+    def test_comparators_consulted_for_identical_values(self):
+        never_equal = {"a": lambda old, new: False}
+
+        assert not diffx_python.semantic_equal(
+            {"a": 1}, {"a": 1}, comparators=never_equal
+        )
+
+    def test_datetime_tolerance_does_not_hide_later_differences(self):
+        old = {"a": "2024-01-01T00:00:00Z", "b": 1}
+        new = {"a": "2024-01-01T00:00:01Z", "b": 2}
+
+        assert not diffx_python.semantic_equal(old, new, datetime_tolerance=5)
+        new["b"] = 1
+        assert diffx_python.semantic_equal(old, new, datetime_tolerance=5)
+
+    def test_unsupported_types_still_rejected(self):
+        value = {"a": object()}
+
+        with pytest.raises(TypeError):
+            diffx_python.semantic_equal(value, value)
+
+    def test_circular_reference_rejected(self):
+        value = {}
+        value["x"] = value
+
+        with pytest.raises(diffx_python.CircularReferenceError):
+            diffx_python.semantic_equal(value, value)
+
+
+class TestSimilarity:
+    """similarity() scores the share of matching leaves"""
+
+    def test_bounds(self):
+        assert diffx_python.similarity({"a": 1}, {"a": 1}) == 1.0
+        assert diffx_python.similarity({"a": 1}, {"b": 2}) == 0.0
+        assert diffx_python.similarity({}, {}) == 1.0
+
+    def test_proportion_of_matching_leaves(self):
+        old = {"a": {"x": 1, "y": 2}, "b": [1, 2]}
+        new = {"a": {"x": 1, "y": 3}, "b": [1, 2]}
+
+        assert diffx_python.similarity(old, new) == 0.75
+
+    def test_added_leaves_lower_the_score(self):
+        score = diffx_python.similarity({"a": 1}, {"a": 1, "b": 2, "c": 3})
+
+        assert score == pytest.approx(0.5)
+
+    def test_options_apply(self):
+        old = {"items": [1, 2, 3], "ts": 1}
+        new = {"items": [3, 1, 2], "ts": 2}
+
+        assert diffx_python.similarity(old, new) < 0.5
+        score = diffx_python.similarity(
+            old, new, array_order="unordered", ignore_paths=["ts"]
+        )
+        assert score == 1.0
+
+    def test_max_results_does_not_cut_scoring_short(self):
+        old = {"a": 1, "b": 2}
+        new = {"a": 1, "b": 3}
+
+        assert diffx_python.similarity(old, new, max_results=0) == 0.5
+
+
+class TestCanonicalize:
+    """canonicalize() returns the document as diff() compares it"""
+
+    def test_without_options_sorts_keys_only(self):
+        doc = {"b": [3, 1], "a": "X"}
+        result = diffx_python.canonicalize(doc)
+        assert result == doc
+        assert list(result) == ["a", "b"]
+
+    def test_ignored_keys_and_paths_are_removed(self):
+        doc = {"name": "web", "updatedAt": 1, "meta": {"uid": "x", "labels": {}}}
+        result = diffx_python.canonicalize(
+            doc, ignore_keys_regex="^updatedAt$", ignore_paths=["meta.uid"]
+        )
+        assert result == {"name": "web", "meta": {"labels": {}}}
+
+    def test_include_paths_keep_only_included_subtrees(self):
+        doc = {"spec": {"replicas": 3, "image": "web"}, "status": {"ready": 1}}
+        result = diffx_python.canonicalize(doc, include_paths=["spec.replicas"])
+        assert result == {"spec": {"replicas": 3}}
+
+    def test_strings_are_folded(self):
+        result = diffx_python.canonicalize(
+            {"a": "Hello World"}, ignore_case=True, ignore_whitespace=True
+        )
+        assert result == {"a": "helloworld"}
+
+    def test_ignored_values_are_replaced(self):
+        result = diffx_python.canonicalize(
+            {"id": "2024-01-02", "n": "x"}, ignore_values_regex=r"^\d{4}-\d\d-\d\d$"
+        )
+        assert result == {"id": "<ignored>", "n": "x"}
+
+    def test_numbers_round_to_epsilon(self):
+        result = diffx_python.canonicalize(
+            {"a": 1.04, "b": -0.01, "c": 7}, epsilon=0.1, epsilons={"c": 5}
+        )
+        assert result == {"a": pytest.approx(1.0), "b": 0.0, "c": 5.0}
+
+    def test_unordered_arrays_are_sorted(self):
+        old = {"tags": ["b", "a", {"x": 1}]}
+        new = {"tags": [{"x": 1}, "a", "b"]}
+        assert diffx_python.canonicalize(
+            old, array_order="unordered"
+        ) == diffx_python.canonicalize(new, array_order="unordered")
+        assert diffx_python.canonicalize(old)["tags"] == ["b", "a", {"x": 1}]
+
+    def test_id_keyed_arrays_are_sorted(self):
+        doc = [{"id": 2, "v": "b"}, {"id": 1, "v": "a"}]
+        assert diffx_python.canonicalize(doc, array_id_key="id") == [
+            {"id": 1, "v": "a"},
+            {"id": 2, "v": "b"},
+        ]
+        moves = diffx_python.canonicalize(doc, array_id_key="id", detect_moves=True)
+        assert moves == doc
+
+    def test_normalize_and_presets_apply(self):
+        result = diffx_python.canonicalize({"a": "X"}, normalize=str.lower)
+        assert result == {"a": "x"}
+        limits = {"resources": {"limits": {"cpu": "500m"}}}
+        manifest = {"spec": limits, "status": {"phase": "Running"}}
+        assert diffx_python.canonicalize(manifest, preset="kubernetes") == {
+            "spec": {"resources": {"limits": {"cpu": 0.5}}}
+        }
+
+    def test_equal_documents_share_a_canonical_form(self):
+        options = {"ignore_keys_regex": "^ts$", "ignore_case": True}
+        old = {"name": "Web", "ts": 1}
+        new = {"name": "web", "ts": 2}
+        assert diffx_python.diff(old, new, **options) == []
+        assert diffx_python.canonicalize(old, **options) == diffx_python.canonicalize(
+            new, **options
+        )
+
+    def test_unknown_option_raises(self):
+        with pytest.raises(TypeError, match="canonicalize"):
+            diffx_python.canonicalize({}, ignore_kyes_regex="x")
+
+
+class TestSemanticHash:
+    """semantic_hash() digests the canonical form of a document"""
+
+    def test_is_sha256_of_canonical_json(self):
+        doc = {"b": [1, 2], "a": "x"}
+        expected = hashlib.sha256(b'{"a":"x","b":[1,2]}').hexdigest()
+        assert diffx_python.semantic_hash(doc) == expected
+
+    def test_key_order_does_not_matter(self):
+        assert diffx_python.semantic_hash(
+            {"a": 1, "b": 2}
+        ) == diffx_python.semantic_hash({"b": 2, "a": 1})
+
+    def test_respects_ignore_rules_and_epsilon(self):
+        options = {"ignore_paths": ["meta"], "epsilon": 0.1}
+        old = {"price": 9.99, "meta": {"fetched": 1}}
+        new = {"price": 10.01, "meta": {"fetched": 2}}
+        assert diffx_python.semantic_hash(old) != diffx_python.semantic_hash(new)
+        assert diffx_python.semantic_hash(old, **options) == diffx_python.semantic_hash(
+            new, **options
+        )
+
+    def test_accepts_diff_options(self):
+        options = diffx_python.DiffOptions(array_order="unordered")
+        assert diffx_python.semantic_hash(
+            [1, 2], options=options
+        ) == diffx_python.semantic_hash([2, 1], options=options)
+
+    def test_different_documents_differ(self):
+        assert diffx_python.semantic_hash({"a": 1}) != diffx_python.semantic_hash(
+            {"a": "1"}
+        )
+
+
+class TestDiffStats:
+    """diff_stats() summarizes a diff without returning the results"""
+
+    def test_counts_per_kind_and_top_level_key(self):
+        old = {"users": [{"id": 1, "n": "a"}, {"id": 2}], "cfg": {"x": 1}, "v": 1}
+        new = {"users": [{"id": 1, "n": "b"}], "cfg": {"x": 2}, "v": 1, "new": 3}
+
+        stats = diffx_python.diff_stats(old, new)
+
+        assert stats["total"] == 4
+        assert stats["added"] == 1
+        assert stats["removed"] == 1
+        assert stats["modified"] == 2
+        assert stats["unchanged"] == 0
+        assert stats["by_top_level_key"] == {"users": 2, "cfg": 1, "new": 1}
+        assert stats["truncated"] is False
+
+    def test_traversal_stats(self):
+        stats = diffx_python.diff_stats({"a": {"b": [1, 2]}}, {"a": {"b": [1, 3]}})
+
+        assert stats["max_depth"] == 3
+        assert stats["nodes_compared"] == 5
+
+    def test_equal_inputs(self):
+        stats = diffx_python.diff_stats({"a": 1}, {"a": 1})
+
+        assert stats["total"] == 0
+        assert stats["by_top_level_key"] == {}
+
+    def test_options_apply(self):
+        old = {"a": 1, "ts": 1}
+        new = {"a": 1, "ts": 2}
+
+        stats = diffx_python.diff_stats(old, new, ignore_paths=["ts"])
+        assert stats["total"] == 0
+
+        stats = diffx_python.diff_stats(old, new, report_unchanged=True)
+        assert stats["total"] == 1
+        assert stats["unchanged"] == 1
+        assert stats["by_top_level_key"] == {"ts": 1}
+
+    def test_max_results_sets_truncated(self):
+        stats = diffx_python.diff_stats(
+            {"a": 1, "b": 2}, {"a": 2, "b": 3}, max_results=1
+        )
+
+        assert stats["total"] == 1
+        assert stats["truncated"] is True
+
+
+class TestToTree:
+    """to_tree() nests results by path"""
+
+    def test_changes_attach_at_their_location(self):
+        old = {"users": [{"name": "a"}], "cfg": {"x": 1}}
+        new = {"users": [{"name": "b"}], "cfg": {"x": 2, "y": 1}}
+
+        tree = diffx_python.diff(old, new).to_tree()
+
+        assert tree["changes"] == []
+        assert set(tree["children"]) == {"users", "cfg"}
+        name = tree["children"]["users"]["children"]["[0]"]["children"]["name"]
+        assert name["changes"] == [
+            {
+                "type": "Modified",
+                "path": "users[0].name",
+                "old_value": "a",
+                "new_value": "b",
+            }
+        ]
+        assert name["children"] == {}
+        cfg = tree["children"]["cfg"]["children"]
+        assert [c["type"] for c in cfg["y"]["changes"]] == ["Added"]
+
+    def test_root_change(self):
+        tree = diffx_python.to_tree(diffx_python.diff(1, 2))
+
+        assert len(tree["changes"]) == 1
+        assert tree["children"] == {}
+
+    def test_node_holds_changes_and_children(self):
+        results = [
+            {"type": "Moved", "path": "items[1]", "from_index": 1, "to_index": 0},
+            {"type": "Added", "path": "items[1].x", "value": 1},
+        ]
+
+        item = diffx_python.to_tree(results)["children"]["items"]["children"]["[1]"]
+
+        assert item["changes"] == [results[0]]
+        assert item["children"]["x"]["changes"] == [results[1]]
+
+    def test_empty(self):
+        assert diffx_python.to_tree([]) == {"changes": [], "children": {}}
+
+
+class TestGroupByPrefix:
+    """group_by_prefix() groups results by leading path segments"""
+
+    OLD = {"users": [{"name": "a"}], "cfg": {"x": 1}, "v": 1}
+    NEW = {"users": [{"name": "b"}], "cfg": {"x": 2, "y": 1}, "v": 2}
+
+    def test_top_level_keys(self):
+        groups = diffx_python.diff(self.OLD, self.NEW).group_by_prefix()
+
+        assert {key: len(group) for key, group in groups.items()} == {
+            "cfg": 2,
+            "users": 1,
+            "v": 1,
+        }
+        assert [r["path"] for r in groups["cfg"]] == ["cfg.x", "cfg.y"]
+
+    def test_deeper_prefixes(self):
+        results = diffx_python.diff(self.OLD, self.NEW)
+
+        groups = diffx_python.group_by_prefix(results, depth=2)
+
+        assert set(groups) == {"cfg.x", "cfg.y", "users[0]", "v"}
+
+    def test_root_change(self):
+        groups = diffx_python.group_by_prefix(diffx_python.diff(1, 2))
+
+        assert list(groups) == [""]
+
+    def test_depth_must_be_positive(self):
+        with pytest.raises(ValueError, match="depth"):
+            diffx_python.group_by_prefix([], depth=0)
+
+
+class TestSortResults:
+    """sort= and sort_results() give a stable result order"""
+
+    OLD = {"b": list(range(12)), "a": 1, "c": {"x": 1}}
+    NEW = {"b": [0, 1, -2] + list(range(3, 10)) + [-10, 11], "a": 2, "c": {}, "d": 1}
+
+    def paths(self, results):
+        return [r["path"] for r in results]
+
+    def test_path_order_compares_strings(self):
+        results = diffx_python.diff(self.OLD, self.NEW, sort="path")
+
+        assert self.paths(results) == ["a", "b[10]", "b[2]", "c.x", "d"]
+
+    def test_document_order_compares_indices_numerically(self):
+        results = diffx_python.diff(self.OLD, self.NEW, sort="document")
+
+        assert self.paths(results) == ["a", "b[2]", "b[10]", "c.x", "d"]
+
+    def test_type_order_groups_kinds(self):
+        results = diffx_python.diff(self.OLD, self.NEW, sort="type")
+
+        assert [(r["type"], r["path"]) for r in results] == [
+            ("Added", "d"),
+            ("Removed", "c.x"),
+            ("Modified", "a"),
+            ("Modified", "b[2]"),
+            ("Modified", "b[10]"),
+        ]
+
+    def test_order_does_not_depend_on_input_order(self):
+        results = diffx_python.diff(self.OLD, self.NEW)
+        shuffled = list(reversed(results))
+
+        for by in ("path", "type", "document"):
+            assert diffx_python.sort_results(shuffled, by) == diffx_python.diff(
+                self.OLD, self.NEW, sort=by
+            )
+
+    def test_sort_results_returns_a_new_list(self):
+        results = diffx_python.diff(self.OLD, self.NEW, sort="document")
+        before = list(results)
+
+        sorted_results = diffx_python.sort_results(results)
+
+        assert results == before
+        assert self.paths(sorted_results) == ["a", "b[10]", "b[2]", "c.x", "d"]
+
+    def test_parents_sort_before_children(self):
+        results = [
+            {"type": "Added", "path": "items[1].x", "value": 1},
+            {"type": "Moved", "path": "items[1]", "from_index": 1, "to_index": 0},
+        ]
+
+        sorted_results = diffx_python.sort_results(results, by="document")
+
+        assert self.paths(sorted_results) == ["items[1]", "items[1].x"]
+
+    def test_invalid_order(self):
+        with pytest.raises(ValueError, match="Invalid sort"):
+            diffx_python.diff({}, {}, sort="random")
+        with pytest.raises(ValueError, match="Invalid sort"):
+            diffx_python.sort_results([], by="random")
+
+
+class TestResultFilters:
+    """filter_by_type() and filter_by_path() prune result lists"""
+
+    OLD = {"spec": {"image": "v1", "ports": [80]}, "legacy": 1}
+    NEW = {"spec": {"image": "v2", "ports": [80, 443]}, "status": "ok"}
+
+    def test_filter_by_type(self):
+        results = diffx_python.diff(self.OLD, self.NEW)
+
+        added = diffx_python.filter_by_type(results, ["Added"])
+        changed = results.filter_by_type(["Modified", "Removed"])
+
+        assert [r["path"] for r in added] == ["spec.ports[1]", "status"]
+        assert [r["path"] for r in changed] == ["legacy", "spec.image"]
+        assert results.filter_by_type("Removed") == [
+            {"type": "Removed", "path": "legacy", "value": 1}
+        ]
+
+    def test_filter_by_path_includes_descendants(self):
+        results = diffx_python.diff(self.OLD, self.NEW)
+
+        spec = diffx_python.filter_by_path(results, "spec")
+        ports = results.filter_by_path(["spec.ports[*]", "status"])
+
+        assert [r["path"] for r in spec] == ["spec.image", "spec.ports[1]"]
+        assert [r["path"] for r in ports] == ["spec.ports[1]", "status"]
+
+    def test_methods_keep_truncated(self):
+        results = diffx_python.diff(self.OLD, self.NEW, max_results=2)
+
+        assert results.filter_by_type("Modified").truncated is True
+        assert results.filter_by_path("**").truncated is True
+
+    def test_unknown_type(self):
+        with pytest.raises(ValueError, match="Unknown result type 'Changed'"):
+            diffx_python.filter_by_type([], "Changed")
+
+
+class TestFlatten:
+    """flatten() and unflatten() convert between documents and path mappings"""
+
+    DOC = {"spec": {"ports": [80, {"name": "tls"}], "labels": {}}, "replicas": 2}
+
+    def test_flatten_uses_result_paths(self):
+        flat = diffx_python.flatten(self.DOC)
+
+        assert flat == {
+            "replicas": 2,
+            "spec.labels": {},
+            "spec.ports[0]": 80,
+            "spec.ports[1].name": "tls",
+        }
+        new = {"spec": {"ports": [80, {"name": "https"}], "labels": {}}}
+        for result in diffx_python.diff(self.DOC, new):
+            if result["type"] == "Modified":
+                assert flat[result["path"]] == result["old_value"]
+
+    def test_round_trip(self):
+        assert diffx_python.unflatten(diffx_python.flatten(self.DOC)) == self.DOC
+        assert diffx_python.flatten("x") == {"": "x"}
+        assert diffx_python.unflatten({"": "x"}) == "x"
+        assert diffx_python.unflatten({}) == {}
+
+    def test_conflicting_paths(self):
+        with pytest.raises(ValueError, match="conflicts"):
+            diffx_python.unflatten({"a": 1, "a.b": 2})
+
+    def test_list_gaps(self):
+        with pytest.raises(ValueError, match="no value for 'a\\[0\\]'"):
+            diffx_python.unflatten({"a[1]": 1})
+
+    def test_id_segments(self):
+        with pytest.raises(ValueError, match="list position"):
+            diffx_python.unflatten({"items[id=1]": {}})
+
+    def test_special_keys_escaped(self):
+        doc = {"a.b": 1, "a": {"b": 2}, "c[0]": 3, "d\\e": [4]}
+
+        flat = diffx_python.flatten(doc)
+
+        assert flat == {"a\\.b": 1, "a.b": 2, "c\\[0\\]": 3, "d\\\\e[0]": 4}
+        assert diffx_python.unflatten(flat) == doc
+
+    def test_empty_key_rejected(self):
+        with pytest.raises(ValueError, match="Empty keys"):
+            diffx_python.flatten({"": 1})
+
+
+class TestPathSegments:
+    """path_style="segments" adds unambiguous path segments to each result"""
+
+    def test_keys_and_indices(self):
+        results = diffx_python.diff(
+            {"items": [{"name": "a"}]},
+            {"items": [{"name": "b"}]},
+            path_style="segments",
+        )
+
+        assert results == [
+            {
+                "type": "Modified",
+                "path": "items[0].name",
+                "old_value": "a",
+                "new_value": "b",
+                "segments": ["items", 0, "name"],
+            }
+        ]
+
+    def test_keys_containing_separators(self):
+        old = {"a.b": {"c": 1}, "a": {"b": {"c": 5}}, "k[0]": 1}
+        new = {"a.b": {"c": 2}, "a": {"b": {"c": 6}}}
+
+        results = diffx_python.diff(old, new, path_style="segments")
+
+        segments = {r["old_value"]: r["segments"] for r in results if "old_value" in r}
+        assert segments == {1: ["a.b", "c"], 5: ["a", "b", "c"]}
+        removed = results.filter_by_type("Removed")
+        assert removed[0]["segments"] == ["k[0]"]
+
+    def test_id_segments(self):
+        results = diffx_python.diff(
+            {"items": [{"id": 1, "v": "x"}]},
+            {"items": [{"id": 1, "v": "y"}]},
+            array_id_key="id",
+            path_style="segments",
+        )
+
+        assert results[0]["segments"] == ["items", {"id": 1}, "v"]
+
+    def test_renames_get_new_segments(self):
+        results = diffx_python.diff(
+            {"x.y": {"a": 1}},
+            {"z": {"a": 1}},
+            detect_renames=True,
+            path_style="segments",
+        )
+
+        assert results[0]["segments"] == ["x.y"]
+        assert results[0]["new_segments"] == ["z"]
+
+    def test_string_style_by_default(self):
+        assert "segments" not in diffx_python.diff({"a": 1}, {"a": 2})[0]
+
+    def test_invalid_style(self):
+        with pytest.raises(ValueError, match="Invalid path_style"):
+            diffx_python.DiffOptions(path_style="list")
+
+
+class TestPathHelpers:
+    """parse_path() and format_path() convert between paths and segments"""
+
+    def test_parse_path(self):
+        assert diffx_python.parse_path("spec.ports[0].name") == [
+            "spec",
+            "ports",
+            0,
+            "name",
+        ]
+        assert diffx_python.parse_path("items[id=1]") == ["items", {"id": 1}]
+        assert diffx_python.parse_path("") == []
+
+    def test_escaped_keys(self):
+        path = r"labels.app\.kubernetes\.io/name"
+
+        segments = diffx_python.parse_path(path)
+
+        assert segments == ["labels", "app.kubernetes.io/name"]
+        assert diffx_python.format_path(segments) == path
+        assert diffx_python.format_path(["a[0]", "b\\c"]) == r"a\[0\].b\\c"
+
+    def test_round_trips_result_segments(self):
+        results = diffx_python.diff(
+            {"a.b": [{"id": "x]", "v": 1}]},
+            {"a.b": [{"id": "x]", "v": 2}]},
+            array_id_key="id",
+            path_style="segments",
+        )
+
+        path = diffx_python.format_path(results[0]["segments"])
+
+        assert path == r'a\.b[id="x]"].v'
+        assert diffx_python.parse_path(path) == results[0]["segments"]
+
+    def test_malformed_paths(self):
+        for path in ["a..b", "a.", "a[x]", "a[0", "a]", "a[0]b"]:
+            with pytest.raises(ValueError, match="Invalid path"):
+                diffx_python.parse_path(path)
+
+    def test_invalid_segments(self):
+        with pytest.raises(TypeError, match="str, int or dict"):
+            diffx_python.format_path([1.5])
+        with pytest.raises(ValueError, match="negative"):
+            diffx_python.format_path(["a", -1])
+
+
+class TestPathAccess:
+    """get_at_path() and set_at_path() follow the paths diff results carry"""
+
+    def test_get_result_context(self):
+        old = {"spec": {"ports": [{"port": 80, "name": "http"}]}}
+        new = {"spec": {"ports": [{"port": 8080, "name": "http"}]}}
+
+        result = diffx_python.diff(old, new)[0]
+
+        assert diffx_python.get_at_path(old, result["path"]) == 80
+        parent = result["path"].rsplit(".", 1)[0]
+        assert diffx_python.get_at_path(new, parent) == {"port": 8080, "name": "http"}
+
+    def test_keys_containing_dots(self):
+        doc = {"labels": {"app.kubernetes.io/name": "web"}}
+
+        assert diffx_python.get_at_path(doc, "labels.app.kubernetes.io/name") == "web"
+        assert diffx_python.get_at_path(doc, ["labels", "app.kubernetes.io/name"]) == (
+            "web"
+        )
+
+    def test_id_segments(self):
+        doc = {"items": [{"id": 1, "v": "x"}, {"id": 2, "v": "y"}]}
+
+        assert diffx_python.get_at_path(doc, "items[id=2].v") == "y"
+
+        diffx_python.set_at_path(doc, "items[id=2].v", "z")
+
+        assert doc["items"][1]["v"] == "z"
+
+    def test_set_applies_a_fix_in_place(self):
+        old = {"image": "v1", "ports": [80]}
+        new = {"image": "v2", "ports": [80, 443], "debug": True}
+
+        for result in diffx_python.diff(old, new):
+            if result["type"] == "Added":
+                diffx_python.set_at_path(old, result["path"], result["value"])
+            elif result["type"] == "Modified":
+                diffx_python.set_at_path(old, result["path"], result["new_value"])
+
+        assert old == new
+
+    def test_missing_paths(self):
+        doc = {"a": {"b": 1}, "l": [1]}
+
+        with pytest.raises(KeyError, match="a.c"):
+            diffx_python.get_at_path(doc, "a.c")
+        with pytest.raises(KeyError):
+            diffx_python.set_at_path(doc, "x.y", 1)
+        with pytest.raises(KeyError):
+            diffx_python.set_at_path(doc, "l[3]", 1)
+        with pytest.raises(ValueError, match="root"):
+            diffx_python.set_at_path(doc, "", 1)
+
+
+class TestResultStorage:
+    """results_to_json() and results_from_json() round-trip stored results"""
+
+    def test_round_trip(self):
+        results = diffx_python.diff(
+            {"a": 1, "b": [1, {"c": None}]},
+            {"a": "1", "b": [2, {"c": True}], "d": {}},
+            path_style="segments",
+        )
+
+        restored = diffx_python.results_from_json(
+            diffx_python.results_to_json(results)
+        )
+
+        assert restored == results
+        assert isinstance(restored, diffx_python.DiffResults)
+        assert restored.truncated is False
+
+    def test_documented_schema(self):
+        results = diffx_python.diff({"a": 1, "b": 2}, {}, max_results=1)
+
+        stored = json.loads(diffx_python.results_to_json(results))
+
+        assert stored == {
+            "format": "diffx-results",
+            "version": 1,
+            "truncated": True,
+            "results": [{"type": "Removed", "path": "a", "value": 1}],
+        }
+        assert diffx_python.results_from_json(json.dumps(stored)).truncated is True
+
+    def test_rejects_other_documents(self):
+        with pytest.raises(ValueError, match="Not a diffx results document"):
+            diffx_python.results_from_json("[]")
+        newer = {"format": "diffx-results", "version": 99, "results": []}
+        with pytest.raises(ValueError, match="Unsupported results version 99"):
+            diffx_python.results_from_json(json.dumps(newer))
+
+    def test_rejects_malformed_results(self):
+        results = [
+            {"type": "Added", "path": "a", "value": 1},
+            {"type": "Added", "path": "b"},
+        ]
+
+        with pytest.raises(ValueError, match="Invalid result 1: Missing 'value'"):
+            diffx_python.results_to_json(results)
+
+
+class TestPickling:
+    """Results, options and errors survive pickle, e.g. for multiprocessing"""
+
+    def test_results(self):
+        results = diffx_python.diff({"a": 1, "b": 2}, {}, max_results=1)
+
+        for protocol in range(pickle.HIGHEST_PROTOCOL + 1):
+            restored = pickle.loads(pickle.dumps(results, protocol=protocol))
+
+            assert restored == results
+            assert isinstance(restored, diffx_python.DiffResults)
+            assert restored.truncated is True
+
+    def test_options(self):
+        options = diffx_python.DiffOptions(
+            epsilon=0.1, ignore_paths=["meta"], preset="lenient"
+        )
+
+        for protocol in range(pickle.HIGHEST_PROTOCOL + 1):
+            restored = pickle.loads(pickle.dumps(options, protocol=protocol))
+
+            assert restored == options
+            assert repr(restored) == repr(options)
+        assert diffx_python.diff({"a": 1.0}, {"a": 1.05}, options=restored) == []
+
+    def test_errors(self):
+        error = diffx_python.DiffTimeoutError("Diff exceeded timeout_ms=5")
+
+        restored = pickle.loads(pickle.dumps(error))
+
+        assert isinstance(restored, diffx_python.DiffTimeoutError)
+        assert str(restored) == str(error)
+
+
+def apply_merge_patch(target, patch):
+    """Apply an RFC 7386 merge patch, as the receiving API would"""
+    if not isinstance(patch, dict):
+        return patch
+    result = dict(target) if isinstance(target, dict) else {}
+    for key, value in patch.items():
+        if value is None:
+            result.pop(key, None)
+        else:
+            result[key] = apply_merge_patch(result.get(key), value)
+    return result
+
+
+class TestMergePatch:
+    """to_merge_patch() exports results as an RFC 7386 JSON Merge Patch"""
+
+    def test_nested_changes(self):
+        old = {"name": "app", "spec": {"replicas": 1, "debug": True}, "ports": [80]}
+        new = {"name": "app", "spec": {"replicas": 3, "tls": {"on": True}}}
+
+        patch = diffx_python.to_merge_patch(diffx_python.diff(old, new))
+
+        assert patch == {
+            "spec": {"replicas": 3, "debug": None, "tls": {"on": True}},
+            "ports": None,
+        }
+        assert apply_merge_patch(old, patch) == new
+
+    def test_renames_and_type_changes(self):
+        old = {"hostname": "db1", "port": "5432"}
+        new = {"host": "db1", "port": 5432}
+
+        results = diffx_python.diff(old, new, detect_renames=True)
+        patch = diffx_python.to_merge_patch(results)
+
+        assert patch == {"hostname": None, "host": "db1", "port": 5432}
+        assert apply_merge_patch(old, patch) == new
+
+    def test_no_changes(self):
+        assert diffx_python.to_merge_patch([]) == {}
+        results = diffx_python.diff({"a": 1}, {"a": 1}, report_unchanged=True)
+        assert diffx_python.to_merge_patch(results) == {}
+
+    def test_rejects_changes_inside_lists(self):
+        results = diffx_python.diff({"ports": [80, 443]}, {"ports": [80, 8443]})
+
+        with pytest.raises(ValueError, match=r"'ports\[1\]'.*replaced whole"):
+            diffx_python.to_merge_patch(results)
+
+    def test_rejects_null_values(self):
+        for new in [{"a": None}, {"a": {"b": None}}]:
+            results = diffx_python.diff({"a": 1}, new)
+
+            with pytest.raises(ValueError, match="null values mean removal"):
+                diffx_python.to_merge_patch(results)
+
+
+class TestPatchValidation:
+    """validate_patch() checks stored changes against a drifted document"""
+
+    OLD = {"port": 80, "debug": True, "tags": ["a"], "db": {"host": "x"}}
+    NEW = {"port": 8080, "tags": ["a", "b"], "db": {"host": "x", "pool": 5}}
+
+    def test_clean_document(self):
+        stored = diffx_python.results_to_json(diffx_python.diff(self.OLD, self.NEW))
+        results = diffx_python.results_from_json(stored)
+
+        assert diffx_python.validate_patch(self.OLD, results) == []
+
+    def test_drifted_document(self):
+        results = diffx_python.diff(self.OLD, self.NEW)
+        drifted = {"port": 81, "tags": [], "db": {"host": "x", "pool": 3}}
+
+        issues = diffx_python.validate_patch(drifted, results)
+
+        found = {issue.path: (issue.type, issue.reason) for issue in issues}
+        assert found == {
+            "db.pool": ("Added", "exists"),
+            "debug": ("Removed", "missing"),
+            "port": ("Modified", "mismatch"),
+            "tags[1]": ("Added", "missing"),
+        }
+        mismatch = next(issue for issue in issues if issue.path == "port")
+        assert results[mismatch.index]["path"] == "port"
+        assert (mismatch.expected, mismatch.actual) == (80, 81)
+        assert "expected old value 80" in str(mismatch)
+
+    def test_does_not_modify_document(self):
+        drifted = {"port": 81, "tags": []}
+        snapshot = json.dumps(drifted)
+
+        diffx_python.validate_patch(drifted, diffx_python.diff(self.OLD, self.NEW))
+
+        assert json.dumps(drifted) == snapshot
+
+    def test_renames(self):
+        results = diffx_python.diff(
+            {"hostname": "db1"}, {"host": "db1"}, detect_renames=True
+        )
+
+        assert diffx_python.validate_patch({"hostname": "db1"}, results) == []
+        issues = diffx_python.validate_patch(
+            {"hostname": "db2", "host": "db1"}, results
+        )
+        assert [issue.reason for issue in issues] == ["mismatch", "exists"]
+        assert [issue.path for issue in issues] == ["hostname", "host"]
+
+    def test_keys_containing_dots(self):
+        results = [{"type": "Removed", "path": "a.b", "value": 1}]
+
+        assert diffx_python.validate_patch({"a.b": 1}, results) == []
+        assert diffx_python.validate_patch({"a": {"b": 1}}, results) == []
+
+
+# ============================================================================
+# COMPLEX DATA STRUCTURES WITH FIXTURES
+# ============================================================================
+
+
+class TestComplexStructures:
+    """Test complex data structures"""
+
+    def test_diff_nested_objects(self):
+        old = TestFixtures.nested_object_old()
+        new = TestFixtures.nested_object_new()
+
+        results = diffx_python.diff(old, new)
+
+        # Should find multiple changes in nested structure
+        assert len(results) > 1
+
+        # Verify some expected changes
+        paths = [result["path"] for result in results]
+        assert any("database.host" in path for path in paths)
+        assert any("monitoring" in path for path in paths)
+
+    def test_diff_large_dataset(self):
+        """Test performance with larger dataset"""
+        # Create large dataset (smaller than Rust version to avoid timeout)
+        old_data = {}
+        new_data = {}
+
+        for i in range(100):  # Smaller dataset for Python test
+            old_data[f"key_{i}"] = i
+            new_data[f"key_{i}"] = i + 1
+
+        results = diffx_python.diff(old_data, new_data)
+        assert len(results) == 100
+
+
+# ============================================================================
+# ERROR HANDLING TESTS
+# ============================================================================
+
+
+class TestErrorHandling:
+    """Test error handling in Python bindings"""
+
+    def test_invalid_regex_pattern(self):
+        old = {"test": "value"}
+        new = {"test": "value2"}
+
+        with pytest.raises(Exception):  # Should raise ValueError for invalid regex
+            diffx_python.diff(old, new, ignore_keys_regex="[invalid_regex")
+
+    def test_invalid_output_format(self):
+        old = {"test": "value"}
+        new = {"test": "value2"}
+
+        with pytest.raises(Exception):  # Should raise ValueError for invalid format
+            diffx_python.diff(old, new, output_format="invalid_format")
+
+    def test_unsupported_python_types(self):
+        """Test handling of complex Python types that can't be converted"""
+        # Most complex types should be convertible or raise clear errors
+        # This test ensures we handle edge cases gracefully
+
+        old = {"simple": "value"}
+        new = {"simple": "value"}
+
+        # These should work fine
+        results = diffx_python.diff(old, new)
+        assert len(results) == 0
+
+    def test_timeout_raises_diff_timeout_error(self):
+        old = {f"key{i}": {"value": i} for i in range(5000)}
+        new = {f"key{i}": {"value": i + 1} for i in range(5000)}
+
+        with pytest.raises(diffx_python.DiffTimeoutError, match="timeout_ms"):
+            diffx_python.diff(old, new, timeout_ms=0)
+
+    def test_timeout_error_is_timeout_error(self):
+        assert issubclass(diffx_python.DiffTimeoutError, TimeoutError)
+
+    def test_generous_timeout_completes(self):
+        results = diffx_python.diff({"a": 1}, {"a": 2}, timeout_ms=60_000)
+
+        assert len(results) == 1
+
+    def test_memory_limit_raises_memory_limit_error(self):
+        old = {f"key{i}": [i] * 100 for i in range(2000)}
+        new = {f"key{i}": [i + 1] * 100 for i in range(2000)}
+
+        with pytest.raises(diffx_python.MemoryLimitError, match="memory_limit_mb"):
+            diffx_python.diff(old, new, memory_limit_mb=1)
+
+    def test_generous_memory_limit_completes(self):
+        results = diffx_python.diff({"a": 1}, {"a": 2}, memory_limit_mb=64)
+
+        assert len(results) == 1
+
+    def test_unknown_option_suggests_close_match(self):
+        with pytest.raises(TypeError, match="epsilion.*did you mean 'epsilon'"):
+            diffx_python.diff({"a": 1.0}, {"a": 1.05}, epsilion=0.1)
+
+    def test_unknown_options_are_all_listed(self):
+        with pytest.raises(TypeError) as excinfo:
+            diffx_python.diff({}, {}, ignore_path=["a"], colour=True)
+
+        message = str(excinfo.value)
+        assert "'ignore_path' (did you mean 'ignore_paths'" in message
+        assert "'colour'" in message
+
+    def test_diff_strings_rejects_unknown_option(self):
+        with pytest.raises(TypeError, match="array_idkey"):
+            diffx_python.diff_strings("[]", "[]", "json", array_idkey="id")
+
+
+# ============================================================================
+# INTEGRATION TESTS
+# ============================================================================
+
+
+class TestIntegration:
+    """Integration tests that mirror Rust test scenarios"""
+
+    def test_unified_api_comprehensive(self):
+        """Comprehensive test using multiple options together"""
+        old = TestFixtures.array_with_ids_old()
+        new = TestFixtures.array_with_ids_new()
+
+        results = diffx_python.diff(old, new, array_id_key="id", output_format="json")
+
+        # Should detect changes in the array with ID-based comparison
+        assert len(results) > 0
+
+        # Verify result structure
+        for result in results:
+            assert "type" in result
+            assert "path" in result
+            assert result["type"] in ["Added", "Removed", "Modified", "TypeChanged"]
+
+    def test_type_conversion_fidelity(self):
+        """Test that data roundtrips through Rust conversion correctly"""
+        test_cases = [
+            TestFixtures.simple_object_old(),
+            TestFixtures.numeric_precision_old(),
+            TestFixtures.type_changes_old(),
+        ]
+
+        for test_data in test_cases:
+            # Diff with itself should produce no changes
+            results = diffx_python.diff(test_data, test_data)
+            assert len(results) == 0, f"Self-diff should be empty for {test_data}"
+
+    def test_backwards_compatibility_workflow(self):
+        """Test that common usage patterns work correctly"""
+        # Simulate common usage: load JSON, compare, get results
+        old_json_str = '{"name": "old", "version": 1}'
+        new_json_str = '{"name": "new", "version": 2}'
+
+        old_data = json.loads(old_json_str)
+        new_data = json.loads(new_json_str)
+
+        results = diffx_python.diff(old_data, new_data)
+
+        assert len(results) == 2  # name and version changed
+
+        # Results should be easily convertible back to JSON
+        results_json = json.dumps(results, default=str)
+        assert len(results_json) > 0
+
+
+# ============================================================================
+# FILE COMPARISON TESTS
+# ============================================================================
+
+
+class TestDiffFiles:
+    """Test diff_files() with multi-document YAML streams"""
+
+    OLD_STREAM = """
+kind: Service
+metadata:
+  name: web
+spec:
+  port: 80
+---
+kind: Deployment
+metadata:
+  name: web
+spec:
+  replicas: 2
+"""
+
+    NEW_STREAM = """
+kind: Deployment
+metadata:
+  name: web
+spec:
+  replicas: 3
+---
+kind: Service
+metadata:
+  name: web
+spec:
+  port: 80
+"""
+
+    def write_streams(self, tmp_path):
+        old_file = tmp_path / "old.yaml"
+        new_file = tmp_path / "new.yaml"
+        old_file.write_text(self.OLD_STREAM)
+        new_file.write_text(self.NEW_STREAM)
+        return str(old_file), str(new_file)
+
+    def test_multi_document_streams_compare_by_position(self, tmp_path):
+        old_file, new_file = self.write_streams(tmp_path)
+
+        results = diffx_python.diff_files(old_file, new_file)
+
+        paths = [result["path"] for result in results]
+        assert any(path.startswith("[0]") for path in paths)
+        assert any(path.startswith("[1]") for path in paths)
+
+    def test_multi_document_streams_align_by_document_key(self, tmp_path):
+        old_file, new_file = self.write_streams(tmp_path)
+
+        results = diffx_python.diff_files(
+            old_file, new_file, document_key=["kind", "metadata.name"]
+        )
+
+        assert len(results) == 1
+        assert results[0]["path"] == "Deployment/web.spec.replicas"
+        assert results[0]["old_value"] == 2
+        assert results[0]["new_value"] == 3
+
+    def test_duplicate_document_key_raises(self, tmp_path):
+        old_file, new_file = self.write_streams(tmp_path)
+
+        with pytest.raises(diffx_python.DiffError, match="Duplicate document key"):
+            diffx_python.diff_files(old_file, new_file, document_key="metadata.name")
+
+
+class TestLineNumbers:
+    """Test line_numbers=True and source_lines()"""
+
+    def test_source_lines_json(self):
+        content = '{\n  "a": [1,\n    {"b": 2}],\n  "c": {"d": 3}, "e": 4\n}'
+
+        lines = diffx_python.source_lines(content, "json")
+
+        assert lines == [
+            {"a": 2, "a[0]": 2, "a[1]": 3, "a[1].b": 3, "c": 4, "c.d": 4, "e": 4}
+        ]
+
+    def test_source_lines_yaml_stream(self):
+        content = "a: 1\nb:\n  - x\n  - y\n---\nc: 2\n"
+
+        lines = diffx_python.source_lines(content, "yaml")
+
+        assert lines == [{"a": 1, "b": 2, "b[0]": 3, "b[1]": 4}, {"c": 6}]
+
+    def test_source_lines_toml_and_ini(self):
+        toml = "a = 1\n\n[server]\nport = 80\n\n[[hosts]]\nname = 'x'\n"
+        ini = "[server]\nport = 80\n\nhost = a\n"
+
+        assert diffx_python.source_lines(toml, "toml") == [
+            {"a": 1, "server": 3, "server.port": 4, "hosts[0]": 6, "hosts[0].name": 7}
+        ]
+        assert diffx_python.source_lines(ini, "ini") == [
+            {"server": 1, "server.port": 2, "server.host": 4}
+        ]
+
+    def test_source_lines_rejects_other_formats(self):
+        with pytest.raises(ValueError, match="JSON, YAML, TOML and INI"):
+            diffx_python.source_lines("<a/>", "xml")
+
+    def test_diff_strings_attaches_lines(self):
+        old = '{\n  "host": "a",\n  "port": 80\n}'
+        new = '{\n  "port": 81,\n\n  "tls": true\n}'
+
+        results = diffx_python.diff_strings(old, new, "json", line_numbers=True)
+        by_path = {result["path"]: result for result in results}
+
+        assert by_path["host"]["old_line"] == 2
+        assert by_path["host"]["new_line"] is None
+        assert by_path["port"]["old_line"] == 3
+        assert by_path["port"]["new_line"] == 2
+        assert by_path["tls"]["old_line"] is None
+        assert by_path["tls"]["new_line"] == 4
+
+    def test_moved_elements_use_both_indexes(self):
+        old = "[\n  1,\n  2\n]"
+        new = "[\n  2,\n  1\n]"
+
+        results = diffx_python.diff_strings(
+            old, new, "json", line_numbers=True, detect_moves=True
+        )
+
+        moved = [result for result in results if result["type"] == "Moved"]
+        assert moved
+        for result in moved:
+            assert result["old_line"] == 2 + result["from_index"]
+            assert result["new_line"] == 2 + result["to_index"]
+
+    def test_lines_are_off_by_default(self):
+        results = diffx_python.diff_strings('{"a": 1}', '{"a": 2}', "json")
+
+        assert "old_line" not in results[0]
+
+    def test_toml_and_ini_lines(self):
+        results = diffx_python.diff_strings(
+            "a = 1\nb = 2", "b = 3", "toml", line_numbers=True
+        )
+        by_path = {result["path"]: result for result in results}
+
+        assert by_path["a"]["old_line"] == 1
+        assert by_path["b"]["old_line"] == 2
+        assert by_path["b"]["new_line"] == 1
+
+        results = diffx_python.diff_strings(
+            "[s]\nk = 1", "[s]\n\nk = 2", "cfg", line_numbers=True
+        )
+
+        assert (results[0]["old_line"], results[0]["new_line"]) == (2, 3)
+
+    def test_formats_without_positions_rejected(self, tmp_path):
+        with pytest.raises(ValueError, match="not 'xml'"):
+            diffx_python.diff_strings("<a>1</a>", "<a>2</a>", "xml", line_numbers=True)
+
+        old_file = tmp_path / "old.csv"
+        new_file = tmp_path / "new.csv"
+        old_file.write_text("a,b\n1,2\n")
+        new_file.write_text("a,b\n1,3\n")
+        with pytest.raises(ValueError, match="not 'csv'"):
+            diffx_python.diff_files(old_file, new_file, line_numbers=True)
+
+    def test_sniffed_format_gets_lines(self, tmp_path):
+        old_file = tmp_path / "old.conf"
+        new_file = tmp_path / "new.conf"
+        old_file.write_text('{\n  "port": 80\n}')
+        new_file.write_text('{\n\n  "port": 81\n}')
+
+        results = diffx_python.diff_files(old_file, new_file, line_numbers=True)
+
+        assert (results[0]["old_line"], results[0]["new_line"]) == (2, 3)
+
+    def test_yaml_streams_aligned_by_document_key(self, tmp_path):
+        old_file = tmp_path / "old.yaml"
+        new_file = tmp_path / "new.yaml"
+        old_file.write_text(TestDiffFiles.OLD_STREAM)
+        new_file.write_text(TestDiffFiles.NEW_STREAM)
+
+        results = diffx_python.diff_files(
+            str(old_file),
+            str(new_file),
+            document_key=["kind", "metadata.name"],
+            line_numbers=True,
+        )
+
+        assert results[0]["path"] == "Deployment/web.spec.replicas"
+        assert results[0]["old_line"] == 12
+        assert results[0]["new_line"] == 6
+
+    def test_yaml_streams_by_position(self, tmp_path):
+        old_file = tmp_path / "old.yaml"
+        new_file = tmp_path / "new.yaml"
+        old_file.write_text(TestDiffFiles.OLD_STREAM)
+        new_file.write_text(TestDiffFiles.NEW_STREAM)
+
+        results = diffx_python.diff_files(
+            str(old_file), str(new_file), line_numbers=True
+        )
+        by_path = {result["path"]: result for result in results}
+
+        assert by_path["[0].kind"]["old_line"] == 2
+        assert by_path["[1].kind"]["new_line"] == 8
+
+
+class TestComments:
+    """Test comments=True and source_comments()"""
+
+    OLD_YAML = """\
+# Listening port
+port: 80  # http
+hosts:
+  - a  # primary
+  - b
+motd: |
+  # part of the value
+  hello
+"""
+
+    NEW_YAML = """\
+# Listening port
+port: 80  # https
+hosts:
+  - a  # primary
+  - b  # backup
+motd: |
+  # part of the value
+  hello
+"""
+
+    def test_source_comments_yaml(self):
+        comments = diffx_python.source_comments(self.OLD_YAML, "yaml")
+
+        assert comments == [
+            {
+                "port": "Listening port\nhttp",
+                "hosts": None,
+                "hosts[0]": "primary",
+                "hosts[1]": None,
+                "motd": None,
+            }
+        ]
+
+    def test_source_comments_toml(self):
+        content = """\
+# Primary server
+[server]
+host = "a"  # the host
+ports = [
+  80,  # not a key
+]
+
+[[db]]
+name = "x"
+[[db]]
+# replica
+name = "y"
+"""
+
+        comments = diffx_python.source_comments(content, "toml")
+
+        assert comments == [
+            {
+                "server": "Primary server",
+                "server.host": "the host",
+                "server.ports": None,
+                "db[0]": None,
+                "db[0].name": None,
+                "db[1]": None,
+                "db[1].name": "replica",
+            }
+        ]
+
+    def test_source_comments_rejects_other_formats(self):
+        with pytest.raises(ValueError, match="only available for YAML, TOML and INI"):
+            diffx_python.source_comments("{}", "json")
+
+    def test_comment_changes_are_reported(self):
+        results = diffx_python.diff_strings(
+            self.OLD_YAML, self.NEW_YAML, "yaml", comments=True
+        )
+
+        assert results == [
+            {
+                "type": "Comment",
+                "path": "hosts[1]",
+                "old_comment": None,
+                "new_comment": "backup",
+            },
+            {
+                "type": "Comment",
+                "path": "port",
+                "old_comment": "Listening port\nhttp",
+                "new_comment": "Listening port\nhttps",
+            },
+        ]
+
+    def test_comments_are_off_by_default(self):
+        results = diffx_python.diff_strings(self.OLD_YAML, self.NEW_YAML, "yaml")
+
+        assert results == []
+
+    def test_added_keys_report_no_comment(self):
+        results = diffx_python.diff_strings(
+            "a: 1\n", "a: 1\nb: 2  # new\n", "yaml", comments=True
+        )
+
+        assert [result["type"] for result in results] == ["Added"]
+
+    def test_ini_comments(self):
+        old = "[app]\n# the port\nport = 80\n"
+        new = "[app]\n; the listening port\nport = 80\n"
+
+        results = diffx_python.diff_strings(old, new, "ini", comments=True)
+
+        assert results == [
+            {
+                "type": "Comment",
+                "path": "app.port",
+                "old_comment": "the port",
+                "new_comment": "the listening port",
+            }
+        ]
+
+    def test_ignore_paths_apply(self):
+        results = diffx_python.diff_strings(
+            self.OLD_YAML,
+            self.NEW_YAML,
+            "yaml",
+            comments=True,
+            ignore_paths=["hosts"],
+        )
+
+        assert [result["path"] for result in results] == ["port"]
+
+    def test_yaml_streams_and_line_numbers(self, tmp_path):
+        old_file = tmp_path / "old.yaml"
+        new_file = tmp_path / "new.yaml"
+        old_file.write_text("a: 1\n---\nb: 2  # two\n")
+        new_file.write_text("a: 1\n---\nb: 2  # 2\n")
+
+        results = diffx_python.diff_files(
+            str(old_file), str(new_file), comments=True, line_numbers=True
+        )
+
+        assert results == [
+            {
+                "type": "Comment",
+                "path": "[1].b",
+                "old_comment": "two",
+                "new_comment": "2",
+                "old_line": 3,
+                "new_line": 3,
+            }
+        ]
+
+    def test_results_round_trip(self):
+        results = diffx_python.diff_strings(
+            self.OLD_YAML, self.NEW_YAML, "yaml", comments=True
+        )
+
+        stored = diffx_python.results_to_json(results)
+
+        assert diffx_python.results_from_json(stored) == results
+
+    def test_max_results_counts_comments(self):
+        results = diffx_python.diff_strings(
+            self.OLD_YAML, self.NEW_YAML, "yaml", comments=True, max_results=1
+        )
+
+        assert [result["path"] for result in results] == ["hosts[1]"]
+        assert results.truncated
+
+    def test_sorted_with_other_changes(self):
+        old = "a: 1  # one\nb: 1\n"
+        new = "a: 1  # uno\nb: 2\n"
+
+        results = diffx_python.diff_strings(
+            old, new, "yaml", comments=True, sort="path", path_filter="a"
+        )
+
+        assert [result["type"] for result in results] == ["Comment"]
+        results = diffx_python.diff_strings(
+            old, new, "yaml", comments=True, sort="path"
+        )
+        assert [result["path"] for result in results] == ["a", "b"]
+
+    def test_core_formats(self):
+        results = diffx_python.diff_strings(
+            "a: 1  # one\n", "a: 1  # uno\n", "yaml", comments=True
+        )
+
+        assert json.loads(diffx_python.format_output(results, "json")) == [
+            {"Comment": ["a", "one", "uno"]}
+        ]
+        assert diffx_python.format_output(results, "diffx") == (
+            '{"Comment":["a","one","uno"]}\n'
+        )
+
+    def test_unified_shows_comment_lines(self):
+        old = {"a": 1, "b": 2}
+        results = diffx_python.diff_strings(
+            "a: 1  # one\nb: 2\n", "a: 1  # uno\nb: 2\n", "yaml", comments=True
+        )
+
+        output = diffx_python.format_output(results, "unified", old=old, new=old)
+
+        assert "-  // one\n" in output
+        assert "+  // uno\n" in output
+
+
+class TestRun:
+    """Test the run() parse -> diff -> format -> write workflow"""
+
+    def write_files(self, tmp_path):
+        old_file = tmp_path / "old.json"
+        new_file = tmp_path / "new.json"
+        old_file.write_text('{"name": "Alice", "age": 30, "role": "admin"}')
+        new_file.write_text('{"name": "Alice", "age": 31, "city": "<Tokyo>"}')
+        return old_file, new_file
+
+    def test_writes_html_report_and_returns_summary(self, tmp_path):
+        old_file, new_file = self.write_files(tmp_path)
+        report = tmp_path / "report.html"
+
+        summary = diffx_python.run(str(old_file), str(new_file), str(report))
+
+        assert summary["total"] == 3
+        assert summary["added"] == 1
+        assert summary["removed"] == 1
+        assert summary["modified"] == 1
+        assert summary["type_changed"] == 0
+        html = report.read_text()
+        assert html.startswith("<!DOCTYPE html>")
+        assert "&lt;Tokyo&gt;" in html
+
+    def test_writes_json_report(self, tmp_path):
+        old_file, new_file = self.write_files(tmp_path)
+        report = tmp_path / "report.json"
+
+        diffx_python.run(old_file, new_file, report, format="json")
+
+        assert len(json.loads(report.read_text())) == 3
+
+    def test_writes_unified_report(self, tmp_path):
+        old_file, new_file = self.write_files(tmp_path)
+        report = tmp_path / "report.diff"
+
+        diffx_python.run(old_file, new_file, report, format="unified")
+
+        lines = report.read_text().splitlines()
+        assert lines[0] == f"--- {old_file}"
+        assert lines[1] == f"+++ {new_file}"
+        assert '-  "age": 30,' in lines
+        assert '+  "age": 31,' in lines
+
+    def test_passes_diff_options(self, tmp_path):
+        old_file, new_file = self.write_files(tmp_path)
+        report = tmp_path / "report.html"
+
+        summary = diffx_python.run(
+            old_file, new_file, report, ignore_keys_regex="^(role|city)$"
+        )
+
+        assert summary["total"] == 1
+
+    def test_invalid_format_raises_before_writing(self, tmp_path):
+        old_file, new_file = self.write_files(tmp_path)
+        report = tmp_path / "report.txt"
+
+        with pytest.raises(ValueError):
+            diffx_python.run(old_file, new_file, report, format="invalid")
+        assert not report.exists()
+
+    def test_missing_file_raises(self, tmp_path):
+        with pytest.raises(FileNotFoundError):
+            diffx_python.run(
+                tmp_path / "missing.json", tmp_path / "missing.json", tmp_path / "r"
+            )
 
 
 # ============================================================================
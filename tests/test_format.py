@@ -3,7 +3,13 @@
 Based on diffx-js format.test.js for parity.
 """
 
+import csv
+import io
 import json
+import re
+import sys
+import unicodedata
+from xml.etree import ElementTree
 
 import pytest
 
@@ -57,6 +63,10 @@ class TestFormatOutput:
         with pytest.raises(Exception):
             diffx_python.format_output(results, "invalid")
 
+    def test_invalid_format_lists_formats(self):
+        with pytest.raises(ValueError, match='^Invalid format: "invalid" \\(expected'):
+            diffx_python.format_output([], "invalid")
+
     def test_handles_empty_results(self):
         output = diffx_python.format_output([], "json")
 
@@ -81,3 +91,445 @@ class TestFormatOutput:
 
         output = diffx_python.format_output(results, "json")
         assert isinstance(output, str)
+
+
+class TestHtmlFormat:
+    """format_output(results, "html") renders a standalone report"""
+
+    def test_renders_document(self):
+        results = diffx_python.diff({"name": "<Alice>"}, {"name": "Bob", "age": 1})
+
+        html = diffx_python.format_output(results, "html")
+
+        assert html.startswith("<!DOCTYPE html>")
+        assert "<style>" in html
+        assert "2 difference(s): 1 added, 1 modified" in html
+        assert '<tr class="added">' in html
+        assert "&quot;&lt;Alice&gt;&quot;" in html
+
+    def test_sections_nest_by_path(self):
+        old = {"spec": {"template": {"image": "v1"}, "replicas": 1}}
+        new = {"spec": {"template": {"image": "v2"}, "replicas": 2}}
+
+        html = diffx_python.format_output(diffx_python.diff(old, new), "html")
+
+        assert html.count("<details open>") == 3
+        assert '<span class="key">spec</span> <span class="count">2</span>' in html
+        # A chain without changes of its own collapses into one section
+        assert '<span class="key">template.image</span>' in html
+
+    def test_old_and_new_columns(self):
+        results = [
+            {"type": "TypeChanged", "path": "a", "old_value": 1, "new_value": "1"}
+        ]
+
+        html = diffx_python.format_output(results, "html")
+
+        assert "<th>Old value</th><th>New value</th>" in html
+        assert '<td class="value">1</td><td class="value">&quot;1&quot;</td>' in html
+        assert "1 type changed" in html
+
+    def test_fragment_scopes_its_styles(self):
+        results = diffx_python.diff({"a": 1}, {"a": 2})
+
+        html = diffx_python.format_output(results, "html", fragment=True)
+
+        assert html.startswith("<style>.diffx{")
+        assert "body{" not in html
+        assert "<!DOCTYPE" not in html
+        assert '<div class="diffx">' in html
+        assert '<tr class="modified">' in html
+
+    def test_notebook_display(self):
+        results = diffx_python.diff({"a": 1}, {"a": 2})
+
+        assert results._repr_html_() == diffx_python.format_output(
+            results, "html", fragment=True
+        )
+
+
+class TestUnifiedFormat:
+    """format_output(results, "unified") renders a unified diff"""
+
+    OLD = {"spec": {"image": "v1", "ports": [80], "replicas": 2}, "legacy": 1}
+    NEW = {"spec": {"image": "v2", "ports": [80, 443], "replicas": 2}}
+
+    def render(self, old, new, **kwargs):
+        results = diffx_python.diff(old, new, **kwargs)
+        return diffx_python.format_output(results, "unified", old=old, new=new)
+
+    def test_renders_canonical_documents(self):
+        output = self.render(self.OLD, self.NEW)
+
+        assert output.splitlines() == [
+            "--- old",
+            "+++ new",
+            "@@ -1,9 +1,9 @@ legacy",
+            " {",
+            '-  "legacy": 1,',
+            '   "spec": {',
+            '-    "image": "v1",',
+            '+    "image": "v2",',
+            '     "ports": [',
+            "-      80",
+            "+      80,",
+            "+      443",
+            "     ],",
+            '     "replicas": 2',
+            "   }",
+        ]
+
+    def test_hunk_headers_name_the_changed_path(self):
+        old = {key: index for index, key in enumerate("abcdefghij")}
+        new = dict(old, a=-1, j=-1)
+
+        output = self.render(old, new)
+
+        headers = [line for line in output.splitlines() if line.startswith("@@")]
+        assert headers == ["@@ -1,5 +1,5 @@ a", "@@ -8,5 +8,5 @@ j"]
+
+    def test_context(self):
+        results = diffx_python.diff({"a": 1, "b": 2}, {"a": 1, "b": 3})
+
+        output = diffx_python.format_output(
+            results, "unified", old={"a": 1, "b": 2}, new={"a": 1, "b": 3}, context=0
+        )
+
+        assert output.splitlines()[2:] == [
+            "@@ -3,1 +3,1 @@ b",
+            '-  "b": 2',
+            '+  "b": 3',
+        ]
+
+    def test_ignored_paths_are_left_out(self):
+        padding = {f"k{i}": i for i in range(10)}
+        old = dict(self.OLD, a_updated={"at": 1}, **padding)
+        new = dict(self.NEW, a_updated={"at": 2}, **padding)
+
+        output = self.render(old, new, ignore_paths=["a_updated"])
+
+        assert '"image": "v2"' in output
+        assert '"at"' not in output
+
+    def test_equal_documents(self):
+        assert self.render(self.OLD, self.OLD) == ""
+
+    def test_requires_documents(self):
+        results = diffx_python.diff(self.OLD, self.NEW)
+
+        with pytest.raises(ValueError, match="old= and new="):
+            diffx_python.format_output(results, "unified")
+
+    def test_rejects_unknown_options(self):
+        with pytest.raises(TypeError, match="did you mean 'context'"):
+            diffx_python.format_output([], "json", contxt=1)
+
+
+class TestSideBySideFormat:
+    """format_output(results, "side-by-side") renders two columns"""
+
+    def test_markers_and_alignment(self):
+        results = [
+            {"type": "Removed", "path": "age", "value": 30},
+            {
+                "type": "Modified",
+                "path": "name",
+                "old_value": "Alice",
+                "new_value": "Bob",
+            },
+            {"type": "Added", "path": "city", "value": "Tokyo"},
+        ]
+
+        output = diffx_python.format_output(results, "side-by-side", width=41)
+
+        assert output.splitlines() == [
+            "age: 30             <",
+            'name: "Alice"       | name: "Bob"',
+            "                    > city: \"Tokyo\"",
+        ]
+
+    def test_long_values_wrap(self):
+        results = diffx_python.diff({"a": "x" * 30}, {"a": "y" * 30})
+
+        lines = diffx_python.format_output(results, "side-by-side", width=41)
+        lines = lines.splitlines()
+
+        assert len(lines) == 2
+        assert all(len(line) <= 41 for line in lines)
+        assert lines[0].index("|") == 20
+
+    def test_wide_characters_take_two_columns(self):
+        def width(text):
+            wide = [unicodedata.east_asian_width(c) in "WF" for c in text]
+            return len(text) + sum(wide)
+
+        results = diffx_python.diff({"a": "東京" * 10}, {"a": "大阪"})
+
+        lines = diffx_python.format_output(results, "side-by-side", width=41)
+        lines = lines.splitlines()
+
+        assert len(lines) == 3
+        assert all(width(line) <= 41 for line in lines)
+        assert width(lines[0][: lines[0].index("|")]) == 20
+        assert lines[0].endswith('| a: "大阪"')
+
+    def test_default_width(self):
+        results = diffx_python.diff({"a": "x" * 100}, {"a": "y" * 100})
+
+        output = diffx_python.format_output(results, "side-by-side")
+
+        assert max(len(line) for line in output.splitlines()) <= 80
+
+    def test_rejects_narrow_width(self):
+        with pytest.raises(ValueError, match="width"):
+            diffx_python.format_output([], "side-by-side", width=5)
+
+
+class TestColorOutput:
+    """format_output(..., color=...) adds ANSI colors to the diffx format"""
+
+    RESULTS = [
+        {"type": "Added", "path": "city", "value": "Tokyo"},
+        {"type": "Removed", "path": "age", "value": 30},
+        {
+            "type": "Modified",
+            "path": "name",
+            "old_value": "Alice",
+            "new_value": "Bob",
+        },
+    ]
+
+    def test_colors_lines_by_kind(self):
+        plain = diffx_python.format_output(self.RESULTS, "diffx")
+
+        colored = diffx_python.format_output(self.RESULTS, "diffx", color=True)
+
+        lines = colored.splitlines()
+        assert lines[0].startswith("\x1b[32m")
+        assert lines[1].startswith("\x1b[31m")
+        assert lines[2].startswith("\x1b[33m")
+        assert all(line.endswith("\x1b[0m") for line in lines)
+        assert re.sub(r"\x1b\[\d+m", "", colored) == plain
+
+    def test_off_by_default(self):
+        output = diffx_python.format_output(self.RESULTS, "diffx")
+
+        assert "\x1b[" not in output
+        assert output == diffx_python.format_output(self.RESULTS, "diffx", color=False)
+
+    def test_auto_follows_tty(self, monkeypatch):
+        monkeypatch.delenv("NO_COLOR", raising=False)
+        monkeypatch.delenv("TERM", raising=False)
+        monkeypatch.setattr(sys, "stdout", io.StringIO())
+        assert "\x1b[" not in diffx_python.format_output(
+            self.RESULTS, "diffx", color="auto"
+        )
+
+        monkeypatch.setattr(sys, "stdout", FakeTerminal())
+        assert "\x1b[" in diffx_python.format_output(
+            self.RESULTS, "diffx", color="auto"
+        )
+
+    def test_auto_honors_no_color(self, monkeypatch):
+        monkeypatch.setattr(sys, "stdout", FakeTerminal())
+        monkeypatch.setenv("NO_COLOR", "1")
+
+        output = diffx_python.format_output(self.RESULTS, "diffx", color="auto")
+
+        assert "\x1b[" not in output
+
+    def test_invalid_color(self):
+        with pytest.raises(ValueError, match="color must be"):
+            diffx_python.format_output(self.RESULTS, "diffx", color="always")
+
+    def test_other_formats_reject_color(self):
+        with pytest.raises(ValueError, match="diffx format"):
+            diffx_python.format_output(self.RESULTS, "json", color=True)
+
+
+class TestGithubFormat:
+    """format_output(results, "github") emits workflow annotation commands"""
+
+    def test_one_annotation_per_change(self):
+        results = diffx_python.diff(
+            {"port": 80, "debug": True}, {"port": 8080}, report_unchanged=True
+        )
+
+        output = diffx_python.format_output(results, "github", file="app.json")
+
+        assert output.splitlines() == [
+            "::warning file=app.json,title=Removed debug::debug removed (was true)",
+            "::warning file=app.json,title=Modified port::port changed from 80 to 8080",
+        ]
+
+    def test_comment_changes(self):
+        results = [
+            {
+                "type": "Comment",
+                "path": "port",
+                "old_comment": None,
+                "new_comment": "HTTP only",
+            }
+        ]
+
+        output = diffx_python.format_output(results, "github")
+
+        assert output == "::warning title=Comment port::port comment added: HTTP only\n"
+
+    def test_level_and_no_file(self):
+        results = [{"type": "Added", "path": "a", "value": 1}]
+
+        output = diffx_python.format_output(results, "github", level="error")
+
+        assert output == "::error title=Added a::a added: 1\n"
+
+    def test_escapes_properties_and_messages(self):
+        results = [{"type": "Added", "path": "a", "value": "50%\nmore"}]
+
+        output = diffx_python.format_output(results, "github", file="x:y,z.json")
+
+        assert output == (
+            "::warning file=x%3Ay%2Cz.json,title=Added a::a added: \"50%25\\nmore\"\n"
+        )
+
+    def test_invalid_level(self):
+        with pytest.raises(ValueError, match="Invalid level"):
+            diffx_python.format_output([], "github", level="fatal")
+
+
+class TestJunitFormat:
+    """format_output(results, "junit") writes a JUnit XML test report"""
+
+    def test_one_failing_case_per_difference(self):
+        results = diffx_python.diff(
+            {"port": 80, "debug": True}, {"port": 8080}, report_unchanged=True
+        )
+
+        output = diffx_python.format_output(results, "junit", suite="app.json")
+
+        suite = ElementTree.fromstring(output.encode()).find("testsuite")
+        assert suite.get("name") == "app.json"
+        assert suite.get("tests") == "2"
+        assert suite.get("failures") == "2"
+        failures = {
+            case.get("name"): case.find("failure").get("type")
+            for case in suite.iter("testcase")
+        }
+        assert failures == {"debug": "Removed", "port": "Modified"}
+
+    def test_unchanged_paths_pass(self):
+        results = [
+            {"type": "Unchanged", "path": "a", "value": 1},
+            {"type": "Modified", "path": "b", "old_value": 1, "new_value": 2},
+        ]
+
+        output = diffx_python.format_output(results, "junit")
+
+        suite = ElementTree.fromstring(output.encode()).find("testsuite")
+        assert suite.get("name") == "diffx"
+        assert suite.get("tests") == "2"
+        assert suite.get("failures") == "1"
+        passed, failed = suite.iter("testcase")
+        assert passed.find("failure") is None
+        assert failed.find("failure").text == "old: 1\nnew: 2"
+
+    def test_escapes_markup(self):
+        results = [{"type": "Added", "path": "a&b", "value": "<x>"}]
+
+        output = diffx_python.format_output(results, "junit", suite='"s"')
+
+        case = ElementTree.fromstring(output.encode()).find("testsuite/testcase")
+        assert case.get("name") == "a&b"
+        assert case.get("classname") == '"s"'
+        assert case.find("failure").text == 'added: "<x>"'
+
+
+class TestTapFormat:
+    """format_output(results, "tap") writes a Test Anything Protocol stream"""
+
+    def test_not_ok_per_difference(self):
+        results = [
+            {"type": "Modified", "path": "port", "old_value": 80, "new_value": 8080},
+            {"type": "Unchanged", "path": "host", "value": "db"},
+            {"type": "Added", "path": "tls", "value": {"on": True}},
+        ]
+
+        output = diffx_python.format_output(results, "tap")
+
+        assert output.splitlines() == [
+            "TAP version 13",
+            "1..3",
+            "not ok 1 - port",
+            "  ---",
+            "  type: Modified",
+            "  old: 80",
+            "  new: 8080",
+            "  ...",
+            "ok 2 - host",
+            "not ok 3 - tls",
+            "  ---",
+            "  type: Added",
+            '  value: {"on":true}',
+            "  ...",
+        ]
+
+    def test_empty_plan(self):
+        assert diffx_python.format_output([], "tap") == "TAP version 13\n1..0\n"
+
+    def test_escapes_directives(self):
+        results = [{"type": "Removed", "path": "a#b", "value": 1}]
+
+        output = diffx_python.format_output(results, "tap")
+
+        assert "not ok 1 - a\\#b\n" in output
+
+
+class TestDelimitedFormat:
+    """format_output(results, "csv"/"tsv") exports one row per difference"""
+
+    RESULTS = [
+        {"type": "Modified", "path": "port", "old_value": 80, "new_value": 8080},
+        {"type": "Removed", "path": "name", "value": 'a,"b"'},
+        {"type": "Added", "path": "tls", "value": {"on": True}},
+    ]
+
+    def test_csv_columns(self):
+        output = diffx_python.format_output(self.RESULTS, "csv")
+
+        rows = list(csv.reader(io.StringIO(output)))
+        assert rows == [
+            ["type", "path", "old_value", "new_value"],
+            ["Modified", "port", "80", "8080"],
+            ["Removed", "name", '"a,\\"b\\""', ""],
+            ["Added", "tls", "", '{"on":true}'],
+        ]
+        assert json.loads(rows[2][2]) == 'a,"b"'
+
+    def test_tsv(self):
+        output = diffx_python.format_output(self.RESULTS[:1], "tsv")
+
+        assert output == "type\tpath\told_value\tnew_value\nModified\tport\t80\t8080\n"
+
+    def test_comment_columns(self):
+        results = [
+            {
+                "type": "Comment",
+                "path": "port",
+                "old_comment": "http",
+                "new_comment": None,
+            }
+        ]
+
+        output = diffx_python.format_output(results, "csv")
+
+        assert output.splitlines()[1] == 'Comment,port,"""http""",'
+
+    def test_empty_results_keep_the_header(self):
+        output = diffx_python.format_output([], "csv")
+
+        assert output == "type,path,old_value,new_value\n"
+
+
+class FakeTerminal(io.StringIO):
+    def isatty(self):
+        return True
@@ -4,6 +4,10 @@ These tests verify that the parser functions work correctly
 based on the diffx-core specification and diffx-js parity.
 """
 
+import dataclasses
+import datetime
+import json
+
 import pytest
 import diffx_python
 
@@ -62,6 +66,41 @@ items:
         result = diffx_python.parse_yaml(yaml)
         assert result["items"] == [1, 2, 3]
 
+    def test_unsigned_64_bit_integers_stay_exact(self):
+        result = diffx_python.parse_yaml("id: 18446744073709551615")
+        assert result == {"id": 2**64 - 1}
+
+
+class TestParseYamlAll:
+    """Multi-document YAML stream tests."""
+
+    def test_parses_each_document(self):
+        yaml = """
+kind: Service
+metadata:
+  name: web
+---
+kind: Deployment
+metadata:
+  name: web
+"""
+        result = diffx_python.parse_yaml_all(yaml)
+        assert len(result) == 2
+        assert result[0]["kind"] == "Service"
+        assert result[1]["kind"] == "Deployment"
+
+    def test_skips_empty_documents(self):
+        yaml = "---\na: 1\n---\n# only a comment\n---\nb: 2\n...\n"
+        result = diffx_python.parse_yaml_all(yaml)
+        assert result == [{"a": 1}, {"b": 2}]
+
+    def test_single_document(self):
+        assert diffx_python.parse_yaml_all("a: 1") == [{"a": 1}]
+
+    def test_raises_with_document_index(self):
+        with pytest.raises(ValueError, match="document 1"):
+            diffx_python.parse_yaml_all("a: 1\n---\nb: [unclosed")
+
 
 class TestParseToml:
     """TOML parser tests - based on diffx-core spec."""
@@ -88,6 +127,45 @@ age = 30
         assert result["items"] == [1, 2, 3]
 
 
+class TestTomlDatetimes:
+    """TOML datetimes are normalized and compared semantically."""
+
+    def test_offset_datetimes_normalize_to_utc(self):
+        result = diffx_python.parse_toml("at = 2024-01-01T09:00:00+09:00")
+        assert result["at"] == "2024-01-01T00:00:00Z"
+
+    def test_local_date_and_time_forms(self):
+        toml = "dt = 2024-01-01 12:30:00\nd = 2024-01-01\nt = 12:30:00.5"
+        result = diffx_python.parse_toml(toml)
+        assert result == {
+            "dt": "2024-01-01T12:30:00",
+            "d": "2024-01-01",
+            "t": "12:30:00.500",
+        }
+
+    def test_equal_instants_produce_no_diff(self):
+        old = "at = 2024-01-01T00:00:00Z"
+        new = "at = 2024-01-01 00:00:00+00:00"
+        assert diffx_python.diff_strings(old, new, "toml") == []
+
+    def test_datetime_tolerance(self):
+        old = "at = 2024-01-01T00:00:00Z"
+        new = "at = 2024-01-01T00:00:01.500Z"
+        assert len(diffx_python.diff_strings(old, new, "toml")) == 1
+        loose = diffx_python.diff_strings(old, new, "toml", datetime_tolerance=2)
+        strict = diffx_python.diff_strings(old, new, "toml", datetime_tolerance=1)
+        assert loose == []
+        assert len(strict) == 1
+
+    def test_tolerance_ignores_non_datetime_strings(self):
+        results = diffx_python.diff({"v": "a"}, {"v": "b"}, datetime_tolerance=60)
+        assert len(results) == 1
+
+    def test_negative_tolerance_raises(self):
+        with pytest.raises(ValueError):
+            diffx_python.diff({}, {}, datetime_tolerance=-1)
+
+
 class TestParseCsv:
     """CSV parser tests - based on diffx-core spec."""
 
@@ -106,6 +184,129 @@ class TestParseCsv:
         assert result[0]["description"] == "Hello, World"
 
 
+class TestParseCsvDialect:
+    """CSV dialect options."""
+
+    def test_semicolon_delimiter(self):
+        csv = "name;price\nApfel;1,50\nBirne;2,00"
+        result = diffx_python.parse_csv(csv, delimiter=";")
+        assert result == [
+            {"name": "Apfel", "price": "1,50"},
+            {"name": "Birne", "price": "2,00"},
+        ]
+
+    def test_pipe_delimiter_and_custom_quote(self):
+        csv = "id|note\n1|'a|b'"
+        result = diffx_python.parse_csv(csv, delimiter="|", quotechar="'")
+        assert result == [{"id": "1", "note": "a|b"}]
+
+    def test_escapechar(self):
+        csv = 'id,note\n1,"say \\"hi\\""'
+        result = diffx_python.parse_csv(csv, escapechar="\\")
+        assert result[0]["note"] == 'say "hi"'
+
+    def test_comment_lines_are_skipped(self):
+        csv = "id,name\n# exported 2024-01-01\n1,Alice"
+        result = diffx_python.parse_csv(csv, comment="#")
+        assert result == [{"id": "1", "name": "Alice"}]
+
+    def test_rejects_multi_character_delimiter(self):
+        with pytest.raises(ValueError, match="delimiter"):
+            diffx_python.parse_csv("a,b", delimiter=";;")
+
+    def test_headerless_rows_are_lists(self):
+        result = diffx_python.parse_csv("1,Alice\n2,Bob", has_header=False)
+        assert result == [["1", "Alice"], ["2", "Bob"]]
+
+    def test_columns_key_headerless_rows(self):
+        result = diffx_python.parse_csv("1,Alice\n2,Bob", columns=["id", "name"])
+        assert result == [{"id": "1", "name": "Alice"}, {"id": "2", "name": "Bob"}]
+
+    def test_columns_replace_existing_header(self):
+        result = diffx_python.parse_csv(
+            "ID,NAME\n1,Alice", columns=["id", "name"], has_header=True
+        )
+        assert result == [{"id": "1", "name": "Alice"}]
+
+    def test_columns_length_mismatch_raises(self):
+        with pytest.raises(ValueError, match="columns"):
+            diffx_python.parse_csv("1,Alice,x", columns=["id", "name"])
+
+    def test_headerless_diff_by_column_index(self):
+        results = diffx_python.diff_strings(
+            "1,Alice\n2,Bob", "1,Alice\n2,Robert", "csv", has_header=False
+        )
+        assert [r["path"] for r in results] == ["[1][1]"]
+
+    def test_infer_types_converts_numbers(self):
+        result = diffx_python.parse_csv(
+            "id,count,ratio,label\n1,42,3.14,x\n2,-7,1e3,", infer_types=True
+        )
+        assert result[0] == {"id": 1, "count": 42, "ratio": 3.14, "label": "x"}
+        assert result[1] == {"id": 2, "count": -7, "ratio": 1000.0, "label": ""}
+
+    def test_infer_types_keeps_unsigned_64_bit_ids_exact(self):
+        result = diffx_python.parse_csv("id\n18446744073709551615", infer_types=True)
+        assert result == [{"id": 2**64 - 1}]
+
+    def test_infer_types_keeps_larger_integers_as_exact_strings(self):
+        result = diffx_python.parse_csv(
+            "id,neg\n123456789012345678901234567890,-99999999999999999999",
+            infer_types=True,
+        )
+        assert result == [
+            {"id": "123456789012345678901234567890", "neg": "-99999999999999999999"}
+        ]
+
+    def test_infer_types_keeps_leading_zeros_and_non_numbers(self):
+        result = diffx_python.parse_csv(
+            "zip,code,flag,nan\n01234,+5,true,NaN", infer_types=True
+        )
+        assert result == [{"zip": "01234", "code": "+5", "flag": "true", "nan": "NaN"}]
+
+    def test_infer_types_enables_epsilon(self):
+        results = diffx_python.diff_strings(
+            "m,v\na,1.000", "m,v\na,1.001", "csv", infer_types=True, epsilon=0.01
+        )
+        assert results == []
+
+    def test_diff_strings_with_dialect(self):
+        results = diffx_python.diff_strings(
+            "id;v\n1;a", "id;v\n1;b", "csv", delimiter=";"
+        )
+        assert [r["path"] for r in results] == ["[0].v"]
+
+
+class TestParseTsv:
+    """TSV parser tests."""
+
+    def test_parses_tab_separated_rows(self):
+        result = diffx_python.parse_tsv("name\tage\nAlice\t30\nBob\t25")
+        assert result == [{"name": "Alice", "age": "30"}, {"name": "Bob", "age": "25"}]
+
+    def test_keeps_commas_inside_fields(self):
+        result = diffx_python.parse_tsv("name\tnote\nAlice\ta, b")
+        assert result[0]["note"] == "a, b"
+
+    def test_accepts_csv_options(self):
+        result = diffx_python.parse_tsv("1\t2.5", has_header=False, infer_types=True)
+        assert result == [[1, 2.5]]
+
+    def test_diff_strings_tsv(self):
+        results = diffx_python.diff_strings("a\tb\n1\t2", "a\tb\n1\t3", "tsv")
+        assert [r["path"] for r in results] == ["[0].b"]
+
+    def test_diff_files_detects_tsv_extension(self, tmp_path):
+        old_file = tmp_path / "old.tsv"
+        new_file = tmp_path / "new.tsv"
+        old_file.write_text("id\tname\n1\tAlice\n")
+        new_file.write_text("id\tname\n1\tAlicia\n")
+
+        results = diffx_python.diff_files(str(old_file), str(new_file))
+
+        assert [r["path"] for r in results] == ["[0].name"]
+
+
 class TestParseIni:
     """INI parser tests - based on diffx-core spec."""
 
@@ -132,6 +333,62 @@ enabled = true
         assert result["cache"]["enabled"] == "true"
 
 
+class TestParseIniOptions:
+    """configparser-compatible INI options."""
+
+    INI = """
+root = /srv
+[App]
+Name = web
+path = %(root)s/app
+discount = 10%%
+"""
+
+    def test_case_insensitive_keys(self):
+        result = diffx_python.parse_ini(self.INI, case_insensitive_keys=True)
+        assert result["App"]["name"] == "web"
+        assert "Name" not in result["App"]
+
+    def test_case_insensitive_sections(self):
+        result = diffx_python.parse_ini(self.INI, case_insensitive_sections=True)
+        assert result["app"]["Name"] == "web"
+
+    def test_keys_outside_sections_stay_top_level(self):
+        result = diffx_python.parse_ini(self.INI, default_section=None)
+        assert result["root"] == "/srv"
+        assert "root" not in result["App"]
+
+    def test_default_section_is_inherited(self):
+        result = diffx_python.parse_ini(self.INI, default_section="DEFAULT")
+        assert result["DEFAULT"] == {"root": "/srv"}
+        assert result["App"]["root"] == "/srv"
+
+    def test_interpolation(self):
+        result = diffx_python.parse_ini(self.INI, interpolation=True)
+        assert result["App"]["path"] == "/srv/app"
+        assert result["App"]["discount"] == "10%"
+
+    def test_interpolation_resolves_default_section_keys(self):
+        ini = "[DEFAULT]\nroot = /srv\n[App]\npath = %(root)s/app\n"
+        result = diffx_python.parse_ini(ini, interpolation=True)
+        assert result["App"] == {"path": "/srv/app", "root": "/srv"}
+
+    def test_interpolation_missing_option_raises(self):
+        with pytest.raises(ValueError, match="missing option"):
+            diffx_python.parse_ini("[a]\nx = %(nope)s\n", interpolation=True)
+
+    def test_multiline_values(self):
+        result = diffx_python.parse_ini(
+            "[a]\nhosts = one\n  two\n", case_insensitive_keys=True
+        )
+        assert result["a"]["hosts"] == "one\ntwo"
+
+    def test_interpolated_values_compare_equal(self):
+        old = "[a]\nbase = /srv\npath = %(base)s/app\n"
+        new = "[a]\nbase = /srv\npath = /srv/app\n"
+        assert diffx_python.diff_strings(old, new, "ini", interpolation=True) == []
+
+
 class TestParseXml:
     """XML parser tests - based on diffx-core spec."""
 
@@ -144,6 +401,58 @@ class TestParseXml:
             diffx_python.parse_xml("<invalid")
 
 
+class TestParseXmlMapping:
+    """Configurable XML attribute/text mapping."""
+
+    XML = '<server port="8080"><name>web</name><tag>a</tag><tag>b</tag>up</server>'
+
+    def test_default_prefixes(self):
+        result = diffx_python.parse_xml(self.XML)
+        assert result == {
+            "server": {
+                "@port": "8080",
+                "name": "web",
+                "tag": ["a", "b"],
+                "#text": "up",
+            }
+        }
+
+    def test_custom_prefix_and_text_key(self):
+        result = diffx_python.parse_xml(
+            self.XML, attribute_prefix="_", text_key="value"
+        )
+        assert result["server"]["_port"] == "8080"
+        assert result["server"]["value"] == "up"
+
+    def test_merge_attributes(self):
+        result = diffx_python.parse_xml(self.XML, merge_attributes=True)
+        assert result["server"]["port"] == "8080"
+        assert result["server"]["name"] == "web"
+
+    def test_leaf_and_empty_elements(self):
+        result = diffx_python.parse_xml("<root><empty/><leaf>x</leaf></root>")
+        assert result == {"root": {"empty": None, "leaf": "x"}}
+
+    def test_raises_on_mismatched_tags(self):
+        with pytest.raises(ValueError):
+            diffx_python.parse_xml("<a><b></a>")
+
+    def test_round_trip_keeps_attributes_and_text(self):
+        doc = diffx_python.parse_xml(self.XML)
+
+        assert diffx_python.parse_xml(diffx_python.dump_xml(doc)) == doc
+        converted = diffx_python.convert(self.XML, "xml", "xml")
+        assert diffx_python.parse_xml(converted) == doc
+
+    def test_diff_paths_follow_mapping(self):
+        old = '<server port="80"/>'
+        new = '<server port="8080"/>'
+        results = diffx_python.diff_strings(
+            old, new, "xml", attribute_prefix="", merge_attributes=True
+        )
+        assert [r["path"] for r in results] == ["server.port"]
+
+
 class TestParserDiffIntegration:
     """Integration: parser + diff."""
 
@@ -170,3 +479,560 @@ class TestParserDiffIntegration:
 
         assert len(results) == 1
         assert results[0]["path"] == "age"
+
+
+class TestDetectFormat:
+    """detect_format() sniffs the format of a document from its content"""
+
+    SAMPLES = [
+        ('{"name": "web"}', "json"),
+        ("[1, 2]", "json"),
+        ("name: web\nport: 80\n", "yaml"),
+        ("- a\n- b\n", "yaml"),
+        ('name = "web"\n[server]\nport = 80\n', "toml"),
+        ("[server]\nhost = example.com\n", "ini"),
+        ("<config><port>80</port></config>", "xml"),
+        ("name,port\nweb,80\n", "csv"),
+        ("name\tport\nweb\t80\n", "tsv"),
+    ]
+
+    def test_formats(self):
+        for content, expected in self.SAMPLES:
+            assert diffx_python.detect_format(content) == expected, content
+
+    def test_accepts_bytes(self):
+        assert diffx_python.detect_format(b'{"name": "web"}') == "json"
+
+    def test_rejects_plain_text(self):
+        with pytest.raises(ValueError, match="Could not detect the format"):
+            diffx_python.detect_format("just some words")
+
+    def test_rejects_invalid_utf8(self):
+        with pytest.raises(ValueError, match="not valid UTF-8"):
+            diffx_python.detect_format(b"\xff\xfe")
+
+    def test_diff_files_sniffs_unknown_extensions(self, tmp_path):
+        old_file = tmp_path / "old.conf"
+        new_file = tmp_path / "new.conf"
+        old_file.write_text("name: web\nport: 80\n")
+        new_file.write_text("name: web\nport: 8080\n")
+
+        results = diffx_python.diff_files(str(old_file), str(new_file))
+
+        assert results == [
+            {"type": "Modified", "path": "port", "old_value": 80, "new_value": 8080}
+        ]
+
+    def test_run_sniffs_unknown_extensions(self, tmp_path):
+        old_file = tmp_path / "old.conf"
+        new_file = tmp_path / "new.conf"
+        old_file.write_text("[app]\nport = 80\n")
+        new_file.write_text("[app]\nport = 8080\n")
+
+        summary = diffx_python.run(
+            str(old_file), str(new_file), str(tmp_path / "report.json"), format="json"
+        )
+
+        assert summary["modified"] == 1
+
+    def test_undetectable_files_raise_diff_error(self, tmp_path):
+        old_file = tmp_path / "old.conf"
+        old_file.write_text("just some words")
+
+        with pytest.raises(diffx_python.DiffError, match="Unsupported file format"):
+            diffx_python.diff_files(str(old_file), str(old_file))
+
+
+class TestConvert:
+    """convert() parses one format and writes another"""
+
+    def test_yaml_to_json(self):
+        result = diffx_python.convert("name: web\nport: 80\n", "yaml", "json")
+        assert result == '{\n  "name": "web",\n  "port": 80\n}\n'
+
+    def test_toml_to_yaml(self):
+        toml = 'name = "web"\n[server]\nports = [80, 443]\n'
+        result = diffx_python.convert(toml, "toml", "yml")
+        assert result == "name: web\nserver:\n  ports:\n    - 80\n    - 443\n"
+
+    def test_yaml_quotes_strings_that_look_like_other_types(self):
+        doc = {"a": "80", "b": "true", "c": "", "d": "key: value", "e": "- x"}
+        yaml = diffx_python.convert(json.dumps(doc), "json", "yaml")
+        assert diffx_python.parse_yaml(yaml) == doc
+
+    def test_round_trips(self):
+        doc = {"server": {"host": "example.com", "port": 8080, "tls": True}}
+        for format in ["json", "yaml", "toml"]:
+            text = diffx_python.convert(json.dumps(doc), "json", format)
+            assert json.loads(diffx_python.convert(text, format, "json")) == doc, format
+
+    def test_ini_to_json(self):
+        result = diffx_python.convert("[app]\nport = 80\n", "ini", "json")
+        assert json.loads(result) == {"app": {"port": "80"}}
+
+    def test_json_to_ini(self):
+        result = diffx_python.convert('{"app": {"port": "80"}}', "json", "ini")
+        assert result == "[app]\nport = 80\n"
+
+    def test_json_to_xml(self):
+        doc = {"server": {"@port": "8080", "name": "web"}}
+        xml = diffx_python.convert(json.dumps(doc), "json", "xml")
+        assert diffx_python.parse_xml(xml) == doc
+
+    def test_json_to_csv(self):
+        rows = [{"id": "1", "name": "a"}, {"id": "2", "name": "b,c"}]
+        csv = diffx_python.convert(json.dumps(rows), "json", "csv")
+        assert csv == 'id,name\n1,a\n2,"b,c"\n'
+        assert diffx_python.parse_csv(csv) == rows
+
+    def test_csv_to_tsv(self):
+        result = diffx_python.convert("id,name\n1,a\n", "csv", "tsv")
+        assert result == "id\tname\n1\ta\n"
+
+    def test_parser_options_apply(self):
+        result = diffx_python.convert("id;n\n1;2\n", "csv", "json", delimiter=";")
+        assert json.loads(result) == [{"id": "1", "n": "2"}]
+
+    def test_unrepresentable_values_raise(self):
+        with pytest.raises(ValueError, match="null"):
+            diffx_python.convert('{"a": {"b": null}}', "json", "toml")
+        with pytest.raises(ValueError, match="scalars"):
+            diffx_python.convert('{"a": {"b": {"c": 1}}}', "json", "ini")
+        with pytest.raises(ValueError, match="one root element"):
+            diffx_python.convert('{"a": 1, "b": 2}', "json", "xml")
+
+    def test_unknown_format_raises(self):
+        with pytest.raises(ValueError, match="Unsupported format: hcl"):
+            diffx_python.convert("{}", "json", "hcl")
+
+    def test_invalid_input_raises_parse_error(self):
+        with pytest.raises(diffx_python.ParseError):
+            diffx_python.convert("{", "json", "yaml")
+
+
+class TestDump:
+    """dump_* functions write documents back out in each format"""
+
+    DOC = {"server": {"host": "example.com", "ports": [80, 443]}, "name": "web"}
+
+    def test_dump_json_indent(self):
+        doc = {"b": 1, "a": [1]}
+        assert diffx_python.dump_json(doc) == '{\n  "a": [\n    1\n  ],\n  "b": 1\n}\n'
+        assert diffx_python.dump_json({"a": 1}, indent=4) == '{\n    "a": 1\n}\n'
+        assert diffx_python.dump_json(doc, indent=None) == '{"a":[1],"b":1}'
+
+    def test_dump_yaml_indent(self):
+        result = diffx_python.dump_yaml({"a": {"b": [{"c": 1, "d": 2}]}}, indent=4)
+        assert result == "a:\n    b:\n        - c: 1\n          d: 2\n"
+        assert diffx_python.parse_yaml(result) == {"a": {"b": [{"c": 1, "d": 2}]}}
+
+    def test_dump_yaml_rejects_zero_indent(self):
+        with pytest.raises(ValueError, match="indent"):
+            diffx_python.dump_yaml({"a": 1}, indent=0)
+
+    def test_dump_toml(self):
+        result = diffx_python.dump_toml(self.DOC)
+        assert diffx_python.parse_toml(result) == self.DOC
+
+    def test_dump_toml_rejects_none(self):
+        with pytest.raises(ValueError, match="null \\(at 'a\\[1\\]'\\)"):
+            diffx_python.dump_toml({"a": [1, None]})
+
+    def test_dump_xml_mapping_options(self):
+        doc = {"server": {"_port": "8080", "value": "up", "name": "web"}}
+        mapping = {"attribute_prefix": "_", "text_key": "value"}
+        xml = diffx_python.dump_xml(doc, indent=0, **mapping)
+        assert xml.splitlines()[1:] == [
+            '<server port="8080">up',
+            "<name>web</name>",
+            "</server>",
+        ]
+        assert diffx_python.parse_xml(xml, **mapping) == doc
+
+    def test_dump_ini(self):
+        result = diffx_python.dump_ini({"top": "1", "app": {"port": 80, "debug": True}})
+        assert result == "top = 1\n\n[app]\ndebug = true\nport = 80\n"
+
+    def test_dump_ini_rejects_nesting(self):
+        with pytest.raises(ValueError, match="app.db"):
+            diffx_python.dump_ini({"app": {"db": {"host": "x"}}})
+
+    def test_dump_csv_columns(self):
+        rows = [{"id": 1, "name": "a"}, {"id": 2}]
+        result = diffx_python.dump_csv(rows, columns=["name", "id"])
+        assert result == "name,id\na,1\n,2\n"
+        assert diffx_python.dump_csv(rows, delimiter="\t") == "id\tname\n1\ta\n2\t\n"
+
+    def test_dump_csv_lists(self):
+        assert diffx_python.dump_csv([[1, "a"], [2, "b"]]) == "1,a\n2,b\n"
+
+    def test_dump_csv_rejects_unknown_columns(self):
+        with pytest.raises(ValueError, match="not a column"):
+            diffx_python.dump_csv([{"id": 1, "name": "a"}], columns=["id"])
+
+    def test_sort_keys_false_keeps_dict_order(self):
+        doc = {"name": "web", "server": {"port": 80, "host": "a"}, "id": 1}
+
+        assert diffx_python.dump_json(doc, indent=None, sort_keys=False) == (
+            '{"name":"web","server":{"port":80,"host":"a"},"id":1}'
+        )
+        assert diffx_python.dump_yaml(doc, sort_keys=False) == (
+            "name: web\nserver:\n  port: 80\n  host: a\nid: 1\n"
+        )
+        assert diffx_python.dump_toml(doc, sort_keys=False) == (
+            'name = "web"\nid = 1\n\n[server]\nport = 80\nhost = "a"\n'
+        )
+        assert diffx_python.dump_ini(doc, sort_keys=False) == (
+            "name = web\nid = 1\n\n[server]\nport = 80\nhost = a\n"
+        )
+        xml = diffx_python.dump_xml({"r": doc}, indent=0, sort_keys=False)
+        assert xml.splitlines()[2:5] == [
+            "<name>web</name>",
+            "<server>",
+            "<port>80</port>",
+        ]
+        rows = [{"name": "a", "id": 1}, {"tag": "x"}]
+        assert diffx_python.dump_csv(rows, sort_keys=False) == (
+            "name,id,tag\na,1,\n,,x\n"
+        )
+
+    def test_sort_keys_false_keeps_field_order(self):
+        @dataclasses.dataclass
+        class Server:
+            port: int
+            host: str
+
+        result = diffx_python.dump_json([Server(80, "a")], indent=None, sort_keys=False)
+
+        assert result == '[{"port":80,"host":"a"}]'
+
+    def test_dumps_accept_python_values(self):
+        doc = {"when": datetime.date(2024, 1, 2), "tags": ("a", "b")}
+        assert diffx_python.parse_yaml(diffx_python.dump_yaml(doc)) == {
+            "when": "2024-01-02",
+            "tags": ["a", "b"],
+        }
+
+
+class TestParseErrors:
+    """ParseError locates the error in the input."""
+
+    def test_json_position_and_snippet(self):
+        with pytest.raises(diffx_python.ParseError) as info:
+            diffx_python.parse_json('{\n  "a": 1,\n  "b": \n}')
+        assert (info.value.line, info.value.column) == (4, 1)
+        assert info.value.snippet == "}"
+
+    def test_toml_position(self):
+        with pytest.raises(diffx_python.ParseError) as info:
+            diffx_python.parse_toml("a = 1\nb = \n")
+        assert (info.value.line, info.value.column) == (2, 5)
+        assert info.value.snippet == "b = "
+
+    def test_yaml_stream_lines_count_from_the_stream(self):
+        content = "a: 1\n---\nb: [1, 2\nc: 3\n---\nd: 4\n"
+        for kwargs in ({}, {"yaml_anchors": "preserve"}):
+            with pytest.raises(diffx_python.ParseError) as info:
+                diffx_python.parse_yaml_all(content, **kwargs)
+            assert info.value.line == 4
+            assert info.value.snippet == "c: 3"
+
+    def test_xml_position(self):
+        with pytest.raises(diffx_python.ParseError) as info:
+            diffx_python.parse_xml("<a>\n  <b x=1/>\n</a>", merge_attributes=True)
+        assert (info.value.line, info.value.column) == (2, 3)
+        assert info.value.snippet == "  <b x=1/>"
+
+    def test_csv_row_line(self):
+        with pytest.raises(diffx_python.ParseError) as info:
+            diffx_python.parse_csv("1,2\n3,4\n5\n", columns=["x", "y"])
+        assert info.value.line == 3
+        assert info.value.column is None
+        assert info.value.snippet == "5"
+
+    def test_ini_line(self):
+        with pytest.raises(diffx_python.ParseError) as info:
+            diffx_python.parse_ini("[s]\nk = v\n= x\n", interpolation=True)
+        assert info.value.line == 3
+
+    def test_is_a_value_error_without_position(self):
+        assert issubclass(diffx_python.ParseError, ValueError)
+        with pytest.raises(diffx_python.ParseError) as info:
+            diffx_python.parse_xml("<a>", attribute_prefix="@")
+        assert info.value.line is None
+        assert info.value.snippet is None
+
+
+class TestLenientParsing:
+    """strict=False skips malformed records with a ParseWarning."""
+
+    def test_csv_skips_bad_rows(self):
+        with pytest.warns(diffx_python.ParseWarning, match="line: 3"):
+            result = diffx_python.parse_csv("a,b\n1,2\n3\n4,5\n", strict=False)
+        assert result == [{"a": "1", "b": "2"}, {"a": "4", "b": "5"}]
+
+    def test_tsv_skips_rows_not_matching_columns(self):
+        with pytest.warns(diffx_python.ParseWarning, match="line: 2"):
+            result = diffx_python.parse_tsv(
+                "1\t2\n3\n", columns=["x", "y"], strict=False
+            )
+        assert result == [{"x": "1", "y": "2"}]
+
+    def test_yaml_stream_skips_bad_documents(self):
+        content = "a: 1\n---\nb: [\n---\nc: 3\n"
+        for kwargs in ({}, {"yaml_anchors": "preserve"}):
+            with pytest.warns(diffx_python.ParseWarning, match="document 1"):
+                result = diffx_python.parse_yaml_all(content, strict=False, **kwargs)
+            assert result == [{"a": 1}, {"c": 3}]
+
+    def test_strict_by_default(self):
+        with pytest.raises(diffx_python.ParseError):
+            diffx_python.parse_csv("a,b\n1,2\n3\n")
+        with pytest.raises(diffx_python.ParseError):
+            diffx_python.parse_yaml_all("a: 1\n---\nb: [\n")
+
+    def test_diff_files_and_run(self, tmp_path):
+        old = tmp_path / "old.csv"
+        new = tmp_path / "new.csv"
+        old.write_text("a,b\n1,2\n3\n")
+        new.write_text("a,b\n1,3\n")
+
+        with pytest.warns(diffx_python.ParseWarning):
+            results = diffx_python.diff_files(old, new, strict=False)
+        assert [r["path"] for r in results] == ["[0].b"]
+
+        report = tmp_path / "report.json"
+        with pytest.warns(diffx_python.ParseWarning, match="old.csv"):
+            summary = diffx_python.run(old, new, report, format="json", strict=False)
+        assert summary["modified"] == 1
+
+
+class TestDuplicateKeys:
+    """duplicate_keys reports keys that appear twice in JSON and YAML."""
+
+    JSON = '{\n  "port": 80,\n  "tls": {"on": true, "on": false},\n  "port": 81\n}'
+
+    def test_ignored_by_default(self):
+        assert diffx_python.parse_json(self.JSON) == {"port": 81, "tls": {"on": False}}
+
+    def test_error_names_path_and_position(self):
+        with pytest.raises(diffx_python.ParseError, match="'tls.on'") as info:
+            diffx_python.parse_json(self.JSON, duplicate_keys="error")
+        assert (info.value.line, info.value.column) == (3, 23)
+
+    def test_warn_keeps_last_value(self):
+        with pytest.warns(diffx_python.ParseWarning, match="'port' at line 4"):
+            result = diffx_python.parse_json(self.JSON, duplicate_keys="warn")
+        assert result["port"] == 81
+
+    def test_yaml_paths_and_documents(self):
+        content = "a: 1\n---\nitems:\n  - k: 1\n    k: 2\n"
+        with pytest.raises(diffx_python.ParseError, match="'items\\[0\\].k'") as info:
+            diffx_python.parse_yaml_all(content, duplicate_keys="error")
+        assert "document 1" in str(info.value)
+        assert info.value.line == 5
+
+    def test_yaml_merge_keys_are_not_duplicates(self):
+        content = "base: &b {x: 1}\nmore: &m {y: 2}\nc:\n  <<: *b\n  <<: *m\n"
+        diffx_python.parse_yaml(content, duplicate_keys="error")
+
+    def test_keys_in_separate_objects_are_fine(self):
+        content = '[{"id": 1}, {"id": 2}]'
+        assert diffx_python.parse_json(content, duplicate_keys="error") == [
+            {"id": 1},
+            {"id": 2},
+        ]
+
+    def test_diff_files(self, tmp_path):
+        old = tmp_path / "old.json"
+        new = tmp_path / "new.json"
+        old.write_text('{"a": 1, "a": 2}')
+        new.write_text('{"a": 2}')
+        with pytest.raises(diffx_python.ParseError):
+            diffx_python.diff_files(old, new, duplicate_keys="error")
+
+    def test_invalid_mode(self):
+        with pytest.raises(ValueError, match="Invalid duplicate_keys"):
+            diffx_python.parse_json("{}", duplicate_keys="first")
+
+
+class TestParseLimits:
+    """Hardened parse mode for untrusted input."""
+
+    def test_limits_are_off_by_default(self):
+        deep = "[" * 50 + "]" * 50
+        assert diffx_python.parse_json(deep) is not None
+
+    def test_hardened_rejects_deep_nesting(self):
+        deep = "[" * 100 + "]" * 100
+        with pytest.raises(diffx_python.ParseLimitError, match="max_nesting_depth"):
+            diffx_python.parse_json(deep, hardened=True)
+
+    def test_max_string_length(self):
+        with pytest.raises(diffx_python.ParseLimitError, match="max_string_length"):
+            diffx_python.parse_yaml("key: " + "x" * 100, max_string_length=10)
+
+    def test_max_nodes(self):
+        with pytest.raises(diffx_python.ParseLimitError, match="max_nodes"):
+            diffx_python.parse_csv("a,b\n1,2\n3,4", max_nodes=3)
+
+    def test_max_input_bytes(self):
+        with pytest.raises(diffx_python.ParseLimitError, match="max_input_bytes"):
+            diffx_python.parse_toml('name = "Alice"', max_input_bytes=4)
+
+    def test_explicit_limit_overrides_hardened_default(self):
+        deep = "[" * 100 + "]" * 100
+        result = diffx_python.parse_json(deep, hardened=True, max_nesting_depth=None)
+        assert isinstance(result, list)
+
+    def test_json_limits_apply_while_parsing(self):
+        # The limit is hit before the parser reaches the syntax error
+        with pytest.raises(diffx_python.ParseLimitError, match="max_nodes=3"):
+            diffx_python.parse_json("[" + "1, " * 10 + "oops", max_nodes=3)
+        with pytest.raises(diffx_python.ParseLimitError, match="max_nesting_depth"):
+            diffx_python.parse_json("[" * 100 + "oops", hardened=True)
+
+    def test_yaml_limits_apply_while_loading(self):
+        content = "a: " + "x" * 100 + "\nb: [oops"
+        with pytest.raises(diffx_python.ParseLimitError, match="max_string_length"):
+            diffx_python.parse_yaml(
+                content, yaml_anchors="resolve", max_string_length=10
+            )
+
+    def test_hardened_yaml_bounds_aliases_without_anchor_mode(self):
+        lines = ['a: &a ["x", "x", "x", "x", "x", "x", "x", "x", "x", "x"]']
+        for name, previous in zip("bcdefghij", "abcdefghi"):
+            lines.append(f"{name}: &{name} [{', '.join(['*' + previous] * 10)}]")
+
+        with pytest.raises(diffx_python.ParseLimitError, match="aliases expand"):
+            diffx_python.parse_yaml("\n".join(lines), hardened=True)
+        with pytest.raises(diffx_python.ParseLimitError, match="aliases expand"):
+            diffx_python.parse_yaml_all("\n".join(lines), hardened=True)
+
+    def test_hardened_yaml_keeps_merge_keys_literal(self):
+        content = "a: &x {b: 1}\nc:\n  <<: *x\n"
+        assert diffx_python.parse_yaml(content, hardened=True) == (
+            diffx_python.parse_yaml(content)
+        )
+
+    def test_yaml_parse_error_prefixed_once(self):
+        with pytest.raises(diffx_python.ParseError) as error:
+            diffx_python.parse_yaml("a: [")
+        assert str(error.value).count("YAML parse error") == 1
+
+    def test_bool_limits_rejected(self):
+        for name in ["max_nodes", "max_nesting_depth", "memory_limit_mb"]:
+            with pytest.raises(TypeError, match=f"{name} must be an integer"):
+                diffx_python.parse_json("[]", **{name: True})
+
+    def test_limit_error_is_value_error(self):
+        with pytest.raises(ValueError):
+            diffx_python.parse_json('{"a": 1}', max_nodes=1)
+
+    def test_diff_strings_passes_limits_to_parser(self):
+        with pytest.raises(diffx_python.ParseLimitError):
+            diffx_python.diff_strings('{"a": 1}', '{"a": 2}', "json", max_nodes=1)
+
+    def test_unknown_parse_option_rejected(self):
+        with pytest.raises(TypeError, match="did you mean 'delimiter'"):
+            diffx_python.parse_csv("a;b\n1;2", delimeter=";")
+
+
+class TestMemoryLimit:
+    """memory_limit_mb fails fast instead of exhausting memory."""
+
+    def test_input_larger_than_budget_rejected(self):
+        content = "[" + "1," * 600_000 + "1]"
+        with pytest.raises(diffx_python.MemoryLimitError, match="memory_limit_mb=1"):
+            diffx_python.parse_json(content, memory_limit_mb=1)
+
+    def test_parsed_size_counts_against_budget(self):
+        # ~900 KB of input expands to several MB of parsed values
+        content = "[" + "0," * 450_000 + "0]"
+        with pytest.raises(diffx_python.MemoryLimitError, match="allocated"):
+            diffx_python.parse_json(content, memory_limit_mb=1)
+
+    def test_budget_applies_while_parsing(self):
+        # The budget runs out before the parser reaches the syntax error
+        content = "[" + "0," * 450_000 + "oops"
+        with pytest.raises(diffx_python.MemoryLimitError, match="allocated"):
+            diffx_python.parse_json(content, memory_limit_mb=1)
+
+    def test_within_budget(self):
+        assert diffx_python.parse_yaml("a: 1", memory_limit_mb=64) == {"a": 1}
+
+    def test_memory_limit_error_is_memory_error(self):
+        assert issubclass(diffx_python.MemoryLimitError, MemoryError)
+
+    def test_diff_files_checks_file_size_first(self, tmp_path):
+        old_file = tmp_path / "old.json"
+        new_file = tmp_path / "new.json"
+        old_file.write_text("[" + "1," * 600_000 + "1]")
+        new_file.write_text("[]")
+
+        with pytest.raises(diffx_python.MemoryLimitError, match="old.json"):
+            diffx_python.diff_files(str(old_file), str(new_file), memory_limit_mb=1)
+
+
+class TestYamlAnchors:
+    """YAML anchor/alias and merge-key handling."""
+
+    YAML = """
+defaults: &defaults
+  adapter: postgres
+  host: localhost
+development:
+  <<: *defaults
+  host: dev.local
+replica: *defaults
+"""
+
+    def test_resolve_applies_merge_keys(self):
+        result = diffx_python.parse_yaml(self.YAML, yaml_anchors="resolve")
+        assert result["development"] == {"adapter": "postgres", "host": "dev.local"}
+        assert result["replica"] == {"adapter": "postgres", "host": "localhost"}
+
+    def test_preserve_keeps_aliases_and_merge_keys(self):
+        result = diffx_python.parse_yaml(self.YAML, yaml_anchors="preserve")
+        assert result["development"] == {"<<": "*defaults", "host": "dev.local"}
+        assert result["replica"] == "*defaults"
+
+    def test_preserve_keeps_scalar_types(self):
+        result = diffx_python.parse_yaml(
+            "a: 1\nb: 1.5\nc: true\nd: null\ne: '1'", yaml_anchors="preserve"
+        )
+        assert result == {"a": 1, "b": 1.5, "c": True, "d": None, "e": "1"}
+
+    def test_parse_yaml_all_with_anchor_mode(self):
+        result = diffx_python.parse_yaml_all(
+            "a: &x 1\nb: *x\n---\nc: 2", yaml_anchors="resolve"
+        )
+        assert result == [{"a": 1, "b": 1}, {"c": 2}]
+
+    def test_alias_expansion_is_bounded(self):
+        lines = ['a: &a ["x", "x", "x", "x", "x", "x", "x", "x", "x", "x"]']
+        for name, previous in zip("bcdefghij", "abcdefghi"):
+            lines.append(f"{name}: &{name} [{', '.join(['*' + previous] * 10)}]")
+
+        with pytest.raises(diffx_python.ParseLimitError, match="aliases expand"):
+            diffx_python.parse_yaml("\n".join(lines), yaml_anchors="resolve")
+
+    def test_max_nodes_counts_expanded_aliases(self):
+        content = "a: &a [1, 2, 3, 4]\nb: *a\nc: *a"
+        assert diffx_python.parse_yaml(content, yaml_anchors="resolve", max_nodes=16)
+        with pytest.raises(diffx_python.ParseLimitError, match="max_nodes=15"):
+            diffx_python.parse_yaml(content, yaml_anchors="resolve", max_nodes=15)
+
+    def test_invalid_mode_raises(self):
+        with pytest.raises(ValueError, match="yaml_anchors"):
+            diffx_python.parse_yaml("a: 1", yaml_anchors="expand")
+
+    def test_warns_when_only_anchor_structure_changed(self):
+        old = "base: &b 1\ncopy: *b"
+        new = "base: 1\ncopy: 1"
+
+        with pytest.warns(diffx_python.YamlAnchorWarning):
+            results = diffx_python.diff_strings(
+                old, new, "yaml", yaml_anchors="resolve"
+            )
+        assert results == []